@@ -1 +1,10 @@
 from .simulator import Simulator
+from .distributed import DistributedSimulator
+from .processes import QueueInput
+from .nengo_rs import (
+    BuildError,
+    ShapeMismatchError,
+    SignalTypeError,
+    SimulationError,
+    ThreadPool,
+)
@@ -0,0 +1,519 @@
+"""Simulation of a model split across several worker processes.
+
+All operators writing to the same signal are placed in one partition, so that
+every signal has a single owning partition. Signals read in other partitions
+are sent over TCP once per step: after all writers for set and incremented
+signals, and before the update for updated signals (readers of those expect
+the value from the previous step).
+"""
+
+import argparse
+import multiprocessing
+import os
+import socket
+from multiprocessing.connection import Client, Listener
+
+import numpy as np
+from nengo.builder import Model
+from nengo.builder import operator as core_op
+from nengo.builder import neurons
+from nengo.cache import get_default_decoder_cache
+from nengo.utils.graphs import BidirectionalDAG, toposort
+from nengo.utils.simulator import operator_dependency_graph
+
+from .nengo_rs import (
+    Engine,
+    Probe,
+    ReceiveSignal,
+    SendSignal,
+    SignalF64,
+    SignalU64,
+    ThreadPool,
+)
+from .simulator import Simulator
+
+
+def _base(signal):
+    return signal if signal.base is None else signal.base
+
+
+def _free_port(host):
+    with socket.socket() as s:
+        s.bind((host, 0))
+        return s.getsockname()[1]
+
+
+def partition_operators(operators, n_partitions):
+    """Maps each operator to a partition index, in topological order.
+
+    `TimeUpdate` operators are not mapped because every partition runs its own
+    copy of them.
+    """
+    dg = BidirectionalDAG(operator_dependency_graph(operators))
+    order = [
+        op for op in toposort(dg.forward) if not isinstance(op, core_op.TimeUpdate)
+    ]
+
+    parent = {op: op for op in order}
+
+    def find(op):
+        while parent[op] is not op:
+            parent[op] = parent[parent[op]]
+            op = parent[op]
+        return op
+
+    writers = {}
+    for op in order:
+        for signal in op.sets + op.incs + op.updates:
+            writers.setdefault(_base(signal), []).append(op)
+    for ops in writers.values():
+        for op in ops[1:]:
+            parent[find(op)] = find(ops[0])
+
+    groups = {}
+    for op in order:
+        groups.setdefault(find(op), []).append(op)
+
+    group_partitions = {}
+    n_assigned = 0
+    for root, group in groups.items():
+        group_partitions[root] = min(
+            n_assigned * n_partitions // len(order), n_partitions - 1
+        )
+        n_assigned += len(group)
+    return {op: group_partitions[find(op)] for op in order}
+
+
+class _Boundary:
+    def __init__(self, signal, source, target, address, updated, writers, key):
+        self.signal = signal
+        self.source = source
+        self.target = target
+        self.address = address
+        self.updated = updated
+        self.writers = writers
+        self.key = key
+
+
+class _PartitionModel:
+    """The parts of a built model that the operators of a partition use."""
+
+    def __init__(self, dt, step, time):
+        self.dt = dt
+        self.step = step
+        self.time = time
+
+
+class _PartitionSpec:
+    """Everything a worker needs to build the engine of one partition.
+
+    `operators` lists ``(kind, payload, dependencies)`` in the order of the
+    engine operators, where `kind` is ``"receive"``, ``"send"``, or ``"op"``
+    for a builder operator. `probes` maps probe indices to their signal,
+    label, and sampling period in steps.
+    """
+
+    def __init__(self, model, seed, operators, spiking_outputs, probes):
+        self.model = model
+        self.seed = seed
+        self.operators = operators
+        self.spiking_outputs = spiking_outputs
+        self.probes = probes
+
+
+class _PartitionBuilder(Simulator):
+    def __init__(self, spec, thread_pool):
+        self._setup(spec.model, seed=spec.seed, thread_pool=thread_pool)
+        self.spec = spec
+
+    def build(self):
+        signal_to_engine_id = {
+            self.model.step: SignalU64("step", 0),
+            self.model.time: SignalF64("time", 0.0),
+        }
+        ops = []
+        for kind, payload, dependencies in self.spec.operators:
+            if kind == "receive":
+                address, peer, signal = payload
+                ops.append(
+                    ReceiveSignal(
+                        address,
+                        peer,
+                        self.get_sig(signal_to_engine_id, signal),
+                        dependencies,
+                    )
+                )
+            elif kind == "send":
+                address, signal = payload
+                ops.append(
+                    SendSignal(
+                        address,
+                        self.get_sig(signal_to_engine_id, signal),
+                        dependencies,
+                    )
+                )
+            else:
+                ops.append(
+                    self._convert_op(
+                        payload,
+                        signal_to_engine_id,
+                        dependencies,
+                        self.spec.spiking_outputs,
+                    )
+                )
+
+        probes = {
+            i: Probe(
+                self.get_sig(signal_to_engine_id, signal),
+                time=signal_to_engine_id[self.model.time],
+                name=label,
+                sample_every=sample_every,
+            )
+            for i, (signal, label, sample_every) in self.spec.probes.items()
+        }
+        engine = Engine(
+            list(signal_to_engine_id.values()),
+            ops,
+            list(probes.values()),
+            thread_pool=self.thread_pool,
+            dt=self.dt,
+        )
+        return engine, probes
+
+
+def _run_worker(spec, conn):
+    try:
+        builder = _PartitionBuilder(
+            spec, ThreadPool(pool_size=max(2, os.cpu_count() or 1))
+        )
+        engine, probes = builder.build()
+        engine.reset()
+    except Exception as err:
+        conn.send(("error", err))
+        return
+    conn.send(("ok", None))
+
+    while True:
+        command, arg = conn.recv()
+        try:
+            if command == "run":
+                engine.run_steps(arg)
+                result = None
+            elif command == "reset":
+                engine.reset()
+                result = None
+            elif command == "data":
+                result = probes[arg].get_data()
+            elif command == "close":
+                break
+            else:
+                raise ValueError(f"Unknown command {command}.")
+        except Exception as err:
+            conn.send(("error", err))
+        else:
+            conn.send(("ok", result))
+
+
+def serve(address, authkey):
+    """Runs the worker for one partition of a `DistributedSimulator` that
+    lists `address` (a ``(host, port)`` tuple) in its `workers`.
+
+    Only a simulator with the same `authkey` can connect. The worker builds
+    the partition it is sent, runs it until the simulator is closed, and
+    returns.
+    """
+    with Listener(address, authkey=authkey) as listener:
+        with listener.accept() as conn:
+            _run_worker(conn.recv(), conn)
+
+
+class DistributedSimulator:
+    """Runs a model split into `n_partitions` worker processes.
+
+    Partition ``i`` listens for the signals it receives on ``hosts[i]`` (all
+    partitions run on localhost by default). Without a `base_port`, free ports
+    are picked automatically, which only works for local hosts.
+
+    By default, the workers are forked on the local machine. With `workers`,
+    partition ``i`` is sent to the worker serving at ``workers[i]`` (a
+    ``(host, port)`` tuple) instead, which is started on that host with::
+
+        NENGO_RS_AUTHKEY=<authkey> python -m nengo_rs.distributed host:port
+
+    The same `authkey` (bytes) must be given here. Each worker only receives
+    the operators and signals of its partition, which must be picklable for
+    remote workers (e.g., node functions cannot be lambdas).
+    """
+
+    def __init__(
+        self,
+        network,
+        n_partitions=2,
+        dt=0.001,
+        seed=None,
+        hosts=None,
+        base_port=None,
+        workers=None,
+        authkey=None,
+    ):
+        if n_partitions < 1:
+            raise ValueError("At least one partition is required.")
+        if workers is not None and len(workers) != n_partitions:
+            raise ValueError("Exactly one worker per partition must be given.")
+        if hosts is None:
+            hosts = (
+                ["127.0.0.1"] * n_partitions
+                if workers is None
+                else [host for host, _ in workers]
+            )
+        if len(hosts) != n_partitions:
+            raise ValueError("Exactly one host per partition must be given.")
+
+        self.seed = seed
+        self.model = Model(
+            dt=float(dt),
+            label="Nengo RS model",
+            decoder_cache=get_default_decoder_cache(),
+        )
+        self.model.build(network)
+
+        self._hosts = hosts
+        self._assignment = partition_operators(self.model.operators, n_partitions)
+        self._boundaries = self._find_boundaries(hosts, base_port)
+        self._probe_partitions = [
+            self._owner(_base(self.model.sig[probe]["in"]))
+            for probe in self.model.probes
+        ]
+        self._n_steps = 0
+
+        self._workers = []
+        if workers is None:
+            context = multiprocessing.get_context("fork")
+            for i in range(n_partitions):
+                conn, child_conn = context.Pipe()
+                process = context.Process(
+                    target=_run_worker,
+                    args=(self._partition_spec(i), child_conn),
+                    daemon=True,
+                )
+                process.start()
+                # Only the worker may hold its end, so that its exit is noticed.
+                child_conn.close()
+                self._workers.append((process, conn))
+        else:
+            for i, address in enumerate(workers):
+                conn = Client(tuple(address), authkey=authkey)
+                conn.send(self._partition_spec(i))
+                self._workers.append((None, conn))
+        for i in range(n_partitions):
+            self._receive(i)
+
+        self.data = _DistributedData(self)
+
+    def _partition_spec(self, index):
+        dg = BidirectionalDAG(operator_dependency_graph(self.model.operators))
+        toposorted_dg = toposort(dg.forward)
+
+        operators = []
+        received = {}
+        for boundary in sorted(
+            (b for b in self._boundaries if b.target == index), key=lambda b: b.key
+        ):
+            operators.append(
+                (
+                    "receive",
+                    (boundary.address, self._hosts[boundary.source], boundary.signal),
+                    [] if len(operators) == 0 else [len(operators) - 1],
+                )
+            )
+            received[boundary.signal] = len(operators) - 1
+
+        sent_before_update = {}
+        for boundary in self._boundaries:
+            if boundary.source == index and boundary.updated:
+                sent_before_update.setdefault(boundary.signal, []).append(
+                    len(operators)
+                )
+                operators.append(("send", (boundary.address, boundary.signal), []))
+
+        node_indices = {}
+        for op in toposorted_dg:
+            if self._assignment.get(op, index) != index:
+                continue
+            dependencies = {
+                node_indices[node] for node in dg.backward[op] if node in node_indices
+            }
+            dependencies.update(
+                received[_base(s)] for s in op.reads if _base(s) in received
+            )
+            for signal in op.updates:
+                dependencies.update(sent_before_update.get(_base(signal), []))
+            node_indices[op] = len(operators)
+            operators.append(("op", op, sorted(dependencies)))
+
+        for boundary in self._boundaries:
+            if boundary.source == index and not boundary.updated:
+                operators.append(
+                    (
+                        "send",
+                        (boundary.address, boundary.signal),
+                        sorted(node_indices[op] for op in boundary.writers),
+                    )
+                )
+
+        read = {s for op in node_indices for r in op.reads for s in (r, _base(r))}
+        spiking_outputs = {
+            op.output
+            for op in toposorted_dg
+            if isinstance(op, neurons.SimNeurons)
+            and op.neurons.spiking
+            and op.output in read
+        }
+        probes = {
+            i: (
+                self.model.sig[probe]["in"],
+                probe.label,
+                1
+                if probe.sample_every is None
+                else max(1, int(round(probe.sample_every / self.dt))),
+            )
+            for i, probe in enumerate(self.model.probes)
+            if self._probe_partitions[i] == index
+        }
+        return _PartitionSpec(
+            _PartitionModel(self.model.dt, self.model.step, self.model.time),
+            self.seed,
+            operators,
+            spiking_outputs,
+            probes,
+        )
+
+    def _owner(self, signal):
+        for op in self._assignment:
+            if any(_base(s) is signal for s in op.sets + op.incs + op.updates):
+                return self._assignment[op]
+        return 0
+
+    def _find_boundaries(self, hosts, base_port):
+        order = {op: i for i, op in enumerate(self._assignment)}
+        writers = {}
+        for op in self._assignment:
+            for signal in op.sets + op.incs + op.updates:
+                writers.setdefault(_base(signal), []).append(op)
+
+        boundaries = []
+        seen = set()
+        for op, target in self._assignment.items():
+            for signal in op.reads:
+                signal = _base(signal)
+                if signal not in writers:
+                    continue
+                source = self._assignment[writers[signal][0]]
+                if source == target or (signal, target) in seen:
+                    continue
+                seen.add((signal, target))
+                updated = any(
+                    _base(s) is signal for w in writers[signal] for s in w.updates
+                )
+                port = (
+                    _free_port(hosts[target])
+                    if base_port is None
+                    else base_port + len(boundaries)
+                )
+                boundaries.append(
+                    _Boundary(
+                        signal,
+                        source,
+                        target,
+                        f"{hosts[target]}:{port}",
+                        updated,
+                        writers[signal],
+                        -1 if updated else max(order[w] for w in writers[signal]),
+                    )
+                )
+        return boundaries
+
+    def _receive(self, index):
+        status, result = self._workers[index][1].recv()
+        if status == "error":
+            raise result
+        return result
+
+    def _broadcast(self, command, arg=None):
+        for _, conn in self._workers:
+            conn.send((command, arg))
+        # Read every reply before raising, so that none is left to be taken
+        # as the reply to the next command.
+        errors = []
+        for i in range(len(self._workers)):
+            try:
+                self._receive(i)
+            except Exception as err:
+                errors.append(err)
+        if errors:
+            raise errors[0]
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback):
+        self.close()
+
+    def close(self):
+        for process, conn in self._workers:
+            if process is None or process.is_alive():
+                try:
+                    conn.send(("close", None))
+                except OSError:
+                    pass
+            conn.close()
+            if process is not None:
+                process.join()
+        self._workers = []
+
+    @property
+    def dt(self):
+        return self.model.dt
+
+    def run(self, time_in_seconds):
+        self.run_steps(int(time_in_seconds / self.dt))
+
+    def run_steps(self, n_steps):
+        self._broadcast("run", n_steps)
+        self._n_steps += n_steps
+
+    def run_step(self):
+        self.run_steps(1)
+
+    def reset(self):
+        self._broadcast("reset")
+        self._n_steps = 0
+
+    def trange(self):
+        return (1 + np.arange(self._n_steps)) * self.dt
+
+
+class _DistributedData:
+    def __init__(self, sim):
+        self._sim = sim
+
+    def __getitem__(self, key):
+        index = self._sim.model.probes.index(key)
+        partition = self._sim._probe_partitions[index]
+        self._sim._workers[partition][1].send(("data", index))
+        return self._sim._receive(partition)
+
+
+def main():
+    parser = argparse.ArgumentParser(
+        description="Serves one partition of a nengo_rs DistributedSimulator. "
+        "The authentication key is read from NENGO_RS_AUTHKEY."
+    )
+    parser.add_argument("address", help="host:port to listen on")
+    args = parser.parse_args()
+    host, port = args.address.rsplit(":", 1)
+    serve((host, int(port)), os.environb[b"NENGO_RS_AUTHKEY"])
+
+
+if __name__ == "__main__":
+    main()
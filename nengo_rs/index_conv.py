@@ -13,9 +13,18 @@ def strides_to_steps(strides, base_strides):
     )
 
 
+def stop_of(start, size, step, base_size):
+    """End of a slice with `size` elements. A negative step reaching index 0
+    stops at None, as numpy counts negative ends from the back."""
+    stop = start + step * size
+    if step < 0:
+        return stop if stop >= 0 else None
+    return min(stop, base_size)
+
+
 def slices_from_signal(signal):
     return tuple(
-        slice(start, min(start + step * size, base_size), step)
+        slice(start, stop_of(start, size, step, base_size), step)
         for start, size, step, base_size in zip(
             offset_to_multiindex(signal.elemoffset, signal.base.elemstrides),
             signal.shape,
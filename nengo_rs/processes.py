@@ -0,0 +1,25 @@
+"""Processes that are implemented natively by the Nengo RS simulator."""
+
+import nengo
+import numpy as np
+
+from .nengo_rs import InputQueue
+
+
+class QueueInput(nengo.Process):
+    """Outputs the most recent value passed to `push`.
+
+    Values can be pushed between or during runs (e.g., from another thread) to
+    feed live external data into a simulation without a Python callback in
+    the loop. Until the first value is pushed, the output is zero.
+    """
+
+    def __init__(self, size_out, **kwargs):
+        super().__init__(default_size_in=0, default_size_out=size_out, **kwargs)
+        self.queue = InputQueue()
+
+    def push(self, value):
+        self.queue.push(np.asarray(value, dtype=np.float64))
+
+    def make_step(self, shape_in, shape_out, dt, rng, state):
+        raise NotImplementedError("QueueInput is only supported by nengo_rs.")
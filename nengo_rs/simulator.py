@@ -1,38 +1,109 @@
+import nengo
 from nengo.builder import Model
 from nengo.builder import operator as core_op
 from nengo.builder import neurons
 from nengo.builder import processes
-from nengo.builder.signal import SignalDict
+from nengo.builder import transforms
+from nengo.builder.signal import Signal, SignalDict
 from nengo.cache import get_default_decoder_cache
 from nengo.utils.graphs import BidirectionalDAG, toposort
 from nengo.utils.simulator import operator_dependency_graph
+import copy
 import numpy as np
+import os
+import time
 
 from .index_conv import slices_from_signal
+from .processes import QueueInput as QueueInputProcess
 from .nengo_rs import (
     Engine,
-    SignalArrayF64,
     SignalArrayViewF64,
     SignalF64,
     SignalU64,
+    make_signal,
     Reset,
     TimeUpdate,
     ElementwiseInc,
+    ConvInc,
     Copy,
     DotInc,
+    PresentInput,
     Probe,
+    QueueInput,
     SimNeurons,
     SimProcess,
     SimPyFunc,
+    Sink,
+    SparseDotInc,
+    SpikeDotInc,
+    ThreadPool,
 )
 
 
+# Transposed convolutions are only available in newer nengo versions.
+_CONV_OPS = tuple(
+    op
+    for op in (transforms.ConvInc, getattr(transforms, "ConvTransposeInc", None))
+    if op is not None
+)
+
+
+def _conv_geometry(conv, transpose=False):
+    """Arguments describing the layout of `conv` for a `ConvInc` operator.
+
+    Padding of a transposed convolution is the one of the forward convolution
+    from its output to its input.
+    """
+
+    def two_dimensional(values):
+        return (1,) * (2 - len(values)) + tuple(values)
+
+    input_size = two_dimensional(conv.input_shape.spatial_shape)
+    output_size = two_dimensional(conv.output_shape.spatial_shape)
+    kernel_size = two_dimensional(conv.kernel_size)
+    strides = two_dimensional(conv.strides)
+    if conv.padding == "same":
+        sizes = (output_size, input_size) if transpose else (input_size, output_size)
+        padding = tuple(
+            max((o - 1) * s + k - i, 0) // 2
+            for i, o, k, s in zip(*sizes, kernel_size, strides)
+        )
+    else:
+        padding = (0, 0)
+    return (
+        input_size,
+        output_size,
+        kernel_size,
+        strides,
+        padding,
+        conv.input_shape.n_channels,
+        conv.n_filters,
+        conv.channels_last,
+        getattr(conv, "groups", 1),
+        transpose,
+    )
+
+
 class Simulator:
+    def is_batched(self, signal):
+        return self.n_trials is not None and not signal.readonly
+
+    def batched(self, value):
+        value = np.asarray(value)
+        return np.tile(value, (self.n_trials,) + (1,) * value.ndim)
+
     def add_sig(self, signal_to_engine_id, signal):
         if signal is None or signal in signal_to_engine_id:
             pass
         elif signal.base is None or signal is signal.base:
-            signal_to_engine_id[signal] = SignalArrayF64(signal)
+            if self.is_batched(signal):
+                signal_to_engine_id[signal] = make_signal(
+                    signal.name, self.batched(signal.initial_value)
+                )
+            else:
+                signal_to_engine_id[signal] = make_signal(signal)
+            if signal.readonly:
+                signal_to_engine_id[signal].make_read_only()
         else:
             current = signal
             sliceinfo = slices_from_signal(signal)
@@ -48,6 +119,8 @@ class Simulator:
                     )
                     for a, b in zip(sliceinfo, slices_from_signal(current))
                 )
+            if self.is_batched(current.base):
+                sliceinfo = (slice(0, self.n_trials, 1),) + sliceinfo
             self.add_sig(signal_to_engine_id, current.base)
             try:
                 signal_to_engine_id[signal] = SignalArrayViewF64(
@@ -63,142 +136,468 @@ class Simulator:
         self.add_sig(signal_to_engine_id, signal)
         return signal_to_engine_id[signal]
 
-    def __init__(self, network, dt=0.001, seed=None):
-        self.model = Model(
+    def __init__(
+        self,
+        network,
+        dt=0.001,
+        seed=None,
+        n_trials=None,
+        thread_pool=None,
+        dt_controller=None,
+        rates=None,
+        probe_memory_budget=None,
+        sparsity_threshold=0.9,
+        resume_from=None,
+    ):
+        """Builds `network` for simulation with a timestep of `dt`.
+
+        With a `dt_controller`, the timestep may change between steps. It is
+        called as ``dt_controller(t, dt)`` before each step and returns the
+        timestep to use for that step. Neurons and the simulation time follow
+        the current timestep, but processes keep using the initial `dt`.
+
+        `rates` maps ensembles, nodes, or networks to a slower timestep (a
+        multiple of `dt`) at which their operators run. Their outputs are held
+        between their steps and their inputs are sampled when they run.
+
+        With a `probe_memory_budget` (in bytes), older probe samples exceeding
+        it are moved to temporary files until the data is accessed.
+
+        Constant weights of dot products with at least a `sparsity_threshold`
+        fraction of zeros are multiplied as sparse matrices. Use None to
+        always multiply them as dense matrices.
+
+        With `resume_from`, the simulation continues from a checkpoint file
+        written for the same network (see `set_checkpoints`). The probe data
+        only contains the samples taken after resuming.
+        """
+        if dt_controller is not None and rates:
+            raise ValueError("A dt_controller cannot be combined with rates.")
+        model = Model(
             dt=float(dt),
             label="Nengo RS model",
             decoder_cache=get_default_decoder_cache(),
         )
-        self.model.build(network)
+        model.build(network)
+        self._setup(
+            model,
+            seed=seed,
+            n_trials=n_trials,
+            thread_pool=thread_pool,
+            dt_signal=None if dt_controller is None else SignalF64("dt", float(dt)),
+            sparsity_threshold=sparsity_threshold,
+        )
+        self.dt_controller = dt_controller
+        self.probe_memory_budget = probe_memory_budget
+        self._times = []
+        self._periods = self._find_periods(rates or {})
 
-        signal_to_engine_id = {}
+        (
+            self._sig_to_ngine_id,
+            self.probe_mapping,
+            self._engine,
+        ) = self._build_engine({})
+        self.data = SimData(self)
+        print("initialized")
+
+        self._engine.prepare()
+        if resume_from is not None:
+            self._engine.resume(os.fspath(resume_from))
+
+    def _setup(
+        self,
+        model,
+        seed=None,
+        n_trials=None,
+        thread_pool=None,
+        dt_signal=None,
+        sparsity_threshold=0.9,
+    ):
+        """Sets the attributes used to convert operators of the built `model`.
+
+        Other builders of engines reuse `_convert_op` through this method.
+        """
+        self.model = model
+        self.seed = seed
+        self.n_trials = n_trials
+        self.thread_pool = thread_pool
+        self.sparsity_threshold = sparsity_threshold
+        self._dt_signal = dt_signal
+        self._diagonals = {}
+
+    def _find_periods(self, rates):
+        """Maps operators running slower than every step to their period.
+
+        All writers of a signal run at the slowest rate of any of them.
+        """
+        signal_periods = {}
+        for obj, dt in rates.items():
+            period = int(round(dt / self.dt))
+            if period < 1 or not np.isclose(period * self.dt, dt):
+                raise ValueError(f"Rate {dt} of {obj} is not a multiple of dt.")
+            if isinstance(obj, nengo.Network):
+                objs = obj.all_ensembles + obj.all_nodes
+            else:
+                objs = [obj]
+            for o in objs:
+                signals = list(self.model.sig[o].values())
+                if isinstance(o, nengo.Ensemble):
+                    signals += self.model.sig[o.neurons].values()
+                for signal in signals:
+                    if signal is not None:
+                        base = signal if signal.base is None else signal.base
+                        signal_periods[base] = max(
+                            period, signal_periods.get(base, 1)
+                        )
+
+        def writes(op):
+            return [
+                s if s.base is None else s.base for s in op.sets + op.incs + op.updates
+            ]
+
+        periods = {}
+        changed = True
+        while changed:
+            changed = False
+            for op in self.model.operators:
+                if isinstance(op, core_op.TimeUpdate):
+                    continue
+                period = max(
+                    [periods.get(op, 1)]
+                    + [signal_periods.get(s, 1) for s in writes(op)]
+                )
+                if period > 1:
+                    periods[op] = period
+                for s in writes(op):
+                    if signal_periods.get(s, 1) < period:
+                        signal_periods[s] = period
+                        changed = True
+
+        # Operators without outputs (e.g., sinks) run at the rate of their inputs.
+        for op in self.model.operators:
+            if not writes(op) and not isinstance(op, core_op.TimeUpdate):
+                period = max(
+                    [1]
+                    + [
+                        signal_periods.get(s if s.base is None else s.base, 1)
+                        for s in op.reads
+                    ]
+                )
+                if period > 1:
+                    periods[op] = period
+        return periods
+
+    def _build_engine(self, signal_to_engine_id, overridden=()):
         for signal_dict in self.model.sig.values():
             for signal in signal_dict.values():
                 self.add_sig(signal_to_engine_id, signal)
         x = SignalU64("step", 0)
         signal_to_engine_id[self.model.step] = x
         signal_to_engine_id[self.model.time] = SignalF64("time", 0.0)
-        self._sig_to_ngine_id = signal_to_engine_id
 
         dg = BidirectionalDAG(operator_dependency_graph(self.model.operators))
         toposorted_dg = toposort(dg.forward)
         node_indices = {node: idx for idx, node in enumerate(toposorted_dg)}
 
+        spiking_outputs = {
+            op.output
+            for op in toposorted_dg
+            if isinstance(op, neurons.SimNeurons) and op.neurons.spiking
+        }
+
         ops = []
         for op in toposorted_dg:
             dependencies = [node_indices[node] for node in dg.backward[op]]
-            if isinstance(op, core_op.Reset):
-                ops.append(
-                    Reset(
-                        np.asarray(op.value, dtype=np.float64),
-                        self.get_sig(signal_to_engine_id, op.dst),
-                        dependencies,
-                    )
+            ops.append(
+                self._convert_op(
+                    op,
+                    signal_to_engine_id,
+                    dependencies,
+                    spiking_outputs,
+                    self._periods.get(op, 1),
+                    overridden,
                 )
-            elif isinstance(op, core_op.TimeUpdate):
-                ops.append(
-                    TimeUpdate(
-                        dt,
-                        self.get_sig(signal_to_engine_id, self.model.step),
-                        self.get_sig(signal_to_engine_id, self.model.time),
-                        dependencies,
+            )
+
+        probe_mapping = {}
+        for probe in self.model.probes:
+            probe_mapping[probe] = Probe(
+                signal_to_engine_id[self.model.sig[probe]["in"]],
+                time=signal_to_engine_id[self.model.time],
+                name=probe.label,
+                sample_every=1
+                if probe.sample_every is None
+                else max(1, int(round(probe.sample_every / self.dt))),
+            )
+
+        signals = list(signal_to_engine_id.values())
+        if self._dt_signal is not None:
+            signals.append(self._dt_signal)
+        engine = Engine(
+            signals,
+            ops,
+            list(probe_mapping.values()),
+            thread_pool=self.thread_pool,
+            dt=self.dt,
+            probe_memory_budget=self.probe_memory_budget,
+            seed=self.seed,
+        )
+        return signal_to_engine_id, probe_mapping, engine
+
+    def _diagonal(self, A):
+        """Returns a signal with the diagonal of the constant matrix `A`.
+
+        Returns None if `A` is not a constant diagonal matrix. For a scalar
+        times the identity, the signal has a single element that broadcasts.
+        """
+        if A not in self._diagonals:
+            self._diagonals[A] = None
+            if A.readonly and A.ndim == 2 and A.shape[0] == A.shape[1] > 0:
+                diagonal = np.diag(A.initial_value)
+                if np.array_equal(A.initial_value, np.diag(diagonal)):
+                    if np.all(diagonal == diagonal[0]):
+                        diagonal = diagonal[:1]
+                    self._diagonals[A] = Signal(
+                        np.asarray(diagonal, dtype=np.float64),
+                        name=f"{A.name}.diagonal",
+                        readonly=True,
                     )
+        return self._diagonals[A]
+
+    def _is_sparse(self, A):
+        """Whether the constant matrix `A` is sparse enough to be multiplied
+        as a sparse matrix."""
+        if self.sparsity_threshold is None or not A.readonly or A.ndim != 2:
+            return False
+        if A.size == 0:
+            return False
+        zeros = 1.0 - np.count_nonzero(A.initial_value) / A.size
+        return zeros >= self.sparsity_threshold
+
+    def _convert_op(
+        self,
+        op,
+        signal_to_engine_id,
+        dependencies,
+        spiking_outputs,
+        period=1,
+        overridden=(),
+    ):
+        dt = self.dt * period
+        if isinstance(op, core_op.Reset):
+            return Reset(
+                np.asarray(op.value, dtype=np.float64),
+                self.get_sig(signal_to_engine_id, op.dst),
+                dependencies,
+                period=period,
+            )
+        elif isinstance(op, core_op.TimeUpdate):
+            return TimeUpdate(
+                self.dt,
+                self.get_sig(signal_to_engine_id, self.model.step),
+                self.get_sig(signal_to_engine_id, self.model.time),
+                dependencies,
+                dt_signal=self._dt_signal,
+            )
+        elif isinstance(op, core_op.ElementwiseInc):
+            return ElementwiseInc(
+                self.get_sig(signal_to_engine_id, op.Y),
+                self.get_sig(signal_to_engine_id, op.A),
+                self.get_sig(signal_to_engine_id, op.X),
+                dependencies,
+                period=period,
+            )
+        elif isinstance(op, core_op.Copy):
+            assert op.src_slice is None and op.dst_slice is None
+            return Copy(
+                op.inc,
+                self.get_sig(signal_to_engine_id, op.src),
+                self.get_sig(signal_to_engine_id, op.dst),
+                dependencies,
+                period=period,
+            )
+        elif isinstance(op, core_op.DotInc):
+            if self.is_batched(op.A):
+                raise NotImplementedError(
+                    "Batched simulation does not support learned transforms."
                 )
-            elif isinstance(op, core_op.ElementwiseInc):
-                ops.append(
-                    ElementwiseInc(
-                        self.get_sig(signal_to_engine_id, op.Y),
-                        self.get_sig(signal_to_engine_id, op.A),
-                        self.get_sig(signal_to_engine_id, op.X),
-                        dependencies,
-                    )
+            if getattr(op.A, "sparse", False):
+                return SparseDotInc(
+                    self.get_sig(signal_to_engine_id, op.A),
+                    self.get_sig(signal_to_engine_id, op.Y),
+                    self.get_sig(signal_to_engine_id, op.X),
+                    dependencies,
+                    period=period,
                 )
-            elif isinstance(op, core_op.Copy):
-                assert op.src_slice is None and op.dst_slice is None
-                ops.append(
-                    Copy(
-                        op.inc,
-                        self.get_sig(signal_to_engine_id, op.src),
-                        self.get_sig(signal_to_engine_id, op.dst),
-                        dependencies,
-                    )
+            diagonal = None if op.A in overridden else self._diagonal(op.A)
+            if diagonal is not None:
+                return ElementwiseInc(
+                    self.get_sig(signal_to_engine_id, op.Y),
+                    self.get_sig(signal_to_engine_id, diagonal),
+                    self.get_sig(signal_to_engine_id, op.X),
+                    dependencies,
+                    period=period,
                 )
-            elif isinstance(op, core_op.DotInc):
-                ops.append(
-                    DotInc(
-                        self.get_sig(signal_to_engine_id, op.Y),
-                        self.get_sig(signal_to_engine_id, op.A),
-                        self.get_sig(signal_to_engine_id, op.X),
-                        dependencies,
-                    )
+            if op.A not in overridden and self._is_sparse(op.A):
+                return SparseDotInc(
+                    np.asarray(op.A.initial_value, dtype=np.float64),
+                    self.get_sig(signal_to_engine_id, op.Y),
+                    self.get_sig(signal_to_engine_id, op.X),
+                    dependencies,
+                    period=period,
                 )
-            elif isinstance(op, neurons.SimNeurons):
-                signals = SignalDict()
-                op.init_signals(signals)
-                ops.append(
-                    SimNeurons(
-                        self.dt,
-                        op.neurons.step_math,
-                        [signals[s] for s in op.states]
-                        if hasattr(op, "states")
-                        else [],
-                        self.get_sig(signal_to_engine_id, op.J),
-                        self.get_sig(signal_to_engine_id, op.output),
-                        dependencies,
+            is_spike_input = (
+                self.n_trials is None
+                and op.A.ndim == 2
+                and (op.X in spiking_outputs or op.X.base in spiking_outputs)
+            )
+            return (SpikeDotInc if is_spike_input else DotInc)(
+                self.get_sig(signal_to_engine_id, op.Y),
+                self.get_sig(signal_to_engine_id, op.A),
+                self.get_sig(signal_to_engine_id, op.X),
+                dependencies,
+                period=period,
+            )
+        elif isinstance(op, _CONV_OPS):
+            if self.is_batched(op.W):
+                raise NotImplementedError(
+                    "Batched simulation does not support learned transforms."
+                )
+            transpose = not isinstance(op, transforms.ConvInc)
+            return ConvInc(
+                *_conv_geometry(op.conv, transpose=transpose),
+                self.get_sig(signal_to_engine_id, op.Y),
+                self.get_sig(signal_to_engine_id, op.W),
+                self.get_sig(signal_to_engine_id, op.X),
+                dependencies,
+                period=period,
+            )
+        elif isinstance(op, neurons.SimNeurons):
+            signals = SignalDict()
+            op.init_signals(signals)
+            return SimNeurons(
+                dt,
+                op.neurons.step_math,
+                [
+                    self.batched(signals[s])
+                    if self.is_batched(op.output)
+                    else signals[s]
+                    for s in op.states
+                ]
+                if hasattr(op, "states")
+                else [],
+                self.get_sig(signal_to_engine_id, op.J),
+                self.get_sig(signal_to_engine_id, op.output),
+                dependencies,
+                period=period,
+                dt_signal=self._dt_signal,
+            )
+        elif isinstance(op, processes.SimProcess) and isinstance(
+            op.process, nengo.processes.PresentInput
+        ):
+            inputs = np.asarray(op.process.inputs, dtype=np.float64)
+            return PresentInput(
+                op.mode == "inc",
+                inputs.reshape(len(inputs), -1),
+                float(op.process.presentation_time),
+                dt,
+                self.get_sig(signal_to_engine_id, op.t),
+                self.get_sig(signal_to_engine_id, op.output),
+                dependencies,
+                period=period,
+            )
+        elif isinstance(op, processes.SimProcess) and isinstance(
+            op.process, QueueInputProcess
+        ):
+            return QueueInput(
+                op.process.queue,
+                self.get_sig(signal_to_engine_id, op.output),
+                dependencies,
+                period=period,
+            )
+        elif isinstance(op, processes.SimProcess):
+            signals = SignalDict()
+            op.init_signals(signals)
+            shape_in = (0,) if op.input is None else op.input.shape
+            shape_out = op.output.shape
+            if self.is_batched(op.output):
+                step_fns = [
+                    op.process.make_step(
+                        shape_in,
+                        shape_out,
+                        dt,
+                        np.random.RandomState(
+                            None if self.seed is None else self.seed + i
+                        ),
+                        {k: np.copy(signals[s]) for k, s in op.state.items()},
                     )
+                    for i in range(self.n_trials)
+                ]
+                step_fn = lambda t, *x, step_fns=step_fns: np.stack(
+                    [
+                        np.asarray(f(t, *(xs[i] for xs in x)), dtype=float)
+                        for i, f in enumerate(step_fns)
+                    ]
                 )
-            elif isinstance(op, processes.SimProcess):
-                signals = SignalDict()
-                op.init_signals(signals)
-                shape_in = (0,) if op.input is None else op.input.shape
-                shape_out = op.output.shape
+            else:
                 rng = None
                 state = {k: signals[s] for k, s in op.state.items()}
-                step_fn = op.process.make_step(shape_in, shape_out, self.dt, rng, state)
-                ops.append(
-                    SimProcess(
-                        op.mode == "inc",
-                        lambda *args, step_fn=step_fn: np.asarray(
-                            step_fn(*args), dtype=float
-                        ),
-                        self.get_sig(signal_to_engine_id, op.t),
-                        self.get_sig(signal_to_engine_id, op.output),
-                        None
-                        if op.input is None
-                        else self.get_sig(signal_to_engine_id, op.input),
-                        dependencies,
-                    )
+                step_fn = op.process.make_step(
+                    shape_in, shape_out, dt, rng, state
                 )
-            elif isinstance(op, core_op.SimPyFunc):
-                ops.append(
-                    SimPyFunc(
-                        lambda *args, op=op: np.asarray(op.fn(*args), dtype=float),
-                        self.get_sig(signal_to_engine_id, op.output),
-                        None
-                        if op.t is None
-                        else self.get_sig(signal_to_engine_id, op.t),
-                        None
-                        if op.x is None
-                        else self.get_sig(signal_to_engine_id, op.x),
-                        dependencies,
-                    )
+            return SimProcess(
+                op.mode == "inc",
+                lambda *args, step_fn=step_fn: np.asarray(
+                    step_fn(*args), dtype=float
+                ),
+                self.get_sig(signal_to_engine_id, op.t),
+                self.get_sig(signal_to_engine_id, op.output),
+                None
+                if op.input is None
+                else self.get_sig(signal_to_engine_id, op.input),
+                dependencies,
+                period=period,
+            )
+        elif isinstance(op, core_op.SimPyFunc) and op.output is None:
+            if op.x is not None and self.is_batched(op.x):
+                fn = lambda *args, op=op: [op.fn(*args[:-1], x) for x in args[-1]]
+            else:
+                fn = op.fn
+            return Sink(
+                fn,
+                None
+                if op.t is None
+                else self.get_sig(signal_to_engine_id, op.t),
+                None
+                if op.x is None
+                else self.get_sig(signal_to_engine_id, op.x),
+                dependencies,
+                period=period,
+            )
+        elif isinstance(op, core_op.SimPyFunc):
+            if op.x is not None and self.is_batched(op.x):
+                fn = lambda *args, op=op: np.stack(
+                    [
+                        np.asarray(op.fn(*args[:-1], x), dtype=float)
+                        for x in args[-1]
+                    ]
                 )
             else:
-                raise Exception(f"missing: {op}")
-
-        self.probe_mapping = {}
-        for probe in self.model.probes:
-            self.probe_mapping[probe] = Probe(
-                signal_to_engine_id[self.model.sig[probe]["in"]]
+                fn = lambda *args, op=op: np.asarray(op.fn(*args), dtype=float)
+            return SimPyFunc(
+                fn,
+                self.get_sig(signal_to_engine_id, op.output),
+                None
+                if op.t is None
+                else self.get_sig(signal_to_engine_id, op.t),
+                None
+                if op.x is None
+                else self.get_sig(signal_to_engine_id, op.x),
+                dependencies,
+                period=period,
             )
-
-        self._engine = Engine(
-            list(signal_to_engine_id.values()), ops, list(self.probe_mapping.values())
-        )
-        self.data = SimData(self)
-        print("initialized")
-
-        self._engine.reset()
+        else:
+            raise Exception(f"missing: {op}")
 
     def __enter__(self):
         return self
@@ -213,15 +612,191 @@ class Simulator:
     def dt(self):
         return self.model.dt
 
-    def run(self, time_in_seconds):
+    def run(self, time_in_seconds, time_limit=None):
+        """Runs the simulation for `time_in_seconds` and returns the number of
+        steps completed.
+
+        With a `time_limit` in seconds of wall-clock time, the run stops at
+        the first step boundary after it is exceeded, so that it can be
+        continued later, e.g., from a checkpoint.
+        """
         print("run")
-        n_steps = int(time_in_seconds / self.dt)
-        self._engine.run_steps(n_steps)
+        if self.dt_controller is None:
+            n_steps = int(time_in_seconds / self.dt)
+            return self._engine.run_steps(n_steps, time_limit)
+        deadline = None if time_limit is None else time.monotonic() + time_limit
+        end = self._time() + time_in_seconds
+        n_steps = 0
+        while self._time() < end:
+            if deadline is not None and time.monotonic() >= deadline:
+                break
+            self.run_step()
+            n_steps += 1
+        return n_steps
 
     def run_step(self):
+        if self.dt_controller is not None:
+            dt = float(self.dt_controller(self._time(), self._dt_signal.get()))
+            if not dt > 0:
+                raise ValueError(f"Timestep must be positive, got {dt}.")
+            self._dt_signal.set(dt)
         self._engine.run_step()
+        if self.dt_controller is not None:
+            self._times.append(self._time())
+
+    def _time(self):
+        return self._sig_to_ngine_id[self.model.time].get()
+
+    def _constant_signals(self, exclude=()):
+        written = {
+            signal.base
+            for op in self.model.operators
+            for signal in op.sets + op.incs + op.updates
+        }
+        return {
+            signal: engine_signal
+            for signal, engine_signal in self._sig_to_ngine_id.items()
+            if signal is signal.base and signal not in written and signal not in exclude
+        }
+
+    def clone(self):
+        """Returns a simulator of the same model with its own simulation state.
+
+        Signals that are never written by any operator (weights, encoders, ...)
+        are shared with this simulator instead of being copied.
+        """
+        clone = copy.copy(self)
+        if self._dt_signal is not None:
+            clone._dt_signal = SignalF64("dt", self.dt)
+            clone._times = []
+        (
+            clone._sig_to_ngine_id,
+            clone.probe_mapping,
+            clone._engine,
+        ) = self._build_engine(self._constant_signals())
+        clone.data = SimData(clone)
+        clone._engine.reset()
+        return clone
+
+    def run_sweep(self, overrides, time_in_seconds):
+        """Runs a variant of the model for each dict in `overrides` concurrently.
+
+        Each dict maps builder signals (e.g. ``sim.model.sig[conn]["weights"]``)
+        to the initial value used in that variant. Constant signals that are not
+        overridden are shared between all variants. Returns a list with a dict of
+        probe data for each variant.
+        """
+        variants = []
+        for override in overrides:
+            signal_to_engine_id = self._constant_signals(exclude=override)
+            for signal, value in override.items():
+                if signal is not signal.base:
+                    raise ValueError(f"Cannot override signal view {signal.name}.")
+                value = np.asarray(value, dtype=np.float64)
+                signal_to_engine_id[signal] = make_signal(
+                    signal.name,
+                    self.batched(value) if self.is_batched(signal) else value,
+                )
+            _, probe_mapping, engine = self._build_engine(
+                signal_to_engine_id, overridden=override
+            )
+            engine.reset()
+            variants.append((probe_mapping, engine))
+
+        Engine.run_steps_concurrently(
+            [engine for _, engine in variants], int(time_in_seconds / self.dt)
+        )
+        return [
+            {probe: p.get_data() for probe, p in probe_mapping.items()}
+            for probe_mapping, _ in variants
+        ]
+
+    def add_pre_step_hook(self, hook):
+        """Calls ``hook(step)`` before each step, after the previous probes."""
+        self._engine.add_pre_step_hook(hook)
+
+    def add_post_step_hook(self, hook):
+        """Calls ``hook(step)`` after each step, once its probes have recorded."""
+        self._engine.add_post_step_hook(hook)
+
+    def save_probes(self, path):
+        """Writes the data of all probes, keyed by label, and their sample
+        times, keyed by ``<label>.time``, to an ``.npz`` file."""
+        self._engine.save_probes(path)
+
+    def verify(self, n_steps=10):
+        """Runs `n_steps` trial steps with extra checks and returns a list of
+        the problems found, e.g. non-finite values. The state of the
+        simulation is restored afterwards."""
+        return self._engine.verify(n_steps)
+
+    def warn_about_slow_callbacks(self, fraction=0.5):
+        """Logs a warning with the ``nengo_rs`` logger for each node whose
+        function consistently takes longer than `fraction` of the timestep,
+        which keeps the model from running in real time."""
+        self._engine.warn_about_slow_callbacks(fraction)
+
+    def inject_fault(self, signal, kind, fraction=0.0, start=0.0, end=None, std=0.0):
+        """Alters the builder `signal` (e.g. ``sim.model.sig[ens.neurons]["out"]``)
+        each step after the operators writing it, without changing the model.
+
+        With `kind` ``"drop_spikes"`` or ``"zero_rows"``, a `fraction` of the
+        elements or rows is zeroed. With ``"perturb"``, Gaussian noise with
+        standard deviation `std` is added from `start` up to `end` seconds.
+        """
+        if signal is not signal.base:
+            raise ValueError(f"Cannot inject a fault into signal view {signal.name}.")
+        self._engine.inject_fault(
+            self._sig_to_ngine_id[signal],
+            kind,
+            fraction=fraction,
+            start=int(round(start / self.dt)),
+            end=None if end is None else int(round(end / self.dt)),
+            std=std,
+        )
+
+    def set_checkpoints(self, path, interval=1.0):
+        """Writes a checkpoint of the simulation state to `path` after every
+        `interval` simulated seconds of a run, replacing the previous one, or
+        stops with ``None``. Pass the path as `resume_from` to continue."""
+        self._engine.set_checkpoints(
+            None if path is None else os.fspath(path),
+            max(1, int(round(interval / self.dt))),
+        )
+
+    def save_checkpoint(self, path):
+        """Writes a checkpoint of the current simulation state to `path`."""
+        self._engine.save_checkpoint(os.fspath(path))
+
+    def record_step(self, step):
+        """Records the inputs and outputs of each operator on the step with
+        the given index (counted from zero since the last reset) of later
+        runs, or stops recording with ``None``."""
+        self._engine.record_step(step)
+
+    def step_record(self):
+        """Returns the record of the step chosen with `record_step`, or
+        ``None`` until a run has reached it."""
+        return self._engine.step_record()
+
+    def replay_step(self, record, reference=None, tolerance=0.0):
+        """Steps each operator in `record` once from its recorded inputs and
+        returns the outputs that differ from `reference` (or the recorded
+        ones) by more than `tolerance`. The state of the simulation is
+        restored afterwards."""
+        return self._engine.replay_step(record, reference, tolerance)
+
+    def memory_report(self, time_in_seconds=0.0):
+        """Bytes used by the signals, operators, and probes of the model.
+
+        The ``total`` is the expected memory use after running for another
+        `time_in_seconds`, so that it can be checked before a long run.
+        """
+        return self._engine.memory_report(int(time_in_seconds / self.dt))
 
     def trange(self):
+        if self.dt_controller is not None:
+            return np.array(self._times)
         step = self._sig_to_ngine_id[self.model.step].get()
         return np.arange(1, step + 1) * self.dt
 
@@ -233,3 +808,7 @@ class SimData:
     def __getitem__(self, key):
         return self._sim.probe_mapping[key].get_data()
 
+    def with_times(self, key):
+        """Returns a tuple of the sample times and the data of probe `key`."""
+        return self._sim.probe_mapping[key].get_data(with_times=True)
+
@@ -0,0 +1,29 @@
+"""Utilities for stepping native operators without building a model."""
+
+from .nengo_rs import Engine, Probe, make_signal
+
+
+def step_operator(make_op, signals, n_steps=1, outputs=None):
+    """Steps a single operator `n_steps` times and returns its outputs.
+
+    `signals` maps names to initial values, from which the signals are
+    created with `make_signal`. The operator is created by calling `make_op`
+    with these signals as keyword arguments, for example::
+
+        step_operator(
+            lambda target, a, b: CircularConv(False, False, target, a, b, []),
+            {"target": np.zeros(4), "a": a, "b": b},
+        )
+
+    Returns a dict mapping the names in `outputs` (all signals by default) to
+    an array of their values after each step.
+    """
+    engine_signals = {name: make_signal(name, value) for name, value in signals.items()}
+    op = make_op(**engine_signals)
+    probes = {
+        name: Probe(engine_signals[name], name=name)
+        for name in (signals if outputs is None else outputs)
+    }
+    engine = Engine(list(engine_signals.values()), [op], list(probes.values()))
+    engine.run_steps(n_steps)
+    return {name: probe.get_data() for name, probe in probes.items()}
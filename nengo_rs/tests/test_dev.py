@@ -1,6 +1,8 @@
 import nengo
 import nengo_rs
 import numpy as np
+import pytest
+import time
 
 
 def test_dev():
@@ -14,3 +16,480 @@ def test_dev():
 
     assert np.allclose(sim.trange(), np.arange(0.0, 1.0, dt) + dt)
     assert np.allclose(sim.data[probe], 0.5)
+
+
+def test_batched_trials():
+    with nengo.Network() as model:
+        node = nengo.Node(0.5)
+        ens = nengo.Ensemble(10, 1)
+        nengo.Connection(node, ens)
+        probe = nengo.Probe(node)
+        ens_probe = nengo.Probe(ens, synapse=0.01)
+
+    dt = 0.001
+    with nengo_rs.Simulator(model, dt=dt, n_trials=3) as sim:
+        sim.run(0.1)
+
+    assert sim.data[probe].shape == (100, 3, 1)
+    assert np.allclose(sim.data[probe], 0.5)
+    assert np.allclose(sim.data[ens_probe][:, 0], sim.data[ens_probe][:, 1])
+
+
+def test_run_sweep():
+    with nengo.Network() as model:
+        node = nengo.Node(0.5)
+        out = nengo.Node(size_in=1)
+        conn = nengo.Connection(node, out, transform=1.0, synapse=None)
+        probe = nengo.Probe(out)
+
+    with nengo_rs.Simulator(model) as sim:
+        weights = sim.model.sig[conn]["weights"]
+        data = sim.run_sweep([{weights: 1.0}, {weights: 2.0}], 0.01)
+
+    assert np.allclose(data[0][probe], 0.5)
+    assert np.allclose(data[1][probe], 1.0)
+
+
+def test_clone_has_independent_state():
+    with nengo.Network() as model:
+        node = nengo.Node(lambda t: t)
+        probe = nengo.Probe(node)
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.run(0.01)
+        clone = sim.clone()
+        clone.run(0.02)
+
+    assert len(sim.data[probe]) == 10
+    assert len(clone.data[probe]) == 20
+    assert np.allclose(clone.data[probe][:, 0], clone.trange())
+
+
+def test_simulators_share_thread_pool():
+    with nengo.Network() as model:
+        node = nengo.Node(lambda t: t)
+        probe = nengo.Probe(node)
+
+    thread_pool = nengo_rs.ThreadPool(pool_size=2)
+    with nengo_rs.Simulator(model, thread_pool=thread_pool) as sim_a:
+        with nengo_rs.Simulator(model, thread_pool=thread_pool) as sim_b:
+            sim_a.run(0.01)
+            sim_b.run(0.01)
+
+    assert np.allclose(sim_a.data[probe], sim_b.data[probe])
+
+
+def test_distributed_simulator_matches_simulator():
+    with nengo.Network(seed=0) as model:
+        stim = nengo.Node(lambda t: np.sin(10 * t))
+        a = nengo.Ensemble(20, 1)
+        b = nengo.Ensemble(20, 1)
+        nengo.Connection(stim, a)
+        nengo.Connection(a, b)
+        probes = [
+            nengo.Probe(a, synapse=0.01),
+            nengo.Probe(b, synapse=0.01),
+            nengo.Probe(b, synapse=0.01, sample_every=0.005),
+        ]
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.run(0.1)
+    with nengo_rs.DistributedSimulator(model, n_partitions=2) as dist_sim:
+        dist_sim.run(0.1)
+
+    assert np.allclose(dist_sim.trange(), sim.trange())
+    for probe in probes:
+        assert np.allclose(dist_sim.data[probe], sim.data[probe])
+    assert len(dist_sim.data[probes[2]]) < len(dist_sim.data[probes[1]])
+
+
+def test_raises_errors_of_python_functions():
+    def fail(t):
+        raise ZeroDivisionError()
+
+    with nengo.Network() as model:
+        nengo.Node(fail)
+
+    with nengo_rs.Simulator(model) as sim:
+        with pytest.raises(nengo_rs.SimulationError) as excinfo:
+            sim.run_step()
+
+    assert isinstance(excinfo.value.__cause__, ZeroDivisionError)
+
+
+def test_diagonal_transforms_are_elementwise():
+    with nengo.Network() as model:
+        node = nengo.Node([0.5, 0.25])
+        scaled = nengo.Node(size_in=2)
+        doubled = nengo.Node(size_in=2)
+        nengo.Connection(node, scaled, transform=np.diag([2.0, 3.0]), synapse=None)
+        nengo.Connection(node, doubled, transform=2 * np.eye(2), synapse=None)
+        probes = [nengo.Probe(scaled), nengo.Probe(doubled)]
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.run(0.01)
+
+    assert np.allclose(sim.data[probes[0]], [1.0, 0.75])
+    assert np.allclose(sim.data[probes[1]], [1.0, 0.5])
+
+
+@pytest.mark.parametrize("sparsity_threshold", [0.5, None])
+def test_sparse_transforms_match_dense(sparsity_threshold):
+    transform = np.zeros((3, 4))
+    transform[0, 1] = 2.0
+    transform[2, 3] = -1.0
+    with nengo.Network() as model:
+        node = nengo.Node([0.5, 0.25, -1.0, 1.5])
+        out = nengo.Node(size_in=3)
+        nengo.Connection(node, out, transform=transform, synapse=None)
+        probe = nengo.Probe(out)
+
+    with nengo_rs.Simulator(model, sparsity_threshold=sparsity_threshold) as sim:
+        sim.run(0.01)
+
+    assert np.allclose(sim.data[probe], [0.5, 0.0, -1.5])
+
+
+def test_scipy_sparse_transforms():
+    scipy_sparse = pytest.importorskip("scipy.sparse")
+    weights = scipy_sparse.csr_matrix(([2.0, -1.0], ([0, 2], [1, 3])), shape=(3, 4))
+    with nengo.Network() as model:
+        node = nengo.Node([0.5, 0.25, -1.0, 1.5])
+        out = nengo.Node(size_in=3)
+        nengo.Connection(
+            node, out, transform=nengo.Sparse((3, 4), init=weights), synapse=None
+        )
+        probe = nengo.Probe(out)
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.run(0.01)
+
+    assert np.allclose(sim.data[probe], [0.5, 0.0, -1.5])
+
+
+@pytest.mark.parametrize("padding", ["valid", "same"])
+def test_convolution_matches_nengo(padding):
+    conv = nengo.Convolution(
+        n_filters=3,
+        input_shape=(6, 5, 2),
+        kernel_size=(3, 3),
+        strides=(2, 1),
+        padding=padding,
+        init=nengo.dists.Uniform(-1, 1),
+    )
+    with nengo.Network(seed=0) as model:
+        node = nengo.Node(np.linspace(-1, 1, conv.input_shape.size))
+        out = nengo.Node(size_in=conv.output_shape.size)
+        nengo.Connection(node, out, transform=conv, synapse=None)
+        probe = nengo.Probe(out)
+
+    with nengo.Simulator(model) as ref_sim:
+        ref_sim.run(0.002)
+    with nengo_rs.Simulator(model) as sim:
+        sim.run(0.002)
+
+    assert np.allclose(sim.data[probe], ref_sim.data[probe])
+
+
+def test_present_input():
+    inputs = [[0.1, 0.2], [0.3, 0.4], [0.5, 0.6]]
+    with nengo.Network() as model:
+        process = nengo.processes.PresentInput(inputs, presentation_time=0.002)
+        node = nengo.Node(process)
+        probe = nengo.Probe(node)
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.run(0.008)
+
+    expected = np.concatenate([np.repeat(inputs, 2, axis=0)] * 2)[:8]
+    assert np.allclose(sim.data[probe], expected)
+
+
+def test_queue_input():
+    process = nengo_rs.QueueInput(size_out=2)
+    with nengo.Network() as model:
+        node = nengo.Node(process)
+        probe = nengo.Probe(node)
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.run_step()
+        process.push([0.1, 0.2])
+        process.push([0.3, 0.4])
+        sim.run(0.002)
+
+    assert np.allclose(sim.data[probe], [[0.0, 0.0], [0.3, 0.4], [0.3, 0.4]])
+
+
+def test_output_sink():
+    received = []
+    with nengo.Network() as model:
+        stim = nengo.Node([0.5, 0.25])
+        sink = nengo.Node(lambda t, x: received.append((t, x)), size_in=2)
+        nengo.Connection(stim, sink, synapse=None)
+
+    with nengo_rs.Simulator(model, rates={sink: 0.002}) as sim:
+        sim.run(0.006)
+
+    assert np.allclose([t for t, _ in received], [0.001, 0.003, 0.005])
+    assert np.allclose([x for _, x in received], [0.5, 0.25])
+
+
+def test_step_hooks():
+    calls = []
+    with nengo.Network() as model:
+        stim = nengo.Node(lambda t: t)
+        p = nengo.Probe(stim)
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.add_pre_step_hook(lambda step: calls.append(("pre", step)))
+        sim.add_post_step_hook(
+            lambda step: calls.append(("post", step, len(sim.data[p])))
+        )
+        sim.run(0.002)
+
+    assert calls == [("pre", 0), ("post", 0, 1), ("pre", 1), ("post", 1, 2)]
+
+
+def test_run_steps_chunked():
+    with nengo.Network() as model:
+        stim = nengo.Node(lambda t: t)
+        nengo.Probe(stim, label="stim")
+
+    with nengo_rs.Simulator(model) as sim:
+        chunks = list(sim._engine.run_steps_chunked(5, 2, with_data=True))
+
+    assert [steps for steps, _ in chunks] == [2, 4, 5]
+    assert [len(data["stim"]) for _, data in chunks] == [2, 2, 1]
+    assert np.allclose(chunks[-1][1]["stim"], [[0.005]])
+
+
+def test_state_dict_transfers_state_between_engines():
+    with nengo.Network() as model:
+        stim = nengo.Node(lambda t: t)
+        ens = nengo.Ensemble(10, 1)
+        nengo.Connection(stim, ens, synapse=0.01)
+
+    with nengo_rs.Simulator(model) as sim, nengo_rs.Simulator(model) as other:
+        sim.run(0.01)
+        state = sim._engine.state_dict()
+        assert state["time"] == pytest.approx(0.01)
+
+        other._engine.load_state_dict(state)
+        loaded = other._engine.state_dict()
+        assert loaded.keys() == state.keys()
+        for name, value in state.items():
+            assert np.array_equal(loaded[name], value)
+
+        with pytest.raises(KeyError):
+            other._engine.load_state_dict({"missing": 0.0})
+
+
+def test_checked_integer_increments_fail_instead_of_overflowing():
+    from nengo_rs.nengo_rs import ElementwiseIncI64, Engine, make_signal
+
+    counter = make_signal("counter", np.array([2**62], dtype=np.int64))
+    big = make_signal("big", np.array([2**62], dtype=np.int64))
+    two = make_signal("two", np.array([2], dtype=np.int64))
+    engine = Engine(
+        [counter, big, two],
+        [ElementwiseIncI64(counter, big, two, [], checked=True)],
+        [],
+    )
+
+    with pytest.raises(OverflowError, match="overflowed"):
+        engine.run_steps(1)
+    assert np.array_equal(engine.state_dict()["counter"], [2**62])
+
+
+@pytest.mark.parametrize("dtype", [np.float64, np.float32, np.int64, np.complex128])
+def test_state_dict_keeps_the_dtype_of_array_signals(dtype):
+    signal = nengo_rs.nengo_rs.make_signal("x", np.array([1.5, 2.5], dtype=dtype))
+    engine = nengo_rs.nengo_rs.Engine([signal], [], [])
+
+    assert engine.state_dict()["x"].dtype == dtype
+    engine.load_state_dict({"x": np.array([3, 4], dtype=dtype)})
+    assert np.array_equal(engine.state_dict()["x"], [3, 4])
+
+
+def test_state_dict_round_trips_complex_signals():
+    signal = nengo_rs.nengo_rs.make_signal("x", np.array([1 + 2j, -3j]))
+    engine = nengo_rs.nengo_rs.Engine([signal], [], [])
+    other = nengo_rs.nengo_rs.Engine(
+        [nengo_rs.nengo_rs.make_signal("x", np.zeros(2, dtype=np.complex128))], [], []
+    )
+
+    other.load_state_dict(engine.state_dict())
+    assert np.array_equal(other.state_dict()["x"], [1 + 2j, -3j])
+
+
+def test_read_only_signals_are_left_alone_when_restoring_state():
+    with nengo.Network() as model:
+        stim = nengo.Node([0.5, 0.25])
+        out = nengo.Node(size_in=2)
+        conn = nengo.Connection(stim, out, transform=[[1.0, 2.0], [3.0, 4.0]])
+
+    with nengo_rs.Simulator(model) as sim:
+        weights = sim.model.sig[conn]["weights"]
+        assert weights.readonly
+        sim.record_step(1)
+        sim.run_steps(3)
+        assert sim.verify(2) == []
+        assert sim.replay_step(sim.step_record()) == []
+
+        state = sim._engine.state_dict()
+        assert weights.name not in state
+        sim._engine.load_state_dict(state)
+        sim.run_steps(1)
+
+
+def test_save_probes_saves_the_sample_times_of_each_probe(tmp_path):
+    with nengo.Network() as model:
+        node = nengo.Node(0.5)
+        nengo.Probe(node, label="fast")
+        nengo.Probe(node, label="slow", sample_every=0.005)
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.run(0.01)
+        sim.save_probes(str(tmp_path / "probes.npz"))
+
+    archive = np.load(str(tmp_path / "probes.npz"))
+    assert np.allclose(archive["fast.time"], np.arange(1, 11) * 0.001)
+    assert np.allclose(archive["slow.time"], [0.005, 0.01])
+    assert archive["fast"].shape == (10, 1)
+    assert archive["slow"].shape == (2, 1)
+
+
+def test_save_probes_saves_the_statistics_of_statistics_probes(tmp_path):
+    from nengo_rs.nengo_rs import Engine, Probe, make_signal
+
+    signal = make_signal("x", np.array([1.0, -2.0]))
+    engine = Engine([signal], [], [Probe.statistics(signal, name="stats")])
+    engine.run_steps(3)
+    engine.save_probes(str(tmp_path / "probes.npz"))
+
+    archive = np.load(str(tmp_path / "probes.npz"))
+    assert archive["stats.count"] == 3
+    assert np.allclose(archive["stats.mean"], [1.0, -2.0])
+    assert np.allclose(archive["stats.variance"], [0.0, 0.0])
+    assert "stats" not in archive
+    assert "stats.time" not in archive
+
+
+def test_memory_report_predicts_probe_growth():
+    with nengo.Network() as model:
+        node = nengo.Node([0.5, 0.25])
+        probe = nengo.Probe(node, label="node")
+
+    with nengo_rs.Simulator(model) as sim:
+        before = sim.memory_report(0.01)
+        sim.run(0.01)
+        after = sim.memory_report()
+
+    # Two float64 values and the time per sample.
+    assert before["probes"]["node"]["bytes_per_step"] == 24
+    assert after["probes"]["node"]["bytes"] == 240
+    assert after["total"] == pytest.approx(before["total"])
+    assert all(bytes >= 0 for bytes in after["signals"].values())
+
+
+def test_slow_node_functions_are_logged(caplog):
+    with nengo.Network() as model:
+        fast = nengo.Node(lambda t: t, label="fast")
+        slow = nengo.Node(
+            lambda t, x: time.sleep(0.002) or x, size_in=1, label="slow"
+        )
+        nengo.Connection(fast, slow, synapse=None)
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.warn_about_slow_callbacks(0.5)
+        with caplog.at_level("WARNING", logger="nengo_rs"):
+            sim.run(0.1)
+
+    assert len(caplog.records) == 1
+    assert '"slow"' in caplog.records[0].getMessage()
+
+
+def test_run_stops_when_the_time_limit_is_exceeded():
+    with nengo.Network() as model:
+        node = nengo.Node(lambda t: time.sleep(0.005) or t)
+        probe = nengo.Probe(node)
+
+    with nengo_rs.Simulator(model) as sim:
+        n_steps = sim.run(10.0, time_limit=0.05)
+        assert 0 < n_steps < 10000
+        assert sim.data[probe].shape == (n_steps, 1)
+        assert sim.run(0.002) == 2
+
+
+def test_resuming_from_a_checkpoint_continues_the_run(tmp_path):
+    with nengo.Network(seed=0) as model:
+        stim = nengo.Node(np.sin)
+        ens = nengo.Ensemble(10, 1)
+        nengo.Connection(stim, ens)
+        probe = nengo.Probe(ens, synapse=0.01)
+
+    path = tmp_path / "checkpoint.bin"
+    with nengo_rs.Simulator(model) as sim:
+        sim.set_checkpoints(path, interval=0.005)
+        sim.run(0.007)
+        sim.run(0.003)
+        expected = sim.data[probe][5:]
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.set_checkpoints(path, interval=0.005)
+        sim.run(0.007)
+
+    with nengo_rs.Simulator(model, resume_from=path) as resumed:
+        resumed.run(0.005)
+        assert np.allclose(resumed.data[probe], expected)
+
+
+def test_replaying_a_recorded_step_matches_the_run():
+    with nengo.Network() as model:
+        stim = nengo.Node(np.sin)
+        ens = nengo.Ensemble(10, 1)
+        nengo.Connection(stim, ens)
+        probe = nengo.Probe(ens, synapse=0.01)
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.record_step(5)
+        sim.run(0.004)
+        assert sim.step_record() is None
+        sim.run(0.004)
+        record = sim.step_record()
+        assert record.step == 5
+        assert any(op is not None for op in record.operators())
+        data = sim.data[probe].copy()
+
+        assert sim.replay_step(record) == []
+
+        sim.run(0.002)
+    assert np.all(sim.data[probe][:8] == data)
+
+
+def test_verify_reports_non_finite_values_and_restores_state():
+    with nengo.Network() as model:
+        node = nengo.Node(lambda t: np.inf if t > 0.002 else 1.0)
+        out = nengo.Node(size_in=1)
+        nengo.Connection(node, out, synapse=None)
+        probe = nengo.Probe(out)
+
+    with nengo_rs.Simulator(model) as sim:
+        problems = sim.verify(5)
+        assert len(problems) > 0
+        assert all("non-finite" in problem for problem in problems)
+
+        sim.run(0.002)
+    assert sim.data[probe].shape == (2, 1)
+    assert np.allclose(sim.data[probe], 1.0)
+
+
+def test_dropping_all_spikes_silences_the_ensemble():
+    with nengo.Network() as model:
+        ens = nengo.Ensemble(10, 1, intercepts=nengo.dists.Uniform(-1, -0.5))
+        probe = nengo.Probe(ens.neurons)
+
+    with nengo_rs.Simulator(model) as sim:
+        sim.inject_fault(sim.model.sig[ens.neurons]["out"], "drop_spikes", fraction=1.0)
+        sim.run(0.01)
+    assert np.all(sim.data[probe] == 0.0)
@@ -30,3 +30,8 @@ def test_slices_from_signal():
         slice(0, 5, 1),
         slice(4, 20, 1),
     )
+    assert slices_from_signal(base[::-1, 10:2:-2, 1:]) == (
+        slice(19, None, -1),
+        slice(10, 2, -2),
+        slice(1, 20, 1),
+    )
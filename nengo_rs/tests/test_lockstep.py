@@ -0,0 +1,30 @@
+from nengo_rs.nengo_rs import BuildError, ElementwiseInc, Engine, Probe, make_signal
+import numpy as np
+import pytest
+
+
+def accumulator(name, input):
+    target = make_signal(name, np.zeros(1))
+    one = make_signal("one", np.ones(1))
+    probe = Probe(target, name=name)
+    engine = Engine([target, input, one], [ElementwiseInc(target, input, one, [])], [probe])
+    return engine, target, probe
+
+
+def test_lockstep_copies_coupled_signals_after_each_step():
+    input = make_signal("input", np.ones(1))
+    source, total, _ = accumulator("total", input)
+    received = make_signal("received", np.zeros(1))
+    target, _, probe = accumulator("total_of_totals", received)
+
+    Engine.run_lockstep([source, target], [(total, received)], 3)
+
+    assert np.allclose(probe.get_data(), [[0.0], [1.0], [3.0]])
+
+
+def test_lockstep_rejects_coupling_into_written_signals():
+    input = make_signal("input", np.ones(1))
+    engine, total, _ = accumulator("total", input)
+
+    with pytest.raises(BuildError):
+        Engine.run_lockstep([engine], [(input, total)], 1)
@@ -0,0 +1,25 @@
+from nengo_rs.nengo_rs import DotInc, ElementwiseInc
+from nengo_rs.testing import step_operator
+import numpy as np
+
+
+def test_step_operator_returns_outputs_of_each_step():
+    outputs = step_operator(
+        lambda target, a, x: ElementwiseInc(target, a, x, []),
+        {"target": np.zeros(2), "a": np.array([1.0, 2.0]), "x": np.ones(2)},
+        n_steps=3,
+        outputs=["target"],
+    )
+
+    assert list(outputs) == ["target"]
+    assert np.allclose(outputs["target"], [[1.0, 2.0], [2.0, 4.0], [3.0, 6.0]])
+
+
+def test_step_operator_probes_all_signals_by_default():
+    outputs = step_operator(
+        lambda target, w, x: DotInc(target, w, x, []),
+        {"target": np.zeros(2), "w": np.eye(2), "x": np.array([3.0, 4.0])},
+    )
+
+    assert outputs.keys() == {"target", "w", "x"}
+    assert np.allclose(outputs["target"], [[3.0, 4.0]])
@@ -0,0 +1,189 @@
+use futures::executor::ThreadPool;
+use std::io;
+
+#[cfg(target_os = "linux")]
+pub fn available_cores() -> io::Result<Vec<usize>> {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((0..libc::CPU_SETSIZE as usize)
+            .filter(|&core| libc::CPU_ISSET(core, &set))
+            .collect())
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(core: usize) -> io::Result<()> {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        libc::CPU_SET(core, &mut set);
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn available_cores() -> io::Result<Vec<usize>> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "CPU affinity is not supported on this platform",
+    ))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_core: usize) -> io::Result<()> {
+    available_cores().map(|_| ())
+}
+
+/// OS scheduling priority of worker threads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThreadPriority {
+    /// Nice value from -20 (highest priority) to 19 (lowest). Raising the
+    /// priority usually requires privileges.
+    Nice(i32),
+    /// Real-time FIFO scheduling with a priority from 1 to 99, where
+    /// permitted.
+    RealTime(i32),
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_current_thread_priority(priority: ThreadPriority) -> io::Result<()> {
+    let result = unsafe {
+        match priority {
+            ThreadPriority::Nice(nice) if (-20..=19).contains(&nice) => {
+                let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+                libc::setpriority(libc::PRIO_PROCESS, tid, nice)
+            }
+            ThreadPriority::RealTime(level) if (1..=99).contains(&level) => {
+                let param = libc::sched_param {
+                    sched_priority: level,
+                };
+                libc::sched_setscheduler(0, libc::SCHED_FIFO, &param)
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Thread priority {:?} is out of range.", priority),
+                ))
+            }
+        }
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_current_thread_priority(_priority: ThreadPriority) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "Thread priorities are not supported on this platform",
+    ))
+}
+
+/// Creates a thread pool whose `i`-th worker is pinned to `cores[i % cores.len()]`,
+/// if given, and runs with the given `priority`. The priority is
+/// tried on a separate thread first, so that it fails here instead of in the
+/// workers.
+pub fn worker_thread_pool(
+    pool_size: Option<usize>,
+    cores: Option<Vec<usize>>,
+    priority: Option<ThreadPriority>,
+) -> io::Result<ThreadPool> {
+    if let Some(cores) = &cores {
+        if cores.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "At least one core must be given.",
+            ));
+        }
+        let available = available_cores()?;
+        if let Some(core) = cores.iter().find(|core| !available.contains(core)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Core {} is not available to this process.", core),
+            ));
+        }
+    }
+    if let Some(priority) = priority {
+        std::thread::spawn(move || set_current_thread_priority(priority))
+            .join()
+            .expect("Failed to try the thread priority.")?;
+    }
+
+    let mut builder = ThreadPool::builder();
+    if let Some(pool_size) = pool_size.or_else(|| cores.as_ref().map(Vec::len)) {
+        builder.pool_size(pool_size);
+    }
+    builder
+        .after_start(move |i| {
+            if let Some(cores) = &cores {
+                pin_current_thread(cores[i % cores.len()]).expect("Failed to pin worker thread.");
+            }
+            if let Some(priority) = priority {
+                set_current_thread_priority(priority).expect("Failed to set the thread priority.");
+            }
+        })
+        .create()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use futures::channel::oneshot;
+    use futures::executor::block_on;
+
+    #[test]
+    fn pins_current_thread() {
+        let core = available_cores().unwrap()[0];
+        std::thread::spawn(move || {
+            pin_current_thread(core).unwrap();
+            assert_eq!(available_cores().unwrap(), vec![core]);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn worker_thread_pool_runs_workers_on_given_core() {
+        let core = *available_cores().unwrap().last().unwrap();
+        let thread_pool = worker_thread_pool(None, Some(vec![core]), None).unwrap();
+        let (sender, receiver) = oneshot::channel();
+        thread_pool.spawn_ok(async move {
+            sender.send(available_cores().unwrap()).unwrap();
+        });
+        assert_eq!(block_on(receiver).unwrap(), vec![core]);
+    }
+
+    #[test]
+    fn worker_thread_pool_runs_workers_with_given_priority() {
+        let thread_pool =
+            worker_thread_pool(Some(1), None, Some(ThreadPriority::Nice(19))).unwrap();
+        let (sender, receiver) = oneshot::channel();
+        thread_pool.spawn_ok(async move {
+            let tid = unsafe { libc::syscall(libc::SYS_gettid) } as libc::id_t;
+            sender
+                .send(unsafe { libc::getpriority(libc::PRIO_PROCESS, tid) })
+                .unwrap();
+        });
+        assert_eq!(block_on(receiver).unwrap(), 19);
+    }
+
+    #[test]
+    fn worker_thread_pool_rejects_invalid_priorities() {
+        let error = worker_thread_pool(None, None, Some(ThreadPriority::RealTime(0))).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn worker_thread_pool_rejects_unavailable_cores() {
+        assert!(worker_thread_pool(None, Some(vec![libc::CPU_SETSIZE as usize]), None).is_err());
+        assert!(worker_thread_pool(None, Some(vec![]), None).is_err());
+    }
+}
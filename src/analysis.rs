@@ -0,0 +1,118 @@
+use crate::operator::OperatorNode;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Operators of the same type and signal shapes that would likely run faster
+/// fused into a single operator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeSuggestion {
+    pub operator: String,
+    /// Shapes of the signals read and then written by each operator.
+    pub shapes: Vec<Vec<usize>>,
+    /// Indices of the operators in the engine.
+    pub indices: Vec<usize>,
+    /// Mean time to step one of the operators.
+    pub mean_step: Duration,
+    /// Scheduling overhead per step that fusing would save.
+    pub estimated_savings: Duration,
+}
+
+/// Groups operators by type, signal shapes, and period. Groups of several
+/// operators taking less time to step than the scheduling `overhead` per
+/// operator are suggested, the largest estimated savings first.
+pub fn suggest_merges(
+    operators: &[Arc<OperatorNode>],
+    step_durations: &[Duration],
+    overhead: Duration,
+) -> Vec<MergeSuggestion> {
+    struct Group {
+        operator: String,
+        shapes: Vec<Vec<usize>>,
+        period: u64,
+        indices: Vec<usize>,
+    }
+    let mut groups: Vec<Group> = vec![];
+    for (i, node) in operators.iter().enumerate() {
+        let operator = &node.operator;
+        let name = operator.name();
+        let shapes: Vec<Vec<usize>> = operator
+            .reads()
+            .iter()
+            .chain(operator.writes().iter())
+            .map(|s| s.shape().to_vec())
+            .collect();
+        match groups
+            .iter_mut()
+            .find(|g| g.operator == name && g.shapes == shapes && g.period == node.period)
+        {
+            Some(group) => group.indices.push(i),
+            None => groups.push(Group {
+                operator: name,
+                shapes,
+                period: node.period,
+                indices: vec![i],
+            }),
+        }
+    }
+
+    let mut suggestions: Vec<MergeSuggestion> = groups
+        .into_iter()
+        .filter(|group| group.indices.len() > 1)
+        .map(|group| {
+            let n = group.indices.len();
+            let total: Duration = group.indices.iter().map(|i| step_durations[*i]).sum();
+            MergeSuggestion {
+                operator: group.operator,
+                shapes: group.shapes,
+                indices: group.indices,
+                mean_step: total / n as u32,
+                estimated_savings: overhead * (n - 1) as u32,
+            }
+        })
+        .filter(|suggestion| suggestion.mean_step < overhead)
+        .collect();
+    suggestions.sort_by_key(|s| std::cmp::Reverse(s.estimated_savings));
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::CopyOp;
+    use crate::signal::ArraySignal;
+    use ndarray::prelude::*;
+    use std::marker::PhantomData;
+
+    fn copy(len: usize) -> Arc<OperatorNode> {
+        let signal = |name: &str| {
+            Arc::new(ArraySignal::from_array(
+                name.to_string(),
+                Array::zeros(IxDyn(&[len])),
+            ))
+        };
+        Arc::new(OperatorNode::new(
+            Box::new(CopyOp {
+                inc: false,
+                src: signal("src"),
+                dst: signal("dst"),
+                data_type: PhantomData::<ArrayD<f64>>,
+            }),
+            vec![],
+        ))
+    }
+
+    #[test]
+    fn it_suggests_groups_of_cheap_operators_with_equal_shapes() {
+        let operators = vec![copy(2), copy(3), copy(2), copy(2), copy(3)];
+        let nanos = |n| Duration::from_nanos(n);
+        let durations = vec![nanos(10), nanos(10), nanos(20), nanos(30), nanos(500)];
+
+        let suggestions = suggest_merges(&operators, &durations, nanos(100));
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].indices, vec![0, 2, 3]);
+        assert_eq!(suggestions[0].shapes, vec![vec![2], vec![2]]);
+        assert_eq!(suggestions[0].mean_step, nanos(20));
+        assert_eq!(suggestions[0].estimated_savings, nanos(200));
+    }
+}
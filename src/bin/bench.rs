@@ -0,0 +1,204 @@
+//! Runs a synthetic operator graph without Python operators and reports the
+//! achieved steps per second.
+//!
+//! The graph consists of `depth` layers of `width` signals with `size`
+//! elements each. Every signal beyond the first layer is reset and then
+//! computed from two signals of the previous layer by an operator drawn from
+//! the mix of `dot`, `elementwise`, and `copy` operators.
+//!
+//! Usage: `bench [--width N] [--depth N] [--size N] [--steps N]
+//! [--mix dot=1,elementwise=1,copy=1]`
+
+use ndarray::ArrayD;
+use nengo_rs::engine::Engine;
+use nengo_rs::operator::{CopyOp, DotInc, ElementwiseInc, Operator, OperatorNode, Reset};
+use nengo_rs::signal::{ArraySignal, Signal};
+use std::env;
+use std::marker::PhantomData;
+use std::process;
+use std::sync::Arc;
+use std::time::Instant;
+
+struct Config {
+    width: usize,
+    depth: usize,
+    size: usize,
+    steps: i64,
+    mix: Vec<(String, usize)>,
+}
+
+impl Config {
+    fn from_args() -> Result<Self, String> {
+        let mut config = Config {
+            width: 16,
+            depth: 8,
+            size: 64,
+            steps: 1000,
+            mix: vec![
+                ("dot".to_string(), 1),
+                ("elementwise".to_string(), 1),
+                ("copy".to_string(), 1),
+            ],
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}.", arg))?;
+            let parse = |value: &str| {
+                value
+                    .parse::<usize>()
+                    .map_err(|e| format!("Invalid value for {}: {}", arg, e))
+            };
+            match arg.as_str() {
+                "--width" => config.width = parse(&value)?,
+                "--depth" => config.depth = parse(&value)?,
+                "--size" => config.size = parse(&value)?,
+                "--steps" => config.steps = parse(&value)? as i64,
+                "--mix" => {
+                    config.mix = value
+                        .split(',')
+                        .map(|part| match part.split_once('=') {
+                            Some((name, weight)) => Ok((name.to_string(), parse(weight)?)),
+                            None => Err(format!("Invalid mix entry {}.", part)),
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                _ => return Err(format!("Unknown argument {}.", arg)),
+            }
+        }
+        if config.width == 0 || config.depth == 0 || config.size == 0 {
+            return Err("Width, depth, and size must be at least 1.".to_string());
+        }
+        if let Some((name, _)) = config
+            .mix
+            .iter()
+            .find(|(name, _)| !["dot", "elementwise", "copy"].contains(&name.as_str()))
+        {
+            return Err(format!("Unknown operator {} in mix.", name));
+        }
+        if config.mix.iter().all(|(_, weight)| *weight == 0) {
+            return Err("The operator mix must not be empty.".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Picks operators in proportion to their weight in the mix.
+    fn operator_kind(&self, index: usize) -> &str {
+        let total: usize = self.mix.iter().map(|(_, weight)| weight).sum();
+        let mut slot = index % total;
+        for (name, weight) in self.mix.iter() {
+            if slot < *weight {
+                return name;
+            }
+            slot -= weight;
+        }
+        unreachable!()
+    }
+}
+
+fn signal(name: String, shape: &[usize], value: f64) -> Arc<ArraySignal<f64>> {
+    Arc::new(ArraySignal::from_array(
+        name,
+        ArrayD::from_elem(shape, value),
+    ))
+}
+
+fn node(operator: Box<dyn Operator + Sync + Send>, dependencies: Vec<usize>) -> Arc<OperatorNode> {
+    Arc::new(OperatorNode::new(operator, dependencies))
+}
+
+fn build(config: &Config) -> Result<Engine, String> {
+    let size = config.size;
+    let mut signals: Vec<Arc<dyn Signal + Send + Sync>> = vec![];
+    let mut operators = vec![];
+    let weights = signal("weights".to_string(), &[size, size], 1. / size as f64);
+    let gains = signal("gains".to_string(), &[size], 0.5);
+    signals.push(Arc::clone(&weights) as Arc<_>);
+    signals.push(Arc::clone(&gains) as Arc<_>);
+
+    let mut layer: Vec<(Arc<ArraySignal<f64>>, Option<usize>)> = (0..config.width)
+        .map(|i| (signal(format!("input{}", i), &[size], 1.), None))
+        .collect();
+    for (s, _) in layer.iter() {
+        signals.push(Arc::clone(s) as Arc<_>);
+    }
+
+    for depth in 1..config.depth {
+        let mut next = Vec::with_capacity(config.width);
+        for i in 0..config.width {
+            let target = signal(format!("layer{}_{}", depth, i), &[size], 0.);
+            signals.push(Arc::clone(&target) as Arc<_>);
+            operators.push(node(
+                Box::new(Reset {
+                    value: ArrayD::zeros(vec![size]),
+                    target: Arc::clone(&target),
+                }),
+                vec![],
+            ));
+            let reset = operators.len() - 1;
+
+            let (source, source_op) = &layer[i];
+            let (other, other_op) = &layer[(i + 1) % config.width];
+            let dependencies: Vec<usize> = vec![Some(reset), *source_op, *other_op]
+                .into_iter()
+                .flatten()
+                .collect();
+            let operator: Box<dyn Operator + Sync + Send> =
+                match config.operator_kind(depth * config.width + i) {
+                    "dot" => Box::new(DotInc {
+                        target: Arc::clone(&target),
+                        left: Arc::clone(&weights),
+                        right: Arc::clone(source),
+                        transpose_left: false,
+                    }),
+                    "elementwise" => Box::new(ElementwiseInc {
+                        target: Arc::clone(&target),
+                        left: Arc::clone(&gains),
+                        right: Arc::clone(other),
+                        checked: false,
+                    }),
+                    _ => Box::new(CopyOp {
+                        inc: true,
+                        src: Arc::clone(source),
+                        dst: Arc::clone(&target),
+                        data_type: PhantomData,
+                    }),
+                };
+            operators.push(node(operator, dependencies));
+            next.push((target, Some(operators.len() - 1)));
+        }
+        layer = next;
+    }
+
+    Engine::new(signals, operators, vec![]).map_err(|e| e.to_string())
+}
+
+fn main() {
+    let config = Config::from_args().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(2);
+    });
+    let engine = build(&config).unwrap_or_else(|err| {
+        eprintln!("Building the operator graph failed: {}", err);
+        process::exit(1);
+    });
+    engine.reset();
+
+    let start = Instant::now();
+    if let Err(err) = engine.run_steps(config.steps) {
+        eprintln!("Simulation failed: {:?}", err);
+        process::exit(1);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    println!(
+        "{} steps of {}x{} operators ({} elements) in {:.3} s: {:.1} steps/s",
+        config.steps,
+        config.width,
+        config.depth,
+        config.size,
+        elapsed,
+        config.steps as f64 / elapsed
+    );
+}
@@ -1,4 +1,12 @@
+use crate::binding::exceptions::{ShapeMismatchError, SimulationError};
+use crate::engine::{BuildError, RunError};
+use crate::operator::{ErrorCause, OperatorError};
+use pyo3::exceptions as exc;
+use pyo3::prelude::*;
+use pyo3::AsPyRef;
+
 pub mod engine;
+pub mod exceptions;
 pub mod operator;
 pub mod probe;
 pub mod signal;
@@ -6,3 +14,44 @@ pub mod signal;
 trait Wrapper<T> {
     fn get(&self) -> &T;
 }
+
+impl From<BuildError> for PyErr {
+    fn from(err: BuildError) -> PyErr {
+        match err {
+            BuildError::InconsistentShapes(_) => {
+                PyErr::new::<ShapeMismatchError, _>(err.to_string())
+            }
+            err => PyErr::new::<exceptions::BuildError, _>(err.to_string()),
+        }
+    }
+}
+
+impl From<RunError> for PyErr {
+    fn from(err: RunError) -> PyErr {
+        match err {
+            RunError::Operator(err) => err.into(),
+            RunError::Interrupted(err) => err,
+        }
+    }
+}
+
+impl From<OperatorError> for PyErr {
+    fn from(err: OperatorError) -> PyErr {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        match err.cause {
+            ErrorCause::Python(cause) => {
+                let err = PyErr::new::<SimulationError, _>(format!("{} failed.", err.operator));
+                let instance = err.to_object(py);
+                match instance.as_ref(py).setattr("__cause__", cause) {
+                    Ok(()) => PyErr::from_instance(instance.as_ref(py)),
+                    Err(err) => err,
+                }
+            }
+            ErrorCause::Io(_) => PyErr::new::<SimulationError, _>(err.to_string()),
+            ErrorCause::Shape(_) => PyErr::new::<ShapeMismatchError, _>(err.to_string()),
+            ErrorCause::Overflow(_) => PyErr::new::<exc::OverflowError, _>(err.to_string()),
+            ErrorCause::ReadOnly(_) => PyErr::new::<exceptions::BuildError, _>(err.to_string()),
+        }
+    }
+}
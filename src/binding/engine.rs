@@ -1,45 +1,857 @@
+use crate::affinity::{worker_thread_pool, ThreadPriority};
+use crate::binding::exceptions::ShapeMismatchError;
 use crate::binding::operator::PyOperator;
 use crate::binding::probe::PyProbe;
 use crate::binding::signal::PySignal;
 use crate::binding::Wrapper;
-use crate::engine::Engine;
+use crate::checkpoint::Checkpoint;
+use crate::engine::{default_pool_size, Engine, StepHook};
+use crate::fault::{Fault, FaultKind};
+use crate::lockstep::{Coupling, Lockstep};
+use crate::metrics::MetricsServer;
+use crate::replay::{StepRecord, Value};
+use crate::signal::{self, ArraySignal, ScalarSignal, Signal, SignalAccess};
+use futures::executor::ThreadPool;
+use ndarray::{ArrayD, IxDyn};
+use numpy::{c64, Element, IntoPyArray, PyArrayDyn};
+use pyo3::exceptions as exc;
 use pyo3::prelude::*;
+use pyo3::types::{PyDict, PySlice};
+use pyo3::AsPyRef;
 use pyo3::PyClass;
+use pyo3::PyIterProtocol;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+
+#[pyclass(name = ThreadPool)]
+pub struct PyThreadPool {
+    thread_pool: ThreadPool,
+    pool_size: usize,
+}
+
+#[pymethods]
+impl PyThreadPool {
+    /// Workers are pinned to the given `cores` and run with the given
+    /// `priority`: a nice value, or a real-time priority from 1 to 99 if
+    /// `realtime`.
+    #[new]
+    #[args(
+        pool_size = "None",
+        cores = "None",
+        priority = "None",
+        realtime = "false"
+    )]
+    fn new(
+        pool_size: Option<usize>,
+        cores: Option<Vec<usize>>,
+        priority: Option<i32>,
+        realtime: bool,
+    ) -> PyResult<Self> {
+        if pool_size == Some(0) {
+            return Err(PyErr::new::<exc::ValueError, _>(
+                "Thread pool size must be at least 1.",
+            ));
+        }
+        let priority = match (priority, realtime) {
+            (Some(priority), false) => Some(ThreadPriority::Nice(priority)),
+            (Some(priority), true) => Some(ThreadPriority::RealTime(priority)),
+            (None, false) => None,
+            (None, true) => {
+                return Err(PyErr::new::<exc::ValueError, _>(
+                    "A real-time thread pool needs a priority.",
+                ))
+            }
+        };
+        let n_threads = pool_size
+            .or_else(|| cores.as_ref().map(Vec::len))
+            .unwrap_or_else(default_pool_size);
+        let thread_pool =
+            worker_thread_pool(pool_size, cores, priority).map_err(|e| match e.kind() {
+                io::ErrorKind::InvalidInput => PyErr::new::<exc::ValueError, _>(e.to_string()),
+                io::ErrorKind::PermissionDenied => {
+                    PyErr::new::<exc::PermissionError, _>(e.to_string())
+                }
+                _ => PyErr::new::<exc::OSError, _>(e.to_string()),
+            })?;
+        Ok(Self {
+            thread_pool,
+            pool_size: n_threads,
+        })
+    }
+}
+
+#[pyclass(name = ChunkedRun)]
+pub struct PyChunkedRun {
+    engine: Py<PyEngine>,
+    n_steps: i64,
+    chunk: i64,
+    steps_done: i64,
+    /// Number of samples of each probe already yielded, if data is yielded.
+    offsets: Option<Vec<usize>>,
+}
+
+#[pyproto]
+impl PyIterProtocol for PyChunkedRun {
+    fn __iter__(slf: PyRef<Self>) -> Py<Self> {
+        slf.into()
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> PyResult<Option<PyObject>> {
+        if slf.steps_done >= slf.n_steps {
+            return Ok(None);
+        }
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let n_steps = slf.chunk.min(slf.n_steps - slf.steps_done);
+        let engine = slf.engine.clone_ref(py);
+        let engine = engine.as_ref(py).borrow();
+        engine.engine.run_steps(n_steps)?;
+        slf.steps_done += n_steps;
+
+        let offsets = match &mut slf.offsets {
+            Some(offsets) => offsets,
+            None => return Ok(Some(slf.steps_done.to_object(py))),
+        };
+        let data = PyDict::new(py);
+        for (probe, offset) in engine.probes.iter().zip(offsets.iter_mut()) {
+            let probe = probe.as_ref(py).borrow();
+            let samples = probe.data_array(py)?;
+            let len = samples.len()?;
+            let start = (*offset).min(len);
+            *offset = len;
+            let slice = PySlice::new(py, start as isize, len as isize, 1);
+            data.set_item(probe.name(), samples.get_item(slice)?)?;
+        }
+        Ok(Some((slf.steps_done, data).to_object(py)))
+    }
+}
+
+fn value_to_py(py: Python, value: &Value) -> PyObject {
+    match value {
+        Value::ArrayF64(value) => value.clone().into_pyarray(py).to_object(py),
+        Value::ArrayF32(value) => value.clone().into_pyarray(py).to_object(py),
+        Value::ArrayI64(value) => value.clone().into_pyarray(py).to_object(py),
+        Value::ArrayC64(value) => value.clone().into_pyarray(py).to_object(py),
+        Value::ArrayU64(value) => value.clone().into_pyarray(py).to_object(py),
+        Value::F64(value) => value.to_object(py),
+        Value::U64(value) => value.to_object(py),
+    }
+}
+
+fn values_to_py(py: Python, values: &[(String, Option<Value>)]) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    for (name, value) in values.iter() {
+        if let Some(value) = value {
+            dict.set_item(name, value_to_py(py, value))?;
+        }
+    }
+    Ok(dict.to_object(py))
+}
+
+/// Signal values each operator read and wrote on one step, see
+/// `Engine.record_step`.
+#[pyclass(name = StepRecord)]
+pub struct PyStepRecord {
+    record: StepRecord,
+}
+
+#[pymethods]
+impl PyStepRecord {
+    #[getter]
+    fn step(&self) -> u64 {
+        self.record.step
+    }
+
+    /// Returns a list with, for each operator of the engine, `None` if it was
+    /// inactive on the step, or a dict with its `operator` name and dicts
+    /// of its `inputs` and `outputs` keyed by signal name.
+    fn operators(&self, py: Python) -> PyResult<Vec<PyObject>> {
+        self.record
+            .operators
+            .iter()
+            .map(|record| match record {
+                Some(record) => {
+                    let result = PyDict::new(py);
+                    result.set_item("operator", &record.operator)?;
+                    result.set_item("inputs", values_to_py(py, &record.inputs)?)?;
+                    result.set_item("outputs", values_to_py(py, &record.outputs)?)?;
+                    Ok(result.to_object(py))
+                }
+                None => Ok(py.None()),
+            })
+            .collect()
+    }
+}
+
+enum SignalValue<'a> {
+    ArrayF64(&'a ArraySignal<f64>, ArrayD<f64>),
+    ArrayF32(&'a ArraySignal<f32>, ArrayD<f32>),
+    ArrayI64(&'a ArraySignal<i64>, ArrayD<i64>),
+    ArrayC64(&'a ArraySignal<c64>, ArrayD<c64>),
+    F64(&'a ScalarSignal<f64>, f64),
+    U64(&'a ScalarSignal<u64>, u64),
+}
+
+/// Value of an array signal in a state dict, which leaves out views and
+/// read-only signals.
+fn array_state<'py, T: Element + Copy + Debug + Send + Sync + 'static>(
+    py: Python<'py>,
+    signal: &ArraySignal<T>,
+) -> Option<&'py PyArrayDyn<T>> {
+    if signal.is_view() || signal.is_read_only() {
+        return None;
+    }
+    let value = signal.read().clone_array();
+    Some(
+        value
+            .into_shape(IxDyn(signal.shape()))
+            .unwrap()
+            .into_pyarray(py),
+    )
+}
+
+fn extract_array_state<T: Element + Debug + Send + Sync + 'static>(
+    signal: &ArraySignal<T>,
+    name: &str,
+    value: &PyAny,
+) -> PyResult<ArrayD<T>> {
+    if signal.is_read_only() {
+        return Err(PyErr::new::<exc::ValueError, _>(format!(
+            "Signal `{}` is read-only.",
+            name
+        )));
+    }
+    let value = value.extract::<&PyArrayDyn<T>>()?.to_owned_array();
+    if value.shape() != signal.shape() {
+        return Err(PyErr::new::<ShapeMismatchError, _>(format!(
+            "Value of shape {:?} does not fit signal `{}` of shape {:?}.",
+            value.shape(),
+            name,
+            signal.shape()
+        )));
+    }
+    Ok(value)
+}
+
+fn resume(engine: &mut Engine, path: &str) -> PyResult<()> {
+    Ok(engine.resume(&Checkpoint::load(Path::new(path))?)?)
+}
 
 #[pyclass(name = Engine)]
 pub struct PyEngine {
     engine: Engine,
+    probes: Vec<Py<PyProbe>>,
+    metrics_server: Option<MetricsServer>,
+}
+
+impl PyEngine {
+    fn check_probe_names(&self, py: Python) -> PyResult<()> {
+        let mut names = HashSet::new();
+        for probe in self.probes.iter() {
+            let probe = probe.as_ref(py).borrow();
+            if !names.insert(probe.name().clone()) {
+                return Err(PyErr::new::<exc::ValueError, _>(format!(
+                    "Several probes are named `{}`.",
+                    probe.name()
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[pymethods]
 impl PyEngine {
     #[new]
-    fn new(signals: &PyAny, operators: &PyAny, probes: &PyAny) -> PyResult<Self> {
+    #[args(
+        thread_pool = "None",
+        dt = "None",
+        probe_memory_budget = "None",
+        seed = "None",
+        unique_signal_names = "false",
+        resume_from = "None"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        signals: &PyAny,
+        operators: &PyAny,
+        probes: &PyAny,
+        thread_pool: Option<&PyThreadPool>,
+        dt: Option<f64>,
+        probe_memory_budget: Option<usize>,
+        seed: Option<u64>,
+        unique_signal_names: bool,
+        resume_from: Option<&str>,
+    ) -> PyResult<Self> {
         fn py_cells_to_pure_rust<T: PyClass + Wrapper<Arc<U>>, U: ?Sized>(
             cells: &Vec<&PyCell<T>>,
         ) -> Vec<Arc<U>> {
             cells.iter().map(|c| Arc::clone(c.borrow().get())).collect()
         }
 
+        let signals = py_cells_to_pure_rust::<PySignal, _>(&signals.extract()?);
+        let operators = py_cells_to_pure_rust::<PyOperator, _>(&operators.extract()?);
+        let py_probes: Vec<&PyCell<PyProbe>> = probes.extract()?;
+        let probes = py_cells_to_pure_rust::<PyProbe, _>(&py_probes);
+        let mut engine = match thread_pool {
+            Some(thread_pool) => {
+                let mut engine = Engine::with_thread_pool(
+                    signals,
+                    operators,
+                    probes,
+                    thread_pool.thread_pool.clone(),
+                )?;
+                engine.set_pool_size(thread_pool.pool_size);
+                engine
+            }
+            None => Engine::new(signals, operators, probes)?,
+        };
+        if unique_signal_names {
+            engine.check_unique_signal_names()?;
+        }
+        if let Some(dt) = dt {
+            engine.set_dt(dt)?;
+        }
+        engine.set_probe_memory_budget(probe_memory_budget);
+        if let Some(seed) = seed {
+            engine.set_seed(seed);
+        }
+        if let Some(path) = resume_from {
+            resume(&mut engine, path)?;
+        }
         Ok(Self {
-            engine: Engine::new(
-                py_cells_to_pure_rust::<PySignal, _>(&signals.extract()?),
-                py_cells_to_pure_rust::<PyOperator, _>(&operators.extract()?),
-                py_cells_to_pure_rust::<PyProbe, _>(&probes.extract()?),
-            ),
+            engine,
+            probes: py_probes.into_iter().map(Py::from).collect(),
+            metrics_server: None,
         })
     }
 
-    fn run_step(&self) {
-        self.engine.run_step();
+    #[getter]
+    fn dt(&self) -> Option<f64> {
+        self.engine.dt()
+    }
+
+    /// Master seed of the stochastic operators, random unless given.
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.engine.seed()
     }
 
-    fn run_steps(&self, n_steps: i64) {
-        self.engine.run_steps(n_steps);
+    fn run_step(&self) -> PyResult<()> {
+        Ok(self.engine.run_step()?)
+    }
+
+    /// Runs `n_steps` steps and returns the number of steps completed. With
+    /// a `time_limit` in seconds of wall-clock time, the run stops at the
+    /// first step boundary after it is exceeded.
+    #[args(time_limit = "None")]
+    fn run_steps(&self, n_steps: i64, time_limit: Option<f64>) -> PyResult<u64> {
+        match time_limit {
+            Some(seconds) if seconds.is_nan() || seconds < 0. => {
+                Err(PyErr::new::<exc::ValueError, _>(
+                    "The time limit must not be negative.",
+                ))
+            }
+            Some(seconds) => Ok(self
+                .engine
+                .run_steps_within(n_steps, Duration::from_secs_f64(seconds))?),
+            None => {
+                self.engine.run_steps(n_steps)?;
+                Ok(n_steps.max(0) as u64)
+            }
+        }
+    }
+
+    /// Returns an iterator running `n_steps` in chunks of `chunk` steps. It
+    /// yields the number of steps run so far after each chunk or, if
+    /// `with_data`, a tuple of it and a dict of the samples each probe
+    /// recorded during the chunk, keyed by probe name.
+    #[args(with_data = "false")]
+    fn run_steps_chunked(
+        slf: PyRef<Self>,
+        n_steps: i64,
+        chunk: i64,
+        with_data: bool,
+    ) -> PyResult<PyChunkedRun> {
+        if chunk < 1 {
+            return Err(PyErr::new::<exc::ValueError, _>(
+                "Chunks must have at least one step.",
+            ));
+        }
+        let offsets = vec![0; slf.probes.len()];
+        Ok(PyChunkedRun {
+            engine: slf.into(),
+            n_steps,
+            chunk,
+            steps_done: 0,
+            offsets: if with_data { Some(offsets) } else { None },
+        })
+    }
+
+    #[staticmethod]
+    fn run_steps_concurrently(engines: Vec<&PyCell<PyEngine>>, n_steps: i64) -> PyResult<()> {
+        let engines: Vec<_> = engines.iter().map(|e| e.borrow()).collect();
+        Ok(Engine::run_steps_concurrently(
+            &engines.iter().map(|e| &e.engine).collect::<Vec<_>>(),
+            n_steps,
+        )?)
+    }
+
+    /// Runs `n_steps` of several engines in lockstep. After each step, the
+    /// value of the first signal of each pair in `couplings` is copied to
+    /// the second, which the engines read in the next step.
+    #[staticmethod]
+    fn run_lockstep(
+        engines: Vec<&PyCell<PyEngine>>,
+        couplings: Vec<(&PyCell<PySignal>, &PyCell<PySignal>)>,
+        n_steps: i64,
+    ) -> PyResult<()> {
+        let engines: Vec<_> = engines.iter().map(|e| e.borrow()).collect();
+        let couplings = couplings
+            .iter()
+            .map(|(source, target)| {
+                Coupling::new(
+                    Arc::clone(source.borrow().get()),
+                    Arc::clone(target.borrow().get()),
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        let lockstep = Lockstep::new(engines.iter().map(|e| &e.engine).collect(), couplings)?;
+        Ok(lockstep.run_steps(n_steps)?)
+    }
+
+    fn prepare(&self) -> PyResult<()> {
+        Ok(self.engine.prepare()?)
+    }
+
+    /// Runs `n_steps` without probes and returns a dict with the
+    /// `steps_per_second` and the seconds spent in `rust_operators`,
+    /// `python_operators`, and `overhead` (scheduling and GIL acquisition)
+    /// out of the `total`. Operator times are CPU times summed over the worker
+    /// threads, so that the overhead is only meaningful without worker
+    /// groups. The signals are reset before and after the run.
+    fn benchmark(&self, py: Python, n_steps: i64) -> PyResult<PyObject> {
+        let benchmark = self.engine.benchmark(n_steps)?;
+        let result = PyDict::new(py);
+        result.set_item("steps_per_second", benchmark.steps_per_second())?;
+        result.set_item("total", benchmark.total.as_secs_f64())?;
+        result.set_item("rust_operators", benchmark.rust_operators.as_secs_f64())?;
+        result.set_item("python_operators", benchmark.python_operators.as_secs_f64())?;
+        result.set_item("overhead", benchmark.overhead().as_secs_f64())?;
+        Ok(result.to_object(py))
     }
 
     fn reset(&self) {
         self.engine.reset();
     }
+
+    /// Unique names of the signals. Signals sharing the name of an earlier
+    /// one get a `#1`, `#2`, ... suffix.
+    fn signal_names(&self) -> Vec<String> {
+        self.engine.signal_names().to_vec()
+    }
+
+    /// Returns the current values of all signals, keyed by their unique
+    /// names (see `signal_names`). The values of array views are part of
+    /// those of their base signals, and read-only signals are left out.
+    fn state_dict(&self, py: Python) -> PyResult<PyObject> {
+        let state = PyDict::new(py);
+        for (signal, name) in self.engine.signals().iter().zip(self.engine.signal_names()) {
+            let any = signal.as_any();
+            if let Some(signal) = any.downcast_ref::<ArraySignal<f64>>() {
+                if let Some(value) = array_state(py, signal) {
+                    state.set_item(name, value)?;
+                }
+            } else if let Some(signal) = any.downcast_ref::<ArraySignal<f32>>() {
+                if let Some(value) = array_state(py, signal) {
+                    state.set_item(name, value)?;
+                }
+            } else if let Some(signal) = any.downcast_ref::<ArraySignal<i64>>() {
+                if let Some(value) = array_state(py, signal) {
+                    state.set_item(name, value)?;
+                }
+            } else if let Some(signal) = any.downcast_ref::<ArraySignal<c64>>() {
+                if let Some(value) = array_state(py, signal) {
+                    state.set_item(name, value)?;
+                }
+            } else if let Some(signal) = any.downcast_ref::<ScalarSignal<f64>>() {
+                state.set_item(name, **signal.read())?;
+            } else if let Some(signal) = any.downcast_ref::<ScalarSignal<u64>>() {
+                state.set_item(name, **signal.read())?;
+            }
+        }
+        Ok(state.to_object(py))
+    }
+
+    /// Sets the signals named in `state` (as returned by `state_dict`) to the
+    /// given values, which the next run starts from. Other signals keep their
+    /// values. Nothing is changed if any name or value does not fit, or if a
+    /// named signal is read-only.
+    fn load_state_dict(&self, state: &PyDict) -> PyResult<()> {
+        let mut values = vec![];
+        for (name, value) in state.iter() {
+            let name: String = name.extract()?;
+            let any = match self.engine.signal(&name) {
+                Some(signal) => signal.as_any(),
+                None => {
+                    return Err(PyErr::new::<exc::KeyError, _>(format!(
+                        "No signal named `{}`.",
+                        name
+                    )))
+                }
+            };
+            values.push(
+                if let Some(signal) = any.downcast_ref::<ArraySignal<f64>>() {
+                    SignalValue::ArrayF64(signal, extract_array_state(signal, &name, value)?)
+                } else if let Some(signal) = any.downcast_ref::<ArraySignal<f32>>() {
+                    SignalValue::ArrayF32(signal, extract_array_state(signal, &name, value)?)
+                } else if let Some(signal) = any.downcast_ref::<ArraySignal<i64>>() {
+                    SignalValue::ArrayI64(signal, extract_array_state(signal, &name, value)?)
+                } else if let Some(signal) = any.downcast_ref::<ArraySignal<c64>>() {
+                    SignalValue::ArrayC64(signal, extract_array_state(signal, &name, value)?)
+                } else if let Some(signal) = any.downcast_ref::<ScalarSignal<f64>>() {
+                    SignalValue::F64(signal, value.extract()?)
+                } else if let Some(signal) = any.downcast_ref::<ScalarSignal<u64>>() {
+                    SignalValue::U64(signal, value.extract()?)
+                } else {
+                    return Err(PyErr::new::<exc::TypeError, _>(format!(
+                        "Signal `{}` cannot be loaded.",
+                        name
+                    )));
+                },
+            );
+        }
+
+        self.engine.initialize();
+        for value in values {
+            match value {
+                SignalValue::ArrayF64(signal, value) => signal.write().assign_array(&value),
+                SignalValue::ArrayF32(signal, value) => signal.write().assign_array(&value),
+                SignalValue::ArrayI64(signal, value) => signal.write().assign_array(&value),
+                SignalValue::ArrayC64(signal, value) => signal.write().assign_array(&value),
+                SignalValue::F64(signal, value) => **signal.write() = value,
+                SignalValue::U64(signal, value) => **signal.write() = value,
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `n_steps` trial steps with extra checks and returns a list of the
+    /// problems found: signal shapes that do not fit, operators reading
+    /// memory they write through another signal, and non-finite values. The
+    /// signal values are restored afterwards.
+    #[args(n_steps = "10")]
+    fn verify(&self, n_steps: i64) -> PyResult<Vec<String>> {
+        Ok(self
+            .engine
+            .verify(n_steps)?
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect())
+    }
+
+    /// Logs a warning with the `nengo_rs` logger for each operator calling
+    /// Python, such as a node function, whose steps consistently take longer
+    /// than `fraction` of its timestep. Calling it again changes the
+    /// fraction.
+    #[args(fraction = "0.5")]
+    fn warn_about_slow_callbacks(&mut self, fraction: f64) {
+        self.engine.warn_about_slow_callbacks(fraction);
+    }
+
+    /// Injects a fault into `signal` without changing the model: with
+    /// `kind` "drop_spikes" or "zero_rows", a `fraction` of elements or rows
+    /// is zeroed; with "perturb", Gaussian noise of standard deviation `std`
+    /// is added on the steps from `start` up to `end` (or the end of the run).
+    #[args(fraction = "0.", start = "0", end = "None", std = "0.")]
+    fn inject_fault(
+        &mut self,
+        signal: &PySignal,
+        kind: &str,
+        fraction: f64,
+        start: u64,
+        end: Option<u64>,
+        std: f64,
+    ) -> PyResult<()> {
+        if !(0. ..=1.).contains(&fraction) {
+            return Err(PyErr::new::<exc::ValueError, _>(
+                "Fault fraction must be between 0 and 1.",
+            ));
+        }
+        if std.is_nan() || std < 0. {
+            return Err(PyErr::new::<exc::ValueError, _>(
+                "Standard deviation of a perturbation must not be negative.",
+            ));
+        }
+        let kind = match kind {
+            "drop_spikes" => FaultKind::DropSpikes { fraction },
+            "zero_rows" => FaultKind::ZeroRows { fraction },
+            "perturb" => FaultKind::Perturb {
+                start,
+                end: end.unwrap_or(u64::MAX),
+                std,
+            },
+            _ => {
+                return Err(PyErr::new::<exc::ValueError, _>(format!(
+                    "Unknown fault kind `{}`.",
+                    kind
+                )))
+            }
+        };
+        Ok(self.engine.inject_fault(Fault {
+            signal: signal.extract_signal("signal")?,
+            kind,
+        })?)
+    }
+
+    /// Writes a checkpoint of all signal values and probe offsets to `path`
+    /// after every `every` steps of `run_steps`, replacing the previous one,
+    /// or stops with `None`. Pass the path as `resume_from` to an engine of
+    /// the same model to continue from it.
+    #[args(every = "1000")]
+    fn set_checkpoints(&mut self, path: Option<&str>, every: u64) {
+        self.engine.set_checkpoints(path.map(PathBuf::from), every);
+    }
+
+    /// Continues from the checkpoint at `path`, written by an engine of the
+    /// same model. Probes start without samples, see `probe_offsets`.
+    fn resume(&mut self, path: &str) -> PyResult<()> {
+        resume(&mut self.engine, path)
+    }
+
+    /// Writes a checkpoint of the current state to `path`.
+    fn save_checkpoint(&self, path: &str) -> PyResult<()> {
+        Ok(self.engine.checkpoint().save(Path::new(path))?)
+    }
+
+    /// Returns the number of samples each probe took before the checkpoint
+    /// the engine resumed from, which are not part of its data.
+    fn probe_offsets(&self) -> Vec<usize> {
+        self.engine.probe_offsets().to_vec()
+    }
+
+    /// Records the values of the signals each operator reads and writes on
+    /// the given step of later runs, counted from the last reset. `None`
+    /// stops recording.
+    fn record_step(&mut self, step: Option<u64>) {
+        self.engine.record_step(step);
+    }
+
+    /// Returns the `StepRecord` of the step chosen with `record_step`, or
+    /// `None` until a run has reached it.
+    fn step_record(&self) -> Option<PyStepRecord> {
+        self.engine
+            .step_record()
+            .map(|record| PyStepRecord { record })
+    }
+
+    /// Steps each operator in `record` once from its recorded inputs and
+    /// returns a list of dicts describing the outputs differing by more than
+    /// `tolerance` from those in `reference`, or from the recorded ones
+    /// without a reference. Each has the operator `index`, `operator` and
+    /// `signal` names, and the `max_difference`. The signal values are
+    /// restored afterwards.
+    #[args(reference = "None", tolerance = "0.")]
+    fn replay_step(
+        &self,
+        py: Python,
+        record: &PyStepRecord,
+        reference: Option<&PyStepRecord>,
+        tolerance: f64,
+    ) -> PyResult<Vec<PyObject>> {
+        self.engine
+            .replay_step(&record.record, reference.map(|r| &r.record), tolerance)?
+            .into_iter()
+            .map(|divergence| {
+                let result = PyDict::new(py);
+                result.set_item("index", divergence.index)?;
+                result.set_item("operator", divergence.operator)?;
+                result.set_item("signal", divergence.signal)?;
+                result.set_item("max_difference", divergence.max_difference)?;
+                Ok(result.to_object(py))
+            })
+            .collect()
+    }
+
+    /// Runs the operators as up to `n_groups` separate tasks on the thread
+    /// pool, grouping operators that touch the same signals, or as a single
+    /// task with `None`.
+    fn set_worker_groups(&mut self, n_groups: Option<usize>) {
+        self.engine.set_worker_groups(n_groups);
+    }
+
+    /// Runs `n_steps` without probes and returns a list of dicts describing
+    /// groups of operators of the same type and signal shapes whose steps
+    /// are dominated by scheduling overhead, so that fusing them would help.
+    /// Each has the `operator` name, signal `shapes`, operator `indices`,
+    /// seconds per `mean_step`, and `estimated_savings` in seconds per step.
+    #[args(n_steps = "100")]
+    fn merge_suggestions(&self, py: Python, n_steps: i64) -> PyResult<Vec<PyObject>> {
+        self.engine
+            .merge_suggestions(n_steps)?
+            .into_iter()
+            .map(|suggestion| {
+                let result = PyDict::new(py);
+                result.set_item("operator", suggestion.operator)?;
+                result.set_item("shapes", suggestion.shapes)?;
+                result.set_item("indices", suggestion.indices)?;
+                result.set_item("mean_step", suggestion.mean_step.as_secs_f64())?;
+                result.set_item(
+                    "estimated_savings",
+                    suggestion.estimated_savings.as_secs_f64(),
+                )?;
+                Ok(result.to_object(py))
+            })
+            .collect()
+    }
+
+    /// Calls `hook(step)` before the operators of each step run, once the
+    /// probes of the previous step have recorded.
+    fn add_pre_step_hook(&mut self, hook: PyObject) {
+        self.engine.add_pre_step_hook(StepHook::Python(hook));
+    }
+
+    /// Calls `hook(step)` after each step, once its probes have recorded.
+    fn add_post_step_hook(&mut self, hook: PyObject) {
+        self.engine.add_post_step_hook(StepHook::Python(hook));
+    }
+
+    /// Wall-clock durations in seconds of the most recent steps (the last
+    /// 1000 by default), oldest first.
+    fn step_durations(&self) -> Vec<f64> {
+        self.engine
+            .step_durations()
+            .iter()
+            .map(|d| d.as_secs_f64())
+            .collect()
+    }
+
+    fn set_step_history(&self, n_steps: usize) {
+        self.engine.set_step_history(n_steps);
+    }
+
+    /// Enables or disables measuring how long operators block on signal
+    /// locks. This applies to all engines and adds overhead to every access.
+    #[staticmethod]
+    fn track_lock_waits(enabled: bool) {
+        signal::track_lock_waits(enabled);
+    }
+
+    /// Returns up to `n` `(signal name, seconds)` pairs of the signals whose
+    /// accesses blocked the longest while lock waits were tracked.
+    #[args(n = "10")]
+    fn contended_signals(&self, n: usize) -> Vec<(String, f64)> {
+        self.engine
+            .contended_signals()
+            .into_iter()
+            .take(n)
+            .map(|(name, wait)| (name, wait.as_secs_f64()))
+            .collect()
+    }
+
+    /// Returns a dict with the bytes used by each signal (keyed by unique
+    /// name), a list of `(operator name, bytes)` for the data held by the
+    /// operators, a dict with the `bytes` and `bytes_per_step` of each probe
+    /// (keyed by probe name), and the `total` bytes expected after another
+    /// `n_steps` steps if no probe samples are moved to disk.
+    #[args(n_steps = "0")]
+    fn memory_report(&self, py: Python, n_steps: u64) -> PyResult<PyObject> {
+        let report = self.engine.memory_report();
+        let signals = PyDict::new(py);
+        for (name, bytes) in report.signals.iter() {
+            signals.set_item(name, bytes)?;
+        }
+        let probes = PyDict::new(py);
+        for (probe, memory) in self.probes.iter().zip(report.probes.iter()) {
+            let entry = PyDict::new(py);
+            entry.set_item("bytes", memory.bytes)?;
+            entry.set_item("bytes_per_step", memory.bytes_per_step)?;
+            probes.set_item(probe.as_ref(py).borrow().name(), entry)?;
+        }
+        let result = PyDict::new(py);
+        result.set_item("signals", signals)?;
+        result.set_item("operators", report.operators.clone())?;
+        result.set_item("probes", probes)?;
+        result.set_item("total", report.predicted_bytes(n_steps))?;
+        Ok(result.to_object(py))
+    }
+
+    /// Returns the engine metrics in the Prometheus text format. Collecting
+    /// them starts with the first call of this or `serve_metrics`.
+    fn metrics(&mut self) -> String {
+        self.engine.enable_metrics().render()
+    }
+
+    /// Serves the engine metrics for Prometheus over HTTP on a background
+    /// thread and returns the bound address. A server started before is shut
+    /// down first.
+    #[args(address = "\"127.0.0.1:9100\"")]
+    fn serve_metrics(&mut self, address: &str) -> PyResult<String> {
+        self.stop_metrics();
+        let server = self
+            .engine
+            .enable_metrics()
+            .serve(address)
+            .map_err(|e| PyErr::new::<exc::OSError, _>(e.to_string()))?;
+        let address = server.address().to_string();
+        self.metrics_server = Some(server);
+        Ok(address)
+    }
+
+    /// Shuts down the server started with `serve_metrics`, if any.
+    fn stop_metrics(&mut self) {
+        if let Some(mut server) = self.metrics_server.take() {
+            server.shutdown();
+        }
+    }
+
+    /// Writes the data of each probe to `<directory>/<probe name>.parquet`.
+    #[args(compression = "\"zstd\"")]
+    fn save_parquet(&self, py: Python, directory: &str, compression: &str) -> PyResult<()> {
+        self.check_probe_names(py)?;
+        for probe in self.probes.iter() {
+            let probe = probe.as_ref(py).borrow();
+            let path = Path::new(directory).join(format!("{}.parquet", probe.name()));
+            probe.save_parquet(path.to_string_lossy().to_object(py).as_ref(py), compression)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the data of all probes, keyed by probe name, and their sample
+    /// times, keyed by `<probe name>.time`, to a compressed `.npz` archive.
+    /// Statistics probes are saved as `<probe name>.count`, `.mean`,
+    /// `.variance`, `.min`, and `.max`.
+    fn save_probes(&self, py: Python, path: &PyAny) -> PyResult<()> {
+        self.check_probe_names(py)?;
+        let arrays = PyDict::new(py);
+        let insert = |key: String, array: &PyAny| {
+            if arrays.contains(&key)? {
+                return Err(PyErr::new::<exc::ValueError, _>(format!(
+                    "Several arrays would be saved as `{}`.",
+                    key
+                )));
+            }
+            arrays.set_item(key, array)
+        };
+        for probe in self.probes.iter() {
+            let probe = probe.as_ref(py).borrow();
+            if probe.is_statistics() {
+                for (key, value) in probe.statistics_dict(py)?.iter() {
+                    insert(format!("{}.{}", probe.name(), key), value)?;
+                }
+                continue;
+            }
+            // Probes sampling at different periods have different times.
+            if probe.records_times() {
+                insert(format!("{}.time", probe.name()), probe.times_array(py)?)?;
+            }
+            insert(probe.name().clone(), probe.data_array(py)?)?;
+        }
+        PyModule::import(py, "numpy")?.call("savez_compressed", (path,), Some(arrays))?;
+        Ok(())
+    }
 }
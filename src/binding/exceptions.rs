@@ -0,0 +1,7 @@
+use pyo3::create_exception;
+use pyo3::exceptions as exc;
+
+create_exception!(nengo_rs, SignalTypeError, exc::TypeError);
+create_exception!(nengo_rs, ShapeMismatchError, exc::ValueError);
+create_exception!(nengo_rs, BuildError, exc::ValueError);
+create_exception!(nengo_rs, SimulationError, exc::RuntimeError);
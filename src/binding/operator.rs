@@ -1,14 +1,15 @@
 use crate::binding::signal::PySignal;
 use crate::binding::Wrapper;
 use crate::operator;
-use crate::operator::OperatorNode;
-use crate::signal::ArraySignal;
+use crate::operator::{Memo, OperatorNode};
+use crate::signal::{ArraySignal, SparseSignal};
 use ndarray::ArrayD;
-use numpy::PyArrayDyn;
+use numpy::{c64, PyArray2, PyArrayDyn};
+use pyo3::exceptions as exc;
 use pyo3::prelude::*;
 use pyo3::types::PyList;
 use std::marker::PhantomData;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 
 #[pyclass(name=Operator)]
 pub struct PyOperator {
@@ -28,11 +29,23 @@ macro_rules! bind_op {
             $(args: ($($aname:ident : $atype:ty),*),)?
             $(signals: [$($sig:ident),*],)?
             $(optionals: [$($optsig:ident),*],)?
-        }, {$($fname:ident : $expr:expr),*}
+            $(keyword_optionals: [$($kwsig:ident),*],)?
+            $(keyword_args: ($($kname:ident : $ktype:ty = $kdefault:literal),*),)?
+            $(keyword_options: ($($oname:ident : $otype:ty = $odefault:literal),*),)?
+        }, {$($fname:ident $(: $expr:expr)?),*}
     ) => {
         #[pymethods]
         impl $name {
             #[new]
+            #[args(
+                condition = "None",
+                period = "1",
+                memoize = "false",
+                $($($kwsig = "None",)*)?
+                $($($kname = $kdefault,)*)?
+                $($($oname = $odefault,)*)?
+            )]
+            #[allow(clippy::too_many_arguments)]
             fn new(
                 $($($aname: $atype,)*)?
                 $($(
@@ -42,24 +55,55 @@ macro_rules! bind_op {
                     $optsig : Option<&PySignal>,
                 )*)?
                 dependencies: Vec<usize>,
+                condition: Option<&PySignal>,
+                period: u64,
+                memoize: bool,
+                $($(
+                    $kwsig : Option<&PySignal>,
+                )*)?
+                $($(
+                    $kname : $ktype,
+                )*)?
+                $($(
+                    $oname : $otype,
+                )*)?
             ) -> PyResult<(Self, PyOperator)> {
+                if period == 0 {
+                    return Err(PyErr::new::<exc::ValueError, _>(
+                        "Operator period must be at least 1.",
+                    ));
+                }
+                let operator = operator::$op_type$(::<$($op_typearg,)*>)? {
+                    $($(
+                        $sig : $sig.extract_signal(stringify!($sig))?,
+                    )*)?
+                    $($(
+                        $optsig: match $optsig {
+                            Some(sig) => Some(sig.extract_signal(stringify!($optsig))?),
+                            None => None
+                        },
+                    )*)?
+                    $($(
+                        $kwsig: match $kwsig {
+                            Some(sig) => Some(sig.extract_signal(stringify!($kwsig))?),
+                            None => None
+                        },
+                    )*)?
+                    $($($kname,)*)?
+                    $($fname $(: $expr)?,)*
+                };
                 Ok((
                     Self {},
                     PyOperator {
                         node: Arc::new(OperatorNode {
-                            operator: Box::new(operator::$op_type$(::<$($op_typearg,)*>)? {
-                                $($(
-                                    $sig : $sig.extract_signal(stringify!($sig))?,
-                                )*)?
-                                $($(
-                                    $optsig: match $optsig {
-                                        Some(sig) => Some(sig.extract_signal(stringify!($optsig))?),
-                                        None => None
-                                    },
-                                )*)?
-                                $($fname: $expr,)*
-                            }),
+                            operator: Box::new(operator),
                             dependencies,
+                            condition: match condition {
+                                Some(sig) => Some(sig.extract_condition("condition")?),
+                                None => None,
+                            },
+                            period,
+                            memo: if memoize { Some(Memo::default()) } else { None },
                         }),
                     },
                 ))
@@ -80,6 +124,30 @@ bind_op!(
     {value: value.extract::<&PyArrayDyn<f64>>()?.to_owned_array()}
 );
 
+#[pyclass(extends=PyOperator, name=ResetF32)]
+pub struct PyResetF32 {}
+
+bind_op!(
+    PyResetF32: Reset<ArrayD<f32>, ArraySignal<f32>>,
+    {
+        args: (value: &PyAny),
+        signals: [target],
+    },
+    {value: value.extract::<&PyArrayDyn<f32>>()?.to_owned_array()}
+);
+
+#[pyclass(extends=PyOperator, name=ResetC128)]
+pub struct PyResetC128 {}
+
+bind_op!(
+    PyResetC128: Reset<ArrayD<c64>, ArraySignal<c64>>,
+    {
+        args: (value: &PyAny),
+        signals: [target],
+    },
+    {value: value.extract::<&PyArrayDyn<c64>>()?.to_owned_array()}
+);
+
 #[pyclass(extends=PyOperator, name=TimeUpdate)]
 pub struct PyTimeUpdate {}
 
@@ -88,8 +156,10 @@ bind_op!(
     {
         args: (dt: f64),
         signals: [step_target, time_target],
+        keyword_optionals: [dt_signal],
+        keyword_args: (compensated: bool = "false"),
     },
-    { dt: dt }
+    { dt, compensation: Default::default() }
 );
 
 #[pyclass(extends=PyOperator, name=ElementwiseInc)]
@@ -97,10 +167,197 @@ pub struct PyElementwiseInc {}
 
 bind_op!(
     PyElementwiseInc: ElementwiseInc<f64>,
-    {signals: [target, left, right],},
+    {
+        signals: [target, left, right],
+        keyword_args: (checked: bool = "false"),
+    },
+    {}
+);
+
+#[pyclass(extends=PyOperator, name=ElementwiseIncF32)]
+pub struct PyElementwiseIncF32 {}
+
+bind_op!(
+    PyElementwiseIncF32: ElementwiseInc<f32>,
+    {
+        signals: [target, left, right],
+        keyword_args: (checked: bool = "false"),
+    },
+    {}
+);
+
+/// Integer counterpart of `ElementwiseInc`, e.g. for counters. With
+/// `checked`, an overflow fails the step instead of wrapping around.
+#[pyclass(extends=PyOperator, name=ElementwiseIncI64)]
+pub struct PyElementwiseIncI64 {}
+
+bind_op!(
+    PyElementwiseIncI64: ElementwiseInc<i64>,
+    {
+        signals: [target, left, right],
+        keyword_args: (checked: bool = "false"),
+    },
+    {}
+);
+
+#[pyclass(extends=PyOperator, name=ElementwiseIncC128)]
+pub struct PyElementwiseIncC128 {}
+
+bind_op!(
+    PyElementwiseIncC128: ElementwiseInc<c64>,
+    {
+        signals: [target, left, right],
+        keyword_args: (checked: bool = "false"),
+    },
+    {}
+);
+
+/// Transforms a real signal into a complex one.
+#[pyclass(extends=PyOperator, name=FftF64)]
+pub struct PyFftF64 {}
+
+bind_op!(
+    PyFftF64: Fft<f64, c64>,
+    {
+        args: (inverse: bool),
+        signals: [input, output],
+    },
+    { inverse, plan: OnceLock::new() }
+);
+
+#[pyclass(extends=PyOperator, name=FftC128)]
+pub struct PyFftC128 {}
+
+bind_op!(
+    PyFftC128: Fft<c64, c64>,
+    {
+        args: (inverse: bool),
+        signals: [input, output],
+    },
+    { inverse, plan: OnceLock::new() }
+);
+
+/// Transforms a complex signal into the real part of the result, as for the
+/// inverse transform of the spectrum of a real signal.
+#[pyclass(extends=PyOperator, name=FftC128ToF64)]
+pub struct PyFftC128ToF64 {}
+
+bind_op!(
+    PyFftC128ToF64: Fft<c64, f64>,
+    {
+        args: (inverse: bool),
+        signals: [input, output],
+    },
+    { inverse, plan: OnceLock::new() }
+);
+
+#[pyclass(extends=PyOperator, name=CircularConv)]
+pub struct PyCircularConv {}
+
+bind_op!(
+    PyCircularConv: CircularConv,
+    {
+        args: (invert_a: bool, invert_b: bool),
+        signals: [target, a, b],
+    },
+    { invert_a, invert_b, plan: OnceLock::new() }
+);
+
+#[pyclass(extends=PyOperator, name=Superposition)]
+pub struct PySuperposition {}
+
+bind_op!(
+    PySuperposition: Superposition,
+    {
+        args: (normalize: bool, inputs: Vec<&PyCell<PySignal>>),
+        signals: [target],
+    },
+    {
+        normalize,
+        inputs: inputs
+            .iter()
+            .map(|s| s.borrow().extract_signal("inputs"))
+            .collect::<PyResult<_>>()?
+    }
+);
+
+#[pyclass(extends=PyOperator, name=Involution)]
+pub struct PyInvolution {}
+
+bind_op!(
+    PyInvolution: Involution,
+    {signals: [target, input],},
     {}
 );
 
+#[pyclass(extends=PyOperator, name=Similarity)]
+pub struct PySimilarity {}
+
+bind_op!(
+    PySimilarity: Similarity,
+    {
+        args: (vocabulary: &PyArray2<f64>),
+        signals: [target, input],
+    },
+    { vocabulary: vocabulary.to_owned_array() }
+);
+
+#[pyclass(extends=PyOperator, name=AssociativeMemory)]
+pub struct PyAssociativeMemory {}
+
+bind_op!(
+    PyAssociativeMemory: AssociativeMemory,
+    {
+        args: (keys: &PyArray2<f64>, values: &PyArray2<f64>, threshold: f64),
+        signals: [target, input],
+        keyword_args: (binary: bool = "false"),
+    },
+    {
+        keys: keys.to_owned_array(),
+        values: values.to_owned_array(),
+        threshold
+    }
+);
+
+#[pyclass(extends=PyOperator, name=SparseDotInc)]
+pub struct PySparseDotInc {}
+
+bind_op!(
+    PySparseDotInc: SparseDotInc,
+    {
+        args: (weights: &PyAny),
+        signals: [target, input],
+    },
+    { weights: extract_sparse_weights(weights)? }
+);
+
+/// Takes the weights of a `SparseDotInc` either as a sparse signal, or as a
+/// constant dense matrix keeping only its nonzero entries.
+fn extract_sparse_weights(weights: &PyAny) -> PyResult<Arc<SparseSignal<f64>>> {
+    match weights.extract::<&PyCell<PySignal>>() {
+        Ok(signal) => signal.borrow().extract_signal("weights"),
+        Err(_) => {
+            let weights = weights.extract::<&PyArray2<f64>>()?.to_owned_array();
+            Ok(Arc::new(SparseSignal::from_dense(
+                "weights".to_string(),
+                weights.view(),
+            )))
+        }
+    }
+}
+
+#[pyclass(extends=PyOperator, name=SparseProjection)]
+pub struct PySparseProjection {}
+
+bind_op!(
+    PySparseProjection: SparseProjection,
+    {
+        args: (density: f64, seed: u64),
+        signals: [target, input],
+    },
+    { density, seed, rows: OnceLock::new() }
+);
+
 #[pyclass(extends=PyOperator, name=Copy)]
 pub struct PyCopy {}
 
@@ -110,7 +367,43 @@ bind_op!(
         args: (inc: bool),
         signals: [src, dst],
     },
-    { inc: inc, data_type: PhantomData }
+    { inc, data_type: PhantomData }
+);
+
+#[pyclass(extends=PyOperator, name=CopyF32)]
+pub struct PyCopyF32 {}
+
+bind_op!(
+    PyCopyF32: CopyOp<ArrayD<f32>, ArraySignal<f32>>,
+    {
+        args: (inc: bool),
+        signals: [src, dst],
+    },
+    { inc, data_type: PhantomData }
+);
+
+#[pyclass(extends=PyOperator, name=CopyI64)]
+pub struct PyCopyI64 {}
+
+bind_op!(
+    PyCopyI64: CopyOp<ArrayD<i64>, ArraySignal<i64>>,
+    {
+        args: (inc: bool),
+        signals: [src, dst],
+    },
+    { inc, data_type: PhantomData }
+);
+
+#[pyclass(extends=PyOperator, name=CopyC128)]
+pub struct PyCopyC128 {}
+
+bind_op!(
+    PyCopyC128: CopyOp<ArrayD<c64>, ArraySignal<c64>>,
+    {
+        args: (inc: bool),
+        signals: [src, dst],
+    },
+    { inc, data_type: PhantomData }
 );
 
 #[pyclass(extends=PyOperator, name=DotInc)]
@@ -118,10 +411,134 @@ pub struct PyDotInc {}
 
 bind_op!(
     PyDotInc: DotInc<f64>,
+    {
+        signals: [target, left, right],
+        keyword_args: (transpose_left: bool = "false"),
+    },
+    {}
+);
+
+#[pyclass(extends=PyOperator, name=DotIncF32)]
+pub struct PyDotIncF32 {}
+
+bind_op!(
+    PyDotIncF32: DotInc<f32>,
+    {
+        signals: [target, left, right],
+        keyword_args: (transpose_left: bool = "false"),
+    },
+    {}
+);
+
+#[pyclass(extends=PyOperator, name=ConvInc)]
+pub struct PyConvInc {}
+
+bind_op!(
+    PyConvInc: ConvInc<f64>,
+    {
+        args: (
+            input_size: (usize, usize),
+            output_size: (usize, usize),
+            kernel_size: (usize, usize),
+            strides: (usize, usize),
+            padding: (usize, usize),
+            channels_in: usize,
+            channels_out: usize,
+            channels_last: bool,
+            groups: usize,
+            transpose: bool
+        ),
+        signals: [target, kernel, input],
+    },
+    {
+        geometry: operator::ConvGeometry {
+            input_size,
+            output_size,
+            kernel_size,
+            strides,
+            padding,
+            channels_in,
+            channels_out,
+            channels_last,
+            groups,
+            transpose,
+        },
+        method: operator::ConvMethod::Auto,
+        plan: Default::default()
+    }
+);
+
+#[pyclass(extends=PyOperator, name=Pool)]
+pub struct PyPool {}
+
+bind_op!(
+    PyPool: Pool<f64>,
+    {
+        args: (
+            kind: &str,
+            input_size: (usize, usize),
+            output_size: (usize, usize),
+            pool_size: (usize, usize),
+            strides: (usize, usize),
+            padding: (usize, usize),
+            channels: usize,
+            channels_last: bool
+        ),
+        signals: [target, input],
+    },
+    {
+        geometry: operator::PoolGeometry {
+            input_size,
+            output_size,
+            pool_size,
+            strides,
+            padding,
+            channels,
+            channels_last,
+        },
+        kind: match kind {
+            "average" => operator::PoolKind::Average,
+            "max" => operator::PoolKind::Max,
+            _ => {
+                return Err(PyErr::new::<exc::ValueError, _>(format!(
+                    "Unknown pooling kind {}, expected 'average' or 'max'.",
+                    kind
+                )))
+            }
+        }
+    }
+);
+
+#[pyclass(extends=PyOperator, name=SpikeDotInc)]
+pub struct PySpikeDotInc {}
+
+bind_op!(
+    PySpikeDotInc: SpikeDotInc<f64>,
     {signals: [target, left, right],},
     {}
 );
 
+#[pyclass(extends=PyOperator, name=WinnerTakeAll)]
+pub struct PyWinnerTakeAll {}
+
+bind_op!(
+    PyWinnerTakeAll: WinnerTakeAll<f64>,
+    {
+        args: (k: usize, one_hot: bool),
+        signals: [input, output],
+    },
+    {
+        k: if k == 0 {
+            return Err(PyErr::new::<exc::ValueError, _>(
+                "WinnerTakeAll requires k to be at least 1.",
+            ));
+        } else {
+            k
+        },
+        one_hot
+    }
+);
+
 #[pyclass(extends=PyOperator, name=SimNeurons)]
 pub struct PySimNeurons {}
 
@@ -130,14 +547,17 @@ bind_op!(
     {
         args: (dt: f64, step_fn: &PyAny, state: &PyList),
         signals: [input_current, output],
+        keyword_optionals: [dt_signal],
     },
     {
-        dt: dt,
+        dt,
         step_fn: step_fn.into(),
         state: state.into()
     }
 );
 
+/// With `reuse_arrays`, the input is passed to Python as a single read-only
+/// array overwritten every step instead of a new array per step.
 #[pyclass(extends=PyOperator, name=SimProcess)]
 pub struct PySimProcess {}
 
@@ -147,13 +567,70 @@ bind_op!(
         args: (mode_inc: bool, step_fn: &PyAny),
         signals: [t, output],
         optionals: [input],
+        keyword_options: (reuse_arrays: bool = "false"),
+    },
+    {
+        mode_inc,
+        step_fn: step_fn.into(),
+        input_array: operator::PyArrayCache::new(reuse_arrays),
+        args: Default::default()
+    }
+);
+
+#[pyclass(extends=PyOperator, name=PresentInput)]
+pub struct PyPresentInput {}
+
+bind_op!(
+    PyPresentInput: PresentInput<f64>,
+    {
+        args: (mode_inc: bool, inputs: &PyAny, presentation_time: f64, dt: f64),
+        signals: [t, output],
+    },
+    {
+        mode_inc,
+        inputs: inputs.extract::<&PyArrayDyn<f64>>()?.to_owned_array(),
+        presentation_time,
+        dt
+    }
+);
+
+/// Queue of arrays to feed into a running simulation with `QueueInput`.
+#[pyclass(name=InputQueue)]
+pub struct PyInputQueue {
+    queue: Arc<operator::InputQueue<f64>>,
+}
+
+#[pymethods]
+impl PyInputQueue {
+    #[new]
+    fn new() -> Self {
+        Self {
+            queue: Arc::new(operator::InputQueue::default()),
+        }
+    }
+
+    fn push(&self, item: &PyArrayDyn<f64>) {
+        self.queue.push(item.to_owned_array());
+    }
+}
+
+#[pyclass(extends=PyOperator, name=QueueInput)]
+pub struct PyQueueInput {}
+
+bind_op!(
+    PyQueueInput: QueueInput<f64>,
+    {
+        args: (queue: &PyCell<PyInputQueue>),
+        signals: [output],
     },
     {
-        mode_inc: mode_inc,
-        step_fn: step_fn.into()
+        queue: Arc::clone(&queue.borrow().queue),
+        latest: Mutex::new(None)
     }
 );
 
+/// With `reuse_arrays`, the `x` is passed to Python as a single read-only
+/// array overwritten every step instead of a new array per step.
 #[pyclass(extends=PyOperator, name=SimPyFunc)]
 pub struct PySimPyFunc {}
 
@@ -163,31 +640,210 @@ bind_op!(
         args: (py_fn: &PyAny),
         signals: [output],
         optionals: [t, x],
+        keyword_options: (reuse_arrays: bool = "false"),
+    },
+    {
+        py_fn: py_fn.into(),
+        x_array: operator::PyArrayCache::new(reuse_arrays),
+        args: Default::default()
+    }
+);
+
+#[pyclass(extends=PyOperator, name=Sink)]
+pub struct PySink {}
+
+bind_op!(
+    PySink: Sink<f64>,
+    {
+        args: (callback: &PyAny),
+        optionals: [t, x],
+    },
+    {callback: operator::SinkCallback::Python(callback.into())}
+);
+
+#[pyclass(extends=PyOperator, name=SendSignal)]
+pub struct PySendSignal {}
+
+bind_op!(
+    PySendSignal: SendSignal,
+    {
+        args: (address: &str),
+        signals: [source],
+    },
+    {channel: operator::TcpChannel::connect(address)}
+);
+
+#[pyclass(extends=PyOperator, name=ReceiveSignal)]
+pub struct PyReceiveSignal {}
+
+bind_op!(
+    PyReceiveSignal: ReceiveSignal,
+    {
+        args: (address: &str, peer: &str),
+        signals: [target],
+    },
+    {channel: operator::TcpChannel::listen(address, peer)?}
+);
+
+/// With `reuse_arrays`, the action is passed to Python as a single read-only
+/// array overwritten every step instead of a new array per step.
+#[pyclass(extends=PyOperator, name=StepEnvironment)]
+pub struct PyStepEnvironment {}
+
+bind_op!(
+    PyStepEnvironment: StepEnvironment,
+    {
+        args: (env: &PyAny),
+        signals: [action, observation],
+        keyword_optionals: [reward, done],
+        keyword_args: (discrete: bool = "false"),
+        keyword_options: (reuse_arrays: bool = "false"),
     },
-    {py_fn: py_fn.into()}
+    {
+        env: env.into(),
+        action_array: operator::PyArrayCache::new(reuse_arrays),
+        state: Default::default()
+    }
+);
+
+#[cfg(feature = "ros2")]
+fn parse_message_fields(fields: &[&str]) -> PyResult<Vec<operator::MessageField>> {
+    fields
+        .iter()
+        .map(|field| field.parse())
+        .collect::<Result<_, String>>()
+        .map_err(PyErr::new::<exc::ValueError, _>)
+}
+
+#[cfg(feature = "ros2")]
+#[pyclass(extends=PyOperator, name=SubscribeTopic)]
+pub struct PySubscribeTopic {}
+
+#[cfg(feature = "ros2")]
+bind_op!(
+    PySubscribeTopic: SubscribeTopic,
+    {
+        args: (topic: &str, message_type: &PyAny, fields: Vec<&str>),
+        signals: [output],
+    },
+    {
+        fields: parse_message_fields(&fields)?,
+        subscriber: operator::Ros2Subscriber::new(topic, message_type)?,
+        latest: Mutex::new(None)
+    }
+);
+
+#[cfg(feature = "ros2")]
+#[pyclass(extends=PyOperator, name=PublishTopic)]
+pub struct PyPublishTopic {}
+
+#[cfg(feature = "ros2")]
+bind_op!(
+    PyPublishTopic: PublishTopic,
+    {
+        args: (topic: &str, message_type: &PyAny, fields: Vec<&str>),
+        signals: [source],
+    },
+    {
+        fields: parse_message_fields(&fields)?,
+        publisher: operator::Ros2Publisher::new(topic, message_type)?
+    }
+);
+
+#[cfg(feature = "audio")]
+#[pyclass(extends=PyOperator, name=RecordAudio)]
+pub struct PyRecordAudio {}
+
+#[cfg(feature = "audio")]
+bind_op!(
+    PyRecordAudio: RecordAudio,
+    {
+        args: (sample_rate: u32, channels: u16),
+        signals: [output],
+    },
+    {
+        channels,
+        stream: operator::AudioStream::record(sample_rate, channels)?
+    }
+);
+
+#[cfg(feature = "audio")]
+#[pyclass(extends=PyOperator, name=PlayAudio)]
+pub struct PyPlayAudio {}
+
+#[cfg(feature = "audio")]
+bind_op!(
+    PyPlayAudio: PlayAudio,
+    {
+        args: (sample_rate: u32, channels: u16),
+        signals: [source],
+    },
+    {
+        channels,
+        stream: operator::AudioStream::play(sample_rate, channels)?
+    }
 );
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::binding::signal::{PySignalArrayF64, PySignalF64, PySignalU64};
+    use crate::binding::exceptions::SignalTypeError;
+    use crate::binding::signal::{
+        PySignalArrayC128, PySignalArrayF32, PySignalArrayF64, PySignalArrayI64, PySignalF64,
+        PySignalSparseF64, PySignalU64,
+    };
     use crate::venv::activate_venv;
     use pyo3::{types::IntoPyDict, wrap_pymodule, ToPyObject};
 
     #[pymodule]
     fn operator(_py: Python, m: &PyModule) -> PyResult<()> {
+        m.add_class::<PyCircularConv>()?;
+        m.add_class::<PyConvInc>()?;
         m.add_class::<PyCopy>()?;
+        m.add_class::<PyCopyC128>()?;
+        m.add_class::<PyCopyF32>()?;
+        m.add_class::<PyCopyI64>()?;
         m.add_class::<PyDotInc>()?;
+        m.add_class::<PyDotIncF32>()?;
         m.add_class::<PyElementwiseInc>()?;
+        m.add_class::<PyElementwiseIncC128>()?;
+        m.add_class::<PyElementwiseIncF32>()?;
+        m.add_class::<PyElementwiseIncI64>()?;
+        m.add_class::<PyFftC128>()?;
+        m.add_class::<PyFftC128ToF64>()?;
+        m.add_class::<PyFftF64>()?;
+        m.add_class::<PyPool>()?;
+        m.add_class::<PyPresentInput>()?;
+        m.add_class::<PyQueueInput>()?;
+        m.add_class::<PyReceiveSignal>()?;
         m.add_class::<PyReset>()?;
+        m.add_class::<PyResetC128>()?;
+        m.add_class::<PyResetF32>()?;
+        m.add_class::<PySendSignal>()?;
         m.add_class::<PySimNeurons>()?;
         m.add_class::<PySimProcess>()?;
         m.add_class::<PySimPyFunc>()?;
+        m.add_class::<PySink>()?;
+        m.add_class::<PySparseDotInc>()?;
+        m.add_class::<PySparseProjection>()?;
+        m.add_class::<PySpikeDotInc>()?;
+        m.add_class::<PyStepEnvironment>()?;
         m.add_class::<PyTimeUpdate>()?;
+        m.add_class::<PyWinnerTakeAll>()?;
+
+        m.add_class::<PyAssociativeMemory>()?;
+        m.add_class::<PyInvolution>()?;
+        m.add_class::<PySimilarity>()?;
+        m.add_class::<PySuperposition>()?;
 
+        m.add_class::<PyInputQueue>()?;
         m.add_class::<PySignalF64>()?;
         m.add_class::<PySignalU64>()?;
         m.add_class::<PySignalArrayF64>()?;
+        m.add_class::<PySignalArrayC128>()?;
+        m.add_class::<PySignalArrayF32>()?;
+        m.add_class::<PySignalArrayI64>()?;
+        m.add_class::<PySignalSparseF64>()?;
 
         Ok(())
     }
@@ -201,10 +857,12 @@ mod tests {
         activate_venv(py);
         let nengo = PyModule::import(py, "nengo")?;
         let numpy = PyModule::import(py, "numpy")?;
+        let sparse = PyModule::import(py, "scipy.sparse")?;
         let operator_module = wrap_pymodule!(operator)(py);
         let locals = [
             ("nengo", nengo.to_object(py)),
             ("np", numpy.to_object(py)),
+            ("sparse", sparse.to_object(py)),
             ("o", operator_module),
         ]
         .into_py_dict(py);
@@ -214,6 +872,61 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn raises_signal_type_error_for_wrong_signal_types() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let operator_module = wrap_pymodule!(operator)(py);
+        let locals = [("o", operator_module)].into_py_dict(py);
+
+        let err = py
+            .eval(
+                "o.TimeUpdate(0.001, o.SignalF64('step', 0.), o.SignalF64('time', 0.), [])",
+                None,
+                Some(locals),
+            )
+            .unwrap_err();
+
+        assert!(err.is_instance::<SignalTypeError>(py));
+        assert!(err.is_instance::<exc::TypeError>(py));
+        assert_eq!(
+            err.to_object(py)
+                .as_ref(py)
+                .str()
+                .unwrap()
+                .to_string_lossy(),
+            "Signal `step_target` must be ScalarSignal<u64>, \
+             but got `step` with dtype f64 and shape []."
+        );
+    }
+
+    #[test]
+    fn can_instantiate_with_condition() {
+        can_instantiate(&format!(
+            "o.Copy(False, {}, {}, [0], condition=o.SignalF64('condition', 0.))",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_with_period() {
+        can_instantiate(&format!(
+            "o.Copy(False, {}, {}, [0], period=10)",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_memoized() {
+        can_instantiate(&format!(
+            "o.Copy(False, {}, {}, [0], memoize=True)",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
     #[test]
     fn can_instantiate_copy() {
         can_instantiate(&format!(
@@ -223,6 +936,24 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn can_instantiate_circular_conv() {
+        can_instantiate(&format!(
+            "o.CircularConv(False, True, {}, {}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_conv_inc() {
+        can_instantiate(&format!(
+            "o.ConvInc((1, 1), (1, 1), (1, 1), (1, 1), (0, 0), 1, 1, True, 1, False, {}, {}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
     #[test]
     fn can_instantiate_dot_inc() {
         can_instantiate(&format!(
@@ -232,6 +963,25 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn can_instantiate_dot_inc_f32() {
+        let signal = "o.SignalArrayF32('x', np.zeros(1, dtype=np.float32))";
+        can_instantiate(&format!(
+            "o.DotIncF32({}, {}, {}, [0])",
+            signal, signal, signal
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_dot_inc_with_transposed_left_operand() {
+        can_instantiate(&format!(
+            "o.DotInc({}, {}, {}, [0], transpose_left=True)",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
     #[test]
     fn can_instantiate_elementwise_inc() {
         can_instantiate(&format!(
@@ -241,6 +991,109 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn can_instantiate_elementwise_inc_i64() {
+        let signal = "o.SignalArrayI64('x', np.zeros(1, dtype=np.int64))";
+        can_instantiate(&format!(
+            "o.ElementwiseIncI64({}, {}, {}, [0], checked=True)",
+            signal, signal, signal
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_elementwise_inc_c128() {
+        let signal = "o.SignalArrayC128('x', np.zeros(1, dtype=complex))";
+        can_instantiate(&format!(
+            "o.ElementwiseIncC128({}, {}, {}, [0])",
+            signal, signal, signal
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_fft() {
+        can_instantiate(&format!(
+            "o.FftF64(False, {}, o.SignalArrayC128('x', np.zeros(1, dtype=complex)), [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_vsa_operators() {
+        can_instantiate(&format!(
+            "o.Superposition(True, [{}, {}], {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+        can_instantiate(&format!(
+            "o.Involution({}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+        can_instantiate(&format!(
+            "o.Similarity(np.ones((1, 1)), {}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+        can_instantiate(&format!(
+            "o.AssociativeMemory(np.ones((1, 1)), np.ones((1, 1)), 0.3, {}, {}, [0], binary=True)",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_sparse_dot_inc() {
+        can_instantiate(&format!(
+            "o.SparseDotInc(np.eye(1), {}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+        can_instantiate(&format!(
+            "o.SparseDotInc(o.SignalSparseF64('w', sparse.eye(1)), {}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_sparse_projection() {
+        can_instantiate(&format!(
+            "o.SparseProjection(0.1, 42, {}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_pool() {
+        can_instantiate(&format!(
+            "o.Pool('max', (1, 1), (1, 1), (1, 1), (1, 1), (0, 0), 1, True, {}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_present_input() {
+        can_instantiate(&format!(
+            "o.PresentInput(False, np.zeros((2, 1)), 0.1, 0.001, o.SignalF64('time', 0.), {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_queue_input() {
+        can_instantiate(&format!(
+            "o.QueueInput(o.InputQueue(), {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
     #[test]
     fn can_instantiate_reset() {
         can_instantiate(&format!(
@@ -295,6 +1148,38 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn can_instantiate_spike_dot_inc() {
+        can_instantiate(&format!(
+            "o.SpikeDotInc({}, {}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_signal_exchange() {
+        can_instantiate(&format!(
+            "o.ReceiveSignal('127.0.0.1:0', '127.0.0.1', {}, [])",
+            DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+        can_instantiate(&format!(
+            "o.SendSignal('127.0.0.1:1', {}, [])",
+            DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn can_instantiate_sink() {
+        can_instantiate(&format!(
+            "o.Sink(lambda t, x: None, o.SignalF64('time', 0.), {}, [0], period=10)",
+            DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
+
     #[test]
     fn can_instantiate_time_update() {
         can_instantiate(
@@ -302,4 +1187,13 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn can_instantiate_winner_take_all() {
+        can_instantiate(&format!(
+            "o.WinnerTakeAll(1, True, {}, {}, [0])",
+            DUMMY_SIGNAL_CONSTRUCTOR, DUMMY_SIGNAL_CONSTRUCTOR
+        ))
+        .unwrap();
+    }
 }
@@ -1,17 +1,175 @@
+use crate::binding::exceptions::ShapeMismatchError;
 use crate::binding::signal::PySignal;
 use crate::binding::Wrapper;
-use crate::probe::{Probe, SignalProbe};
-use crate::signal::ArraySignal;
+use crate::probe::{Decimation, Probe, Projection, SignalProbe, StatisticsProbe};
+use crate::signal::{ArraySignal, ScalarSignal, Signal};
 use ndarray::ArrayD;
 use ndarray::Axis;
-use numpy::PyArrayDyn;
+use numpy::{Element, IntoPyArray, PyArray1, PyArray2, PyArrayDyn};
+use pyo3::exceptions as exc;
 use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyDict, PySlice, PyTuple};
+use std::fmt::Debug;
+use std::os::raw::c_long;
 use std::sync::Arc;
 use std::sync::RwLock;
 
+type ArrayProbe = SignalProbe<ArrayD<f64>, ArraySignal<f64>>;
+type IntegerArrayProbe = SignalProbe<ArrayD<i64>, ArraySignal<i64>>;
+/// Creates the decimation of a probe for a window of the given length.
+type MakeDecimation<T> = fn(usize) -> Decimation<ArrayD<T>>;
+
+fn load_data<T: Element + Copy + Debug + Send + Sync + 'static>(
+    probe: &SignalProbe<ArrayD<T>, ArraySignal<T>>,
+) -> PyResult<Vec<ArrayD<T>>> {
+    probe.load_data().map_err(|e| {
+        PyErr::new::<exc::OSError, _>(format!("Loading spilled probe data failed: {}", e))
+    })
+}
+
+/// All samples stacked along a new first axis.
+fn stacked_data<'p, T: Element + Copy + Debug + Send + Sync + 'static>(
+    py: Python<'p>,
+    probe: &SignalProbe<ArrayD<T>, ArraySignal<T>>,
+) -> PyResult<&'p PyArrayDyn<T>> {
+    let data = load_data(probe)?;
+    let copy = PyArrayDyn::new(py, [&[data.len()], probe.shape()].concat(), false);
+    for (i, x) in data.iter().enumerate() {
+        unsafe {
+            copy.as_array_mut().index_axis_mut(Axis(0), i).assign(x);
+        }
+    }
+    Ok(copy)
+}
+
+/// One array per (flattened) dimension of the samples.
+fn data_columns<'p, T: Element + Copy + Debug + Send + Sync + 'static>(
+    py: Python<'p>,
+    probe: &SignalProbe<ArrayD<T>, ArraySignal<T>>,
+) -> PyResult<Vec<&'p PyArray1<T>>> {
+    let data = load_data(probe)?;
+    let mut values = vec![Vec::with_capacity(data.len()); probe.shape().iter().product()];
+    for x in data.iter() {
+        for (column, value) in values.iter_mut().zip(x.iter()) {
+            column.push(*value);
+        }
+    }
+    Ok(values
+        .iter()
+        .map(|column| PyArray1::from_slice(py, column))
+        .collect())
+}
+
+/// Indices from a slice or a list of integers along the last axis of a signal
+/// of the given shape.
+fn extract_indices(indices: &PyAny, shape: &[usize]) -> PyResult<Vec<usize>> {
+    let len = match shape.last() {
+        Some(len) => *len,
+        None => {
+            return Err(PyErr::new::<exc::ValueError, _>(
+                "Cannot select indices of a scalar signal.",
+            ))
+        }
+    };
+    let indices: Vec<isize> = match indices.downcast::<PySlice>() {
+        Ok(slice) => {
+            let slice = slice.indices(len as c_long)?;
+            (0..slice.slicelength)
+                .map(|i| slice.start + i * slice.step)
+                .collect()
+        }
+        Err(_) => indices.extract()?,
+    };
+    indices
+        .into_iter()
+        .map(|i| {
+            let index = if i < 0 { i + len as isize } else { i };
+            if index < 0 || index >= len as isize {
+                Err(PyErr::new::<exc::IndexError, _>(format!(
+                    "Index {} is out of bounds for a signal of length {}.",
+                    i, len
+                )))
+            } else {
+                Ok(index as usize)
+            }
+        })
+        .collect()
+}
+
+fn extract_decimation(aggregate: Option<&str>) -> PyResult<MakeDecimation<f64>> {
+    match aggregate {
+        None => Ok(Decimation::sample),
+        Some("mean") => Ok(Decimation::mean),
+        Some("max") => Ok(Decimation::max),
+        Some("min") => Ok(Decimation::min),
+        Some(aggregate) => Err(PyErr::new::<exc::ValueError, _>(format!(
+            "Unknown aggregate `{}`, expected `mean`, `max`, or `min`.",
+            aggregate
+        ))),
+    }
+}
+
+fn new_probe<T: Element + Copy + Debug + Send + Sync + 'static>(
+    target: &Arc<ArraySignal<T>>,
+    time: Option<&PySignal>,
+    sample_every: usize,
+    decimation: MakeDecimation<T>,
+) -> PyResult<SignalProbe<ArrayD<T>, ArraySignal<T>>> {
+    if sample_every == 0 {
+        return Err(PyErr::new::<exc::ValueError, _>(
+            "Probe sample_every must be at least 1.",
+        ));
+    }
+    let probe = match time {
+        Some(time) => {
+            SignalProbe::with_time(target, &time.extract_signal::<ScalarSignal<f64>>("time")?)
+        }
+        None => SignalProbe::new(target),
+    };
+    Ok(if sample_every > 1 {
+        probe.decimated(decimation(sample_every))
+    } else {
+        probe
+    })
+}
+
+/// Probes an integer signal such as spike counts, keeping the last sample of
+/// each window. Aggregates and transforms are not supported.
+fn new_integer_probe(
+    target: &Arc<ArraySignal<i64>>,
+    time: Option<&PySignal>,
+    sample_every: usize,
+    aggregate: Option<&str>,
+    indices: Option<&PyAny>,
+    transform: Option<&PyArray2<f64>>,
+) -> PyResult<IntegerArrayProbe> {
+    if aggregate.is_some() || transform.is_some() {
+        return Err(PyErr::new::<exc::ValueError, _>(
+            "Probes of integer signals support neither aggregates nor transforms.",
+        ));
+    }
+    let probe = new_probe(target, time, sample_every, Decimation::sample)?;
+    Ok(match indices {
+        Some(indices) => {
+            let indices = extract_indices(indices, target.shape())?;
+            probe.projected(Projection::select(target, indices))
+        }
+        None => probe,
+    })
+}
+
+fn downcast_probe<P: 'static>(probe: &(dyn Probe + Send + Sync)) -> PyResult<&P> {
+    probe.as_any().downcast_ref::<P>().ok_or_else(|| {
+        PyErr::new::<exc::TypeError, _>("The probe does not support this kind of data.")
+    })
+}
+
 #[pyclass(name=Probe)]
 pub struct PyProbe {
     probe: Arc<RwLock<dyn Probe + Send + Sync>>,
+    /// Defaults to the name of the probed signal.
+    #[pyo3(get)]
+    name: String,
 }
 
 impl Wrapper<Arc<RwLock<dyn Probe + Send + Sync>>> for PyProbe {
@@ -20,43 +178,319 @@ impl Wrapper<Arc<RwLock<dyn Probe + Send + Sync>>> for PyProbe {
     }
 }
 
+impl PyProbe {
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Samples stacked along a new first axis, with the dtype of the probed
+    /// signal.
+    pub(crate) fn data_array<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let probe = self.probe.read().unwrap();
+        if let Some(probe) = probe.as_any().downcast_ref::<IntegerArrayProbe>() {
+            return Ok(stacked_data(py, probe)?.as_ref());
+        }
+        Ok(stacked_data(py, downcast_probe::<ArrayProbe>(&*probe)?)?.as_ref())
+    }
+
+    /// Whether the probe records the time of each sample, which requires a
+    /// time signal when it is created.
+    pub(crate) fn records_times(&self) -> bool {
+        let probe = self.probe.read().unwrap();
+        if let Some(probe) = probe.as_any().downcast_ref::<IntegerArrayProbe>() {
+            return probe.get_times().is_some();
+        }
+        match probe.as_any().downcast_ref::<ArrayProbe>() {
+            Some(probe) => probe.get_times().is_some(),
+            None => false,
+        }
+    }
+
+    /// Whether the probe was created with `Probe.statistics`, so that it
+    /// keeps summary statistics instead of samples.
+    pub(crate) fn is_statistics(&self) -> bool {
+        self.probe.read().unwrap().as_any().is::<StatisticsProbe>()
+    }
+
+    pub(crate) fn statistics_dict<'p>(&self, py: Python<'p>) -> PyResult<&'p PyDict> {
+        let probe = self.probe.read().unwrap();
+        let probe = downcast_probe::<StatisticsProbe>(&*probe)?;
+        let result = PyDict::new(py);
+        result.set_item("count", probe.count())?;
+        result.set_item("mean", probe.mean().to_owned().into_pyarray(py))?;
+        result.set_item("variance", probe.variance().into_pyarray(py))?;
+        result.set_item("min", probe.min().to_owned().into_pyarray(py))?;
+        result.set_item("max", probe.max().to_owned().into_pyarray(py))?;
+        Ok(result)
+    }
+
+    pub(crate) fn times_array<'p>(&self, py: Python<'p>) -> PyResult<&'p PyArray1<f64>> {
+        let probe = self.probe.read().unwrap();
+        let times = match probe.as_any().downcast_ref::<IntegerArrayProbe>() {
+            Some(probe) => probe.get_times(),
+            None => downcast_probe::<ArrayProbe>(&*probe)?.get_times(),
+        };
+        match times {
+            Some(times) => Ok(PyArray1::from_slice(py, times)),
+            None => Err(PyErr::new::<exc::ValueError, _>(
+                "Probe does not record times.",
+            )),
+        }
+    }
+
+    /// Columns of a table of the probe data: the sample times followed by
+    /// one column per (flattened) dimension of the probed signal.
+    fn table_columns<'p>(&self, py: Python<'p>) -> PyResult<&'p PyDict> {
+        let columns = PyDict::new(py);
+        columns.set_item("time", self.times_array(py)?)?;
+
+        let probe = self.probe.read().unwrap();
+        let values: Vec<&PyAny> = match probe.as_any().downcast_ref::<IntegerArrayProbe>() {
+            Some(probe) => data_columns(py, probe)?
+                .into_iter()
+                .map(|c| c.as_ref())
+                .collect(),
+            None => data_columns(py, downcast_probe::<ArrayProbe>(&*probe)?)?
+                .into_iter()
+                .map(|c| c.as_ref())
+                .collect(),
+        };
+        for (i, column) in values.into_iter().enumerate() {
+            columns.set_item(i.to_string(), column)?;
+        }
+        Ok(columns)
+    }
+}
+
 #[pymethods]
 impl PyProbe {
+    /// With `sample_every` > 1, only one sample per window of that many steps
+    /// is stored: the last one, or the elementwise `mean`, `max`, or `min`
+    /// of the window if given as `aggregate`. `indices`, a slice or a list,
+    /// selects the recorded entries along the last axis of the target.
+    /// Alternatively, the last axis is multiplied with a `transform` matrix.
+    /// Integer targets support neither aggregates nor transforms.
     #[new]
-    fn new(target: &PySignal) -> PyResult<Self> {
+    #[args(
+        time = "None",
+        name = "None",
+        sample_every = "1",
+        aggregate = "None",
+        indices = "None",
+        transform = "None"
+    )]
+    fn new(
+        target: &PySignal,
+        time: Option<&PySignal>,
+        name: Option<String>,
+        sample_every: usize,
+        aggregate: Option<&str>,
+        indices: Option<&PyAny>,
+        transform: Option<&PyArray2<f64>>,
+    ) -> PyResult<Self> {
+        let name = name.unwrap_or_else(|| target.get().name().clone());
+        if target.get().as_any().is::<ArraySignal<i64>>() {
+            let target = target.extract_signal("target")?;
+            let probe =
+                new_integer_probe(&target, time, sample_every, aggregate, indices, transform)?;
+            return Ok(Self {
+                probe: Arc::new(RwLock::new(probe)),
+                name,
+            });
+        }
+        let target = target.extract_signal("target")?;
+        let probe = new_probe(&target, time, sample_every, extract_decimation(aggregate)?)?;
+        let probe = match (indices, transform) {
+            (Some(_), Some(_)) => {
+                return Err(PyErr::new::<exc::ValueError, _>(
+                    "A probe cannot have both indices and a transform.",
+                ))
+            }
+            (Some(indices), None) => {
+                let indices = extract_indices(indices, target.shape())?;
+                probe.projected(Projection::select(&target, indices))
+            }
+            (None, Some(transform)) => {
+                let transform = transform.readonly().as_array().to_owned();
+                let len = target.shape().last().copied().unwrap_or(1);
+                if transform.ncols() != len {
+                    return Err(PyErr::new::<ShapeMismatchError, _>(format!(
+                        "Transform of shape {:?} does not fit a target of shape {:?}.",
+                        transform.shape(),
+                        target.shape()
+                    )));
+                }
+                probe.projected(Projection::transform(&target, transform))
+            }
+            (None, None) => probe,
+        };
         Ok(Self {
-            probe: Arc::new(RwLock::new(
-                SignalProbe::<ArrayD<f64>, ArraySignal<f64>>::new(
-                    &target.extract_signal("target")?,
-                ),
-            )),
+            probe: Arc::new(RwLock::new(probe)),
+            name,
         })
     }
 
-    fn get_data(&self) -> PyResult<PyObject> {
-        let probe = self.probe.read().unwrap();
-        let probe = probe
-            .as_any()
-            .downcast_ref::<SignalProbe<ArrayD<f64>, ArraySignal<f64>>>()
-            .unwrap();
-        let data = probe.get_data();
+    /// Records the concatenation of the `targets` along their last axis as
+    /// one sample per step. The other axes of the targets must match.
+    #[staticmethod]
+    #[args(time = "None", name = "None", sample_every = "1", aggregate = "None")]
+    fn concatenated(
+        targets: Vec<&PyCell<PySignal>>,
+        time: Option<&PySignal>,
+        name: Option<String>,
+        sample_every: usize,
+        aggregate: Option<&str>,
+    ) -> PyResult<Self> {
+        let targets = targets
+            .iter()
+            .map(|t| t.borrow().extract_signal("targets"))
+            .collect::<PyResult<Vec<Arc<ArraySignal<f64>>>>>()?;
+        let first = match targets.first() {
+            Some(first) => first,
+            None => {
+                return Err(PyErr::new::<exc::ValueError, _>(
+                    "A concatenating probe needs at least one target.",
+                ))
+            }
+        };
+        let leading = &first.shape()[..first.shape().len().saturating_sub(1)];
+        for target in targets.iter() {
+            let shape = target.shape();
+            if shape.is_empty() || &shape[..shape.len() - 1] != leading {
+                return Err(PyErr::new::<ShapeMismatchError, _>(format!(
+                    "Cannot concatenate `{}` of shape {:?} with `{}` of shape {:?}.",
+                    target.name(),
+                    shape,
+                    first.name(),
+                    first.shape()
+                )));
+            }
+        }
+        let name = name.unwrap_or_else(|| {
+            targets
+                .iter()
+                .map(|t| t.name().as_str())
+                .collect::<Vec<_>>()
+                .join("+")
+        });
+        let probe = new_probe(first, time, sample_every, extract_decimation(aggregate)?)?
+            .projected(Projection::concatenate(&targets));
+        Ok(Self {
+            probe: Arc::new(RwLock::new(probe)),
+            name,
+        })
+    }
+
+    /// Keeps running statistics of each element of `target` instead of its
+    /// samples (see `statistics`), so that its memory use does not grow.
+    #[staticmethod]
+    #[args(name = "None")]
+    fn statistics(target: &PySignal, name: Option<String>) -> PyResult<Self> {
+        let name = name.unwrap_or_else(|| target.get().name().clone());
+        let target = target.extract_signal("target")?;
+        Ok(Self {
+            probe: Arc::new(RwLock::new(StatisticsProbe::new(&target))),
+            name,
+        })
+    }
 
+    /// Returns a dict with the number of samples (`count`) and the
+    /// elementwise `mean`, `variance`, `min`, and `max` of a probe created
+    /// with `Probe.statistics`.
+    fn get_statistics(&self) -> PyResult<PyObject> {
         let gil = Python::acquire_gil();
         let py = gil.python();
-        let copy = PyArrayDyn::new(py, [&[data.len()], probe.shape()].concat(), false);
-        for (i, x) in data.iter().enumerate() {
-            unsafe {
-                copy.as_array_mut().index_axis_mut(Axis(0), i).assign(x);
-            }
+        Ok(self.statistics_dict(py)?.to_object(py))
+    }
+
+    /// Returns the probed values, or a tuple of the sample times and values
+    /// if `with_times` is true.
+    #[args(with_times = "false")]
+    fn get_data(&self, with_times: bool) -> PyResult<PyObject> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let data = self.data_array(py)?;
+        if !with_times {
+            return Ok(data.to_object(py));
         }
-        Ok(copy.to_object(py))
+        Ok(PyTuple::new(
+            py,
+            &[self.times_array(py)?.to_object(py), data.to_object(py)],
+        )
+        .to_object(py))
+    }
+
+    /// Returns a dict with `data`, `coords`, `dims`, and `name` that can be
+    /// passed to `xarray.DataArray(**d)`. The first dimension is the time,
+    /// the others are named by `dims` (`dim_0`, `dim_1`, ... by default).
+    #[args(dims = "None")]
+    fn to_xarray_dict(&self, dims: Option<Vec<String>>) -> PyResult<PyObject> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let data = self.data_array(py)?;
+        let n_dims = data.getattr("ndim")?.extract::<usize>()? - 1;
+        let dims = dims.unwrap_or_else(|| (0..n_dims).map(|i| format!("dim_{}", i)).collect());
+        if dims.len() != n_dims {
+            return Err(PyErr::new::<exc::ValueError, _>(format!(
+                "Expected {} dimension names, but got {}.",
+                n_dims,
+                dims.len()
+            )));
+        }
+
+        let result = PyDict::new(py);
+        result.set_item("data", data)?;
+        result.set_item("coords", [("time", self.times_array(py)?)].into_py_dict(py))?;
+        result.set_item(
+            "dims",
+            [vec!["time".to_string()], dims].concat().to_object(py),
+        )?;
+        result.set_item("name", &self.name)?;
+        Ok(result.to_object(py))
+    }
+
+    /// Returns the probe data as a `pyarrow.Table` with a time column and one
+    /// column per dimension. Requires the `arrow` extra.
+    fn to_arrow(&self) -> PyResult<PyObject> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let pyarrow = PyModule::import(py, "pyarrow")?;
+        Ok(pyarrow
+            .call1("table", (self.table_columns(py)?,))?
+            .to_object(py))
+    }
+
+    /// Returns the probe data as a `pandas.DataFrame` with a time column and
+    /// one column per dimension. Requires the `pandas` extra.
+    fn to_pandas(&self) -> PyResult<PyObject> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let pandas = PyModule::import(py, "pandas")?;
+        Ok(pandas
+            .call1("DataFrame", (self.table_columns(py)?,))?
+            .to_object(py))
+    }
+
+    /// Writes the probe data as a compressed Parquet file with a time column
+    /// and one column per dimension. Requires the `arrow` extra.
+    #[args(compression = "\"zstd\"")]
+    pub fn save_parquet(&self, path: &PyAny, compression: &str) -> PyResult<()> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let parquet = PyModule::import(py, "pyarrow.parquet")?;
+        parquet.call(
+            "write_table",
+            (self.to_arrow()?, path),
+            Some([("compression", compression)].into_py_dict(py)),
+        )?;
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::binding::signal::PySignalArrayF64;
+    use crate::binding::signal::{PySignalArrayF64, PySignalArrayI64};
     use crate::signal::{ArraySignal, Signal, SignalAccess};
     use crate::venv::activate_venv;
     use ndarray::prelude::*;
@@ -66,6 +500,7 @@ mod tests {
     fn probe(_py: Python, m: &PyModule) -> PyResult<()> {
         m.add_class::<PyProbe>()?;
         m.add_class::<PySignalArrayF64>()?;
+        m.add_class::<PySignalArrayI64>()?;
         Ok(())
     }
 
@@ -124,4 +559,67 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_statistics_probe_binding() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let numpy = PyModule::import(py, "numpy").unwrap();
+        let locals = [
+            ("np", numpy.to_object(py)),
+            ("p", wrap_pymodule!(probe)(py)),
+        ]
+        .into_py_dict(py);
+        py.run(
+            "signal = p.SignalArrayF64('x', np.array([1., 2.]))\n\
+             probe = p.Probe.statistics(signal)",
+            None,
+            Some(locals),
+        )
+        .unwrap();
+        let py_probe: &PyCell<PyProbe> = locals.get_item("probe").unwrap().extract().unwrap();
+        py_probe.borrow().get().write().unwrap().probe();
+
+        let count: u64 = py
+            .eval("probe.get_statistics()['count']", None, Some(locals))
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(count, 1);
+        assert!(py.eval("probe.get_data()", None, Some(locals)).is_err());
+    }
+
+    #[test]
+    fn test_integer_probe_binding() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let numpy = PyModule::import(py, "numpy").unwrap();
+        let locals = [
+            ("np", numpy.to_object(py)),
+            ("p", wrap_pymodule!(probe)(py)),
+        ]
+        .into_py_dict(py);
+        py.run(
+            "signal = p.SignalArrayI64('x', np.array([1, 2, 3]))\n\
+             probe = p.Probe(signal, indices=[0, 2])",
+            None,
+            Some(locals),
+        )
+        .unwrap();
+        let py_probe: &PyCell<PyProbe> = locals.get_item("probe").unwrap().extract().unwrap();
+        py_probe.borrow().get().write().unwrap().probe();
+
+        let data = py.eval("probe.get_data()", None, Some(locals)).unwrap();
+        let data: &PyArrayDyn<i64> = data.extract().unwrap();
+        assert_eq!(data.readonly().as_array(), array![[1, 3]].into_dyn());
+        assert!(py
+            .eval(
+                "p.Probe(signal, sample_every=2, aggregate='mean')",
+                None,
+                Some(locals)
+            )
+            .is_err());
+    }
 }
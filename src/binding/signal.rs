@@ -1,11 +1,18 @@
+use crate::binding::exceptions::{ShapeMismatchError, SignalTypeError};
 use crate::binding::Wrapper;
-use crate::signal::{ArraySignal, ScalarSignal, Signal, SignalAccess};
-use ndarray::{SliceInfo, SliceOrIndex};
-use numpy::PyArrayDyn;
+use crate::operator::{strip_module_paths, Condition};
+use crate::rng::Distribution;
+use crate::signal::{ArrayRef, ArraySignal, ScalarSignal, Signal, SignalAccess, SparseSignal};
+use ndarray::{IxDyn, SliceInfo, SliceOrIndex};
+use numpy::npyffi::{self, npy_intp, PY_ARRAY_API};
+use numpy::{c64, Element, IntoPyArray, PyArrayDyn};
 use pyo3::exceptions as exc;
 use pyo3::prelude::*;
 use pyo3::types::PySlice;
+use pyo3::wrap_pyfunction;
+use pyo3::{ffi, AsPyPointer};
 use std::any::type_name;
+use std::mem;
 use std::sync::Arc;
 
 #[pyclass(name=Signal)]
@@ -25,14 +32,58 @@ impl PySignal {
         &self,
         name: &str,
     ) -> PyResult<Arc<T>> {
-        Arc::downcast::<T>(Arc::clone(&self.signal).as_any_arc()).or(Err(PyErr::new::<
-            exc::TypeError,
-            _,
-        >(format!(
-            "Signal `{}` must be {}.",
-            name,
-            type_name::<T>()
-        ))))
+        Arc::downcast::<T>(Arc::clone(&self.signal).as_any_arc()).map_err(|_| {
+            PyErr::new::<SignalTypeError, _>(format!(
+                "Signal `{}` must be {}, but got `{}` with dtype {} and shape {:?}.",
+                name,
+                strip_module_paths(type_name::<T>()),
+                self.signal.name(),
+                self.signal.dtype(),
+                self.signal.shape()
+            ))
+        })
+    }
+
+    pub fn extract_condition(&self, name: &str) -> PyResult<Arc<dyn Condition + Send + Sync>> {
+        let signal = Arc::clone(&self.signal).as_any_arc();
+        let signal = match Arc::downcast::<ArraySignal<f64>>(signal) {
+            Ok(signal) => return Ok(signal),
+            Err(signal) => signal,
+        };
+        let signal = match Arc::downcast::<ScalarSignal<f64>>(signal) {
+            Ok(signal) => return Ok(signal),
+            Err(signal) => signal,
+        };
+        match Arc::downcast::<ScalarSignal<u64>>(signal) {
+            Ok(signal) => Ok(signal),
+            Err(_) => Err(PyErr::new::<SignalTypeError, _>(format!(
+                "Signal `{}` cannot be used as a condition, but got `{}` with dtype {} and shape {:?}.",
+                name,
+                self.signal.name(),
+                self.signal.dtype(),
+                self.signal.shape()
+            ))),
+        }
+    }
+}
+
+#[pymethods]
+impl PySignal {
+    /// Keeps the current value of an array signal for good, so that the
+    /// engine rejects operators writing it and reads it without locking.
+    /// Engines built before raise a `BuildError` when they run instead.
+    /// Scalar and sparse signals stay writable.
+    fn make_read_only(&self) {
+        let signal = self.signal.as_any();
+        if let Some(signal) = signal.downcast_ref::<ArraySignal<f64>>() {
+            signal.make_read_only();
+        } else if let Some(signal) = signal.downcast_ref::<ArraySignal<f32>>() {
+            signal.make_read_only();
+        } else if let Some(signal) = signal.downcast_ref::<ArraySignal<i64>>() {
+            signal.make_read_only();
+        } else if let Some(signal) = signal.downcast_ref::<ArraySignal<c64>>() {
+            signal.make_read_only();
+        }
     }
 }
 
@@ -41,14 +92,316 @@ pub struct PySignalArrayF64 {}
 
 #[pymethods]
 impl PySignalArrayF64 {
+    /// Takes either a nengo `Signal`, or a name and an initial value, so
+    /// that nengo is not needed.
     #[new]
-    fn new(signal: &PyAny) -> PyResult<(Self, PySignal)> {
-        let name = signal.getattr("name")?.extract()?;
-        let initial_value = signal.getattr("initial_value")?;
-        let initial_value: &PyArrayDyn<f64> = initial_value.extract()?;
+    #[args(initial_value = "None")]
+    fn new(signal: &PyAny, initial_value: Option<&PyArrayDyn<f64>>) -> PyResult<(Self, PySignal)> {
+        let (name, initial_value) = match initial_value {
+            Some(initial_value) => (signal.extract()?, initial_value),
+            None => (
+                signal.getattr("name")?.extract()?,
+                signal.getattr("initial_value")?.extract()?,
+            ),
+        };
         let signal = Arc::new(ArraySignal::new(name, initial_value));
         Ok((Self {}, PySignal { signal }))
     }
+
+    /// Returns a copy of the current value.
+    fn get<'py>(py_self: PyRef<Self>, py: Python<'py>) -> &'py PyArrayDyn<f64> {
+        get_array(py_self.as_ref(), py)
+    }
+
+    /// Overwrites the current value, e.g. between `run_steps` calls. The next
+    /// reset restores the initial value.
+    fn set(py_self: PyRef<Self>, value: &PyArrayDyn<f64>) -> PyResult<()> {
+        set_array(py_self.as_ref(), value)
+    }
+
+    /// Returns a numpy array sharing the value of the signal instead of a
+    /// copy, e.g. to inspect large state or weight matrices. The array keeps
+    /// the signal alive and is not writeable. The buffer of a signal never
+    /// moves, but its contents are only a consistent value of the signal
+    /// until the next step, reset, or `set`, after which the array shows the
+    /// new value. While a run is in progress, it may show a partially written
+    /// step. Use `get`, or copy the array, to keep a value. Read-only
+    /// signals never change, so their arrays stay valid during runs.
+    fn view<'py>(py_self: PyRef<Self>, py: Python<'py>) -> PyResult<&'py PyArrayDyn<f64>> {
+        let signal = py_self
+            .as_ref()
+            .signal
+            .as_any()
+            .downcast_ref::<ArraySignal<f64>>()
+            .unwrap();
+        let (data, strides) = match &**signal.read() {
+            ArrayRef::Owned(array) => (
+                array.as_ptr(),
+                array
+                    .strides()
+                    .iter()
+                    .map(|&s| (s * mem::size_of::<f64>() as isize) as npy_intp)
+                    .collect::<Vec<_>>(),
+            ),
+            ArrayRef::View(_, _) => unreachable!("SignalArrayF64 is never a view."),
+        };
+        let mut dims: Vec<npy_intp> = signal.shape().iter().map(|&d| d as npy_intp).collect();
+        unsafe {
+            let ptr = PY_ARRAY_API.PyArray_New(
+                PY_ARRAY_API.get_type_object(npyffi::ArrayType::PyArray_Type),
+                dims.len() as i32,
+                dims.as_mut_ptr(),
+                f64::ffi_dtype() as i32,
+                strides.as_ptr() as *mut _,
+                data as *mut _,
+                mem::size_of::<f64>() as i32,
+                0,
+                std::ptr::null_mut(),
+            );
+            ffi::Py_INCREF(py_self.as_ptr());
+            PY_ARRAY_API.PyArray_SetBaseObject(ptr as *mut npyffi::PyArrayObject, py_self.as_ptr());
+            Ok(PyArrayDyn::from_owned_ptr(py, ptr))
+        }
+    }
+}
+
+fn get_array<'py>(signal: &PySignal, py: Python<'py>) -> &'py PyArrayDyn<f64> {
+    let signal = signal
+        .signal
+        .as_any()
+        .downcast_ref::<ArraySignal<f64>>()
+        .unwrap();
+    let value = signal.read().clone_array();
+    value
+        .into_shape(IxDyn(signal.shape()))
+        .unwrap()
+        .into_pyarray(py)
+}
+
+fn set_array(signal: &PySignal, value: &PyArrayDyn<f64>) -> PyResult<()> {
+    let signal = signal
+        .signal
+        .as_any()
+        .downcast_ref::<ArraySignal<f64>>()
+        .unwrap();
+    let value = value.to_owned_array();
+    if value.shape() != signal.shape() {
+        return Err(PyErr::new::<ShapeMismatchError, _>(format!(
+            "Value of shape {:?} does not fit signal `{}` of shape {:?}.",
+            value.shape(),
+            signal.name(),
+            signal.shape()
+        )));
+    }
+    if signal.is_read_only() {
+        return Err(PyErr::new::<exc::ValueError, _>(format!(
+            "Signal `{}` is read-only.",
+            signal.name()
+        )));
+    }
+    signal.write().assign_array(&value);
+    Ok(())
+}
+
+/// Single precision counterpart of `SignalArrayF64`, which halves the memory
+/// and bandwidth needed by large models.
+#[pyclass(extends=PySignal, name=SignalArrayF32)]
+pub struct PySignalArrayF32 {}
+
+#[pymethods]
+impl PySignalArrayF32 {
+    #[new]
+    #[args(initial_value = "None")]
+    fn new(signal: &PyAny, initial_value: Option<&PyArrayDyn<f32>>) -> PyResult<(Self, PySignal)> {
+        let (name, initial_value) = match initial_value {
+            Some(initial_value) => (signal.extract()?, initial_value),
+            None => (
+                signal.getattr("name")?.extract()?,
+                signal.getattr("initial_value")?.extract()?,
+            ),
+        };
+        let signal = Arc::new(ArraySignal::new(name, initial_value));
+        Ok((Self {}, PySignal { signal }))
+    }
+}
+
+/// Integer counterpart of `SignalArrayF64`, e.g. for spike counts or
+/// indices.
+#[pyclass(extends=PySignal, name=SignalArrayI64)]
+pub struct PySignalArrayI64 {}
+
+#[pymethods]
+impl PySignalArrayI64 {
+    #[new]
+    #[args(initial_value = "None")]
+    fn new(signal: &PyAny, initial_value: Option<&PyArrayDyn<i64>>) -> PyResult<(Self, PySignal)> {
+        let (name, initial_value) = match initial_value {
+            Some(initial_value) => (signal.extract()?, initial_value),
+            None => (
+                signal.getattr("name")?.extract()?,
+                signal.getattr("initial_value")?.extract()?,
+            ),
+        };
+        let signal = Arc::new(ArraySignal::new(name, initial_value));
+        Ok((Self {}, PySignal { signal }))
+    }
+}
+
+/// Complex counterpart of `SignalArrayF64`.
+#[pyclass(extends=PySignal, name=SignalArrayC128)]
+pub struct PySignalArrayC128 {}
+
+#[pymethods]
+impl PySignalArrayC128 {
+    #[new]
+    #[args(initial_value = "None")]
+    fn new(signal: &PyAny, initial_value: Option<&PyArrayDyn<c64>>) -> PyResult<(Self, PySignal)> {
+        let (name, initial_value) = match initial_value {
+            Some(initial_value) => (signal.extract()?, initial_value),
+            None => (
+                signal.getattr("name")?.extract()?,
+                signal.getattr("initial_value")?.extract()?,
+            ),
+        };
+        let signal = Arc::new(ArraySignal::new(name, initial_value));
+        Ok((Self {}, PySignal { signal }))
+    }
+}
+
+/// Sparse matrix signal taking any `scipy.sparse` matrix as initial value,
+/// which is stored in CSR format without densifying it.
+#[pyclass(extends=PySignal, name=SignalSparseF64)]
+pub struct PySignalSparseF64 {}
+
+#[pymethods]
+impl PySignalSparseF64 {
+    #[new]
+    #[args(initial_value = "None")]
+    fn new(signal: &PyAny, initial_value: Option<&PyAny>) -> PyResult<(Self, PySignal)> {
+        let (name, initial_value) = match initial_value {
+            Some(initial_value) => (signal.extract()?, initial_value),
+            None => (
+                signal.getattr("name")?.extract()?,
+                signal.getattr("initial_value")?,
+            ),
+        };
+        let csr = initial_value.call_method0("tocsr")?;
+        let list = |attr: &str| csr.getattr(attr)?.call_method0("tolist");
+        let signal = SparseSignal::<f64>::new(
+            name,
+            csr.getattr("shape")?.extract()?,
+            list("indptr")?.extract()?,
+            list("indices")?.extract()?,
+            csr.getattr("data")?
+                .call_method1("astype", ("float64",))?
+                .call_method0("tolist")?
+                .extract()?,
+        )
+        .map_err(|e| PyErr::new::<ShapeMismatchError, _>(e.message))?;
+        Ok((
+            Self {},
+            PySignal {
+                signal: Arc::new(signal),
+            },
+        ))
+    }
+}
+
+/// Signal whose initial value is drawn in Rust from a nengo `Uniform` or
+/// `Gaussian` distribution on every reset.
+#[pyclass(extends=PySignal, name=SignalArraySampledF64)]
+pub struct PySignalArraySampledF64 {}
+
+#[pymethods]
+impl PySignalArraySampledF64 {
+    #[new]
+    fn new(
+        name: String,
+        shape: Vec<usize>,
+        distribution: &PyAny,
+        seed: u64,
+    ) -> PyResult<(Self, PySignal)> {
+        let distribution = extract_distribution(distribution)?;
+        let signal = Arc::new(ArraySignal::<f64>::sampled(name, shape, distribution, seed));
+        Ok((Self {}, PySignal { signal }))
+    }
+}
+
+fn extract_distribution(distribution: &PyAny) -> PyResult<Distribution> {
+    let kind: String = distribution.get_type().getattr("__name__")?.extract()?;
+    match kind.as_str() {
+        "Uniform" if !distribution.getattr("integer")?.is_true()? => Ok(Distribution::Uniform {
+            low: distribution.getattr("low")?.extract()?,
+            high: distribution.getattr("high")?.extract()?,
+        }),
+        "Gaussian" => Ok(Distribution::Gaussian {
+            mean: distribution.getattr("mean")?.extract()?,
+            std: distribution.getattr("std")?.extract()?,
+        }),
+        _ => Err(PyErr::new::<exc::ValueError, _>(format!(
+            "Cannot sample from {} in Rust.",
+            distribution.repr()?
+        ))),
+    }
+}
+
+/// Creates the signal class fitting the dtype and shape of the initial value
+/// of a nengo `Signal`, or of `initial_value` with `signal` as name: a
+/// `SignalU64` for non-negative integer scalars, a `SignalSparseF64` for
+/// `scipy.sparse` matrices, a `SignalArrayC128` for complex values, a
+/// `SignalArrayF32` for single precision floats, a `SignalArrayI64` for other
+/// integers fitting into 64 bits, and a `SignalArrayF64` with the values
+/// converted to floats for other real values.
+#[pyfunction(initial_value = "None")]
+fn make_signal(py: Python, signal: &PyAny, initial_value: Option<&PyAny>) -> PyResult<PyObject> {
+    let (name, initial_value): (String, &PyAny) = match initial_value {
+        Some(initial_value) => (signal.extract()?, initial_value),
+        None => (
+            signal.getattr("name")?.extract()?,
+            signal.getattr("initial_value")?,
+        ),
+    };
+    if initial_value.hasattr("tocsr")? {
+        let signal = py
+            .get_type::<PySignalSparseF64>()
+            .call1((name, initial_value))?;
+        return Ok(signal.to_object(py));
+    }
+    let value = PyModule::import(py, "numpy")?.call1("asarray", (initial_value,))?;
+    let dtype = value.getattr("dtype")?;
+    let kind: String = dtype.getattr("kind")?.extract()?;
+    let ndim: usize = value.getattr("ndim")?.extract()?;
+    let signal = match kind.as_str() {
+        "i" | "u" if ndim == 0 => match value.call_method0("item")?.extract::<u64>() {
+            Ok(value) => py.get_type::<PySignalU64>().call1((name, value))?,
+            Err(_) => py
+                .get_type::<PySignalArrayI64>()
+                .call1((name, value.call_method1("astype", ("int64",))?))?,
+        },
+        "i" | "u" if kind == "i" || dtype.getattr("itemsize")?.extract::<usize>()? < 8 => py
+            .get_type::<PySignalArrayI64>()
+            .call1((name, value.call_method1("astype", ("int64",))?))?,
+        "f" if dtype.getattr("itemsize")?.extract::<usize>()? == 4 => {
+            py.get_type::<PySignalArrayF32>().call1((name, value))?
+        }
+        "b" | "i" | "u" | "f" => py
+            .get_type::<PySignalArrayF64>()
+            .call1((name, value.call_method1("astype", ("float64",))?))?,
+        "c" => py
+            .get_type::<PySignalArrayC128>()
+            .call1((name, value.call_method1("astype", ("complex128",))?))?,
+        _ => {
+            return Err(PyErr::new::<SignalTypeError, _>(format!(
+                "No signal supports values of dtype {}.",
+                dtype.str()?
+            )))
+        }
+    };
+    Ok(signal.to_object(py))
+}
+
+pub fn add_functions(m: &PyModule) -> PyResult<()> {
+    m.add_wrapped(wrap_pyfunction!(make_signal))
 }
 
 #[pyclass(extends=PySignal, name=SignalArrayViewF64)]
@@ -64,18 +417,36 @@ impl PySignalArrayViewF64 {
     ) -> PyResult<(Self, PySignal)> {
         let base: &PyCell<PySignal> = base.extract().unwrap();
         let base: Arc<ArraySignal<f64>> = base.borrow().extract_signal("base")?;
+        if let ArrayRef::View(_, _) = **base.read() {
+            return Err(PyErr::new::<SignalTypeError, _>(format!(
+                "Base of view `{}` must not be a view itself.",
+                name
+            )));
+        }
 
-        let slice_info: Vec<&PySlice> = slice_info.extract()?;
+        let slice_info: Vec<&PyAny> = slice_info.extract()?;
+        if slice_info.len() != base.shape().len() {
+            return Err(PyErr::new::<ShapeMismatchError, _>(format!(
+                "View `{}` has {} slices, but its base has {} dimensions.",
+                name,
+                slice_info.len(),
+                base.shape().len()
+            )));
+        }
         let slice_info = Box::new(
             SliceInfo::new(
                 slice_info
                     .into_iter()
-                    .map(|py_slice| {
-                        Ok(SliceOrIndex::Slice {
-                            start: py_slice.getattr("start")?.extract()?,
-                            step: py_slice.getattr("step")?.extract()?,
-                            end: Some(py_slice.getattr("stop")?.extract()?),
-                        })
+                    .zip(base.shape().iter())
+                    .enumerate()
+                    .map(|(axis, (elem, &len))| match elem.downcast::<PySlice>() {
+                        Ok(py_slice) => numpy_slice(
+                            py_slice.getattr("start")?.extract()?,
+                            py_slice.getattr("stop")?.extract()?,
+                            py_slice.getattr("step")?.extract()?,
+                            len,
+                        ),
+                        Err(_) => numpy_index(elem.extract()?, axis, len),
                     })
                     .collect::<PyResult<Vec<SliceOrIndex>>>()?,
             )
@@ -85,6 +456,87 @@ impl PySignalArrayViewF64 {
         let signal = Arc::new(ArraySignal::new_view(name, base, slice_info));
         Ok((Self {}, PySignal { signal }))
     }
+
+    /// Returns a copy of the viewed part of the base signal.
+    fn get<'py>(py_self: PyRef<Self>, py: Python<'py>) -> &'py PyArrayDyn<f64> {
+        get_array(py_self.as_ref(), py)
+    }
+
+    /// Overwrites the viewed part of the base signal.
+    fn set(py_self: PyRef<Self>, value: &PyArrayDyn<f64>) -> PyResult<()> {
+        set_array(py_self.as_ref(), value)
+    }
+}
+
+/// Converts the numpy slice `start:stop:step` along an axis of length `len`
+/// to the ndarray slice selecting the same elements in the same order.
+/// Negative bounds count from the end and are clipped like in numpy, and
+/// `None` bounds extend to the end the step walks towards. With a negative
+/// step, numpy walks down from `start`, whereas ndarray reverses the range
+/// `start..end`.
+fn numpy_slice(
+    start: Option<isize>,
+    stop: Option<isize>,
+    step: Option<isize>,
+    len: usize,
+) -> PyResult<SliceOrIndex> {
+    let step = step.unwrap_or(1);
+    if step == 0 {
+        return Err(PyErr::new::<exc::ValueError, _>(
+            "Slice step cannot be zero.",
+        ));
+    }
+    let len = len as isize;
+    let resolve = |i: isize| {
+        if i < 0 {
+            (i + len).max(if step < 0 { -1 } else { 0 })
+        } else {
+            i.min(if step < 0 { len - 1 } else { len })
+        }
+    };
+    let start = start.map_or(if step < 0 { len - 1 } else { 0 }, resolve);
+    let stop = stop.map_or(if step < 0 { -1 } else { len }, resolve);
+    let n = if step > 0 && start < stop {
+        (stop - start - 1) / step + 1
+    } else if step < 0 && stop < start {
+        (start - stop - 1) / -step + 1
+    } else {
+        0
+    };
+    Ok(match n {
+        0 => SliceOrIndex::Slice {
+            start: 0,
+            end: Some(0),
+            step: 1,
+        },
+        _ if step > 0 => SliceOrIndex::Slice {
+            start,
+            end: Some(start + step * (n - 1) + 1),
+            step,
+        },
+        _ => SliceOrIndex::Slice {
+            start: start + step * (n - 1),
+            end: Some(start + 1),
+            step,
+        },
+    })
+}
+
+/// Converts the numpy index `index` along `axis` of length `len`, which may
+/// count from the end, to an ndarray index dropping the axis.
+fn numpy_index(index: isize, axis: usize, len: usize) -> PyResult<SliceOrIndex> {
+    let resolved = if index < 0 {
+        index + len as isize
+    } else {
+        index
+    };
+    if resolved < 0 || resolved >= len as isize {
+        return Err(PyErr::new::<exc::IndexError, _>(format!(
+            "Index {} is out of bounds for axis {} with size {}.",
+            index, axis, len
+        )));
+    }
+    Ok(SliceOrIndex::Index(resolved))
 }
 
 #[pyclass(extends=PySignal, name=SignalU64)]
@@ -137,12 +589,21 @@ impl PySignalF64 {
             .unwrap()
             .read()
     }
+
+    fn set(py_self: PyRef<Self>, value: f64) {
+        **py_self
+            .as_ref()
+            .signal
+            .as_any()
+            .downcast_ref::<ScalarSignal<f64>>()
+            .unwrap()
+            .write() = value;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::signal::ArrayRef;
     use crate::venv::activate_venv;
     use ndarray::prelude::*;
     use ndarray::Ix;
@@ -152,6 +613,12 @@ mod tests {
     #[pymodule]
     fn signal(_py: Python, m: &PyModule) -> PyResult<()> {
         m.add_class::<PySignalArrayF64>()?;
+        m.add_class::<PySignalArraySampledF64>()?;
+        m.add_class::<PySignalArrayF32>()?;
+        m.add_class::<PySignalArrayI64>()?;
+        m.add_class::<PySignalArrayC128>()?;
+        m.add_class::<PySignalSparseF64>()?;
+        m.add_wrapped(wrap_pyfunction!(make_signal))?;
         m.add_class::<PySignalArrayViewF64>()?;
         m.add_class::<PySignalF64>()?;
         m.add_class::<PySignalU64>()?;
@@ -212,6 +679,185 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_py_signal_array_f64_from_name_and_array() {
+        test_binding::<_, ArraySignal<f64>>(
+            "s.SignalArrayF64('TestSignal', np.array([[1., 2.]]))",
+            "TestSignal",
+            &[1, 2],
+            ArrayRef::Owned(array![[1., 2.]].into_dyn()),
+        );
+    }
+
+    #[test]
+    fn test_make_signal_for_float_arrays() {
+        test_binding::<_, ArraySignal<f64>>(
+            "s.make_signal(nengo.builder.signal.Signal(np.array([1., 2.], dtype=np.float16), name='TestSignal'))",
+            "TestSignal",
+            &[2],
+            ArrayRef::Owned(array![1., 2.].into_dyn()),
+        );
+    }
+
+    #[test]
+    fn test_make_signal_for_single_precision_arrays() {
+        test_binding::<_, ArraySignal<f32>>(
+            "s.make_signal('TestSignal', np.array([1., 2.], dtype=np.float32))",
+            "TestSignal",
+            &[2],
+            ArrayRef::Owned(array![1f32, 2.].into_dyn()),
+        );
+    }
+
+    #[test]
+    fn test_make_signal_for_integer_arrays() {
+        test_binding::<_, ArraySignal<i64>>(
+            "s.make_signal('TestSignal', np.array([-1, 2], dtype=np.int32))",
+            "TestSignal",
+            &[2],
+            ArrayRef::Owned(array![-1i64, 2].into_dyn()),
+        );
+    }
+
+    #[test]
+    fn test_make_signal_for_integer_scalars() {
+        test_binding::<_, ScalarSignal<u64>>(
+            "s.make_signal('TestSignal', np.int64(3))",
+            "TestSignal",
+            &[],
+            3,
+        );
+    }
+
+    #[test]
+    fn array_signals_can_be_inspected_and_overridden() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let numpy = PyModule::import(py, "numpy").unwrap();
+        let signal_module = wrap_pymodule!(signal)(py);
+        let locals = [("np", numpy.to_object(py)), ("s", signal_module)].into_py_dict(py);
+
+        py.run(
+            "base = s.SignalArrayF64('base', np.zeros(3))\n\
+             view = s.SignalArrayViewF64('view', (slice(0, 2, 1),), base)\n\
+             view.set(np.array([1., 2.]))\n\
+             assert np.array_equal(base.get(), [1., 2., 0.])\n\
+             base.set(np.array([3., 4., 5.]))\n\
+             assert np.array_equal(view.get(), [3., 4.])",
+            None,
+            Some(locals),
+        )
+        .unwrap();
+        assert!(py
+            .eval("base.set(np.zeros(2))", None, Some(locals))
+            .is_err());
+    }
+
+    #[test]
+    fn array_signal_views_share_the_buffer() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let numpy = PyModule::import(py, "numpy").unwrap();
+        let signal_module = wrap_pymodule!(signal)(py);
+        let locals = [("np", numpy.to_object(py)), ("s", signal_module)].into_py_dict(py);
+
+        py.run(
+            "signal = s.SignalArrayF64('x', np.arange(6.).reshape((2, 3)))\n\
+             signal.make_read_only()\n\
+             view = signal.view()\n\
+             del signal\n\
+             assert np.array_equal(view, np.arange(6.).reshape((2, 3)))\n\
+             assert not view.flags.writeable",
+            None,
+            Some(locals),
+        )
+        .unwrap();
+        let signal: &PyCell<PySignal> = py
+            .eval("view.base", None, Some(locals))
+            .unwrap()
+            .extract()
+            .unwrap();
+        let signal: Arc<ArraySignal<f64>> = signal.borrow().extract_signal("x").unwrap();
+        let view: &PyArrayDyn<f64> = py
+            .eval("view", None, Some(locals))
+            .unwrap()
+            .extract()
+            .unwrap();
+        match &**signal.read() {
+            ArrayRef::Owned(value) => {
+                assert_eq!(unsafe { view.as_array() }.as_ptr(), value.as_ptr())
+            }
+            ArrayRef::View(_, _) => unreachable!(),
+        }
+
+        assert!(py.run("view[0, 0] = 1.", None, Some(locals)).is_err());
+    }
+
+    #[test]
+    fn array_signal_views_reflect_writes() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let numpy = PyModule::import(py, "numpy").unwrap();
+        let signal_module = wrap_pymodule!(signal)(py);
+        let locals = [("np", numpy.to_object(py)), ("s", signal_module)].into_py_dict(py);
+
+        py.run(
+            "signal = s.SignalArrayF64('y', np.ones(2))\n\
+             view = signal.view()\n\
+             signal.set(np.array([2., 3.]))\n\
+             assert np.array_equal(view, [2., 3.])\n\
+             signal.set(np.array([4., 5.]))\n\
+             assert np.array_equal(view, [4., 5.])\n\
+             assert not view.flags.writeable",
+            None,
+            Some(locals),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_py_signal_array_f32() {
+        test_binding::<_, ArraySignal<f32>>(
+            "s.SignalArrayF32('TestSignal', np.array([1., 2.], dtype=np.float32))",
+            "TestSignal",
+            &[2],
+            ArrayRef::Owned(array![1f32, 2.].into_dyn()),
+        );
+    }
+
+    #[test]
+    fn test_py_signal_array_i64() {
+        test_binding::<_, ArraySignal<i64>>(
+            "s.SignalArrayI64('TestSignal', np.array([1, 2], dtype=np.int64))",
+            "TestSignal",
+            &[2],
+            ArrayRef::Owned(array![1i64, 2].into_dyn()),
+        );
+    }
+
+    #[test]
+    fn test_py_signal_array_c128() {
+        test_binding::<_, ArraySignal<c64>>(
+            "s.make_signal('TestSignal', np.array([1j, 2.]))",
+            "TestSignal",
+            &[2],
+            ArrayRef::Owned(array![c64::new(0., 1.), c64::new(2., 0.)].into_dyn()),
+        );
+    }
+
+    #[test]
+    fn test_py_signal_array_sampled_f64() {
+        test_binding::<_, ArraySignal<f64>>(
+            "s.SignalArraySampledF64('TestSignal', [2, 2], nengo.dists.Uniform(3., 3.), 1)",
+            "TestSignal",
+            &[2, 2],
+            ArrayRef::Owned(array![[3., 3.], [3., 3.]].into_dyn()),
+        );
+    }
+
     fn test_view_binding(
         base_expr: &str,
         expr: &str,
@@ -302,6 +948,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_py_signal_array_view_f64_reversed() {
+        test_view_binding(
+            "nengo.builder.signal.Signal(np.array([0., 1., 2., 3., 4.]), name='BaseSignal')",
+            "s.SignalArrayViewF64('view_signal', (slice(-1, 0, -2),), base_signal)",
+            "view_signal",
+            &[2],
+            ArrayRef::Owned(array![4., 2.].into_dimensionality::<IxDyn>().unwrap()),
+        );
+    }
+
+    #[test]
+    fn test_py_signal_array_view_f64_with_open_slices() {
+        test_view_binding(
+            "nengo.builder.signal.Signal(np.arange(6, dtype=float).reshape((2, 3)), name='BaseSignal')",
+            "s.SignalArrayViewF64('view_signal', (slice(None), slice(None, None, -2)), base_signal)",
+            "view_signal",
+            &[2, 2],
+            ArrayRef::Owned(array![[2., 0.], [5., 3.]].into_dimensionality::<IxDyn>().unwrap()),
+        );
+    }
+
+    #[test]
+    fn test_py_signal_array_view_f64_with_index() {
+        test_view_binding(
+            "nengo.builder.signal.Signal(np.arange(6, dtype=float).reshape((2, 3)), name='BaseSignal')",
+            "s.SignalArrayViewF64('view_signal', (slice(0, 2, 1), -1), base_signal)",
+            "view_signal",
+            &[2],
+            ArrayRef::Owned(array![2., 5.].into_dimensionality::<IxDyn>().unwrap()),
+        );
+    }
+
+    #[test]
+    fn numpy_indices_may_count_from_the_end() {
+        assert_eq!(numpy_index(2, 0, 3).unwrap(), SliceOrIndex::Index(2));
+        assert_eq!(numpy_index(-3, 0, 3).unwrap(), SliceOrIndex::Index(0));
+        assert!(numpy_index(3, 0, 3).is_err());
+        assert!(numpy_index(-4, 0, 3).is_err());
+    }
+
+    #[test]
+    fn numpy_slices_select_the_same_elements_in_ndarray() {
+        let x = Array::from((0..5).collect::<Vec<_>>()).into_dyn();
+        let select = |start, stop, step| {
+            let slice = SliceInfo::<_, IxDyn>::new(vec![numpy_slice(
+                Some(start),
+                Some(stop),
+                Some(step),
+                5,
+            )
+            .unwrap()])
+            .unwrap();
+            x.slice(slice.as_ref()).iter().copied().collect::<Vec<_>>()
+        };
+
+        assert_eq!(select(1, 4, 2), vec![1, 3]);
+        assert_eq!(select(4, -6, -1), vec![4, 3, 2, 1, 0]);
+        assert_eq!(select(-1, 0, -2), vec![4, 2]);
+        assert_eq!(select(3, 10, -1), Vec::<i32>::new());
+        assert_eq!(select(-2, 10, 1), vec![3, 4]);
+        assert!(numpy_slice(Some(0), Some(5), Some(0), 5).is_err());
+    }
+
+    #[test]
+    fn open_numpy_slices_extend_to_the_ends() {
+        let full = SliceOrIndex::Slice {
+            start: 0,
+            end: Some(5),
+            step: 1,
+        };
+        let reversed = SliceOrIndex::Slice {
+            start: 0,
+            end: Some(5),
+            step: -1,
+        };
+
+        assert_eq!(numpy_slice(None, None, None, 5).unwrap(), full);
+        assert_eq!(numpy_slice(None, None, Some(-1), 5).unwrap(), reversed);
+        assert_eq!(
+            numpy_slice(Some(2), None, None, 5).unwrap(),
+            SliceOrIndex::Slice {
+                start: 2,
+                end: Some(5),
+                step: 1,
+            }
+        );
+    }
+
     #[test]
     fn test_py_signal_u64() {
         test_binding::<_, ScalarSignal<u64>>("s.SignalU64('TestSignal', 2)", "TestSignal", &[], 2);
@@ -0,0 +1,226 @@
+use crate::replay::Value;
+use ndarray::ArrayD;
+use numpy::c64;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Identifies checkpoint files and the version of their format.
+const MAGIC: &[u8; 8] = b"NENGORS1";
+
+/// State of an engine from which a later process can resume the run, see
+/// `Engine::set_checkpoints` and `Engine::resume`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub step: u64,
+    pub seed: u64,
+    /// Number of samples each probe has taken before the checkpoint.
+    pub probe_offsets: Vec<usize>,
+    /// Unique names and values of the signals, except for views.
+    pub signals: Vec<(String, Value)>,
+}
+
+fn write_u64<W: Write + ?Sized>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f64(reader: &mut impl Read) -> io::Result<f64> {
+    read_u64(reader).map(f64::from_bits)
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn write_array<T: Copy>(
+    writer: &mut impl Write,
+    array: &ArrayD<T>,
+    write: impl Fn(&mut dyn Write, T) -> io::Result<()>,
+) -> io::Result<()> {
+    write_u64(writer, array.ndim() as u64)?;
+    for &len in array.shape() {
+        write_u64(writer, len as u64)?;
+    }
+    array.iter().try_for_each(|&x| write(writer, x))
+}
+
+fn read_array<T, R: Read>(
+    reader: &mut R,
+    read: impl Fn(&mut R) -> io::Result<T>,
+) -> io::Result<ArrayD<T>> {
+    let ndim = read_u64(reader)?;
+    let shape = (0..ndim)
+        .map(|_| read_u64(reader).map(|len| len as usize))
+        .collect::<io::Result<Vec<_>>>()?;
+    let values = (0..shape.iter().product())
+        .map(|_| read(reader))
+        .collect::<io::Result<Vec<_>>>()?;
+    ArrayD::from_shape_vec(shape, values).map_err(|e| invalid_data(e.to_string()))
+}
+
+fn write_value(writer: &mut impl Write, value: &Value) -> io::Result<()> {
+    match value {
+        Value::ArrayF64(value) => {
+            writer.write_all(&[0])?;
+            write_array(writer, value, |w, x| write_u64(w, x.to_bits()))
+        }
+        Value::ArrayC64(value) => {
+            writer.write_all(&[1])?;
+            write_array(writer, value, |w, x: c64| {
+                write_u64(w, x.re.to_bits())?;
+                write_u64(w, x.im.to_bits())
+            })
+        }
+        Value::ArrayU64(value) => {
+            writer.write_all(&[2])?;
+            write_array(writer, value, |w, x| write_u64(w, x))
+        }
+        Value::ArrayF32(value) => {
+            writer.write_all(&[5])?;
+            write_array(writer, value, |w, x| write_u64(w, x.to_bits().into()))
+        }
+        Value::ArrayI64(value) => {
+            writer.write_all(&[6])?;
+            write_array(writer, value, |w, x| write_u64(w, x as u64))
+        }
+        Value::F64(value) => {
+            writer.write_all(&[3])?;
+            write_u64(writer, value.to_bits())
+        }
+        Value::U64(value) => {
+            writer.write_all(&[4])?;
+            write_u64(writer, *value)
+        }
+    }
+}
+
+fn read_value(reader: &mut impl Read) -> io::Result<Value> {
+    let mut kind = [0];
+    reader.read_exact(&mut kind)?;
+    Ok(match kind[0] {
+        0 => Value::ArrayF64(read_array(reader, read_f64)?),
+        1 => Value::ArrayC64(read_array(reader, |r| {
+            Ok(c64::new(read_f64(r)?, read_f64(r)?))
+        })?),
+        2 => Value::ArrayU64(read_array(reader, read_u64)?),
+        3 => Value::F64(read_f64(reader)?),
+        4 => Value::U64(read_u64(reader)?),
+        5 => Value::ArrayF32(read_array(reader, |r| {
+            Ok(f32::from_bits(read_u64(r)? as u32))
+        })?),
+        6 => Value::ArrayI64(read_array(reader, |r| Ok(read_u64(r)? as i64))?),
+        kind => return Err(invalid_data(format!("Unknown signal kind {}.", kind))),
+    })
+}
+
+impl Checkpoint {
+    /// Writes the checkpoint to a temporary file that then replaces `path`,
+    /// so that a crash while writing keeps the previous checkpoint intact.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+        let file = File::create(&temp_path)?;
+        let mut writer = BufWriter::new(&file);
+        writer.write_all(MAGIC)?;
+        write_u64(&mut writer, self.step)?;
+        write_u64(&mut writer, self.seed)?;
+        write_u64(&mut writer, self.probe_offsets.len() as u64)?;
+        for &offset in self.probe_offsets.iter() {
+            write_u64(&mut writer, offset as u64)?;
+        }
+        write_u64(&mut writer, self.signals.len() as u64)?;
+        for (name, value) in self.signals.iter() {
+            write_u64(&mut writer, name.len() as u64)?;
+            writer.write_all(name.as_bytes())?;
+            write_value(&mut writer, value)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data(format!(
+                "{} is not a checkpoint.",
+                path.display()
+            )));
+        }
+        let step = read_u64(&mut reader)?;
+        let seed = read_u64(&mut reader)?;
+        let probe_offsets = (0..read_u64(&mut reader)?)
+            .map(|_| read_u64(&mut reader).map(|offset| offset as usize))
+            .collect::<io::Result<_>>()?;
+        let signals = (0..read_u64(&mut reader)?)
+            .map(|_| {
+                let mut name = vec![0; read_u64(&mut reader)? as usize];
+                reader.read_exact(&mut name)?;
+                let name = String::from_utf8(name).map_err(|e| invalid_data(e.to_string()))?;
+                Ok((name, read_value(&mut reader)?))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Checkpoint {
+            step,
+            seed,
+            probe_offsets,
+            signals,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    #[test]
+    fn it_reads_the_checkpoint_it_wrote() {
+        let checkpoint = Checkpoint {
+            step: 42,
+            seed: 7,
+            probe_offsets: vec![40, 4],
+            signals: vec![
+                (
+                    "x".to_string(),
+                    Value::ArrayF64(array![[1., -2.], [0.5, 4.]].into_dyn()),
+                ),
+                (
+                    "z".to_string(),
+                    Value::ArrayC64(array![c64::new(1., -1.)].into_dyn()),
+                ),
+                ("spikes".to_string(), Value::ArrayU64(array![3].into_dyn())),
+                (
+                    "counts".to_string(),
+                    Value::ArrayI64(array![-3, 7].into_dyn()),
+                ),
+                (
+                    "y".to_string(),
+                    Value::ArrayF32(array![1.5f32, -0.25].into_dyn()),
+                ),
+                ("t".to_string(), Value::F64(0.042)),
+                ("step".to_string(), Value::U64(42)),
+            ],
+        };
+        let path = std::env::temp_dir().join(format!(
+            "nengo-rs-checkpoint-test-{}.bin",
+            std::process::id()
+        ));
+
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), checkpoint);
+    }
+}
@@ -1,19 +1,375 @@
-use crate::operator::{Operator, OperatorNode};
+use crate::analysis::{suggest_merges, MergeSuggestion};
+use crate::checkpoint::Checkpoint;
+use crate::fault::{Fault, FaultInjector, FaultKind, FaultTrigger};
+use crate::logging::log_warning;
+use crate::metrics::Metrics;
+use crate::operator::{
+    ErrorCause, Operator, OperatorError, OperatorNode, StepWrapper, WrappedOperator,
+};
 use crate::probe::Probe;
-use crate::signal::Signal;
-use crate::sync::Event;
+use crate::replay::{replay, Divergence, StepRecord, StepRecorder, Value};
+use crate::rng::derive_seed;
+use crate::signal::{read_only_signal_count, Region, Signal};
+use crate::slow_callback::{Report, SlowCallbackMonitor};
+use crate::sync::{Event, StepBarrier};
+use crate::verify::{check_operators, is_view, NonFiniteGuard, Problem, Snapshot};
+use futures::channel::oneshot;
 use futures::executor::ThreadPool;
 use futures::future::{BoxFuture, Future, FutureExt, Shared};
 use futures::stream::{FuturesOrdered, FuturesUnordered, StreamExt};
-use pyo3::Python;
-use std::sync::{Arc, RwLock};
+use pyo3::{AsPyRef, PyErr, PyObject, Python};
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{self, Display};
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// Holds the first error raised by an operator while the engine is running.
+type ErrorSlot = Arc<Mutex<Option<OperatorError>>>;
+
+type ProbeFuture = Shared<BoxFuture<'static, bool>>;
+
+/// How often Python signal handlers run while waiting for the thread pool.
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Number of recent step durations kept by default.
+const DEFAULT_STEP_HISTORY: usize = 1000;
+
+pub type HookFn = Box<dyn Fn(u64) -> Result<(), ErrorCause> + Send + Sync>;
+
+/// Called with the index of the step, outside of the operator graph.
+pub enum StepHook {
+    Python(PyObject),
+    Rust(HookFn),
+}
+
+impl StepHook {
+    fn call(&self, step: u64) -> Result<(), ErrorCause> {
+        match self {
+            StepHook::Python(hook) => {
+                let gil = Python::acquire_gil();
+                let py = gil.python();
+                hook.as_ref(py)
+                    .call1((step,))
+                    .map(|_| ())
+                    .map_err(|e| ErrorCause::python(py, e))
+            }
+            StepHook::Rust(hook) => hook(step),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct StepHooks {
+    pre: Vec<Arc<StepHook>>,
+    post: Vec<Arc<StepHook>>,
+}
+
+/// How the operators of a step are run.
+#[derive(Clone)]
+enum Schedule {
+    /// All operators in a single task.
+    Joined,
+    /// Each group of operators in its own task on the thread pool.
+    Grouped(Arc<Vec<Vec<usize>>>, ThreadPool),
+}
+
+/// Wall-clock durations of the most recent steps, and totals over all steps.
+#[derive(Default)]
+pub(crate) struct StepHistory {
+    capacity: usize,
+    durations: VecDeque<Duration>,
+    pub(crate) total_steps: u64,
+    pub(crate) total_duration: Duration,
+}
+
+impl StepHistory {
+    fn push(&mut self, duration: Duration) {
+        self.total_steps += 1;
+        self.total_duration += duration;
+        if self.capacity == 0 {
+            return;
+        }
+        if self.durations.len() == self.capacity {
+            self.durations.pop_front();
+        }
+        self.durations.push_back(duration);
+    }
+}
 
 pub struct Engine {
     signals: Vec<Arc<dyn Signal + Send + Sync>>,
+    /// Unique name of each signal, see `signal_names`.
+    signal_names: Vec<String>,
+    signals_by_name: HashMap<String, usize>,
     operators: Vec<Arc<OperatorNode>>,
     probes: Vec<Arc<RwLock<dyn Probe + Send + Sync>>>,
+    /// For each operator, the probes reading signals it writes. Only these
+    /// probes need to finish before the operator runs in the next step.
+    probe_blockers: Arc<Vec<Vec<usize>>>,
+    hooks: StepHooks,
+    /// Groups of operators run as separate tasks, if enabled.
+    groups: Option<Arc<Vec<Vec<usize>>>>,
     thread_pool: ThreadPool,
+    /// Number of threads of `thread_pool`, see `set_pool_size`.
+    pool_size: usize,
     is_done: Arc<Event>,
+    /// Set when a run is interrupted, so that it stops before its next step.
+    is_cancelled: Arc<AtomicBool>,
+    /// Whether the signals have been reset since the engine was built.
+    is_initialized: AtomicBool,
+    dt: Option<f64>,
+    /// Master seed from which the seeds of stochastic operators are derived.
+    seed: u64,
+    step: AtomicU64,
+    step_history: Arc<Mutex<StepHistory>>,
+    metrics: Option<Arc<Metrics>>,
+    /// Records the operators on a chosen step, see `record_step`.
+    recorder: Option<Arc<StepRecorder>>,
+    /// File and number of steps between checkpoints written by `run_steps`.
+    checkpoints: Option<(PathBuf, u64)>,
+    /// Samples taken by each probe before the checkpoint resumed from.
+    probe_offsets: Vec<usize>,
+    /// Fraction of the timestep shared by the slow callback monitors, as
+    /// `f64` bits, once enabled.
+    slow_callback_fraction: Option<Arc<AtomicU64>>,
+    /// Faults injected into signals, see `inject_fault`.
+    faults: Vec<Arc<FaultInjector>>,
+    /// Value of `read_only_signal_count` when the operators were last
+    /// checked for writes of read-only signals.
+    read_only_checked: AtomicU64,
+}
+
+/// Wall-clock time of a benchmark run and the time spent stepping operators.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Benchmark {
+    pub n_steps: u64,
+    pub total: Duration,
+    /// CPU time spent in operators, summed over all worker threads. With
+    /// worker groups, it can exceed the wall-clock time.
+    pub rust_operators: Duration,
+    pub python_operators: Duration,
+}
+
+impl Benchmark {
+    pub fn steps_per_second(&self) -> f64 {
+        self.n_steps as f64 / self.total.as_secs_f64()
+    }
+
+    /// Time not spent in operators: scheduling, waiting for dependencies,
+    /// and acquiring the GIL. As the operator times are CPU times, this is
+    /// only the wall-clock overhead if the operators run one at a time, i.e.
+    /// without worker groups. Otherwise, it is zero once operators on several
+    /// workers take longer than the run.
+    pub fn overhead(&self) -> Duration {
+        self.total
+            .checked_sub(self.rust_operators + self.python_operators)
+            .unwrap_or_default()
+    }
+}
+
+/// Memory held by an engine, by signal (with its unique name), operator,
+/// and probe (in the order they were given).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryReport {
+    pub signals: Vec<(String, usize)>,
+    pub operators: Vec<(String, usize)>,
+    pub probes: Vec<ProbeMemory>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeMemory {
+    pub bytes: usize,
+    pub bytes_per_step: f64,
+}
+
+impl MemoryReport {
+    pub fn total_bytes(&self) -> usize {
+        self.signals.iter().map(|(_, b)| b).sum::<usize>()
+            + self.operators.iter().map(|(_, b)| b).sum::<usize>()
+            + self.probes.iter().map(|p| p.bytes).sum::<usize>()
+    }
+
+    /// Expected total after another `n_steps` steps, if no probe samples
+    /// are moved to disk.
+    pub fn predicted_bytes(&self, n_steps: u64) -> f64 {
+        let growth: f64 = self.probes.iter().map(|p| p.bytes_per_step).sum();
+        self.total_bytes() as f64 + growth * n_steps as f64
+    }
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    Operator(OperatorError),
+    /// A Python signal handler (e.g., for KeyboardInterrupt) raised while
+    /// waiting for the thread pool.
+    Interrupted(PyErr),
+}
+
+impl From<OperatorError> for RunError {
+    fn from(err: OperatorError) -> Self {
+        RunError::Operator(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// An operator depends on an index that does not refer to an earlier
+    /// operator.
+    InvalidDependency {
+        index: usize,
+        operator: String,
+        dependency: usize,
+    },
+    /// Two operators that are not ordered by their dependencies write
+    /// overlapping memory.
+    WriteConflict {
+        operators: (String, String),
+        signals: (String, String),
+    },
+    /// An operator has a period of zero.
+    InvalidPeriod(String),
+    /// Two operators running at different periods write overlapping memory.
+    /// Signals are only resampled between operators reading and writing them,
+    /// so all writers of a signal must run at the same rate.
+    InconsistentPeriod {
+        operators: (String, String),
+        periods: (u64, u64),
+        signals: (String, String),
+    },
+    /// The timestep is not positive and finite.
+    InvalidDt(f64),
+    /// An operator was built for a different timestep than the engine.
+    InconsistentDt {
+        operator: String,
+        dt: f64,
+        expected: f64,
+    },
+    /// Several signals share a name, but unique names were required.
+    DuplicateSignalName(String),
+    /// A checkpoint was not written by an engine of the same model.
+    IncompatibleCheckpoint(String),
+    /// Coupled signals differ in their type or shape.
+    IncompatibleCoupling { source: String, target: String },
+    /// An operator writes a signal that a coupling copies values into.
+    CoupledSignalWritten { signal: String, operator: String },
+    /// A fault cannot be applied to the signal.
+    InvalidFault { signal: String, reason: String },
+    /// The signal shapes of operators do not fit together, with a message
+    /// for each of these operators.
+    InconsistentShapes(Vec<String>),
+    /// An operator writes memory of a read-only signal.
+    ReadOnlySignalWritten { signal: String, operator: String },
+    /// An operator is memoized, but its steps do not only depend on the
+    /// values of its signals, or it increments its targets.
+    InvalidMemo(String),
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::InvalidDependency {
+                index,
+                operator,
+                dependency,
+            } => write!(
+                f,
+                "Operator {} at index {} depends on index {}, which does not refer \
+                 to an earlier operator.",
+                operator, index, dependency
+            ),
+            BuildError::WriteConflict { operators, signals } => write!(
+                f,
+                "Operators {} (writing `{}`) and {} (writing `{}`) may run concurrently, \
+                 but write overlapping memory.",
+                operators.0, signals.0, operators.1, signals.1
+            ),
+            BuildError::InvalidPeriod(operator) => {
+                write!(f, "Operator {} must have a period of at least 1.", operator)
+            }
+            BuildError::InconsistentPeriod {
+                operators,
+                periods,
+                signals,
+            } => write!(
+                f,
+                "Operators {} (writing `{}` every {} steps) and {} (writing `{}` every \
+                 {} steps) write overlapping memory, but run at different rates.",
+                operators.0, signals.0, periods.0, operators.1, signals.1, periods.1
+            ),
+            BuildError::InvalidDt(dt) => {
+                write!(f, "Timestep must be positive and finite, but is {}.", dt)
+            }
+            BuildError::InconsistentDt {
+                operator,
+                dt,
+                expected,
+            } => write!(
+                f,
+                "Operator {} uses a timestep of {}, but the engine uses {}.",
+                operator, dt, expected
+            ),
+            BuildError::DuplicateSignalName(name) => {
+                write!(f, "Several signals are named `{}`.", name)
+            }
+            BuildError::IncompatibleCheckpoint(reason) => {
+                write!(f, "Checkpoint does not fit the engine: {}.", reason)
+            }
+            BuildError::IncompatibleCoupling { source, target } => write!(
+                f,
+                "Signal `{}` cannot be coupled to `{}` of another type or shape.",
+                source, target
+            ),
+            BuildError::CoupledSignalWritten { signal, operator } => write!(
+                f,
+                "Signal `{}` receives coupled values, but is written by {}.",
+                signal, operator
+            ),
+            BuildError::InvalidFault { signal, reason } => {
+                write!(f, "Cannot inject a fault into `{}`: {}.", signal, reason)
+            }
+            BuildError::InconsistentShapes(messages) => {
+                write!(
+                    f,
+                    "Signal shapes of {} operators do not fit:",
+                    messages.len()
+                )?;
+                messages
+                    .iter()
+                    .try_for_each(|message| write!(f, "\n  {}", message))
+            }
+            BuildError::ReadOnlySignalWritten { signal, operator } => write!(
+                f,
+                "Signal `{}` is read-only, but is written by {}.",
+                signal, operator
+            ),
+            BuildError::InvalidMemo(operator) => write!(
+                f,
+                "Operator {} cannot be memoized, as it is not pure or increments its targets.",
+                operator
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Thread pool used by all engines that are not given an explicit pool, so that
+/// several engines in one process do not oversubscribe the CPU.
+pub fn shared_thread_pool() -> ThreadPool {
+    static THREAD_POOL: OnceLock<ThreadPool> = OnceLock::new();
+    THREAD_POOL
+        .get_or_init(|| ThreadPool::new().unwrap())
+        .clone()
+}
+
+/// Number of threads of a pool created without an explicit size.
+pub fn default_pool_size() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
 }
 
 impl Engine {
@@ -21,265 +377,2193 @@ impl Engine {
         signals: Vec<Arc<dyn Signal + Send + Sync>>,
         operators: Vec<Arc<OperatorNode>>,
         probes: Vec<Arc<RwLock<dyn Probe + Send + Sync>>>,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, BuildError> {
+        Self::with_thread_pool(signals, operators, probes, shared_thread_pool())
+    }
+
+    pub fn with_thread_pool(
+        signals: Vec<Arc<dyn Signal + Send + Sync>>,
+        operators: Vec<Arc<OperatorNode>>,
+        probes: Vec<Arc<RwLock<dyn Probe + Send + Sync>>>,
+        thread_pool: ThreadPool,
+    ) -> Result<Self, BuildError> {
+        check_dependencies(&operators)?;
+        check_periods(&operators)?;
+        check_memos(&operators)?;
+        check_shapes(&operators)?;
+        check_write_conflicts(&operators)?;
+        let read_only_checked = AtomicU64::new(read_only_signal_count());
+        check_read_only_writes(&signals, &operators)?;
+        let is_done = Arc::new(Event::new());
+        is_done.set();
+        let probe_blockers = Arc::new(find_probe_blockers(&operators, &probes));
+        let probe_offsets = vec![0; probes.len()];
+        let dt = operators
+            .iter()
+            .filter_map(|node| node.operator.dt().map(|dt| (node.period, dt)))
+            .min_by_key(|(period, _)| *period)
+            .map(|(period, dt)| dt / period as f64);
+        if let Some(dt) = dt {
+            check_dt(&operators, dt)?;
+        }
+        let signal_names = unique_signal_names(&signals);
+        let signals_by_name = signal_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), i))
+            .collect();
+        Ok(Self {
             signals,
+            signal_names,
+            signals_by_name,
             operators,
             probes,
-            thread_pool: ThreadPool::new().unwrap(),
-            is_done: Arc::new(Event::new()),
+            probe_blockers,
+            hooks: StepHooks::default(),
+            groups: None,
+            thread_pool,
+            pool_size: default_pool_size(),
+            is_done,
+            is_cancelled: Arc::new(AtomicBool::new(false)),
+            is_initialized: AtomicBool::new(false),
+            dt,
+            seed: RandomState::new().build_hasher().finish(),
+            step: AtomicU64::new(0),
+            step_history: Arc::new(Mutex::new(StepHistory {
+                capacity: DEFAULT_STEP_HISTORY,
+                durations: VecDeque::with_capacity(DEFAULT_STEP_HISTORY),
+                total_steps: 0,
+                total_duration: Duration::default(),
+            })),
+            metrics: None,
+            recorder: None,
+            checkpoints: None,
+            probe_offsets,
+            slow_callback_fraction: None,
+            faults: vec![],
+            read_only_checked,
+        })
+    }
+
+    /// Timestep of the simulation, if set or used by any operator.
+    pub fn dt(&self) -> Option<f64> {
+        self.dt
+    }
+
+    /// Sets the timestep, which all operators depending on one must use
+    /// (multiplied by their period).
+    pub fn set_dt(&mut self, dt: f64) -> Result<(), BuildError> {
+        check_dt(&self.operators, dt)?;
+        self.dt = Some(dt);
+        Ok(())
+    }
+
+    /// Adds a hook called before the operators of each step run, once the
+    /// probes of the previous step have recorded.
+    pub fn add_pre_step_hook(&mut self, hook: StepHook) {
+        self.hooks.pre.push(Arc::new(hook));
+    }
+
+    /// Adds a hook called after each step, once its probes have recorded.
+    /// Hooks run in the order they were added and a failing hook stops the
+    /// run like a failing operator. Probes of a step no longer record while
+    /// the next step runs if there are any hooks.
+    pub fn add_post_step_hook(&mut self, hook: StepHook) {
+        self.hooks.post.push(Arc::new(hook));
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Sets the master seed. Each stochastic operator is seeded from it and
+    /// its index, so runs with the same seed give identical results.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.seed_operators();
+    }
+
+    fn seed_operators(&self) {
+        for (i, node) in self.operators.iter().enumerate() {
+            node.operator.set_seed(derive_seed(self.seed, i as u64));
         }
     }
 
-    pub fn run_step(&self) {
-        let gil = Python::acquire_gil();
-        gil.python().allow_threads(|| {
-            self.run_threaded(Self::run_step_async(
-                self.operators.clone(),
-                self.probes.clone(),
-            ));
+    /// Sets the number of threads of the thread pool, which the metrics
+    /// relate the busy time of the operators to. Defaults to the size of
+    /// pools created without an explicit size.
+    pub fn set_pool_size(&mut self, pool_size: usize) {
+        self.pool_size = pool_size.max(1);
+    }
+
+    /// Splits the operators into up to `n_groups` groups, each run as its own
+    /// task on the thread pool, or runs all operators in one task if `None`.
+    /// Operators touching the same signals are grouped together, so that
+    /// these are mostly accessed from a single worker.
+    pub fn set_worker_groups(&mut self, n_groups: Option<usize>) {
+        self.groups = n_groups.map(|n| {
+            let mut groups = vec![vec![]; n.max(1)];
+            for (i, group) in group_operators(&self.operators, n.max(1))
+                .into_iter()
+                .enumerate()
+            {
+                groups[group].push(i);
+            }
+            groups.retain(|group| !group.is_empty());
+            Arc::new(groups)
         });
     }
 
-    pub fn run_steps(&self, n_steps: i64) {
-        for _ in 0..n_steps {
-            self.run_step();
+    fn schedule(&self) -> Schedule {
+        match &self.groups {
+            Some(groups) => Schedule::Grouped(Arc::clone(groups), self.thread_pool.clone()),
+            None => Schedule::Joined,
         }
     }
 
-    pub fn reset(&self) {
-        self.signals.iter().for_each(|s| s.reset());
+    /// Limits the memory used by all probes together to about `bytes`, split
+    /// evenly between them. Older samples beyond it are moved to disk.
+    pub fn set_probe_memory_budget(&self, bytes: Option<usize>) {
+        let n_probes = self.probes.len().max(1);
+        for probe in self.probes.iter() {
+            probe
+                .write()
+                .unwrap()
+                .set_memory_budget(bytes.map(|b| b / n_probes));
+        }
     }
 
-    fn run_threaded<Fut: Future<Output = ()> + Send + 'static>(&self, fut: Fut) {
-        self.is_done.clear();
-        self.thread_pool
-            .spawn_ok(Self::notify_when_done(fut, Arc::clone(&self.is_done)));
-        self.is_done.wait();
+    /// Writes a checkpoint to `path` after every `every` steps of
+    /// `run_steps`, replacing the previous one, or stops with `None`.
+    pub fn set_checkpoints(&mut self, path: Option<PathBuf>, every: u64) {
+        self.checkpoints = path.map(|path| (path, every.max(1)));
     }
 
-    async fn notify_when_done<Fut: Future<Output = ()> + Send + 'static>(
-        fut: Fut,
-        is_done: Arc<Event>,
-    ) {
-        fut.await;
-        is_done.set();
+    /// Returns the current state, from which an engine of the same model can
+    /// resume in another process.
+    pub fn checkpoint(&self) -> Checkpoint {
+        make_checkpoint(
+            self.step.load(Ordering::SeqCst),
+            self.seed,
+            &self.signals,
+            &self.signal_names,
+            &self.probes,
+            &self.probe_offsets,
+        )
     }
 
-    async fn run_step_async(
-        operators: Vec<Arc<OperatorNode>>,
-        probes: Vec<Arc<RwLock<dyn Probe + Send + Sync>>>,
-    ) {
-        Self::run_operators(operators).await;
-        Self::run_probes(probes).await;
+    /// Continues from a checkpoint of an engine of the same model. The
+    /// signals are reset and set to the saved values, and the step counter,
+    /// seed, and probe offsets are restored. Probes start without samples
+    /// and stochastic operators restart their random streams.
+    pub fn resume(&mut self, checkpoint: &Checkpoint) -> Result<(), BuildError> {
+        if checkpoint.probe_offsets.len() != self.probes.len() {
+            return Err(BuildError::IncompatibleCheckpoint(format!(
+                "it has {} probes instead of {}",
+                checkpoint.probe_offsets.len(),
+                self.probes.len()
+            )));
+        }
+        let mut values = vec![];
+        for (name, value) in checkpoint.signals.iter() {
+            match self.signals_by_name.get(name) {
+                Some(&i) if value.fits(&*self.signals[i]) => values.push((i, value)),
+                _ => {
+                    return Err(BuildError::IncompatibleCheckpoint(format!(
+                        "the engine has no signal `{}` of the saved type and shape",
+                        name
+                    )))
+                }
+            }
+        }
+        self.seed = checkpoint.seed;
+        self.reset();
+        for (i, value) in values {
+            if !self.signals[i].is_read_only() {
+                value.assign_to(&*self.signals[i]);
+            }
+        }
+        self.step.store(checkpoint.step, Ordering::SeqCst);
+        self.probe_offsets = checkpoint.probe_offsets.clone();
+        Ok(())
     }
 
-    async fn run_operators(nodes: Vec<Arc<OperatorNode>>) {
-        let mut tasks: Vec<Shared<BoxFuture<'_, ()>>> = Vec::with_capacity(nodes.len());
-        for node in nodes.iter() {
-            let dependencies = node
-                .dependencies
-                .iter()
-                .map(|i| Shared::clone(&tasks[*i]))
-                .collect::<FuturesUnordered<_>>();
-            tasks.push(
-                Self::create_operator_future(&(*node.operator), dependencies)
-                    .boxed()
-                    .shared(),
-            );
-        }
-        tasks
-            .iter()
-            .map(|f| Shared::clone(f))
-            .collect::<FuturesOrdered<_>>()
-            .collect::<()>()
-            .await;
+    /// Number of samples each probe took before the checkpoint the engine
+    /// resumed from, which are not part of its data.
+    pub fn probe_offsets(&self) -> &[usize] {
+        &self.probe_offsets
     }
 
-    async fn run_probes(probes: Vec<Arc<RwLock<dyn Probe + Send + Sync>>>) {
-        probes
+    /// Name of an operator writing memory overlapping `region`, if any.
+    pub(crate) fn writer_of(&self, region: &Region) -> Option<String> {
+        self.operators
             .iter()
-            .map(Self::probe_async)
-            .collect::<FuturesUnordered<_>>()
-            .collect::<()>()
-            .await;
+            .find(|node| {
+                node.operator
+                    .writes()
+                    .iter()
+                    .any(|signal| signal.region().overlaps(region))
+            })
+            .map(|node| node.operator.name())
     }
 
-    async fn probe_async(probe: &Arc<RwLock<dyn Probe + Send + Sync>>) {
-        probe.write().unwrap().probe();
+    pub fn signals(&self) -> &[Arc<dyn Signal + Send + Sync>] {
+        &self.signals
     }
 
-    async fn create_operator_future(
-        operator: &(dyn Operator + Send + Sync),
-        dependencies: FuturesUnordered<Shared<BoxFuture<'_, ()>>>,
-    ) {
-        dependencies.collect::<()>().await;
-        operator.step();
+    /// Unique names of the signals, in the same order. Signals sharing the
+    /// name of an earlier one get a `#1`, `#2`, ... suffix.
+    pub fn signal_names(&self) -> &[String] {
+        &self.signal_names
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::signal::AnySignal;
-    use ndarray::Ix;
-    use std::any::Any;
 
-    #[derive(Debug)]
-    struct FakeSignal {
-        name: String,
-        num_reset_calls: RwLock<u32>,
+    /// Looks up a signal by its unique name.
+    pub fn signal(&self, name: &str) -> Option<&Arc<dyn Signal + Send + Sync>> {
+        self.signals_by_name.get(name).map(|&i| &self.signals[i])
     }
 
-    impl FakeSignal {
-        fn new(name: String) -> Self {
-            Self {
-                name,
-                num_reset_calls: RwLock::new(0),
-            }
+    /// Fails if any signals share a name, so that they would be renamed.
+    pub fn check_unique_signal_names(&self) -> Result<(), BuildError> {
+        match self
+            .signals
+            .iter()
+            .zip(self.signal_names.iter())
+            .find(|(signal, name)| signal.name() != *name)
+        {
+            Some((signal, _)) => Err(BuildError::DuplicateSignalName(signal.name().clone())),
+            None => Ok(()),
         }
     }
 
-    impl Signal for FakeSignal {
-        fn as_any(&self) -> &dyn Any {
-            self
+    pub fn memory_report(&self) -> MemoryReport {
+        MemoryReport {
+            signals: self
+                .signal_names
+                .iter()
+                .zip(self.signals.iter())
+                .map(|(name, signal)| (name.clone(), signal.nbytes()))
+                .collect(),
+            operators: self
+                .operators
+                .iter()
+                .map(|node| (node.operator.name(), node.operator.memory_usage()))
+                .collect(),
+            probes: self
+                .probes
+                .iter()
+                .map(|probe| {
+                    let probe = probe.read().unwrap();
+                    ProbeMemory {
+                        bytes: probe.memory_usage(),
+                        bytes_per_step: probe.bytes_per_step(),
+                    }
+                })
+                .collect(),
         }
+    }
 
-        fn as_any_arc(self: Arc<Self>) -> Arc<AnySignal> {
-            self
+    /// Signals whose accesses blocked while lock wait tracking was enabled
+    /// (see `signal::track_lock_waits`), most contended first.
+    pub fn contended_signals(&self) -> Vec<(String, Duration)> {
+        let mut waits: Vec<(String, Duration)> = self
+            .signals
+            .iter()
+            .map(|s| (s.name().clone(), s.lock_wait()))
+            .filter(|(_, wait)| *wait > Duration::default())
+            .collect();
+        waits.sort_by_key(|(_, wait)| std::cmp::Reverse(*wait));
+        waits
+    }
+
+    /// Wall-clock durations of the most recent steps, oldest first.
+    pub fn step_durations(&self) -> Vec<Duration> {
+        self.step_history
+            .lock()
+            .unwrap()
+            .durations
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// Sets how many recent step durations are kept.
+    pub fn set_step_history(&self, capacity: usize) {
+        let mut history = self.step_history.lock().unwrap();
+        history.capacity = capacity;
+        while history.durations.len() > capacity {
+            history.durations.pop_front();
         }
+    }
 
-        fn name(&self) -> &String {
-            &self.name
+    /// Warns through the `nengo_rs` Python logger about each operator calling
+    /// Python whose steps consistently take longer than `fraction` of its
+    /// timestep, such as a slow node function in a model that is meant to
+    /// run in real time. Calling it again changes the fraction. Without a
+    /// known timestep, nothing is monitored.
+    pub fn warn_about_slow_callbacks(&mut self, fraction: f64) {
+        let dt = match self.dt {
+            Some(dt) => dt,
+            None => return,
+        };
+        if let Some(shared) = &self.slow_callback_fraction {
+            shared.store(fraction.to_bits(), Ordering::SeqCst);
+            return;
         }
+        let shared = Arc::new(AtomicU64::new(fraction.to_bits()));
+        let report: Report = Arc::new(|message| log_warning(&message));
+        self.operators = self
+            .operators
+            .iter()
+            .map(|node| {
+                if node.operator.requires_gil() {
+                    SlowCallbackMonitor::wrap(
+                        node,
+                        dt * node.period as f64,
+                        Arc::clone(&shared),
+                        Arc::clone(&report),
+                    )
+                } else {
+                    Arc::clone(node)
+                }
+            })
+            .collect();
+        self.slow_callback_fraction = Some(shared);
+    }
 
-        fn shape(&self) -> &[Ix] {
-            &[]
+    /// Applies `fault` to its signal each step once the operators writing it
+    /// have stepped, or on each reset if no operator writes it, without
+    /// changing the model. The random numbers of faults are seeded from the
+    /// engine seed.
+    pub fn inject_fault(&mut self, fault: Fault) -> Result<(), BuildError> {
+        let region = fault.signal.region();
+        let writers: Vec<usize> = self
+            .operators
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| {
+                node.operator
+                    .writes()
+                    .iter()
+                    .any(|signal| signal.region().overlaps(&region))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        let invalid = |reason: &str| {
+            Err(BuildError::InvalidFault {
+                signal: fault.signal.name().clone(),
+                reason: reason.to_string(),
+            })
+        };
+        if fault.signal.is_read_only() {
+            return invalid("it is read-only");
+        }
+        if writers.is_empty() && !matches!(fault.kind, FaultKind::ZeroRows { .. }) {
+            return invalid("no operator writes it");
+        }
+        if writers
+            .iter()
+            .any(|&i| self.operators[i].condition.is_some())
+        {
+            return invalid("it is written conditionally");
+        }
+        let period = writers.first().map_or(1, |&i| self.operators[i].period);
+        if writers.iter().any(|&i| self.operators[i].period != period) {
+            return invalid("its writers run at different periods");
         }
+        let injector = Arc::new(FaultInjector::new(fault, writers.len(), period));
+        if self.is_initialized.load(Ordering::SeqCst) {
+            let index = self.operators.len() + self.faults.len();
+            injector.reset(derive_seed(self.seed, index as u64));
+        }
+        for i in writers {
+            self.operators[i] = FaultTrigger::wrap(&self.operators[i], Arc::clone(&injector));
+        }
+        self.faults.push(injector);
+        Ok(())
+    }
 
-        fn reset(&self) {
-            *self.num_reset_calls.write().unwrap() += 1;
+    /// Starts collecting metrics, which also times every operator step, and
+    /// returns them. Repeated calls return the same metrics.
+    pub fn enable_metrics(&mut self) -> Arc<Metrics> {
+        if let Some(metrics) = &self.metrics {
+            return Arc::clone(metrics);
         }
+        let mut operator_nanos: Vec<(String, Arc<AtomicU64>)> = vec![];
+        self.operators = self
+            .operators
+            .iter()
+            .map(|node| {
+                let name = node.operator.name();
+                let nanos = match operator_nanos.iter().find(|(n, _)| *n == name) {
+                    Some((_, nanos)) => Arc::clone(nanos),
+                    None => {
+                        let nanos = Arc::new(AtomicU64::new(0));
+                        operator_nanos.push((name, Arc::clone(&nanos)));
+                        nanos
+                    }
+                };
+                StepTimer::wrap(node, nanos)
+            })
+            .collect();
+        let metrics = Arc::new(Metrics::new(
+            Arc::clone(&self.step_history),
+            operator_nanos,
+            self.probes.clone(),
+            self.pool_size,
+        ));
+        self.metrics = Some(Arc::clone(&metrics));
+        metrics
     }
 
-    #[derive(Debug)]
-    struct FakeOperator {
-        call_counter: Arc<RwLock<u32>>,
-        call_indices: Arc<RwLock<Vec<u32>>>,
+    /// Records the values of the signals each operator reads and writes on
+    /// the given step of later runs, counting from the last reset, replacing
+    /// any earlier record. `None` stops recording.
+    pub fn record_step(&mut self, step: Option<u64>) {
+        self.recorder = step.map(|step| Arc::new(StepRecorder::new(step, &self.operators)));
     }
 
-    impl FakeOperator {
-        fn new(call_counter: Arc<RwLock<u32>>) -> (Self, Arc<RwLock<Vec<u32>>>) {
-            let call_indices = Arc::new(RwLock::new(vec![]));
-            (
-                Self {
-                    call_counter,
-                    call_indices: Arc::clone(&call_indices),
-                },
-                call_indices,
+    /// Returns the record of the step chosen with `record_step` once a run
+    /// has reached it.
+    pub fn step_record(&self) -> Option<StepRecord> {
+        self.recorder
+            .as_ref()
+            .and_then(|recorder| recorder.record())
+    }
+
+    /// Steps each operator in `record` once, in order, from its recorded
+    /// inputs, and returns the outputs differing by more than `tolerance`
+    /// from those in `reference`, or from the recorded ones without a
+    /// reference. Stochastic operators continue their random streams. The
+    /// signal values are restored afterwards.
+    pub fn replay_step(
+        &self,
+        record: &StepRecord,
+        reference: Option<&StepRecord>,
+        tolerance: f64,
+    ) -> Result<Vec<Divergence>, RunError> {
+        self.recheck_read_only_writes()?;
+        let snapshot = Snapshot::save(&self.signals);
+        let result = replay(&self.operators, record, reference, tolerance);
+        snapshot.restore(&self.signals);
+        Ok(result?)
+    }
+
+    /// Runs a single step. If an operator fails, operators depending on it
+    /// are skipped, probes are not updated, and the error is returned.
+    pub fn run_step(&self) -> Result<(), RunError> {
+        self.initialize();
+        let step = self.step.fetch_add(1, Ordering::SeqCst);
+        let error: ErrorSlot = Default::default();
+        let result = self
+            .run_threaded(
+                Self::run_step_async(
+                    step,
+                    StepRecorder::operators_for(&self.recorder, step, &self.operators),
+                    self.probes.clone(),
+                    self.hooks.clone(),
+                    self.schedule(),
+                    Arc::clone(&error),
+                    Arc::clone(&self.step_history),
+                )
+                .map(|_| ()),
             )
+            .and_then(|_| Ok(Self::take_error(&error)?));
+        // A failed step is not counted.
+        if result.is_err() {
+            self.step.fetch_sub(1, Ordering::SeqCst);
         }
+        result
     }
 
-    impl Operator for FakeOperator {
-        fn step(&self) {
-            self.call_indices
-                .write()
-                .unwrap()
-                .push(*self.call_counter.read().unwrap());
-            *self.call_counter.write().unwrap() += 1;
-        }
+    /// Runs `n_steps` steps. Probes record a step while the next step is
+    /// already running, except for operators writing probed signals.
+    pub fn run_steps(&self, n_steps: i64) -> Result<(), RunError> {
+        self.run_steps_until(n_steps, None).map(|_| ())
     }
 
-    struct FakeProbe {
-        call_counter: Arc<RwLock<u32>>,
-        call_indices: Vec<u32>,
+    /// Runs up to `n_steps` steps like `run_steps`, but stops at the first
+    /// step boundary after `time_limit` of wall-clock time has passed.
+    /// Returns the number of steps completed.
+    pub fn run_steps_within(&self, n_steps: i64, time_limit: Duration) -> Result<u64, RunError> {
+        self.run_steps_until(n_steps, Some(Instant::now() + time_limit))
     }
 
-    impl FakeProbe {
-        fn new(call_counter: Arc<RwLock<u32>>) -> Self {
-            Self {
-                call_counter,
-                call_indices: vec![],
+    fn run_steps_until(&self, n_steps: i64, deadline: Option<Instant>) -> Result<u64, RunError> {
+        self.initialize();
+        self.reserve_probe_samples(n_steps);
+        let error: ErrorSlot = Default::default();
+        let completed = Arc::new(AtomicU64::new(0));
+        let result = self.run_threaded(self.run_steps_async(
+            n_steps,
+            Arc::clone(&error),
+            Arc::clone(&self.is_cancelled),
+            deadline,
+            Arc::clone(&completed),
+            None,
+        ));
+        let completed = completed.load(Ordering::SeqCst);
+        // Steps that did not run are not counted.
+        self.step
+            .fetch_sub(n_steps.max(0) as u64 - completed, Ordering::SeqCst);
+        result?;
+        Self::take_error(&error)?;
+        Ok(completed)
+    }
+
+    /// Runs `n_steps` of several engines concurrently on the thread pool of
+    /// the first engine. The engines may share read-only signals. An engine
+    /// stops at its first failing step; the first error is returned.
+    pub fn run_steps_concurrently(engines: &[&Engine], n_steps: i64) -> Result<(), RunError> {
+        Self::run_steps_together(engines, n_steps, None)
+    }
+
+    /// Runs `n_steps` of several engines concurrently like
+    /// `run_steps_concurrently`, and calls `after_step` once all engines
+    /// finished a step, including its probes, before any starts the next.
+    pub fn run_steps_coupled(
+        engines: &[&Engine],
+        n_steps: i64,
+        after_step: Box<dyn Fn() + Send + Sync>,
+    ) -> Result<(), RunError> {
+        let barrier = Arc::new(StepBarrier::new(engines.len(), after_step));
+        Self::run_steps_together(engines, n_steps, Some(barrier))
+    }
+
+    fn run_steps_together(
+        engines: &[&Engine],
+        n_steps: i64,
+        barrier: Option<Arc<StepBarrier>>,
+    ) -> Result<(), RunError> {
+        for engine in engines.iter() {
+            engine.recheck_read_only_writes()?;
+            // An interrupted run may still be finishing its last step.
+            engine.wait_until_done()?;
+        }
+        let error: ErrorSlot = Default::default();
+        engines.iter().for_each(|engine| {
+            engine.initialize();
+            engine.reserve_probe_samples(n_steps);
+        });
+        let completed: Vec<Arc<AtomicU64>> = engines
+            .iter()
+            .map(|_| Arc::new(AtomicU64::new(0)))
+            .collect();
+        for (engine, completed) in engines.iter().zip(completed.iter()) {
+            engine.is_cancelled.store(false, Ordering::SeqCst);
+            engine.is_done.clear();
+            let steps = engine.run_steps_async(
+                n_steps,
+                Arc::clone(&error),
+                Arc::clone(&engine.is_cancelled),
+                None,
+                Arc::clone(completed),
+                barrier.clone(),
+            );
+            engine
+                .thread_pool
+                .spawn_ok(Self::notify_when_done(steps, Arc::clone(&engine.is_done)));
+        }
+        let result = engines
+            .iter()
+            .try_for_each(|engine| engine.wait_until_done());
+        if result.is_err() {
+            // Stop the engines still running when interrupted.
+            for engine in engines.iter() {
+                engine.is_cancelled.store(true, Ordering::SeqCst);
             }
         }
+        // Steps that did not run are not counted.
+        for (engine, completed) in engines.iter().zip(completed.iter()) {
+            engine.step.fetch_sub(
+                n_steps.max(0) as u64 - completed.load(Ordering::SeqCst),
+                Ordering::SeqCst,
+            );
+        }
+        result?;
+        Ok(Self::take_error(&error)?)
     }
 
-    impl Probe for FakeProbe {
-        fn as_any(&self) -> &dyn Any {
-            self
+    /// Preallocates the probe buffers for `n_steps`, so that they are not
+    /// reallocated repeatedly during long runs.
+    fn reserve_probe_samples(&self, n_steps: i64) {
+        for probe in self.probes.iter() {
+            probe.write().unwrap().reserve(n_steps.max(0) as usize);
         }
+    }
 
-        fn probe(&mut self) {
-            self.call_indices.push(*self.call_counter.read().unwrap());
-            *self.call_counter.write().unwrap() += 1;
+    fn take_error(error: &ErrorSlot) -> Result<(), OperatorError> {
+        match error.lock().unwrap().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
         }
     }
 
-    #[test]
-    fn engine_steps_operator_before_probing() {
-        let call_counter = Arc::new(RwLock::new(0));
-        let (fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
-        let operator_node = Arc::new(OperatorNode {
-            operator: Box::new(fake_operator),
-            dependencies: vec![],
+    /// Runs a throwaway step of all pure operators on the thread pool, without
+    /// probes or hooks, and restores the signals afterwards. Errors surface
+    /// before a long run starts and the first measured step is not slowed down
+    /// by a cold thread pool. Values set before are kept, but stochastic
+    /// operators restart their random streams as after a reset.
+    pub fn prepare(&self) -> Result<(), RunError> {
+        self.initialize();
+        let snapshot = Snapshot::save(&self.signals);
+        let operators: Vec<Arc<OperatorNode>> = self
+            .operators
+            .iter()
+            .map(|node| {
+                if node.operator.is_pure() {
+                    Arc::clone(node)
+                } else {
+                    Arc::new(OperatorNode::new(
+                        Box::new(Skipped),
+                        node.dependencies.clone(),
+                    ))
+                }
+            })
+            .collect();
+        let schedule = self.schedule();
+        let error: ErrorSlot = Default::default();
+        let slot = Arc::clone(&error);
+        let result = self.run_threaded(async move {
+            Self::run_scheduled(0, operators, &schedule, &slot, &[], &[]).await;
         });
-        let probe = Arc::new(RwLock::new(FakeProbe::new(Arc::clone(&call_counter))));
-        let engine = Engine::new(
-            vec![],
-            vec![Arc::clone(&operator_node)],
-            vec![Arc::clone(&probe) as Arc<_>],
-        );
+        snapshot.restore(&self.signals);
+        self.seed_operators();
+        result?;
+        Ok(Self::take_error(&error)?)
+    }
 
-        engine.run_step();
+    /// Runs `n_steps` without probes and measures the time spent in Rust and
+    /// Python operators. The signals are reset before and after the run.
+    pub fn benchmark(&self, n_steps: i64) -> Result<Benchmark, RunError> {
+        let rust_nanos = Arc::new(AtomicU64::new(0));
+        let python_nanos = Arc::new(AtomicU64::new(0));
+        let operators: Vec<Arc<OperatorNode>> = self
+            .operators
+            .iter()
+            .map(|node| {
+                let nanos = if node.operator.requires_gil() {
+                    &python_nanos
+                } else {
+                    &rust_nanos
+                };
+                StepTimer::wrap(node, Arc::clone(nanos))
+            })
+            .collect();
+        let n_steps = n_steps.max(0) as u64;
+        let total = self.run_without_probes(operators, n_steps)?;
 
-        assert_eq!(*op_call_indices.read().unwrap(), vec![0]);
-        assert_eq!(probe.read().unwrap().call_indices, vec![1]);
+        Ok(Benchmark {
+            n_steps,
+            total,
+            rust_operators: Duration::from_nanos(rust_nanos.load(Ordering::SeqCst)),
+            python_operators: Duration::from_nanos(python_nanos.load(Ordering::SeqCst)),
+        })
     }
 
-    #[test]
-    fn engine_runs_dependencies_first() {
-        let call_counter = Arc::new(RwLock::new(0));
-        let (fake_dependency, dependency_call_indices) =
-            FakeOperator::new(Arc::clone(&call_counter));
-        let (fake_dependent, dependent_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+    /// Checks the signal shapes, signals read and written by the same
+    /// operator through overlapping memory, and non-finite values written
+    /// during `n_steps` trial steps without probes. The signal values are
+    /// restored afterwards, but stochastic operators restart their random
+    /// streams as after a reset.
+    pub fn verify(&self, n_steps: i64) -> Result<Vec<Problem>, RunError> {
+        let mut problems = check_operators(&self.operators);
+        if problems.iter().any(|p| matches!(p, Problem::Shape { .. })) {
+            return Ok(problems);
+        }
+        self.initialize();
+        let snapshot = Snapshot::save(&self.signals);
+        let found = Arc::new(Mutex::new(vec![]));
+        let operators = self
+            .operators
+            .iter()
+            .map(|node| NonFiniteGuard::wrap(node, Arc::clone(&found)))
+            .collect();
+        let result = self.run_operators_from_current_state(operators, n_steps.max(0) as u64);
+        snapshot.restore(&self.signals);
+        self.seed_operators();
+        problems.extend(found.lock().unwrap().drain(..));
+        match result {
+            Err(RunError::Operator(err)) => problems.push(Problem::Failed(err.to_string())),
+            result => {
+                result?;
+            }
+        }
+        Ok(problems)
+    }
+
+    /// Runs `n_steps` without probes while timing each operator, and
+    /// suggests groups of operators that would benefit from being fused.
+    /// The signals are reset before and after the run.
+    pub fn merge_suggestions(&self, n_steps: i64) -> Result<Vec<MergeSuggestion>, RunError> {
+        let nanos: Vec<Arc<AtomicU64>> = self
+            .operators
+            .iter()
+            .map(|_| Arc::new(AtomicU64::new(0)))
+            .collect();
+        let operators = self
+            .operators
+            .iter()
+            .zip(nanos.iter())
+            .map(|(node, nanos)| StepTimer::wrap(node, Arc::clone(nanos)))
+            .collect();
+        let n_steps = n_steps.max(1) as u64;
+        let total = self.run_without_probes(operators, n_steps)?;
+
+        let nanos: Vec<u64> = nanos.iter().map(|n| n.load(Ordering::SeqCst)).collect();
+        let idle_nanos = (total.as_nanos() as u64).saturating_sub(nanos.iter().sum());
+        let overhead =
+            Duration::from_nanos(idle_nanos / (n_steps * self.operators.len().max(1) as u64));
+        let step_durations: Vec<Duration> = nanos
+            .iter()
+            .map(|nanos| Duration::from_nanos(nanos / n_steps))
+            .collect();
+        Ok(suggest_merges(&self.operators, &step_durations, overhead))
+    }
+
+    /// Runs `n_steps` of the given operators without probes or hooks and
+    /// returns the wall-clock time. The signals are reset before and after.
+    fn run_without_probes(
+        &self,
+        operators: Vec<Arc<OperatorNode>>,
+        n_steps: u64,
+    ) -> Result<Duration, RunError> {
+        self.reset();
+        let result = self.run_operators_from_current_state(operators, n_steps);
+        self.reset();
+        result
+    }
+
+    /// Runs `n_steps` of `operators` without probes, hooks, or advancing the
+    /// step counter of the engine.
+    fn run_operators_from_current_state(
+        &self,
+        operators: Vec<Arc<OperatorNode>>,
+        n_steps: u64,
+    ) -> Result<Duration, RunError> {
+        let error: ErrorSlot = Default::default();
+        let slot = Arc::clone(&error);
+        let is_cancelled = Arc::clone(&self.is_cancelled);
+        let start = Instant::now();
+        let result = self.run_threaded(async move {
+            for step in 0..n_steps {
+                if is_cancelled.load(Ordering::SeqCst)
+                    || !Self::run_operators(step, operators.clone(), &slot, &[], &[]).await
+                {
+                    break;
+                }
+            }
+        });
+        let total = start.elapsed();
+        result?;
+        Self::take_error(&error)?;
+        Ok(total)
+    }
+
+    pub fn reset(&self) {
+        self.is_initialized.store(true, Ordering::SeqCst);
+        self.step.store(0, Ordering::SeqCst);
+        self.signals.iter().for_each(|s| s.reset());
+        self.seed_operators();
+        for (i, fault) in self.faults.iter().enumerate() {
+            fault.reset(derive_seed(self.seed, (self.operators.len() + i) as u64));
+        }
+    }
+
+    /// Resets the signals unless they have been reset before. Values written
+    /// to the signals afterwards are kept by the next run.
+    pub fn initialize(&self) {
+        if !self.is_initialized.load(Ordering::SeqCst) {
+            self.reset();
+        }
+    }
+
+    /// Runs `fut` on the thread pool and waits for it with the GIL released,
+    /// periodically running Python signal handlers. If a handler raises, the
+    /// run is cancelled before its next step and the error is returned
+    /// without waiting further.
+    fn run_threaded<Fut: Future<Output = ()> + Send + 'static>(
+        &self,
+        fut: Fut,
+    ) -> Result<(), RunError> {
+        self.recheck_read_only_writes()?;
+        // An interrupted run may still be finishing its last step.
+        self.wait_until_done()?;
+        self.is_cancelled.store(false, Ordering::SeqCst);
+        self.is_done.clear();
+        self.thread_pool
+            .spawn_ok(Self::notify_when_done(fut, Arc::clone(&self.is_done)));
+        self.wait_until_done()
+    }
+
+    /// Repeats the check for operators writing read-only signals if any
+    /// signal was made read-only since the last check, as writing it would
+    /// panic on the thread pool.
+    fn recheck_read_only_writes(&self) -> Result<(), OperatorError> {
+        let count = read_only_signal_count();
+        if self.read_only_checked.load(Ordering::SeqCst) == count {
+            return Ok(());
+        }
+        if let Err(BuildError::ReadOnlySignalWritten { signal, operator }) =
+            check_read_only_writes(&self.signals, &self.operators)
+        {
+            return Err(OperatorError {
+                operator,
+                cause: ErrorCause::ReadOnly(signal),
+            });
+        }
+        self.read_only_checked.store(count, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn wait_until_done(&self) -> Result<(), RunError> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        while !py.allow_threads(|| self.is_done.wait_timeout(SIGNAL_CHECK_INTERVAL)) {
+            if unsafe { pyo3::ffi::PyErr_CheckSignals() } != 0 {
+                self.is_cancelled.store(true, Ordering::SeqCst);
+                return Err(RunError::Interrupted(PyErr::fetch(py)));
+            }
+        }
+        Ok(())
+    }
+
+    async fn notify_when_done<Fut: Future<Output = ()> + Send + 'static>(
+        fut: Fut,
+        is_done: Arc<Event>,
+    ) {
+        fut.await;
+        is_done.set();
+    }
+
+    fn run_steps_async(
+        &self,
+        n_steps: i64,
+        error: ErrorSlot,
+        is_cancelled: Arc<AtomicBool>,
+        deadline: Option<Instant>,
+        completed: Arc<AtomicU64>,
+        barrier: Option<Arc<StepBarrier>>,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let n_steps = n_steps.max(0) as u64;
+        let first_step = self.step.fetch_add(n_steps, Ordering::SeqCst);
+        let operators = self.operators.clone();
+        let recorder = self.recorder.clone();
+        let checkpoints = self
+            .checkpoints
+            .as_ref()
+            .map(|(path, every)| CheckpointWriter {
+                path: path.clone(),
+                every: *every,
+                seed: self.seed,
+                signals: self.signals.clone(),
+                signal_names: self.signal_names.clone(),
+                probe_offsets: self.probe_offsets.clone(),
+            });
+        let probes = self.probes.clone();
+        let probe_blockers = Arc::clone(&self.probe_blockers);
+        let step_history = Arc::clone(&self.step_history);
+        let hooks = self.hooks.clone();
+        let schedule = self.schedule();
+        async move {
+            let mut probing: Vec<ProbeFuture> = vec![];
+            for step in first_step..first_step + n_steps {
+                if is_cancelled.load(Ordering::SeqCst)
+                    || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    break;
+                }
+                if !hooks.pre.is_empty() {
+                    futures::future::join_all(probing.iter().cloned()).await;
+                    if !Self::run_hooks(&hooks.pre, "Pre-step hook", step, &error) {
+                        break;
+                    }
+                }
+                let start = Instant::now();
+                let succeeded = Self::run_scheduled(
+                    step,
+                    StepRecorder::operators_for(&recorder, step, &operators),
+                    &schedule,
+                    &error,
+                    &probing,
+                    &probe_blockers,
+                )
+                .await;
+                step_history.lock().unwrap().push(start.elapsed());
+                if !succeeded {
+                    break;
+                }
+                completed.fetch_add(1, Ordering::SeqCst);
+                probing = probes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, probe)| Self::probe_after(Arc::clone(probe), probing.get(i).cloned()))
+                    .collect();
+                if !hooks.post.is_empty() {
+                    futures::future::join_all(probing.iter().cloned()).await;
+                    if !Self::run_hooks(&hooks.post, "Post-step hook", step, &error) {
+                        break;
+                    }
+                }
+                if let Some(writer) = &checkpoints {
+                    if (step + 1).is_multiple_of(writer.every) {
+                        futures::future::join_all(probing.iter().cloned()).await;
+                        if let Err(err) = writer.write(step + 1, &probes) {
+                            error.lock().unwrap().get_or_insert(OperatorError {
+                                operator: "Checkpoint".to_string(),
+                                cause: ErrorCause::Io(err),
+                            });
+                            break;
+                        }
+                    }
+                }
+                if let Some(barrier) = &barrier {
+                    futures::future::join_all(probing.iter().cloned()).await;
+                    if !barrier.wait().await {
+                        break;
+                    }
+                }
+            }
+            if let Some(barrier) = &barrier {
+                barrier.leave();
+            }
+            futures::future::join_all(probing).await;
+        }
+    }
+
+    async fn run_step_async(
+        step: u64,
+        operators: Vec<Arc<OperatorNode>>,
+        probes: Vec<Arc<RwLock<dyn Probe + Send + Sync>>>,
+        hooks: StepHooks,
+        schedule: Schedule,
+        error: ErrorSlot,
+        step_history: Arc<Mutex<StepHistory>>,
+    ) -> bool {
+        if !Self::run_hooks(&hooks.pre, "Pre-step hook", step, &error) {
+            return false;
+        }
+        let start = Instant::now();
+        let succeeded = Self::run_scheduled(step, operators, &schedule, &error, &[], &[]).await;
+        if succeeded {
+            Self::run_probes(probes).await;
+        }
+        step_history.lock().unwrap().push(start.elapsed());
+        succeeded && Self::run_hooks(&hooks.post, "Post-step hook", step, &error)
+    }
+
+    /// Calls the hooks in order until one fails.
+    fn run_hooks(hooks: &[Arc<StepHook>], kind: &str, step: u64, error: &ErrorSlot) -> bool {
+        for (i, hook) in hooks.iter().enumerate() {
+            if let Err(cause) = hook.call(step) {
+                error.lock().unwrap().get_or_insert(OperatorError {
+                    operator: format!("{} {}", kind, i),
+                    cause,
+                });
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn run_scheduled(
+        step: u64,
+        nodes: Vec<Arc<OperatorNode>>,
+        schedule: &Schedule,
+        error: &ErrorSlot,
+        previous_probes: &[ProbeFuture],
+        probe_blockers: &[Vec<usize>],
+    ) -> bool {
+        match schedule {
+            Schedule::Joined => {
+                Self::run_operators(step, nodes, error, previous_probes, probe_blockers).await
+            }
+            Schedule::Grouped(groups, thread_pool) => {
+                Self::run_grouped(
+                    step,
+                    nodes,
+                    groups,
+                    thread_pool,
+                    error,
+                    previous_probes,
+                    probe_blockers,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Spawns a task per group that steps its operators in order, once their
+    /// dependencies in any group have completed. Operators are ordered by
+    /// their dependencies, so the groups cannot wait for each other in a
+    /// cycle.
+    async fn run_grouped(
+        step: u64,
+        nodes: Vec<Arc<OperatorNode>>,
+        groups: &[Vec<usize>],
+        thread_pool: &ThreadPool,
+        error: &ErrorSlot,
+        previous_probes: &[ProbeFuture],
+        probe_blockers: &[Vec<usize>],
+    ) -> bool {
+        let (senders, done): (Vec<_>, Vec<Shared<BoxFuture<'static, bool>>>) = nodes
+            .iter()
+            .map(|_| {
+                let (sender, receiver) = oneshot::channel();
+                (
+                    sender,
+                    receiver.map(|r| r.unwrap_or(false)).boxed().shared(),
+                )
+            })
+            .unzip();
+        let mut senders: Vec<Option<oneshot::Sender<bool>>> =
+            senders.into_iter().map(Some).collect();
+        for group in groups.iter() {
+            let tasks: Vec<_> = group
+                .iter()
+                .map(|&i| {
+                    let node = &nodes[i];
+                    let mut dependencies: Vec<Shared<BoxFuture<'static, bool>>> =
+                        node.dependencies.iter().map(|d| done[*d].clone()).collect();
+                    if let Some(blockers) = probe_blockers.get(i) {
+                        dependencies.extend(
+                            blockers
+                                .iter()
+                                .filter_map(|p| previous_probes.get(*p))
+                                .cloned(),
+                        );
+                    }
+                    (Arc::clone(node), dependencies, senders[i].take().unwrap())
+                })
+                .collect();
+            let error = Arc::clone(error);
+            thread_pool.spawn_ok(async move {
+                for (node, dependencies, sender) in tasks {
+                    let succeeded = Self::all_succeeded(dependencies.into_iter()).await
+                        && Self::step_node(step, &node, &error, None);
+                    let _ = sender.send(succeeded);
+                }
+            });
+        }
+        let probes = futures::future::join_all(previous_probes.iter().cloned());
+        futures::join!(probes, Self::all_succeeded(done.into_iter())).1
+    }
+
+    /// Runs the operators of a step while finishing the probes of the
+    /// previous step. Operators wait for the probes listed in
+    /// `probe_blockers`.
+    async fn run_operators(
+        step: u64,
+        nodes: Vec<Arc<OperatorNode>>,
+        error: &ErrorSlot,
+        previous_probes: &[ProbeFuture],
+        probe_blockers: &[Vec<usize>],
+    ) -> bool {
+        let mut tasks: Vec<Shared<BoxFuture<'_, bool>>> = Vec::with_capacity(nodes.len());
+        let mut python_nodes = vec![];
+        for (i, node) in nodes.iter().enumerate() {
+            let mut dependencies = node
+                .dependencies
+                .iter()
+                .map(|i| Shared::clone(&tasks[*i]))
+                .collect::<Vec<_>>();
+            if let Some(blockers) = probe_blockers.get(i) {
+                dependencies.extend(
+                    blockers
+                        .iter()
+                        .filter_map(|p| previous_probes.get(*p))
+                        .map(|p| p.clone().boxed().shared()),
+                );
+            }
+            if node.operator.requires_gil() {
+                let (sender, receiver) = oneshot::channel();
+                python_nodes.push(PythonNode {
+                    node,
+                    dependencies,
+                    sender,
+                });
+                tasks.push(receiver.map(|r| r.unwrap_or(false)).boxed().shared());
+            } else {
+                tasks.push(
+                    Self::create_operator_future(step, node, dependencies, error)
+                        .boxed()
+                        .shared(),
+                );
+            }
+        }
+        let probes = futures::future::join_all(previous_probes.iter().cloned());
+        let python_phase = Self::run_python_operators(step, python_nodes, error);
+        let operators = tasks
+            .iter()
+            .map(|f| Shared::clone(f))
+            .collect::<FuturesOrdered<_>>()
+            .fold(true, |succeeded, x| async move { succeeded && x });
+        futures::join!(probes, python_phase, operators).2
+    }
+
+    fn probe_after(
+        probe: Arc<RwLock<dyn Probe + Send + Sync>>,
+        previous: Option<ProbeFuture>,
+    ) -> ProbeFuture {
+        async move {
+            if let Some(previous) = previous {
+                previous.await;
+            }
+            probe.write().unwrap().probe();
+            true
+        }
+        .boxed()
+        .shared()
+    }
+
+    /// Steps the operators requiring the GIL in order. Successive operators
+    /// whose dependencies have completed are run under a single GIL
+    /// acquisition; the GIL is only released to wait for other operators.
+    async fn run_python_operators(step: u64, nodes: Vec<PythonNode<'_>>, error: &ErrorSlot) {
+        let mut nodes = nodes.into_iter().peekable();
+        while let Some(first) = nodes.next() {
+            let succeeded = Self::all_succeeded(first.dependencies.iter().cloned()).await;
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            first.step(step, succeeded, error, py);
+            while let Some(succeeded) = nodes.peek().and_then(PythonNode::peek_dependencies) {
+                nodes.next().unwrap().step(step, succeeded, error, py);
+            }
+        }
+    }
+
+    async fn all_succeeded<F: Future<Output = bool>>(futures: impl Iterator<Item = F>) -> bool {
+        futures
+            .collect::<FuturesUnordered<_>>()
+            .fold(true, |succeeded, x| async move { succeeded && x })
+            .await
+    }
+
+    async fn run_probes(probes: Vec<Arc<RwLock<dyn Probe + Send + Sync>>>) {
+        probes
+            .iter()
+            .map(Self::probe_async)
+            .collect::<FuturesUnordered<_>>()
+            .collect::<()>()
+            .await;
+    }
+
+    async fn probe_async(probe: &Arc<RwLock<dyn Probe + Send + Sync>>) {
+        probe.write().unwrap().probe();
+    }
+
+    async fn create_operator_future(
+        step: u64,
+        node: &OperatorNode,
+        dependencies: Vec<Shared<BoxFuture<'_, bool>>>,
+        error: &ErrorSlot,
+    ) -> bool {
+        Self::all_succeeded(dependencies.into_iter()).await
+            && Self::step_node(step, node, error, None)
+    }
+
+    /// Steps the operator if it is active, using the given GIL token for
+    /// operators requiring it.
+    fn step_node(step: u64, node: &OperatorNode, error: &ErrorSlot, py: Option<Python>) -> bool {
+        let is_active = step.is_multiple_of(node.period)
+            && match &node.condition {
+                Some(condition) => condition.is_met(),
+                None => true,
+            };
+        if !is_active {
+            return true;
+        }
+        let operator_step = || match py {
+            Some(py) => node.operator.step_with_gil(py),
+            None => node.operator.step(),
+        };
+        let result = match &node.memo {
+            Some(memo) if memo.is_current(&*node.operator) => Ok(()),
+            Some(memo) => operator_step().map(|_| memo.record(&*node.operator)),
+            None => operator_step(),
+        };
+        match result {
+            Ok(()) => true,
+            Err(err) => {
+                error.lock().unwrap().get_or_insert(err);
+                false
+            }
+        }
+    }
+}
+
+fn make_checkpoint(
+    step: u64,
+    seed: u64,
+    signals: &[Arc<dyn Signal + Send + Sync>],
+    signal_names: &[String],
+    probes: &[Arc<RwLock<dyn Probe + Send + Sync>>],
+    probe_offsets: &[usize],
+) -> Checkpoint {
+    Checkpoint {
+        step,
+        seed,
+        probe_offsets: probes
+            .iter()
+            .zip(probe_offsets)
+            .map(|(probe, offset)| offset + probe.read().unwrap().n_samples())
+            .collect(),
+        signals: signals
+            .iter()
+            .zip(signal_names)
+            .filter(|(signal, _)| !is_view(&***signal))
+            .filter_map(|(signal, name)| Value::of(&**signal).map(|value| (name.clone(), value)))
+            .collect(),
+    }
+}
+
+/// Writes the state of an engine to its checkpoint file during a run.
+struct CheckpointWriter {
+    path: PathBuf,
+    every: u64,
+    seed: u64,
+    signals: Vec<Arc<dyn Signal + Send + Sync>>,
+    signal_names: Vec<String>,
+    probe_offsets: Vec<usize>,
+}
+
+impl CheckpointWriter {
+    fn write(&self, step: u64, probes: &[Arc<RwLock<dyn Probe + Send + Sync>>]) -> io::Result<()> {
+        make_checkpoint(
+            step,
+            self.seed,
+            &self.signals,
+            &self.signal_names,
+            probes,
+            &self.probe_offsets,
+        )
+        .save(&self.path)
+    }
+}
+
+fn check_dt(operators: &[Arc<OperatorNode>], expected: f64) -> Result<(), BuildError> {
+    if !(expected.is_finite() && expected > 0.) {
+        return Err(BuildError::InvalidDt(expected));
+    }
+    for node in operators.iter() {
+        let expected = expected * node.period as f64;
+        match node.operator.dt() {
+            Some(dt) if dt != expected => {
+                return Err(BuildError::InconsistentDt {
+                    operator: node.operator.name(),
+                    dt,
+                    expected,
+                })
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Assigns each operator to one of `n_groups` groups. An operator joins the
+/// group already touching most of its signals, among those below their share
+/// of the operators, and the smallest group on ties. Operators requiring the
+/// GIL all join the first group, as they cannot run in parallel anyway.
+fn group_operators(operators: &[Arc<OperatorNode>], n_groups: usize) -> Vec<usize> {
+    let capacity = operators.len().div_ceil(n_groups);
+    let mut buffers: Vec<HashSet<usize>> = vec![HashSet::new(); n_groups];
+    let mut sizes = vec![0; n_groups];
+    operators
+        .iter()
+        .map(|node| {
+            let footprint: Vec<usize> = node
+                .operator
+                .reads()
+                .iter()
+                .chain(node.operator.writes().iter())
+                .map(|s| s.region().buffer())
+                .collect();
+            let group = if node.operator.requires_gil() {
+                0
+            } else {
+                (0..n_groups)
+                    .filter(|g| sizes[*g] < capacity)
+                    .max_by_key(|g| {
+                        let shared = footprint.iter().filter(|b| buffers[*g].contains(b)).count();
+                        (shared, std::cmp::Reverse(sizes[*g]), std::cmp::Reverse(*g))
+                    })
+                    .unwrap_or(0)
+            };
+            buffers[group].extend(footprint);
+            sizes[group] += 1;
+            group
+        })
+        .collect()
+}
+
+fn find_probe_blockers(
+    operators: &[Arc<OperatorNode>],
+    probes: &[Arc<RwLock<dyn Probe + Send + Sync>>],
+) -> Vec<Vec<usize>> {
+    let probed: Vec<Vec<Region>> = probes
+        .iter()
+        .map(|p| {
+            p.read()
+                .unwrap()
+                .reads()
+                .iter()
+                .map(|s| s.region())
+                .collect()
+        })
+        .collect();
+    operators
+        .iter()
+        .map(|node| {
+            let written: Vec<Region> = node.operator.writes().iter().map(|s| s.region()).collect();
+            probed
+                .iter()
+                .enumerate()
+                .filter(|(_, read)| read.iter().any(|r| written.iter().any(|w| w.overlaps(r))))
+                .map(|(i, _)| i)
+                .collect()
+        })
+        .collect()
+}
+
+/// Keeps the name of the first signal with a name and appends the lowest
+/// unused `#n` suffix to the names of later ones.
+fn unique_signal_names(signals: &[Arc<dyn Signal + Send + Sync>]) -> Vec<String> {
+    let mut taken: HashSet<String> = HashSet::new();
+    let mut suffixes: HashMap<&String, usize> = HashMap::new();
+    signals
+        .iter()
+        .map(|signal| {
+            let mut name = signal.name().clone();
+            while taken.contains(&name) {
+                let suffix = suffixes.entry(signal.name()).or_insert(0);
+                *suffix += 1;
+                name = format!("{}#{}", signal.name(), suffix);
+            }
+            taken.insert(name.clone());
+            name
+        })
+        .collect()
+}
+
+/// Fails unless each operator only depends on operators before it, which also
+/// rules out cycles.
+fn check_dependencies(operators: &[Arc<OperatorNode>]) -> Result<(), BuildError> {
+    for (index, node) in operators.iter().enumerate() {
+        if let Some(&dependency) = node.dependencies.iter().find(|&&d| d >= index) {
+            return Err(BuildError::InvalidDependency {
+                index,
+                operator: node.operator.name(),
+                dependency,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Fails if an operator would never run, rather than dividing by zero later.
+fn check_periods(operators: &[Arc<OperatorNode>]) -> Result<(), BuildError> {
+    match operators.iter().find(|node| node.period == 0) {
+        Some(node) => Err(BuildError::InvalidPeriod(node.operator.name())),
+        None => Ok(()),
+    }
+}
+
+/// Fails if a memoized operator could change its signals without any of them
+/// changing, so that skipping its step would lose its effect.
+fn check_memos(operators: &[Arc<OperatorNode>]) -> Result<(), BuildError> {
+    match operators.iter().find(|node| {
+        node.memo.is_some() && (!node.operator.is_pure() || node.operator.increments())
+    }) {
+        Some(node) => Err(BuildError::InvalidMemo(node.operator.name())),
+        None => Ok(()),
+    }
+}
+
+/// Checks the signal shapes of all operators, so that every operator that
+/// would fail on its first step is reported at once.
+fn check_shapes(operators: &[Arc<OperatorNode>]) -> Result<(), BuildError> {
+    let messages: Vec<String> = operators
+        .iter()
+        .filter_map(|node| {
+            node.operator
+                .check_shapes()
+                .err()
+                .map(|err| format!("{}: {}", node.operator.name(), err))
+        })
+        .collect();
+    if messages.is_empty() {
+        Ok(())
+    } else {
+        Err(BuildError::InconsistentShapes(messages))
+    }
+}
+
+/// Fails if two operators write overlapping memory without one (transitively)
+/// depending on the other, unless both only increment, or if they run at
+/// different periods.
+fn check_write_conflicts(operators: &[Arc<OperatorNode>]) -> Result<(), BuildError> {
+    let mut writes: HashMap<usize, Vec<(usize, &dyn Signal, Region)>> = HashMap::new();
+    for (i, node) in operators.iter().enumerate() {
+        for signal in node.operator.writes() {
+            let region = signal.region();
+            writes
+                .entry(region.buffer())
+                .or_default()
+                .push((i, signal, region));
+        }
+    }
+    writes.retain(|_, group| group.len() > 1);
+
+    // Ancestors are only tracked among operators sharing a written buffer
+    // with another one, which keeps the bitsets small for large models.
+    let mut writer_bits: HashMap<usize, usize> = HashMap::new();
+    for &(i, _, _) in writes.values().flatten() {
+        let n_writers = writer_bits.len();
+        writer_bits.entry(i).or_insert(n_writers);
+    }
+    let words = writer_bits.len().div_ceil(64);
+    let mut ancestors: Vec<Vec<u64>> = Vec::with_capacity(operators.len());
+    for node in operators.iter() {
+        let mut bits = vec![0u64; words];
+        for &dependency in node.dependencies.iter() {
+            for (bit, ancestor) in bits.iter_mut().zip(ancestors[dependency].iter()) {
+                *bit |= ancestor;
+            }
+            if let Some(&k) = writer_bits.get(&dependency) {
+                bits[k / 64] |= 1 << (k % 64);
+            }
+        }
+        ancestors.push(bits);
+    }
+    let is_ancestor = |i: usize, j: usize| {
+        let k = writer_bits[&i];
+        ancestors[j][k / 64] & (1 << (k % 64)) != 0
+    };
+
+    for group in writes.values() {
+        for (k, (i, signal_i, region_i)) in group.iter().enumerate() {
+            for (j, signal_j, region_j) in group[k + 1..].iter() {
+                let (period_i, period_j) = (operators[*i].period, operators[*j].period);
+                if period_i != period_j && region_i.overlaps(region_j) {
+                    return Err(BuildError::InconsistentPeriod {
+                        operators: (operators[*i].operator.name(), operators[*j].operator.name()),
+                        periods: (period_i, period_j),
+                        signals: (signal_i.name().clone(), signal_j.name().clone()),
+                    });
+                }
+                let both_increment =
+                    operators[*i].operator.increments() && operators[*j].operator.increments();
+                if i != j && !both_increment && !is_ancestor(*i, *j) && region_i.overlaps(region_j)
+                {
+                    return Err(BuildError::WriteConflict {
+                        operators: (operators[*i].operator.name(), operators[*j].operator.name()),
+                        signals: (signal_i.name().clone(), signal_j.name().clone()),
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fails if an operator writes memory overlapping a read-only signal, among
+/// the engine signals and those the operators access.
+fn check_read_only_writes(
+    signals: &[Arc<dyn Signal + Send + Sync>],
+    operators: &[Arc<OperatorNode>],
+) -> Result<(), BuildError> {
+    let read_only: Vec<(&dyn Signal, Region)> = signals
+        .iter()
+        .map(|signal| &**signal as &dyn Signal)
+        .chain(
+            operators
+                .iter()
+                .flat_map(|node| node.operator.reads().into_iter()),
+        )
+        .filter(|signal| signal.is_read_only())
+        .map(|signal| (signal, signal.region()))
+        .collect();
+    if read_only.is_empty() {
+        return Ok(());
+    }
+    for node in operators.iter() {
+        for written in node.operator.writes() {
+            let region = written.region();
+            if let Some((signal, _)) = read_only.iter().find(|(_, r)| r.overlaps(&region)) {
+                return Err(BuildError::ReadOnlySignalWritten {
+                    signal: signal.name().clone(),
+                    operator: node.operator.name(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Stands in for an operator left out of a step, keeping the dependencies
+/// of the other operators intact.
+#[derive(Debug)]
+struct Skipped;
+
+impl Operator for Skipped {
+    fn step(&self) -> Result<(), OperatorError> {
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+}
+
+/// Adds the time spent stepping the wrapped operator to `nanos`.
+struct StepTimer {
+    nanos: Arc<AtomicU64>,
+}
+
+impl StepTimer {
+    /// Returns a node stepping `node` with the same dependencies and timing
+    /// its steps.
+    fn wrap(node: &Arc<OperatorNode>, nanos: Arc<AtomicU64>) -> Arc<OperatorNode> {
+        WrappedOperator::wrap(node, StepTimer { nanos })
+    }
+}
+
+impl StepWrapper for StepTimer {
+    type State = Instant;
+
+    fn before_step(&self, _operator: &dyn Operator) -> Instant {
+        Instant::now()
+    }
+
+    fn after_step(
+        &self,
+        _operator: &dyn Operator,
+        start: Instant,
+        result: Result<(), OperatorError>,
+    ) -> Result<(), OperatorError> {
+        self.nanos
+            .fetch_add(start.elapsed().as_nanos() as u64, Ordering::SeqCst);
+        result
+    }
+}
+
+/// Operator requiring the GIL, stepped in the Python phase of the engine.
+struct PythonNode<'a> {
+    node: &'a OperatorNode,
+    dependencies: Vec<Shared<BoxFuture<'a, bool>>>,
+    sender: oneshot::Sender<bool>,
+}
+
+impl<'a> PythonNode<'a> {
+    /// Whether all dependencies succeeded, or `None` if some are still running.
+    fn peek_dependencies(&self) -> Option<bool> {
+        self.dependencies
+            .iter()
+            .map(|d| d.peek().copied())
+            .collect::<Option<Vec<_>>>()
+            .map(|results| results.into_iter().all(|x| x))
+    }
+
+    fn step(self, step: u64, dependencies_succeeded: bool, error: &ErrorSlot, py: Python) {
+        let succeeded =
+            dependencies_succeeded && Engine::step_node(step, self.node, error, Some(py));
+        let _ = self.sender.send(succeeded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::{
+        ElementwiseInc, ErrorCause, Memo, Operator, Reset, Sink, SinkCallback, TimeUpdate,
+    };
+    use crate::probe::SignalProbe;
+    use crate::replay::Value;
+    use crate::signal::{AnySignal, ArraySignal, ScalarSignal, SignalAccess};
+    use ndarray::{array, Array, Ix, IxDyn, SliceInfo, SliceOrIndex};
+    use std::any::Any;
+
+    #[derive(Debug)]
+    struct FakeSignal {
+        name: String,
+        num_reset_calls: RwLock<u32>,
+    }
+
+    impl FakeSignal {
+        fn new(name: String) -> Self {
+            Self {
+                name,
+                num_reset_calls: RwLock::new(0),
+            }
+        }
+    }
+
+    impl Signal for FakeSignal {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_arc(self: Arc<Self>) -> Arc<AnySignal> {
+            self
+        }
+
+        fn name(&self) -> &String {
+            &self.name
+        }
+
+        fn shape(&self) -> &[Ix] {
+            &[]
+        }
+
+        fn dtype(&self) -> &'static str {
+            "fake"
+        }
+
+        fn reset(&self) {
+            *self.num_reset_calls.write().unwrap() += 1;
+        }
+
+        fn generation(&self) -> u64 {
+            0
+        }
+
+        fn region(&self) -> Region {
+            Region::whole(self)
+        }
+
+        fn nbytes(&self) -> usize {
+            0
+        }
+    }
+
+    #[derive(Debug)]
+    struct FakeOperator {
+        call_counter: Arc<RwLock<u32>>,
+        call_indices: Arc<RwLock<Vec<u32>>>,
+        input: Option<Arc<ScalarSignal<f64>>>,
+        output: Option<Arc<ScalarSignal<f64>>>,
+        fails: bool,
+        requires_gil: bool,
+    }
+
+    impl FakeOperator {
+        fn new(call_counter: Arc<RwLock<u32>>) -> (Self, Arc<RwLock<Vec<u32>>>) {
+            let call_indices = Arc::new(RwLock::new(vec![]));
+            (
+                Self {
+                    call_counter,
+                    call_indices: Arc::clone(&call_indices),
+                    input: None,
+                    output: None,
+                    fails: false,
+                    requires_gil: false,
+                },
+                call_indices,
+            )
+        }
+    }
+
+    impl Operator for FakeOperator {
+        fn step(&self) -> Result<(), OperatorError> {
+            self.call_indices
+                .write()
+                .unwrap()
+                .push(*self.call_counter.read().unwrap());
+            *self.call_counter.write().unwrap() += 1;
+            if self.fails {
+                Err(OperatorError::new(
+                    self,
+                    ErrorCause::Shape("fake failure".to_string()),
+                ))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn reads(&self) -> Vec<&dyn Signal> {
+            match &self.input {
+                Some(input) => vec![&**input],
+                None => vec![],
+            }
+        }
+
+        fn writes(&self) -> Vec<&dyn Signal> {
+            match &self.output {
+                Some(output) => vec![&**output],
+                None => vec![],
+            }
+        }
+
+        fn requires_gil(&self) -> bool {
+            self.requires_gil
+        }
+    }
+
+    struct FakeProbe {
+        call_counter: Arc<RwLock<u32>>,
+        call_indices: Vec<u32>,
+    }
+
+    impl FakeProbe {
+        fn new(call_counter: Arc<RwLock<u32>>) -> Self {
+            Self {
+                call_counter,
+                call_indices: vec![],
+            }
+        }
+    }
+
+    impl Probe for FakeProbe {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn probe(&mut self) {
+            self.call_indices.push(*self.call_counter.read().unwrap());
+            *self.call_counter.write().unwrap() += 1;
+        }
+
+        fn reads(&self) -> Vec<&dyn Signal> {
+            vec![]
+        }
+    }
+
+    #[test]
+    fn engine_steps_operator_before_probing() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let operator_node = Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]));
+        let probe = Arc::new(RwLock::new(FakeProbe::new(Arc::clone(&call_counter))));
+        let engine = Engine::new(
+            vec![],
+            vec![Arc::clone(&operator_node)],
+            vec![Arc::clone(&probe) as Arc<_>],
+        )
+        .unwrap();
+
+        engine.run_step().unwrap();
+
+        assert_eq!(*op_call_indices.read().unwrap(), vec![0]);
+        assert_eq!(probe.read().unwrap().call_indices, vec![1]);
+    }
+
+    #[test]
+    fn engine_runs_dependencies_first() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_dependency, dependency_call_indices) =
+            FakeOperator::new(Arc::clone(&call_counter));
+        let (fake_dependent, dependent_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
         let operators = vec![
-            Arc::new(OperatorNode {
-                operator: Box::new(fake_dependency),
-                dependencies: vec![],
-            }),
-            Arc::new(OperatorNode {
-                operator: Box::new(fake_dependent),
-                dependencies: vec![0],
-            }),
+            Arc::new(OperatorNode::new(Box::new(fake_dependency), vec![])),
+            Arc::new(OperatorNode::new(Box::new(fake_dependent), vec![0])),
+        ];
+        let engine = Engine::new(vec![], operators, vec![]).unwrap();
+
+        engine.run_step().unwrap();
+
+        assert_eq!(*dependency_call_indices.read().unwrap(), vec![0]);
+        assert_eq!(*dependent_call_indices.read().unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn engine_groups_operators_touching_the_same_signals() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let signal = |name: &str| Arc::new(ScalarSignal::new(name.to_string(), 0.));
+        let (s1, s2, s3) = (signal("s1"), signal("s2"), signal("s3"));
+        let node = |input: Option<&Arc<ScalarSignal<f64>>>,
+                    output: Option<&Arc<ScalarSignal<f64>>>| {
+            let (mut operator, _) = FakeOperator::new(Arc::clone(&call_counter));
+            operator.input = input.cloned();
+            operator.output = output.cloned();
+            Arc::new(OperatorNode::new(Box::new(operator), vec![]))
+        };
+        let operators = vec![
+            node(None, Some(&s1)),
+            node(None, Some(&s2)),
+            node(Some(&s1), Some(&s3)),
+            node(Some(&s2), None),
+        ];
+
+        assert_eq!(group_operators(&operators, 2), vec![0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn engine_runs_grouped_operators_after_their_dependencies() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let mut call_indices = vec![];
+        let operators = (0..4)
+            .map(|i| {
+                let (operator, indices) = FakeOperator::new(Arc::clone(&call_counter));
+                call_indices.push(indices);
+                Arc::new(OperatorNode::new(
+                    Box::new(operator),
+                    if i > 0 { vec![i - 1] } else { vec![] },
+                ))
+            })
+            .collect();
+        let mut engine = Engine::new(vec![], operators, vec![]).unwrap();
+        engine.set_worker_groups(Some(2));
+
+        engine.run_steps(2).unwrap();
+
+        assert_eq!(
+            engine.groups.as_deref(),
+            Some(&vec![vec![0, 2], vec![1, 3]])
+        );
+        for (i, indices) in call_indices.iter().enumerate() {
+            assert_eq!(*indices.read().unwrap(), vec![i as u32, i as u32 + 4]);
+        }
+    }
+
+    #[test]
+    fn engine_returns_operator_error_and_skips_dependents() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (mut fake_dependency, _) = FakeOperator::new(Arc::clone(&call_counter));
+        fake_dependency.fails = true;
+        let (fake_dependent, dependent_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let operators = vec![
+            Arc::new(OperatorNode::new(Box::new(fake_dependency), vec![])),
+            Arc::new(OperatorNode::new(Box::new(fake_dependent), vec![0])),
         ];
-        let engine = Engine::new(vec![], operators, vec![]);
+        let probe = Arc::new(RwLock::new(FakeProbe::new(Arc::clone(&call_counter))));
+        let engine = Engine::new(vec![], operators, vec![Arc::clone(&probe) as Arc<_>]).unwrap();
+
+        let err = match engine.run_steps(3) {
+            Err(RunError::Operator(err)) => err,
+            result => panic!("Expected an operator error, got {:?}.", result),
+        };
+
+        assert_eq!(err.operator, "FakeOperator");
+        assert_eq!(*call_counter.read().unwrap(), 1);
+        assert!(dependent_call_indices.read().unwrap().is_empty());
+        assert!(probe.read().unwrap().call_indices.is_empty());
+    }
+
+    #[test]
+    fn engine_run_steps_runs_multiple_steps() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let operator_node = Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]));
+        let probe = Arc::new(RwLock::new(FakeProbe::new(Arc::clone(&call_counter))));
+        let engine = Engine::new(
+            vec![],
+            vec![Arc::clone(&operator_node)],
+            vec![Arc::clone(&probe) as Arc<_>],
+        )
+        .unwrap();
+
+        engine.run_steps(3).unwrap();
+
+        assert_eq!(*op_call_indices.read().unwrap(), vec![0, 2, 4]);
+        assert_eq!(probe.read().unwrap().call_indices, vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn engine_calls_hooks_around_steps_and_probes() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let operator_node = Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]));
+        let probe = Arc::new(RwLock::new(FakeProbe::new(Arc::clone(&call_counter))));
+        let mut engine = Engine::new(
+            vec![],
+            vec![Arc::clone(&operator_node)],
+            vec![Arc::clone(&probe) as Arc<_>],
+        )
+        .unwrap();
+        let hook_calls = Arc::new(Mutex::new(vec![]));
+        for (name, is_pre) in [("pre", true), ("post", false)].iter() {
+            let (call_counter, hook_calls) = (Arc::clone(&call_counter), Arc::clone(&hook_calls));
+            let hook = StepHook::Rust(Box::new(move |step| {
+                let mut counter = call_counter.write().unwrap();
+                hook_calls.lock().unwrap().push((*name, step, *counter));
+                *counter += 1;
+                Ok(())
+            }));
+            if *is_pre {
+                engine.add_pre_step_hook(hook);
+            } else {
+                engine.add_post_step_hook(hook);
+            }
+        }
+
+        engine.run_steps(2).unwrap();
+
+        assert_eq!(*op_call_indices.read().unwrap(), vec![1, 5]);
+        assert_eq!(probe.read().unwrap().call_indices, vec![2, 6]);
+        assert_eq!(
+            *hook_calls.lock().unwrap(),
+            vec![("pre", 0, 0), ("post", 0, 3), ("pre", 1, 4), ("post", 1, 7)]
+        );
+    }
+
+    #[test]
+    fn engine_stops_at_failing_hook() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let operator_node = Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]));
+        let mut engine = Engine::new(vec![], vec![operator_node], vec![]).unwrap();
+        engine.add_post_step_hook(StepHook::Rust(Box::new(|step| match step {
+            0 => Ok(()),
+            _ => Err(ErrorCause::Shape("assertion failed".to_string())),
+        })));
+
+        let result = engine.run_steps(3);
+
+        match result {
+            Err(RunError::Operator(err)) => assert_eq!(err.operator, "Post-step hook 0"),
+            _ => panic!("Expected the hook to fail."),
+        }
+        assert_eq!(*op_call_indices.read().unwrap(), vec![0, 1]);
+    }
+
+    #[test]
+    fn engine_does_not_count_a_failing_step() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_operator, _) = FakeOperator::new(Arc::clone(&call_counter));
+        let operator_node = Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]));
+        let mut engine = Engine::new(vec![], vec![operator_node], vec![]).unwrap();
+        engine.add_post_step_hook(StepHook::Rust(Box::new(|_| {
+            Err(ErrorCause::Shape("assertion failed".to_string()))
+        })));
+
+        assert!(engine.run_step().is_err());
+
+        assert_eq!(engine.checkpoint().step, 0);
+    }
+
+    #[derive(Debug)]
+    struct FakeStochasticOperator {
+        seeds: Arc<Mutex<Vec<u64>>>,
+    }
+
+    impl Operator for FakeStochasticOperator {
+        fn step(&self) -> Result<(), OperatorError> {
+            Ok(())
+        }
+
+        fn reads(&self) -> Vec<&dyn Signal> {
+            vec![]
+        }
+
+        fn writes(&self) -> Vec<&dyn Signal> {
+            vec![]
+        }
+
+        fn set_seed(&self, seed: u64) {
+            self.seeds.lock().unwrap().push(seed);
+        }
+    }
+
+    fn seeded_engine(n_operators: usize, seed: u64) -> (Engine, Vec<Arc<Mutex<Vec<u64>>>>) {
+        let seeds: Vec<Arc<Mutex<Vec<u64>>>> =
+            (0..n_operators).map(|_| Default::default()).collect();
+        let operators = seeds
+            .iter()
+            .map(|seeds| {
+                Arc::new(OperatorNode::new(
+                    Box::new(FakeStochasticOperator {
+                        seeds: Arc::clone(seeds),
+                    }),
+                    vec![],
+                ))
+            })
+            .collect();
+        let mut engine = Engine::new(vec![], operators, vec![]).unwrap();
+        engine.set_seed(seed);
+        (engine, seeds)
+    }
+
+    #[test]
+    fn engine_seeds_operators_from_master_seed() {
+        let (engine, seeds) = seeded_engine(2, 7);
+        engine.reset();
+        let (_, more_seeds) = seeded_engine(3, 7);
+        let (_, other_seeds) = seeded_engine(2, 8);
+
+        let first = seeds[0].lock().unwrap().clone();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0], first[1]);
+        assert_ne!(first[0], seeds[1].lock().unwrap()[0]);
+        assert_eq!(first[0], more_seeds[0].lock().unwrap()[0]);
+        assert_ne!(first[0], other_seeds[0].lock().unwrap()[0]);
+    }
+
+    #[test]
+    fn engine_skips_operator_while_condition_is_zero() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let condition = Arc::new(ScalarSignal::new("condition".to_string(), 0.));
+        let operator_node = Arc::new(
+            OperatorNode::new(Box::new(fake_operator), vec![])
+                .with_condition(Arc::clone(&condition) as Arc<_>),
+        );
+        let engine = Engine::new(vec![], vec![operator_node], vec![]).unwrap();
+
+        engine.run_step().unwrap();
+        **condition.write() = 1.;
+        engine.run_step().unwrap();
+        **condition.write() = 0.;
+        engine.run_step().unwrap();
+
+        assert_eq!(*op_call_indices.read().unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn engine_runs_operator_every_period_steps() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let (fake_counter, _) = FakeOperator::new(Arc::clone(&call_counter));
+        let operators = vec![
+            Arc::new(OperatorNode::new(Box::new(fake_counter), vec![])),
+            Arc::new(OperatorNode::new(Box::new(fake_operator), vec![0]).with_period(3)),
+        ];
+        let engine = Engine::new(vec![], operators, vec![]).unwrap();
+
+        engine.run_steps(7).unwrap();
+
+        assert_eq!(*op_call_indices.read().unwrap(), vec![1, 5, 9]);
+    }
+
+    #[test]
+    fn engine_reset_restarts_operator_periods() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let operator_node =
+            Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]).with_period(2));
+        let engine = Engine::new(vec![], vec![operator_node], vec![]).unwrap();
+
+        engine.run_step().unwrap();
+        engine.reset();
+        engine.run_step().unwrap();
+
+        assert_eq!(*op_call_indices.read().unwrap(), vec![0, 1]);
+    }
+
+    #[test]
+    fn engine_skips_memoized_operator_while_inputs_are_unchanged() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (mut fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let input = Arc::new(ScalarSignal::new("input".to_string(), 0.));
+        fake_operator.input = Some(Arc::clone(&input));
+        let operator_node = Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]).memoized());
+        let engine = Engine::new(vec![], vec![operator_node], vec![]).unwrap();
 
-        engine.run_step();
+        engine.run_step().unwrap();
+        engine.run_step().unwrap();
+        **input.write() = 1.;
+        engine.run_step().unwrap();
+        engine.run_step().unwrap();
 
-        assert_eq!(*dependency_call_indices.read().unwrap(), vec![0]);
-        assert_eq!(*dependent_call_indices.read().unwrap(), vec![1]);
+        assert_eq!(*op_call_indices.read().unwrap(), vec![0, 1]);
     }
 
     #[test]
-    fn engine_run_steps_runs_multiple_steps() {
+    fn engine_rejects_memoized_operators_that_are_not_pure() {
         let call_counter = Arc::new(RwLock::new(0));
-        let (fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
-        let operator_node = Arc::new(OperatorNode {
-            operator: Box::new(fake_operator),
-            dependencies: vec![],
-        });
-        let probe = Arc::new(RwLock::new(FakeProbe::new(Arc::clone(&call_counter))));
-        let engine = Engine::new(
-            vec![],
-            vec![Arc::clone(&operator_node)],
-            vec![Arc::clone(&probe) as Arc<_>],
+        let (mut fake_operator, _) = FakeOperator::new(call_counter);
+        fake_operator.requires_gil = true;
+        let python_node = Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]).memoized());
+        let mut time_node = time_update(0.001, 1);
+        Arc::get_mut(&mut time_node).unwrap().memo = Some(Memo::default());
+
+        assert_eq!(
+            Engine::new(vec![], vec![python_node], vec![]).err(),
+            Some(BuildError::InvalidMemo("FakeOperator".to_string()))
+        );
+        assert_eq!(
+            Engine::new(vec![], vec![time_node], vec![]).err(),
+            Some(BuildError::InvalidMemo("TimeUpdate<f64, u64>".to_string()))
+        );
+    }
+
+    #[test]
+    fn engine_rejects_memoized_increments() {
+        let signal = |name: &str| {
+            Arc::new(ArraySignal::from_array(
+                name.to_string(),
+                array![1., 1.].into_dyn(),
+            ))
+        };
+        let operator_node = Arc::new(
+            OperatorNode::new(
+                Box::new(ElementwiseInc {
+                    target: signal("target"),
+                    left: signal("left"),
+                    right: signal("right"),
+                    checked: false,
+                }),
+                vec![],
+            )
+            .memoized(),
         );
 
-        engine.run_steps(3);
+        assert_eq!(
+            Engine::new(vec![], vec![operator_node], vec![]).err(),
+            Some(BuildError::InvalidMemo("ElementwiseInc<f64>".to_string()))
+        );
+    }
 
-        assert_eq!(*op_call_indices.read().unwrap(), vec![0, 2, 4]);
-        assert_eq!(probe.read().unwrap().call_indices, vec![1, 3, 5]);
+    #[test]
+    fn engines_run_concurrently() {
+        let call_counters = [Arc::new(RwLock::new(0)), Arc::new(RwLock::new(0))];
+        let engines: Vec<_> = call_counters
+            .iter()
+            .map(|call_counter| {
+                let (fake_operator, _) = FakeOperator::new(Arc::clone(call_counter));
+                let operator_node = Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]));
+                Engine::new(vec![], vec![operator_node], vec![]).unwrap()
+            })
+            .collect();
+
+        Engine::run_steps_concurrently(&engines.iter().collect::<Vec<_>>(), 3).unwrap();
+
+        for call_counter in call_counters.iter() {
+            assert_eq!(*call_counter.read().unwrap(), 3);
+        }
+    }
+
+    #[test]
+    fn engines_run_concurrently_count_only_their_completed_steps() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (mut failing_operator, _) = FakeOperator::new(Arc::clone(&call_counter));
+        failing_operator.fails = true;
+        let (fake_operator, _) = FakeOperator::new(Arc::clone(&call_counter));
+        let engines: Vec<_> = vec![failing_operator, fake_operator]
+            .into_iter()
+            .map(|operator| {
+                let operator_node = Arc::new(OperatorNode::new(Box::new(operator), vec![]));
+                Engine::new(vec![], vec![operator_node], vec![]).unwrap()
+            })
+            .collect();
+
+        assert!(Engine::run_steps_concurrently(&engines.iter().collect::<Vec<_>>(), 3).is_err());
+
+        assert_eq!(engines[0].checkpoint().step, 0);
+        assert_eq!(engines[1].checkpoint().step, 3);
+    }
+
+    #[test]
+    fn engines_can_share_a_thread_pool() {
+        let thread_pool = ThreadPool::builder().pool_size(1).create().unwrap();
+        let call_counter = Arc::new(RwLock::new(0));
+        let engines: Vec<_> = (0..2)
+            .map(|_| {
+                let (fake_operator, _) = FakeOperator::new(Arc::clone(&call_counter));
+                let operator_node = Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]));
+                Engine::with_thread_pool(vec![], vec![operator_node], vec![], thread_pool.clone())
+                    .unwrap()
+            })
+            .collect();
+
+        for engine in engines.iter() {
+            engine.run_steps(2).unwrap();
+        }
+
+        assert_eq!(*call_counter.read().unwrap(), 4);
     }
 
     #[test]
@@ -292,7 +2576,8 @@ mod tests {
             signals.iter().map(|s| Arc::clone(s) as Arc<_>).collect(),
             vec![],
             vec![],
-        );
+        )
+        .unwrap();
 
         engine.reset();
 
@@ -300,4 +2585,831 @@ mod tests {
             assert_eq!(*signal.num_reset_calls.read().unwrap(), 1);
         }
     }
+
+    #[test]
+    fn engine_gives_signals_unique_names() {
+        let signals: Vec<Arc<dyn Signal + Send + Sync>> = vec![
+            Arc::new(FakeSignal::new("x".to_string())),
+            Arc::new(FakeSignal::new("x#1".to_string())),
+            Arc::new(FakeSignal::new("y".to_string())),
+            Arc::new(FakeSignal::new("x".to_string())),
+        ];
+        let engine = Engine::new(signals.clone(), vec![], vec![]).unwrap();
+
+        assert_eq!(engine.signal_names(), &["x", "x#1", "y", "x#2"]);
+        assert!(Arc::ptr_eq(engine.signal("x#2").unwrap(), &signals[3]));
+        assert!(engine.signal("z").is_none());
+        assert_eq!(
+            engine.check_unique_signal_names(),
+            Err(BuildError::DuplicateSignalName("x".to_string()))
+        );
+    }
+
+    fn writing_operators(dependencies: Vec<usize>) -> Vec<Arc<OperatorNode>> {
+        let output = Arc::new(ScalarSignal::new("output".to_string(), 0.));
+        let call_counter = Arc::new(RwLock::new(0));
+        let node = |dependencies| {
+            let (mut fake_operator, _) = FakeOperator::new(Arc::clone(&call_counter));
+            fake_operator.output = Some(Arc::clone(&output));
+            Arc::new(OperatorNode::new(Box::new(fake_operator), dependencies))
+        };
+        vec![node(vec![]), node(dependencies)]
+    }
+
+    #[test]
+    fn engine_rejects_concurrent_writes_of_a_signal() {
+        let err = Engine::new(vec![], writing_operators(vec![]), vec![])
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err,
+            BuildError::WriteConflict {
+                operators: ("FakeOperator".to_string(), "FakeOperator".to_string()),
+                signals: ("output".to_string(), "output".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn engine_accepts_ordered_writes_of_a_signal() {
+        assert!(Engine::new(vec![], writing_operators(vec![0]), vec![]).is_ok());
+    }
+
+    #[test]
+    fn engine_accepts_writes_of_a_signal_ordered_through_other_operators() {
+        let mut operators = writing_operators(vec![1]);
+        let (fake_operator, _) = FakeOperator::new(Arc::new(RwLock::new(0)));
+        operators.insert(
+            1,
+            Arc::new(OperatorNode::new(Box::new(fake_operator), vec![0])),
+        );
+        assert!(Engine::new(vec![], operators, vec![]).is_ok());
+    }
+
+    #[test]
+    fn engine_rejects_writes_of_a_signal_at_different_periods() {
+        let mut operators = writing_operators(vec![0]);
+        Arc::get_mut(&mut operators[1]).unwrap().period = 10;
+
+        let err = Engine::new(vec![], operators, vec![]).err().unwrap();
+
+        assert_eq!(
+            err,
+            BuildError::InconsistentPeriod {
+                operators: ("FakeOperator".to_string(), "FakeOperator".to_string()),
+                periods: (1, 10),
+                signals: ("output".to_string(), "output".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn engine_rejects_operators_with_a_period_of_zero() {
+        let err = Engine::new(vec![], vec![time_update(0.001, 0)], vec![])
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err,
+            BuildError::InvalidPeriod("TimeUpdate<f64, u64>".to_string())
+        );
+    }
+
+    #[test]
+    fn engine_rejects_dependencies_on_later_operators() {
+        let err = Engine::new(vec![], writing_operators(vec![1]), vec![])
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err,
+            BuildError::InvalidDependency {
+                index: 1,
+                operator: "FakeOperator".to_string(),
+                dependency: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn engine_rejects_writes_of_read_only_signals() {
+        let weights = Arc::new(ArraySignal::from_array(
+            "weights".to_string(),
+            array![1., 2.].into_dyn(),
+        ));
+        weights.make_read_only();
+        let operator_node = Arc::new(OperatorNode::new(
+            Box::new(ElementwiseInc {
+                target: Arc::clone(&weights),
+                left: Arc::new(ArraySignal::from_array(
+                    "left".to_string(),
+                    array![1., 1.].into_dyn(),
+                )),
+                right: Arc::new(ArraySignal::from_array(
+                    "right".to_string(),
+                    array![1., 1.].into_dyn(),
+                )),
+                checked: false,
+            }),
+            vec![],
+        ));
+
+        let err = Engine::new(vec![weights as Arc<_>], vec![operator_node], vec![])
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err,
+            BuildError::ReadOnlySignalWritten {
+                signal: "weights".to_string(),
+                operator: "ElementwiseInc<f64>".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn engine_fails_to_run_after_written_signals_are_made_read_only() {
+        let target = Arc::new(ArraySignal::from_array(
+            "target".to_string(),
+            array![1., 2.].into_dyn(),
+        ));
+        let input = Arc::new(ArraySignal::from_array(
+            "input".to_string(),
+            array![1., 1.].into_dyn(),
+        ));
+        let operator_node = Arc::new(OperatorNode::new(
+            Box::new(ElementwiseInc {
+                target: Arc::clone(&target),
+                left: Arc::clone(&input),
+                right: input,
+                checked: false,
+            }),
+            vec![],
+        ));
+        let engine = Engine::new(
+            vec![Arc::clone(&target) as Arc<_>],
+            vec![operator_node],
+            vec![],
+        )
+        .unwrap();
+
+        target.make_read_only();
+
+        match engine.run_steps(1) {
+            Err(RunError::Operator(OperatorError {
+                operator,
+                cause: ErrorCause::ReadOnly(signal),
+            })) => {
+                assert_eq!(operator, "ElementwiseInc<f64>");
+                assert_eq!(signal, "target");
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn engines_run_concurrently_check_each_engine_for_read_only_writes() {
+        let target = Arc::new(ArraySignal::from_array(
+            "target".to_string(),
+            array![1., 2.].into_dyn(),
+        ));
+        let input = Arc::new(ArraySignal::from_array(
+            "input".to_string(),
+            array![1., 1.].into_dyn(),
+        ));
+        let (fake_operator, _) = FakeOperator::new(Arc::new(RwLock::new(0)));
+        let engines = [
+            Engine::new(
+                vec![],
+                vec![Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]))],
+                vec![],
+            )
+            .unwrap(),
+            Engine::new(
+                vec![Arc::clone(&target) as Arc<_>],
+                vec![Arc::new(OperatorNode::new(
+                    Box::new(ElementwiseInc {
+                        target: Arc::clone(&target),
+                        left: Arc::clone(&input),
+                        right: input,
+                        checked: false,
+                    }),
+                    vec![],
+                ))],
+                vec![],
+            )
+            .unwrap(),
+        ];
+
+        target.make_read_only();
+
+        match Engine::run_steps_concurrently(&engines.iter().collect::<Vec<_>>(), 1) {
+            Err(RunError::Operator(OperatorError {
+                cause: ErrorCause::ReadOnly(signal),
+                ..
+            })) => assert_eq!(signal, "target"),
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert_eq!(engines[0].checkpoint().step, 0);
+    }
+
+    #[test]
+    fn engine_reports_all_operators_with_inconsistent_shapes() {
+        let node = |name: &str| {
+            Arc::new(OperatorNode::new(
+                Box::new(ElementwiseInc {
+                    target: Arc::new(ArraySignal::from_array(
+                        name.to_string(),
+                        array![0., 0.].into_dyn(),
+                    )),
+                    left: Arc::new(ArraySignal::from_array(
+                        "left".to_string(),
+                        array![1., 2., 3.].into_dyn(),
+                    )),
+                    right: Arc::new(ArraySignal::from_array(
+                        "right".to_string(),
+                        array![1., 2.].into_dyn(),
+                    )),
+                    checked: false,
+                }),
+                vec![],
+            ))
+        };
+
+        let err = Engine::new(vec![], vec![node("a"), node("b")], vec![])
+            .err()
+            .unwrap();
+
+        match err {
+            BuildError::InconsistentShapes(messages) => {
+                assert_eq!(messages.len(), 2);
+                assert!(messages
+                    .iter()
+                    .all(|message| message.starts_with("ElementwiseInc<f64>: ")));
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn engine_steps_operators_requiring_the_gil_in_dependency_order() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let mut operators = vec![];
+        let mut call_indices = vec![];
+        for (requires_gil, dependencies) in [
+            (true, vec![]),
+            (false, vec![0]),
+            (true, vec![1]),
+            (true, vec![]),
+        ]
+        .iter()
+        .cloned()
+        {
+            let (mut fake_operator, op_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+            fake_operator.requires_gil = requires_gil;
+            call_indices.push(op_call_indices);
+            operators.push(Arc::new(OperatorNode::new(
+                Box::new(fake_operator),
+                dependencies,
+            )));
+        }
+        let engine = Engine::new(vec![], operators, vec![]).unwrap();
+
+        engine.run_step().unwrap();
+
+        for (i, op_call_indices) in call_indices.iter().enumerate() {
+            assert_eq!(*op_call_indices.read().unwrap(), vec![i as u32]);
+        }
+    }
+
+    #[test]
+    fn engine_probes_signals_written_in_the_next_step() {
+        let step = Arc::new(ScalarSignal::new("step".to_string(), 0));
+        let operator_node = Arc::new(OperatorNode::new(
+            Box::new(TimeUpdate {
+                dt: 0.001,
+                step_target: Arc::clone(&step),
+                time_target: Arc::new(ScalarSignal::new("time".to_string(), 0.)),
+                dt_signal: None,
+                compensated: false,
+                compensation: Default::default(),
+            }),
+            vec![],
+        ));
+        let probe = Arc::new(RwLock::new(SignalProbe::<u64, _>::new(&step)));
+        let engine = Engine::new(
+            vec![],
+            vec![operator_node],
+            vec![Arc::clone(&probe) as Arc<_>],
+        )
+        .unwrap();
+
+        engine.run_steps(3).unwrap();
+
+        assert_eq!(probe.read().unwrap().get_data(), &vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn engine_prepare_steps_pure_operators_and_restores_the_signals() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (mut python_operator, python_call_indices) =
+            FakeOperator::new(Arc::clone(&call_counter));
+        python_operator.requires_gil = true;
+        let (pure_operator, pure_call_indices) = FakeOperator::new(Arc::clone(&call_counter));
+        let target = Arc::new(ArraySignal::from_array(
+            "target".to_string(),
+            array![0.].into_dyn(),
+        ));
+        let one = Arc::new(ArraySignal::from_array(
+            "one".to_string(),
+            array![1.].into_dyn(),
+        ));
+        let operators = vec![
+            Arc::new(OperatorNode::new(Box::new(python_operator), vec![])),
+            Arc::new(OperatorNode::new(Box::new(pure_operator), vec![0])),
+            Arc::new(OperatorNode::new(
+                Box::new(ElementwiseInc {
+                    target: Arc::clone(&target),
+                    left: Arc::clone(&one),
+                    right: one,
+                    checked: false,
+                }),
+                vec![],
+            )),
+        ];
+        let engine = Engine::new(vec![Arc::clone(&target) as Arc<_>], operators, vec![]).unwrap();
+        engine.reset();
+        target.write().assign_array(&array![0.5].into_dyn());
+
+        engine.prepare().unwrap();
+
+        assert!(python_call_indices.read().unwrap().is_empty());
+        assert_eq!(*pure_call_indices.read().unwrap(), vec![0]);
+        assert_eq!(**target.read(), array![0.5].into_dyn());
+    }
+
+    #[test]
+    fn engine_resets_signals_before_the_first_step() {
+        let signal = Arc::new(FakeSignal::new("signal".to_string()));
+        let engine = Engine::new(vec![Arc::clone(&signal) as Arc<_>], vec![], vec![]).unwrap();
+
+        engine.run_step().unwrap();
+        engine.run_steps(2).unwrap();
+
+        assert_eq!(*signal.num_reset_calls.read().unwrap(), 1);
+    }
+
+    fn time_update(dt: f64, period: u64) -> Arc<OperatorNode> {
+        Arc::new(
+            OperatorNode::new(
+                Box::new(TimeUpdate {
+                    dt,
+                    step_target: Arc::new(ScalarSignal::new("step".to_string(), 0)),
+                    time_target: Arc::new(ScalarSignal::new("time".to_string(), 0.)),
+                    dt_signal: None,
+                    compensated: false,
+                    compensation: Default::default(),
+                }),
+                vec![],
+            )
+            .with_period(period),
+        )
+    }
+
+    #[test]
+    fn engine_takes_dt_from_operators() {
+        let mut engine = Engine::new(vec![], vec![time_update(0.001, 1)], vec![]).unwrap();
+
+        assert_eq!(engine.dt(), Some(0.001));
+        assert_eq!(engine.set_dt(0.), Err(BuildError::InvalidDt(0.)));
+        assert_eq!(
+            engine.set_dt(0.002),
+            Err(BuildError::InconsistentDt {
+                operator: "TimeUpdate<f64, u64>".to_string(),
+                dt: 0.001,
+                expected: 0.002,
+            })
+        );
+    }
+
+    #[test]
+    fn engine_rejects_operators_with_different_dt() {
+        assert!(Engine::new(
+            vec![],
+            vec![time_update(0.001, 1), time_update(0.01, 1)],
+            vec![]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn engine_scales_dt_of_operators_by_their_period() {
+        let engine = Engine::new(
+            vec![],
+            vec![time_update(0.01, 10), time_update(0.001, 1)],
+            vec![],
+        )
+        .unwrap();
+
+        assert_eq!(engine.dt(), Some(0.001));
+    }
+
+    #[test]
+    fn engine_benchmarks_steps_and_resets_afterwards() {
+        let call_counter = Arc::new(RwLock::new(0));
+        let (fake_operator, _) = FakeOperator::new(Arc::clone(&call_counter));
+        let signal = Arc::new(FakeSignal::new("signal".to_string()));
+        let engine = Engine::new(
+            vec![Arc::clone(&signal) as Arc<_>],
+            vec![Arc::new(OperatorNode::new(Box::new(fake_operator), vec![]))],
+            vec![],
+        )
+        .unwrap();
+
+        let benchmark = engine.benchmark(5).unwrap();
+
+        assert_eq!(*call_counter.read().unwrap(), 5);
+        assert_eq!(benchmark.n_steps, 5);
+        assert_eq!(benchmark.python_operators, Duration::from_secs(0));
+        assert!(benchmark.rust_operators <= benchmark.total);
+        assert_eq!(*signal.num_reset_calls.read().unwrap(), 2);
+    }
+
+    #[test]
+    fn engine_keeps_the_durations_of_recent_steps() {
+        let engine = Engine::new(vec![], vec![time_update(0.001, 1)], vec![]).unwrap();
+        engine.set_step_history(3);
+
+        engine.run_steps(5).unwrap();
+        assert_eq!(engine.step_durations().len(), 3);
+
+        engine.set_step_history(1);
+        engine.run_step().unwrap();
+        assert_eq!(engine.step_durations().len(), 1);
+    }
+
+    #[test]
+    fn engine_reports_memory_of_signals_and_probe_growth() {
+        let step = Arc::new(ScalarSignal::new("step".to_string(), 0u64));
+        let probe = Arc::new(RwLock::new(SignalProbe::<u64, _>::new(&step)));
+        let engine = Engine::new(
+            vec![Arc::clone(&step) as Arc<_>],
+            vec![time_update(0.001, 1)],
+            vec![Arc::clone(&probe) as Arc<_>],
+        )
+        .unwrap();
+
+        engine.run_steps(3).unwrap();
+        let report = engine.memory_report();
+
+        assert_eq!(report.signals, vec![("step".to_string(), 16)]);
+        assert_eq!(
+            report.operators,
+            vec![("TimeUpdate<f64, u64>".to_string(), 0)]
+        );
+        assert_eq!(
+            report.probes,
+            vec![ProbeMemory {
+                bytes: 24,
+                bytes_per_step: 8.
+            }]
+        );
+        assert_eq!(report.total_bytes(), 40);
+        assert_eq!(report.predicted_bytes(10), 120.);
+    }
+
+    #[test]
+    fn engine_verifies_steps_and_restores_the_signals() {
+        let target = Arc::new(ArraySignal::from_array(
+            "target".to_string(),
+            array![1.].into_dyn(),
+        ));
+        let engine = Engine::new(
+            vec![Arc::clone(&target) as Arc<_>],
+            vec![Arc::new(OperatorNode::new(
+                Box::new(ElementwiseInc {
+                    target: Arc::clone(&target),
+                    left: Arc::new(ArraySignal::from_array(
+                        "left".to_string(),
+                        array![f64::INFINITY].into_dyn(),
+                    )),
+                    right: Arc::new(ArraySignal::from_array(
+                        "right".to_string(),
+                        array![0.].into_dyn(),
+                    )),
+                    checked: false,
+                }),
+                vec![],
+            ))],
+            vec![],
+        )
+        .unwrap();
+        engine.initialize();
+        target.write().assign_array(&array![2.]);
+
+        let problems = engine.verify(3).unwrap();
+
+        assert_eq!(
+            problems,
+            vec![Problem::NonFinite {
+                operator: "ElementwiseInc<f64>".to_string(),
+                signal: "target".to_string(),
+            }]
+        );
+        assert_eq!(**target.read(), array![2.].into_dyn());
+    }
+
+    #[test]
+    fn engine_records_the_operators_on_a_chosen_step() {
+        let target = Arc::new(ArraySignal::from_array(
+            "target".to_string(),
+            array![0.].into_dyn(),
+        ));
+        let mut engine = Engine::new(
+            vec![Arc::clone(&target) as Arc<_>],
+            vec![Arc::new(OperatorNode::new(
+                Box::new(ElementwiseInc {
+                    target: Arc::clone(&target),
+                    left: Arc::new(ArraySignal::from_array(
+                        "left".to_string(),
+                        array![2.].into_dyn(),
+                    )),
+                    right: Arc::new(ArraySignal::from_array(
+                        "right".to_string(),
+                        array![1.].into_dyn(),
+                    )),
+                    checked: false,
+                }),
+                vec![],
+            ))],
+            vec![],
+        )
+        .unwrap();
+        engine.record_step(Some(1));
+        engine.run_step().unwrap();
+        assert_eq!(engine.step_record(), None);
+
+        engine.run_steps(2).unwrap();
+
+        let record = engine.step_record().unwrap();
+        let recorded = record.operators[0].as_ref().unwrap();
+        assert_eq!(
+            recorded.inputs[2],
+            (
+                "target".to_string(),
+                Some(Value::ArrayF64(array![2.].into_dyn()))
+            )
+        );
+        assert_eq!(engine.replay_step(&record, None, 0.).unwrap(), vec![]);
+        assert_eq!(**target.read(), array![6.].into_dyn());
+    }
+
+    fn increment_by_read_only_signal() -> (Engine, Arc<ArraySignal<f64>>) {
+        let target = Arc::new(ArraySignal::from_array(
+            "target".to_string(),
+            array![0.].into_dyn(),
+        ));
+        let weights = Arc::new(ArraySignal::from_array(
+            "weights".to_string(),
+            array![2.].into_dyn(),
+        ));
+        weights.make_read_only();
+        let engine = Engine::new(
+            vec![
+                Arc::clone(&target) as Arc<_>,
+                Arc::clone(&weights) as Arc<_>,
+            ],
+            vec![Arc::new(OperatorNode::new(
+                Box::new(ElementwiseInc {
+                    target: Arc::clone(&target),
+                    left: weights,
+                    right: Arc::new(ArraySignal::from_array(
+                        "right".to_string(),
+                        array![1.].into_dyn(),
+                    )),
+                    checked: false,
+                }),
+                vec![],
+            ))],
+            vec![],
+        )
+        .unwrap();
+        (engine, target)
+    }
+
+    #[test]
+    fn engine_verifies_steps_reading_read_only_signals() {
+        let (engine, target) = increment_by_read_only_signal();
+        engine.initialize();
+
+        assert_eq!(engine.verify(2).unwrap(), vec![]);
+        assert_eq!(**target.read(), array![0.].into_dyn());
+    }
+
+    #[test]
+    fn engine_replays_steps_reading_read_only_signals() {
+        let (mut engine, target) = increment_by_read_only_signal();
+        engine.record_step(Some(0));
+        engine.run_step().unwrap();
+        let record = engine.step_record().unwrap();
+
+        assert_eq!(engine.replay_step(&record, None, 0.).unwrap(), vec![]);
+        assert_eq!(**target.read(), array![2.].into_dyn());
+    }
+
+    #[test]
+    fn engine_resumes_from_a_checkpoint_written_during_a_run() {
+        fn build() -> (Engine, Arc<ScalarSignal<u64>>, Arc<ScalarSignal<f64>>) {
+            let step = Arc::new(ScalarSignal::new("step".to_string(), 0));
+            let time = Arc::new(ScalarSignal::new("time".to_string(), 0.));
+            let operator_node = Arc::new(OperatorNode::new(
+                Box::new(TimeUpdate {
+                    dt: 0.001,
+                    step_target: Arc::clone(&step),
+                    time_target: Arc::clone(&time),
+                    dt_signal: None,
+                    compensated: false,
+                    compensation: Default::default(),
+                }),
+                vec![],
+            ));
+            let probe = Arc::new(RwLock::new(SignalProbe::<u64, _>::new(&step)));
+            let engine = Engine::new(
+                vec![Arc::clone(&step) as Arc<_>, Arc::clone(&time) as Arc<_>],
+                vec![operator_node],
+                vec![probe as Arc<_>],
+            )
+            .unwrap();
+            (engine, step, time)
+        }
+        let path = std::env::temp_dir().join(format!(
+            "nengo-rs-engine-checkpoint-{}.bin",
+            std::process::id()
+        ));
+        let (mut engine, _, _) = build();
+        engine.set_seed(3);
+        engine.set_checkpoints(Some(path.clone()), 2);
+
+        engine.run_steps(5).unwrap();
+
+        let checkpoint = Checkpoint::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let checkpoint = checkpoint.unwrap();
+        assert_eq!(checkpoint.step, 4);
+        assert_eq!(checkpoint.probe_offsets, vec![4]);
+        let (mut resumed, step, time) = build();
+        resumed.resume(&checkpoint).unwrap();
+        assert_eq!(resumed.seed(), 3);
+        assert_eq!(resumed.probe_offsets(), &[4]);
+        assert_eq!(**step.read(), 4);
+
+        resumed.run_steps(1).unwrap();
+
+        assert_eq!(**step.read(), 5);
+        assert!((**time.read() - 0.005).abs() < 1e-12);
+        assert_eq!(resumed.checkpoint().probe_offsets, vec![5]);
+    }
+
+    #[test]
+    fn engine_stops_at_a_step_boundary_when_the_time_limit_is_exceeded() {
+        let step = Arc::new(ScalarSignal::new("step".to_string(), 0));
+        let operators = vec![
+            Arc::new(OperatorNode::new(
+                Box::new(Sink::<f64> {
+                    t: None,
+                    x: None,
+                    callback: SinkCallback::Rust(Box::new(|_, _| {
+                        std::thread::sleep(Duration::from_millis(5))
+                    })),
+                }),
+                vec![],
+            )),
+            Arc::new(OperatorNode::new(
+                Box::new(TimeUpdate {
+                    dt: 0.001,
+                    step_target: Arc::clone(&step),
+                    time_target: Arc::new(ScalarSignal::new("time".to_string(), 0.)),
+                    dt_signal: None,
+                    compensated: false,
+                    compensation: Default::default(),
+                }),
+                vec![],
+            )),
+        ];
+        let engine = Engine::new(vec![], operators, vec![]).unwrap();
+
+        let completed = engine
+            .run_steps_within(1000, Duration::from_millis(20))
+            .unwrap();
+
+        assert!((1..1000).contains(&completed));
+        assert_eq!(**step.read(), completed);
+        engine.run_step().unwrap();
+        assert_eq!(engine.checkpoint().step, completed + 1);
+    }
+
+    #[test]
+    fn engine_applies_injected_faults_without_changing_the_model() {
+        let spikes = Arc::new(ArraySignal::from_array(
+            "spikes".to_string(),
+            Array::zeros(IxDyn(&[1000])),
+        ));
+        let weights = Arc::new(ArraySignal::from_array(
+            "weights".to_string(),
+            Array::ones(IxDyn(&[10, 2])),
+        ));
+        let mut engine = Engine::new(
+            vec![
+                Arc::clone(&spikes) as Arc<_>,
+                Arc::clone(&weights) as Arc<_>,
+            ],
+            vec![Arc::new(OperatorNode::new(
+                Box::new(Reset {
+                    value: Array::ones(IxDyn(&[1000])),
+                    target: Arc::clone(&spikes),
+                }),
+                vec![],
+            ))],
+            vec![],
+        )
+        .unwrap();
+        assert!(matches!(
+            engine.inject_fault(Fault {
+                signal: Arc::clone(&weights),
+                kind: FaultKind::DropSpikes { fraction: 0.5 },
+            }),
+            Err(BuildError::InvalidFault { .. })
+        ));
+        for (signal, kind) in [
+            (&spikes, FaultKind::DropSpikes { fraction: 0.5 }),
+            (&weights, FaultKind::ZeroRows { fraction: 1. }),
+        ] {
+            engine
+                .inject_fault(Fault {
+                    signal: Arc::clone(signal),
+                    kind,
+                })
+                .unwrap();
+        }
+
+        engine.run_step().unwrap();
+
+        let kept = spikes.read().with_view(|x| x.sum());
+        assert!(kept > 400. && kept < 600., "kept {} of 1000 spikes", kept);
+        assert_eq!(weights.read().with_view(|w| w.sum()), 0.);
+    }
+
+    #[test]
+    fn engine_rejects_faults_in_signals_written_at_different_periods() {
+        let spikes = Arc::new(ArraySignal::from_array(
+            "spikes".to_string(),
+            Array::zeros(IxDyn(&[4])),
+        ));
+        let half = |start: isize, period: u64| {
+            let slice = SliceInfo::new(vec![SliceOrIndex::Slice {
+                start,
+                end: Some(start + 2),
+                step: 1,
+            }])
+            .unwrap();
+            let target = Arc::new(ArraySignal::new_view(
+                "half".to_string(),
+                Arc::clone(&spikes),
+                Box::new(slice),
+            ));
+            Arc::new(
+                OperatorNode::new(
+                    Box::new(Reset {
+                        value: Array::ones(IxDyn(&[2])),
+                        target,
+                    }),
+                    vec![],
+                )
+                .with_period(period),
+            )
+        };
+        let mut engine = Engine::new(
+            vec![Arc::clone(&spikes) as Arc<_>],
+            vec![half(0, 1), half(2, 2)],
+            vec![],
+        )
+        .unwrap();
+
+        let err = engine
+            .inject_fault(Fault {
+                signal: Arc::clone(&spikes),
+                kind: FaultKind::DropSpikes { fraction: 0.5 },
+            })
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err,
+            BuildError::InvalidFault {
+                signal: "spikes".to_string(),
+                reason: "its writers run at different periods".to_string(),
+            }
+        );
+    }
 }
@@ -0,0 +1,222 @@
+use crate::operator::{Operator, OperatorError, OperatorNode, StepWrapper, WrappedOperator};
+use crate::rng::RngStream;
+use crate::signal::{ArraySignal, SignalAccess};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// How a fault alters the value of a signal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FaultKind {
+    /// Zeroes each element with probability `fraction` on every step, such
+    /// as the spikes of a neuron population.
+    DropSpikes { fraction: f64 },
+    /// Zeroes a `fraction` of the rows (along the first axis), such as the
+    /// rows of a weight matrix. The rows are chosen anew on each reset.
+    ZeroRows { fraction: f64 },
+    /// Adds Gaussian noise with standard deviation `std` on the steps from
+    /// `start` up to `end`, counted from the last reset.
+    Perturb { start: u64, end: u64, std: f64 },
+}
+
+/// A fault applied by the engine to `signal` after the operators writing it
+/// have stepped, see `Engine::inject_fault`.
+#[derive(Debug, Clone)]
+pub struct Fault {
+    pub signal: Arc<ArraySignal<f64>>,
+    pub kind: FaultKind,
+}
+
+/// Applies a fault once all `writers` of its signal have stepped, or on
+/// reset for signals without writers.
+#[derive(Debug)]
+pub(crate) struct FaultInjector {
+    fault: Fault,
+    writers: usize,
+    period: u64,
+    rng: RngStream,
+    /// Writers that have stepped since the fault was last applied.
+    stepped: AtomicUsize,
+    /// Steps of the writers since the last reset.
+    steps: AtomicU64,
+}
+
+impl FaultInjector {
+    pub(crate) fn new(fault: Fault, writers: usize, period: u64) -> Self {
+        Self {
+            fault,
+            writers,
+            period,
+            rng: RngStream::default(),
+            stepped: AtomicUsize::new(0),
+            steps: AtomicU64::new(0),
+        }
+    }
+
+    /// Restarts the random numbers from `seed` and applies a `ZeroRows` fault
+    /// to the reset value of the signal.
+    pub(crate) fn reset(&self, seed: u64) {
+        self.rng.reseed(seed);
+        self.stepped.store(0, Ordering::SeqCst);
+        self.steps.store(0, Ordering::SeqCst);
+        if let FaultKind::ZeroRows { .. } = self.fault.kind {
+            self.apply(0);
+        }
+    }
+
+    fn writer_stepped(&self) {
+        if self.stepped.fetch_add(1, Ordering::SeqCst) + 1 == self.writers {
+            self.stepped.store(0, Ordering::SeqCst);
+            let step = self.steps.fetch_add(1, Ordering::SeqCst) * self.period;
+            self.apply(step);
+        }
+    }
+
+    fn apply(&self, step: u64) {
+        let rng = &self.rng;
+        self.fault
+            .signal
+            .write()
+            .with_view_mut(|mut x| match self.fault.kind {
+                FaultKind::DropSpikes { fraction } => {
+                    let first = rng.advance(x.len() as u64);
+                    for (i, x) in x.iter_mut().enumerate() {
+                        if rng.f64_at(first + i as u64) < fraction {
+                            *x = 0.;
+                        }
+                    }
+                }
+                FaultKind::ZeroRows { fraction } => {
+                    for (i, mut row) in x.outer_iter_mut().enumerate() {
+                        if rng.f64_at(i as u64) < fraction {
+                            row.fill(0.);
+                        }
+                    }
+                }
+                FaultKind::Perturb { start, end, std } => {
+                    if (start..end).contains(&step) {
+                        let first = rng.advance(x.len() as u64);
+                        for (i, x) in x.iter_mut().enumerate() {
+                            *x += std * rng.gaussian_at(first + i as u64);
+                        }
+                    }
+                }
+            });
+    }
+}
+
+/// Lets the injector apply the fault once the wrapped writer of a faulty
+/// signal and all other writers have stepped.
+pub(crate) struct FaultTrigger {
+    injector: Arc<FaultInjector>,
+}
+
+impl FaultTrigger {
+    /// Returns a node stepping `node` with the same dependencies. It is
+    /// never skipped by memoization, so that every writer is counted.
+    pub(crate) fn wrap(
+        node: &Arc<OperatorNode>,
+        injector: Arc<FaultInjector>,
+    ) -> Arc<OperatorNode> {
+        WrappedOperator::wrap(node, FaultTrigger { injector })
+    }
+}
+
+impl StepWrapper for FaultTrigger {
+    type State = ();
+
+    fn before_step(&self, _operator: &dyn Operator) {}
+
+    fn after_step(
+        &self,
+        _operator: &dyn Operator,
+        _state: (),
+        result: Result<(), OperatorError>,
+    ) -> Result<(), OperatorError> {
+        if result.is_ok() {
+            self.injector.writer_stepped();
+        }
+        result
+    }
+
+    fn keeps_memo(&self) -> bool {
+        false
+    }
+
+    fn is_pure(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    fn signal(value: ArrayD<f64>) -> Arc<ArraySignal<f64>> {
+        Arc::new(ArraySignal::from_array("x".to_string(), value))
+    }
+
+    #[test]
+    fn it_applies_faults_once_all_writers_stepped() {
+        let x = signal(Array::ones(IxDyn(&[1000])));
+        let injector = FaultInjector::new(
+            Fault {
+                signal: Arc::clone(&x),
+                kind: FaultKind::DropSpikes { fraction: 0.25 },
+            },
+            2,
+            1,
+        );
+        injector.reset(1);
+
+        injector.writer_stepped();
+        assert_eq!(x.read().with_view(|x| x.sum()), 1000.);
+        injector.writer_stepped();
+        let kept = x.read().with_view(|x| x.sum());
+        assert!((700. ..800.).contains(&kept), "kept {} of 1000", kept);
+    }
+
+    #[test]
+    fn it_zeroes_rows_on_reset_and_perturbs_scheduled_steps() {
+        let weights = signal(Array::ones(IxDyn(&[100, 3])));
+        let zero_rows = FaultInjector::new(
+            Fault {
+                signal: Arc::clone(&weights),
+                kind: FaultKind::ZeroRows { fraction: 0.5 },
+            },
+            0,
+            1,
+        );
+        zero_rows.reset(2);
+        let rows = weights.read().with_view(|w| {
+            w.outer_iter()
+                .map(|row| row.sum())
+                .filter(|&sum| sum == 0. || sum == 3.)
+                .count()
+        });
+        assert_eq!(rows, 100);
+        assert!(weights.read().with_view(|w| w.sum()) < 300.);
+
+        let x = signal(Array::zeros(IxDyn(&[4])));
+        let perturb = FaultInjector::new(
+            Fault {
+                signal: Arc::clone(&x),
+                kind: FaultKind::Perturb {
+                    start: 2,
+                    end: 3,
+                    std: 1.,
+                },
+            },
+            1,
+            1,
+        );
+        perturb.reset(3);
+        let mut perturbed = vec![];
+        for _ in 0..4 {
+            x.write().with_view_mut(|mut x| x.fill(0.));
+            perturb.writer_stepped();
+            perturbed.push(x.read().with_view(|x| x.iter().any(|&x| x != 0.)));
+        }
+        assert_eq!(perturbed, vec![false, false, true, false]);
+    }
+}
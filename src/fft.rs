@@ -0,0 +1,87 @@
+use num_complex::Complex;
+use rustfft::{FFTnum, FFTplanner, FFT};
+use std::fmt;
+use std::sync::Arc;
+
+/// Discrete Fourier transform of a fixed length, with the rustfft plans
+/// created once.
+pub struct Dft<T> {
+    forward: Arc<dyn FFT<T>>,
+    inverse: Arc<dyn FFT<T>>,
+}
+
+impl<T> fmt::Debug for Dft<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Dft")
+            .field("n", &self.forward.len())
+            .finish()
+    }
+}
+
+impl<T: FFTnum> Dft<T> {
+    pub fn new(n: usize) -> Self {
+        Dft {
+            forward: FFTplanner::new(false).plan_fft(n),
+            inverse: FFTplanner::new(true).plan_fft(n),
+        }
+    }
+
+    /// Replaces `data`, which must have the length of the transform, with its
+    /// spectrum.
+    pub fn forward(&self, data: &mut [Complex<T>]) {
+        let mut input = data.to_vec();
+        self.forward.process(&mut input, data);
+    }
+
+    /// Replaces the spectrum `data` with the values it was computed from.
+    pub fn inverse(&self, data: &mut [Complex<T>]) {
+        let mut input = data.to_vec();
+        self.inverse.process(&mut input, data);
+        let scale = T::from_usize(data.len()).unwrap();
+        data.iter_mut().for_each(|v| *v = *v / scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_traits::FloatConst;
+
+    fn naive_dft(data: &[Complex<f64>]) -> Vec<Complex<f64>> {
+        let n = data.len() as f64;
+        (0..data.len())
+            .map(|k| {
+                data.iter()
+                    .enumerate()
+                    .map(|(j, x)| {
+                        x * Complex::from_polar(&1., &(-2. * f64::PI() * (j * k) as f64 / n))
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: &[Complex<f64>], expected: &[Complex<f64>]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).norm() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn it_matches_the_naive_dft_for_any_length() {
+        for &n in [1, 2, 5, 8, 12].iter() {
+            let data: Vec<_> = (0..n)
+                .map(|i| Complex::new(i as f64, (i * i) as f64 - 1.))
+                .collect();
+            let dft = Dft::new(n);
+
+            let mut spectrum = data.clone();
+            dft.forward(&mut spectrum);
+            assert_close(&spectrum, &naive_dft(&data));
+
+            dft.inverse(&mut spectrum);
+            assert_close(&spectrum, &data);
+        }
+    }
+}
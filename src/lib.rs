@@ -1,37 +1,112 @@
+mod affinity;
+pub mod analysis;
 mod binding;
-mod engine;
-mod operator;
-mod probe;
-mod signal;
+pub mod checkpoint;
+pub mod engine;
+pub mod fault;
+mod fft;
+pub mod lockstep;
+mod logging;
+pub mod metrics;
+pub mod operator;
+pub mod probe;
+pub mod replay;
+pub mod rng;
+pub mod signal;
+mod slow_callback;
 mod sync;
+pub mod verify;
 
 use crate::binding::{
-    engine::PyEngine,
+    engine::{PyChunkedRun, PyEngine, PyStepRecord, PyThreadPool},
+    exceptions::{BuildError, ShapeMismatchError, SignalTypeError, SimulationError},
     operator::{
-        PyCopy, PyDotInc, PyElementwiseInc, PyReset, PySimNeurons, PySimProcess, PySimPyFunc,
-        PyTimeUpdate,
+        PyAssociativeMemory, PyCircularConv, PyConvInc, PyCopy, PyCopyC128, PyCopyF32, PyCopyI64,
+        PyDotInc, PyDotIncF32, PyElementwiseInc, PyElementwiseIncC128, PyElementwiseIncF32,
+        PyElementwiseIncI64, PyFftC128, PyFftC128ToF64, PyFftF64, PyInputQueue, PyInvolution,
+        PyPool, PyPresentInput, PyQueueInput, PyReceiveSignal, PyReset, PyResetC128, PyResetF32,
+        PySendSignal, PySimNeurons, PySimProcess, PySimPyFunc, PySimilarity, PySink,
+        PySparseDotInc, PySparseProjection, PySpikeDotInc, PyStepEnvironment, PySuperposition,
+        PyTimeUpdate, PyWinnerTakeAll,
     },
     probe::PyProbe,
-    signal::{PySignalArrayF64, PySignalArrayViewF64, PySignalF64, PySignalU64},
+    signal::{
+        PySignalArrayC128, PySignalArrayF32, PySignalArrayF64, PySignalArrayI64,
+        PySignalArraySampledF64, PySignalArrayViewF64, PySignalF64, PySignalSparseF64, PySignalU64,
+    },
 };
 use pyo3::prelude::*;
 
 #[pymodule]
-fn nengo_rs(_py: Python, m: &PyModule) -> PyResult<()> {
+fn nengo_rs(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<PyEngine>()?;
+    m.add_class::<PyThreadPool>()?;
+    m.add_class::<PyChunkedRun>()?;
+    m.add_class::<PyStepRecord>()?;
     m.add_class::<PySignalArrayF64>()?;
+    m.add_class::<PySignalArrayF32>()?;
+    m.add_class::<PySignalArrayI64>()?;
+    m.add_class::<PySignalArrayC128>()?;
+    m.add_class::<PySignalSparseF64>()?;
+    binding::signal::add_functions(m)?;
+    m.add_class::<PySignalArraySampledF64>()?;
     m.add_class::<PySignalArrayViewF64>()?;
     m.add_class::<PySignalF64>()?;
     m.add_class::<PySignalU64>()?;
     m.add_class::<PyReset>()?;
+    m.add_class::<PyResetC128>()?;
+    m.add_class::<PyResetF32>()?;
     m.add_class::<PySimNeurons>()?;
     m.add_class::<PySimProcess>()?;
     m.add_class::<PySimPyFunc>()?;
+    m.add_class::<PySink>()?;
+    m.add_class::<PySparseDotInc>()?;
+    m.add_class::<PySparseProjection>()?;
+    m.add_class::<PySpikeDotInc>()?;
+    m.add_class::<PyStepEnvironment>()?;
+    m.add_class::<PySendSignal>()?;
+    m.add_class::<PyPool>()?;
+    m.add_class::<PyPresentInput>()?;
+    m.add_class::<PyInputQueue>()?;
+    m.add_class::<PyQueueInput>()?;
+    m.add_class::<PyReceiveSignal>()?;
     m.add_class::<PyTimeUpdate>()?;
     m.add_class::<PyElementwiseInc>()?;
+    m.add_class::<PyElementwiseIncC128>()?;
+    m.add_class::<PyElementwiseIncF32>()?;
+    m.add_class::<PyElementwiseIncI64>()?;
+    m.add_class::<PyFftC128>()?;
+    m.add_class::<PyFftC128ToF64>()?;
+    m.add_class::<PyFftF64>()?;
+    m.add_class::<PyCircularConv>()?;
+    m.add_class::<PyConvInc>()?;
     m.add_class::<PyCopy>()?;
+    m.add_class::<PyCopyC128>()?;
+    m.add_class::<PyCopyF32>()?;
+    m.add_class::<PyCopyI64>()?;
     m.add_class::<PyDotInc>()?;
+    m.add_class::<PyDotIncF32>()?;
+    m.add_class::<PyWinnerTakeAll>()?;
+    m.add_class::<PyAssociativeMemory>()?;
+    m.add_class::<PyInvolution>()?;
+    m.add_class::<PySimilarity>()?;
+    m.add_class::<PySuperposition>()?;
     m.add_class::<PyProbe>()?;
+    #[cfg(feature = "ros2")]
+    {
+        m.add_class::<binding::operator::PySubscribeTopic>()?;
+        m.add_class::<binding::operator::PyPublishTopic>()?;
+    }
+    #[cfg(feature = "audio")]
+    {
+        m.add_class::<binding::operator::PyRecordAudio>()?;
+        m.add_class::<binding::operator::PyPlayAudio>()?;
+    }
+
+    m.add("SignalTypeError", py.get_type::<SignalTypeError>())?;
+    m.add("ShapeMismatchError", py.get_type::<ShapeMismatchError>())?;
+    m.add("BuildError", py.get_type::<BuildError>())?;
+    m.add("SimulationError", py.get_type::<SimulationError>())?;
 
     Ok(())
 }
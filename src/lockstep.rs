@@ -0,0 +1,171 @@
+use crate::engine::{BuildError, Engine, RunError};
+use crate::replay::Value;
+use crate::signal::Signal;
+use std::sync::Arc;
+
+/// Copies the value of a signal of one engine to a signal of another.
+#[derive(Clone)]
+pub struct Coupling {
+    pub source: Arc<dyn Signal + Send + Sync>,
+    pub target: Arc<dyn Signal + Send + Sync>,
+}
+
+impl Coupling {
+    /// Fails unless both signals have the same type and shape, and the
+    /// target is not read-only.
+    pub fn new(
+        source: Arc<dyn Signal + Send + Sync>,
+        target: Arc<dyn Signal + Send + Sync>,
+    ) -> Result<Self, BuildError> {
+        match Value::of(&*source) {
+            Some(value) if value.fits(&*target) && !target.is_read_only() => {
+                Ok(Self { source, target })
+            }
+            _ => Err(BuildError::IncompatibleCoupling {
+                source: source.name().clone(),
+                target: target.name().clone(),
+            }),
+        }
+    }
+
+    fn copy(&self) {
+        if let Some(value) = Value::of(&*self.source) {
+            value.assign_to(&*self.target);
+        }
+    }
+}
+
+/// Engines of separately built models, such as a brain and its environment,
+/// stepped together. After each step, the couplings copy outputs of the
+/// engines to their inputs for the next step.
+pub struct Lockstep<'a> {
+    engines: Vec<&'a Engine>,
+    couplings: Vec<Coupling>,
+}
+
+impl<'a> Lockstep<'a> {
+    /// Fails if an operator of any engine writes the target of a coupling,
+    /// which would overwrite the copied values.
+    pub fn new(engines: Vec<&'a Engine>, couplings: Vec<Coupling>) -> Result<Self, BuildError> {
+        for coupling in couplings.iter() {
+            let region = coupling.target.region();
+            if let Some(operator) = engines.iter().find_map(|e| e.writer_of(&region)) {
+                return Err(BuildError::CoupledSignalWritten {
+                    signal: coupling.target.name().clone(),
+                    operator,
+                });
+            }
+        }
+        Ok(Self { engines, couplings })
+    }
+
+    /// Runs `n_steps` steps of all engines concurrently, copying the coupled
+    /// signals after each step. Stops at the first failing step.
+    pub fn run_steps(&self, n_steps: i64) -> Result<(), RunError> {
+        let couplings = self.couplings.clone();
+        Engine::run_steps_coupled(
+            &self.engines,
+            n_steps,
+            Box::new(move || couplings.iter().for_each(Coupling::copy)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::{ElementwiseInc, OperatorNode};
+    use crate::signal::{ArraySignal, SignalAccess};
+    use ndarray::prelude::*;
+
+    fn accumulator(name: &str, input: &Arc<ArraySignal<f64>>) -> (Engine, Arc<ArraySignal<f64>>) {
+        let target = Arc::new(ArraySignal::from_array(
+            name.to_string(),
+            array![0.].into_dyn(),
+        ));
+        let engine = Engine::new(
+            vec![Arc::clone(&target) as Arc<_>, Arc::clone(input) as Arc<_>],
+            vec![Arc::new(OperatorNode::new(
+                Box::new(ElementwiseInc {
+                    target: Arc::clone(&target),
+                    left: Arc::clone(input),
+                    right: Arc::new(ArraySignal::from_array(
+                        "one".to_string(),
+                        array![1.].into_dyn(),
+                    )),
+                    checked: false,
+                }),
+                vec![],
+            ))],
+            vec![],
+        )
+        .unwrap();
+        (engine, target)
+    }
+
+    #[test]
+    fn it_copies_the_coupled_signals_after_each_step() {
+        let input = Arc::new(ArraySignal::from_array(
+            "input".to_string(),
+            array![1.].into_dyn(),
+        ));
+        let (source, sum) = accumulator("sum", &input);
+        let received = Arc::new(ArraySignal::from_array(
+            "received".to_string(),
+            array![0.].into_dyn(),
+        ));
+        let (target, sum_of_sums) = accumulator("sum_of_sums", &received);
+        let lockstep = Lockstep::new(
+            vec![&source, &target],
+            vec![Coupling::new(sum.clone(), received.clone()).unwrap()],
+        )
+        .unwrap();
+
+        lockstep.run_steps(3).unwrap();
+
+        assert_eq!(**sum.read(), array![3.].into_dyn());
+        assert_eq!(**sum_of_sums.read(), array![0. + 1. + 2.].into_dyn());
+        assert!(matches!(
+            Lockstep::new(vec![&source], vec![Coupling::new(input, sum).unwrap()]),
+            Err(BuildError::CoupledSignalWritten { .. })
+        ));
+    }
+
+    #[test]
+    fn it_stops_all_engines_at_the_first_failing_step() {
+        let input = Arc::new(ArraySignal::from_array(
+            "input".to_string(),
+            array![1.].into_dyn(),
+        ));
+        let (source, sum) = accumulator("sum", &input);
+        let overflowing = Arc::new(ArraySignal::from_array(
+            "overflowing".to_string(),
+            array![i64::MAX].into_dyn(),
+        ));
+        let one = Arc::new(ArraySignal::from_array(
+            "one".to_string(),
+            array![1i64].into_dyn(),
+        ));
+        let failing = Engine::new(
+            vec![Arc::clone(&overflowing) as Arc<_>],
+            vec![Arc::new(OperatorNode::new(
+                Box::new(ElementwiseInc {
+                    target: overflowing,
+                    left: Arc::clone(&one),
+                    right: one,
+                    checked: true,
+                }),
+                vec![],
+            ))],
+            vec![],
+        )
+        .unwrap();
+        let lockstep = Lockstep::new(vec![&source, &failing], vec![]).unwrap();
+
+        assert!(lockstep.run_steps(3).is_err());
+
+        assert_eq!(**sum.read(), array![1.].into_dyn());
+        assert_eq!(source.checkpoint().step, 1);
+        assert_eq!(failing.checkpoint().step, 0);
+    }
+}
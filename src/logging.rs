@@ -0,0 +1,17 @@
+use pyo3::prelude::*;
+
+/// Name of the Python logger receiving messages from the engine.
+pub const LOGGER: &str = "nengo_rs";
+
+/// Logs `message` as a warning with the Python logger named `LOGGER`.
+/// Errors of the logging module are printed instead of raised.
+pub fn log_warning(message: &str) {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let result = PyModule::import(py, "logging")
+        .and_then(|logging| logging.call1("getLogger", (LOGGER,)))
+        .and_then(|logger| logger.call_method1("warning", (message,)));
+    if let Err(err) = result {
+        err.print(py);
+    }
+}
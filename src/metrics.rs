@@ -0,0 +1,352 @@
+use crate::engine::StepHistory;
+use crate::probe::Probe;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Time a client may take to send its request or to receive the response
+/// before its connection is dropped.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Number of threads answering requests.
+const HANDLER_THREADS: usize = 4;
+
+/// Connections waiting for a handler thread beyond which new ones are closed.
+const QUEUED_CONNECTIONS: usize = 16;
+
+/// Metrics of an engine in the Prometheus text format, collected once enabled
+/// with `Engine::enable_metrics`.
+pub struct Metrics {
+    step_history: Arc<Mutex<StepHistory>>,
+    operator_nanos: Vec<(String, Arc<AtomicU64>)>,
+    probes: Vec<Arc<RwLock<dyn Probe + Send + Sync>>>,
+    /// Number of threads that step operators concurrently.
+    pool_size: usize,
+}
+
+impl Metrics {
+    pub(crate) fn new(
+        step_history: Arc<Mutex<StepHistory>>,
+        operator_nanos: Vec<(String, Arc<AtomicU64>)>,
+        probes: Vec<Arc<RwLock<dyn Probe + Send + Sync>>>,
+        pool_size: usize,
+    ) -> Self {
+        Metrics {
+            step_history,
+            operator_nanos,
+            probes,
+            pool_size,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let (steps, step_seconds) = {
+            let history = self.step_history.lock().unwrap();
+            (history.total_steps, history.total_duration.as_secs_f64())
+        };
+        let operator_seconds: Vec<(&String, f64)> = self
+            .operator_nanos
+            .iter()
+            .map(|(name, nanos)| {
+                let nanos = nanos.load(Ordering::SeqCst);
+                (name, Duration::from_nanos(nanos).as_secs_f64())
+            })
+            .collect();
+        let busy_seconds: f64 = operator_seconds.iter().map(|(_, s)| s).sum();
+        let probe_bytes: usize = self
+            .probes
+            .iter()
+            .map(|p| p.read().unwrap().memory_usage())
+            .sum();
+
+        let mut out = String::new();
+        metric(
+            &mut out,
+            "nengo_rs_steps_total",
+            "counter",
+            "Simulation steps run.",
+        );
+        writeln!(out, "nengo_rs_steps_total {}", steps).unwrap();
+        metric(
+            &mut out,
+            "nengo_rs_step_seconds_total",
+            "counter",
+            "Wall-clock time spent running steps.",
+        );
+        writeln!(out, "nengo_rs_step_seconds_total {}", step_seconds).unwrap();
+        metric(
+            &mut out,
+            "nengo_rs_operator_seconds_total",
+            "counter",
+            "Time spent stepping operators, by operator type.",
+        );
+        for (name, seconds) in operator_seconds.iter() {
+            writeln!(
+                out,
+                "nengo_rs_operator_seconds_total{{operator=\"{}\"}} {}",
+                escape_label(name),
+                seconds
+            )
+            .unwrap();
+        }
+        metric(
+            &mut out,
+            "nengo_rs_probe_memory_bytes",
+            "gauge",
+            "Memory used by probe samples held in memory.",
+        );
+        writeln!(out, "nengo_rs_probe_memory_bytes {}", probe_bytes).unwrap();
+        metric(
+            &mut out,
+            "nengo_rs_thread_pool_busy_ratio",
+            "gauge",
+            "Fraction of the step time of all pool threads spent stepping operators.",
+        );
+        let busy_ratio = if step_seconds > 0. {
+            busy_seconds / (step_seconds * self.pool_size as f64)
+        } else {
+            0.
+        };
+        writeln!(out, "nengo_rs_thread_pool_busy_ratio {}", busy_ratio).unwrap();
+        out
+    }
+
+    /// Serves the metrics over HTTP on background threads until the returned
+    /// server is shut down. Every request is answered with the current
+    /// metrics by one of a few handler threads, so that a slow client cannot
+    /// block the others. Connections arriving while too many are waiting for
+    /// a handler are closed right away.
+    pub fn serve(self: Arc<Self>, address: impl ToSocketAddrs) -> io::Result<MetricsServer> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::sync_channel::<TcpStream>(QUEUED_CONNECTIONS);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let mut threads: Vec<JoinHandle<()>> = (0..HANDLER_THREADS)
+            .map(|_| {
+                let metrics = Arc::clone(&self);
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || loop {
+                    // Release the lock before responding so that the other
+                    // handlers can take the next connection.
+                    let stream = receiver.lock().unwrap().recv();
+                    // Fails once the accepting thread stopped and the queue
+                    // is empty.
+                    match stream {
+                        // A failing client must not stop the endpoint.
+                        Ok(stream) => {
+                            let _ = metrics.respond(stream);
+                        }
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+        threads.push({
+            let stopped = Arc::clone(&stopped);
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let _ = sender.try_send(stream);
+                }
+            })
+        });
+        Ok(MetricsServer {
+            address,
+            stopped,
+            threads,
+        })
+    }
+
+    fn respond(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 && line != "\r\n" && line != "\n" {
+            line.clear();
+        }
+        let body = self.render();
+        write!(
+            reader.get_mut(),
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
+}
+
+/// Handle of the background threads serving metrics started with
+/// `Metrics::serve`. Dropping it leaves the threads running.
+pub struct MetricsServer {
+    address: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Stops accepting connections and waits for the serving threads to
+    /// exit. Requests already accepted are completed.
+    pub fn shutdown(&mut self) {
+        if self.threads.is_empty() {
+            return;
+        }
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake up the thread blocked on accepting the next connection.
+        let _ = TcpStream::connect(self.address);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+    use crate::operator::{OperatorNode, TimeUpdate};
+    use crate::signal::ScalarSignal;
+    use std::io::Read;
+
+    fn engine_with_metrics() -> (Engine, Arc<Metrics>) {
+        let mut engine = Engine::new(
+            vec![],
+            vec![Arc::new(OperatorNode::new(
+                Box::new(TimeUpdate {
+                    dt: 0.001,
+                    step_target: Arc::new(ScalarSignal::new("step".to_string(), 0)),
+                    time_target: Arc::new(ScalarSignal::new("time".to_string(), 0.)),
+                    dt_signal: None,
+                    compensated: false,
+                    compensation: Default::default(),
+                }),
+                vec![],
+            ))],
+            vec![],
+        )
+        .unwrap();
+        let metrics = engine.enable_metrics();
+        (engine, metrics)
+    }
+
+    #[test]
+    fn it_renders_the_metrics() {
+        let (engine, metrics) = engine_with_metrics();
+        engine.run_steps(3).unwrap();
+
+        let rendered = metrics.render();
+
+        assert!(rendered.contains("nengo_rs_steps_total 3\n"));
+        assert!(
+            rendered.contains("nengo_rs_operator_seconds_total{operator=\"TimeUpdate<f64, u64>\"}")
+        );
+        assert!(rendered.contains("nengo_rs_probe_memory_bytes 0\n"));
+    }
+
+    #[test]
+    fn it_relates_the_busy_time_to_all_pool_threads() {
+        let mut history = StepHistory::default();
+        history.total_steps = 1;
+        history.total_duration = Duration::from_secs(1);
+        let metrics = Metrics::new(
+            Arc::new(Mutex::new(history)),
+            vec![("Op".to_string(), Arc::new(AtomicU64::new(2_000_000_000)))],
+            vec![],
+            4,
+        );
+
+        assert!(metrics
+            .render()
+            .contains("nengo_rs_thread_pool_busy_ratio 0.5\n"));
+    }
+
+    #[test]
+    fn it_closes_connections_beyond_the_queue_limit() {
+        let (_engine, metrics) = engine_with_metrics();
+        let server = Arc::clone(&metrics).serve("127.0.0.1:0").unwrap();
+        let _idle: Vec<TcpStream> = (0..HANDLER_THREADS + QUEUED_CONNECTIONS + 1)
+            .map(|_| TcpStream::connect(server.address()).unwrap())
+            .collect();
+
+        let mut stream = TcpStream::connect(server.address()).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert_eq!(response, "");
+    }
+
+    #[test]
+    fn it_serves_the_metrics_over_http() {
+        let (engine, metrics) = engine_with_metrics();
+        engine.run_step().unwrap();
+        let server = Arc::clone(&metrics).serve("127.0.0.1:0").unwrap();
+
+        let mut stream = TcpStream::connect(server.address()).unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("nengo_rs_steps_total 1\n"));
+    }
+
+    #[test]
+    fn it_answers_while_another_client_is_idle() {
+        let (_engine, metrics) = engine_with_metrics();
+        let server = Arc::clone(&metrics).serve("127.0.0.1:0").unwrap();
+        let _idle = TcpStream::connect(server.address()).unwrap();
+
+        let mut stream = TcpStream::connect(server.address()).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[test]
+    fn it_stops_serving_after_shutdown() {
+        let (_engine, metrics) = engine_with_metrics();
+        let mut server = Arc::clone(&metrics).serve("127.0.0.1:0").unwrap();
+
+        server.shutdown();
+
+        assert!(TcpStream::connect(server.address()).is_err());
+    }
+
+    #[test]
+    fn it_escapes_label_values() {
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
@@ -1,27 +1,566 @@
+#[cfg(feature = "audio")]
+mod audio;
+mod circular_conv;
+mod conv_inc;
 mod copy;
 mod dot_inc;
 mod elementwise_inc;
+mod environment;
+mod fft;
+mod pool;
+mod present_input;
+mod queue_input;
 mod reset;
+#[cfg(feature = "ros2")]
+mod ros2;
+mod signal_exchange;
 mod sim_neurons;
 mod sim_process;
 mod sim_pyfunc;
+mod sink;
+mod sparse_dot_inc;
+mod sparse_projection;
+mod spike_dot_inc;
 mod time_update;
+mod vsa;
+mod winner_take_all;
 
+#[cfg(feature = "audio")]
+pub use crate::operator::audio::*;
+pub use crate::operator::circular_conv::*;
+pub use crate::operator::conv_inc::*;
 pub use crate::operator::copy::*;
 pub use crate::operator::dot_inc::*;
 pub use crate::operator::elementwise_inc::*;
+pub use crate::operator::environment::*;
+pub use crate::operator::fft::*;
+pub use crate::operator::pool::*;
+pub use crate::operator::present_input::*;
+pub use crate::operator::queue_input::*;
 pub use crate::operator::reset::*;
+#[cfg(feature = "ros2")]
+pub use crate::operator::ros2::*;
+pub use crate::operator::signal_exchange::*;
 pub use crate::operator::sim_neurons::*;
 pub use crate::operator::sim_process::*;
 pub use crate::operator::sim_pyfunc::*;
+pub use crate::operator::sink::*;
+pub use crate::operator::sparse_dot_inc::*;
+pub use crate::operator::sparse_projection::*;
+pub use crate::operator::spike_dot_inc::*;
 pub use crate::operator::time_update::*;
-use std::fmt::Debug;
+pub use crate::operator::vsa::*;
+pub use crate::operator::winner_take_all::*;
+use crate::signal::{ArraySignal, ScalarSignal, ShapeError, Signal, SignalAccess};
+use ndarray::{ArrayViewD, Ix};
+use numpy::{Element, PyArrayDyn};
+use pyo3::types::{IntoPyDict, PyTuple};
+use pyo3::{
+    ffi, AsPyPointer, AsPyRef, IntoPyPointer, Py, PyErr, PyObject, PyResult, Python, ToPyObject,
+};
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::io;
+use std::sync::{Arc, Mutex};
 
 pub trait Operator: Debug {
-    fn step(&self);
+    fn step(&self) -> Result<(), OperatorError>;
+    fn reads(&self) -> Vec<&dyn Signal>;
+    fn writes(&self) -> Vec<&dyn Signal>;
+
+    /// Operators calling into Python return `true` and implement
+    /// `step_with_gil`, so that the engine can step them under a single GIL
+    /// acquisition.
+    fn requires_gil(&self) -> bool {
+        false
+    }
+
+    fn step_with_gil(&self, _py: Python) -> Result<(), OperatorError> {
+        self.step()
+    }
+
+    /// Whether the operator only accesses its signals, so that the effect of
+    /// a step is undone by resetting the signals.
+    fn is_pure(&self) -> bool {
+        !self.requires_gil()
+    }
+
+    /// Timestep the operator was built for, if it depends on one.
+    fn dt(&self) -> Option<f64> {
+        None
+    }
+
+    /// Whether the operator only adds to the signals it writes. Increments of
+    /// the same signal commute and may run concurrently.
+    fn increments(&self) -> bool {
+        false
+    }
+
+    /// Checks that the signal shapes fit together before the first step.
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        Ok(())
+    }
+
+    /// Stochastic operators restart their `RngStream` from `seed`, which the
+    /// engine derives from its own seed whenever it resets.
+    fn set_seed(&self, _seed: u64) {}
+
+    /// Bytes of data the operator holds besides its signals.
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    fn name(&self) -> String {
+        strip_module_paths(std::any::type_name::<Self>())
+    }
+}
+
+pub(crate) fn strip_module_paths(type_name: &str) -> String {
+    let mut result = String::with_capacity(type_name.len());
+    let mut segment = String::new();
+    let mut chars = type_name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            segment.clear();
+        } else if c.is_alphanumeric() || c == '_' {
+            segment.push(c);
+        } else {
+            result.push_str(&segment);
+            segment.clear();
+            result.push(c);
+        }
+    }
+    result.push_str(&segment);
+    result
+}
+
+#[derive(Debug)]
+pub enum ErrorCause {
+    /// The Python exception instance raised by a called function.
+    Python(PyObject),
+    Io(io::Error),
+    Shape(String),
+    Overflow(String),
+    /// A signal written by the operator was made read-only after the engine
+    /// was built.
+    ReadOnly(String),
+}
+
+impl ErrorCause {
+    pub fn python(py: Python, mut err: PyErr) -> Self {
+        err.normalize(py);
+        let instance = err.to_object(py);
+        if let Some(traceback) = &err.ptraceback {
+            let _ = instance.as_ref(py).setattr("__traceback__", traceback);
+        }
+        ErrorCause::Python(instance)
+    }
+}
+
+impl Display for ErrorCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorCause::Python(instance) => {
+                let gil = Python::acquire_gil();
+                let py = gil.python();
+                match instance.as_ref(py).repr() {
+                    Ok(repr) => f.write_str(&repr.to_string_lossy()),
+                    Err(_) => f.write_str("Python exception"),
+                }
+            }
+            ErrorCause::Io(err) => Display::fmt(err, f),
+            ErrorCause::Shape(msg) | ErrorCause::Overflow(msg) => f.write_str(msg),
+            ErrorCause::ReadOnly(signal) => write!(f, "Signal `{}` is read-only.", signal),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OperatorError {
+    pub operator: String,
+    pub cause: ErrorCause,
+}
+
+impl OperatorError {
+    pub fn new<O: Operator + ?Sized>(operator: &O, cause: ErrorCause) -> Self {
+        Self {
+            operator: operator.name(),
+            cause,
+        }
+    }
+}
+
+impl Display for OperatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed: {}", self.operator, self.cause)
+    }
+}
+
+/// Fails if `value` cannot be broadcast to a signal of the given `shape`.
+pub(crate) fn check_assignable<T, O: Operator + ?Sized>(
+    operator: &O,
+    value: &ArrayViewD<T>,
+    shape: &[Ix],
+) -> Result<(), OperatorError> {
+    match value.broadcast(shape) {
+        Some(_) => Ok(()),
+        None => Err(OperatorError::new(
+            operator,
+            ErrorCause::Shape(format!(
+                "Cannot assign array of shape {:?} to signal of shape {:?}.",
+                value.shape(),
+                shape
+            )),
+        )),
+    }
+}
+
+impl Error for OperatorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.cause {
+            ErrorCause::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+pub trait Condition: Debug {
+    fn is_met(&self) -> bool;
+}
+
+impl<T: Copy + Default + PartialEq + Debug> Condition for ScalarSignal<T> {
+    fn is_met(&self) -> bool {
+        **self.read() != T::default()
+    }
+}
+
+impl<T: Element + Default + PartialEq + Debug> Condition for ArraySignal<T> {
+    fn is_met(&self) -> bool {
+        self.read().any(|x| *x != T::default())
+    }
+}
+
+/// Remembers the signal generations seen by the last step of an operator, so
+/// that a pure operator can be skipped while none of its signals changed.
+#[derive(Debug, Default)]
+pub struct Memo {
+    generations: Mutex<Option<Vec<u64>>>,
+}
+
+impl Memo {
+    fn current_generations(operator: &dyn Operator) -> Vec<u64> {
+        operator
+            .reads()
+            .iter()
+            .chain(operator.writes().iter())
+            .map(|signal| signal.generation())
+            .collect()
+    }
+
+    pub fn is_current(&self, operator: &dyn Operator) -> bool {
+        match &*self.generations.lock().unwrap() {
+            Some(generations) => *generations == Self::current_generations(operator),
+            None => false,
+        }
+    }
+
+    pub fn record(&self, operator: &dyn Operator) {
+        *self.generations.lock().unwrap() = Some(Self::current_generations(operator));
+    }
+}
+
+/// Numpy array passing the value of a signal to Python. By default, each
+/// step passes a new array, which Python code may keep or modify. With
+/// `reuse`, a single read-only array is allocated and overwritten every step
+/// instead, so that Python code keeping a reference to it sees it change
+/// with the signal.
+#[derive(Debug)]
+pub struct PyArrayCache<T: Element> {
+    reuse: bool,
+    array: Mutex<Option<Py<PyArrayDyn<T>>>>,
+}
+
+impl<T: Element> Default for PyArrayCache<T> {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl<T: Element> PyArrayCache<T> {
+    pub fn new(reuse: bool) -> Self {
+        Self {
+            reuse,
+            array: Mutex::new(None),
+        }
+    }
+
+    /// Returns an array with the current value of `signal`.
+    pub fn update(&self, py: Python, signal: &ArraySignal<T>) -> PyResult<PyObject> {
+        let value = signal.read();
+        if !self.reuse {
+            return Ok(value.to_py_array(py).to_object(py));
+        }
+        let mut cached = self.array.lock().unwrap();
+        match &*cached {
+            Some(array) => value.with_view(|view| {
+                // SAFETY: No other view of the array data may be alive while
+                // it is written. Rust code never borrows the cached array
+                // elsewhere, the lock keeps other steps of the operator from
+                // writing it at the same time, and Python code cannot use the
+                // array while this thread holds the GIL. The shape is read
+                // from the array itself, so `assign` panics instead of
+                // writing out of bounds if Python code reshaped it.
+                unsafe { array.as_ref(py).as_array_mut() }.assign(&view)
+            }),
+            None => {
+                let array = value.to_py_array(py);
+                array.call_method("setflags", (), Some([("write", false)].into_py_dict(py)))?;
+                *cached = Some(Py::from(array));
+            }
+        }
+        Ok(cached.as_ref().unwrap().to_object(py))
+    }
+}
+
+/// Argument tuple reused across steps to call a Python function, so that the
+/// tuple is only allocated while Python code keeps a reference to the
+/// previous one.
+#[derive(Debug, Default)]
+pub struct PyArgsCache(Mutex<Option<Py<PyTuple>>>);
+
+impl PyArgsCache {
+    /// Returns a tuple of `args`, replacing the items of the cached tuple if
+    /// possible.
+    pub fn update(&self, py: Python, args: Vec<PyObject>) -> Py<PyTuple> {
+        let mut cached = self.0.lock().unwrap();
+        let len = args.len() as ffi::Py_ssize_t;
+        let reusable = cached.take().filter(|tuple| unsafe {
+            ffi::Py_REFCNT(tuple.as_ptr()) == 1 && ffi::PyTuple_GET_SIZE(tuple.as_ptr()) == len
+        });
+        // The tuple is created through the C API instead of `PyTuple::new`, so
+        // that no reference to it is left in the GIL pool.
+        let tuple: Py<PyTuple> = match reusable {
+            Some(tuple) => tuple,
+            None => unsafe { Py::from_owned_ptr(py, ffi::PyTuple_New(len)) },
+        };
+        for (i, arg) in args.into_iter().enumerate() {
+            // SAFETY: Tuples must not change once other code can see them.
+            // Either the tuple was just created, or the cache held the only
+            // reference, and the GIL keeps other threads from taking a new
+            // one. `PyTuple_SetItem` requires a reference count of one, takes
+            // over the reference to the new item, and releases the old one.
+            unsafe { ffi::PyTuple_SetItem(tuple.as_ptr(), i as ffi::Py_ssize_t, arg.into_ptr()) };
+        }
+        *cached = Some(tuple.clone_ref(py));
+        tuple
+    }
 }
 
 pub struct OperatorNode {
     pub operator: Box<dyn Operator + Sync + Send>,
     pub dependencies: Vec<usize>,
+    /// The operator is only stepped while this signal is non-zero. Operators
+    /// writing the condition signal need to be listed in `dependencies`.
+    pub condition: Option<Arc<dyn Condition + Sync + Send>>,
+    /// The operator is only stepped every `period` steps, starting with the
+    /// first step after a reset. Must be at least 1.
+    pub period: u64,
+    /// Skips steps while none of the signals of the operator changed. Only
+    /// pure operators that do not increment their targets can be memoized.
+    pub memo: Option<Memo>,
+}
+
+impl OperatorNode {
+    /// Node stepped on every step, without condition or memoization.
+    pub fn new(operator: Box<dyn Operator + Sync + Send>, dependencies: Vec<usize>) -> Self {
+        OperatorNode {
+            operator,
+            dependencies,
+            condition: None,
+            period: 1,
+            memo: None,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Arc<dyn Condition + Sync + Send>) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    pub fn with_period(mut self, period: u64) -> Self {
+        self.period = period;
+        self
+    }
+
+    pub fn memoized(mut self) -> Self {
+        self.memo = Some(Memo::default());
+        self
+    }
+}
+
+/// Adds behaviour around the steps of the operator of another node, see
+/// `WrappedOperator`.
+pub(crate) trait StepWrapper: Send + Sync {
+    /// Passed from `before_step` to `after_step` of the same step.
+    type State;
+
+    fn before_step(&self, operator: &dyn Operator) -> Self::State;
+
+    fn after_step(
+        &self,
+        operator: &dyn Operator,
+        state: Self::State,
+        result: Result<(), OperatorError>,
+    ) -> Result<(), OperatorError>;
+
+    /// Whether the wrapped node may still be skipped by its memo.
+    fn keeps_memo(&self) -> bool {
+        true
+    }
+
+    /// Whether the effects of the wrapper are undone by resetting the
+    /// signals, so that the wrapped operator stays as pure as it is.
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+/// Steps the operator of another node through a `StepWrapper` and forwards
+/// everything else to it.
+pub(crate) struct WrappedOperator<W> {
+    node: Arc<OperatorNode>,
+    wrapper: W,
+}
+
+impl<W: StepWrapper + 'static> WrappedOperator<W> {
+    /// Returns a node with the dependencies, condition, and period of `node`,
+    /// stepping its operator through `wrapper`. The node gets a fresh memo if
+    /// `node` has one and the wrapper keeps it.
+    pub(crate) fn wrap(node: &Arc<OperatorNode>, wrapper: W) -> Arc<OperatorNode> {
+        let keeps_memo = node.memo.is_some() && wrapper.keeps_memo();
+        let operator = Box::new(WrappedOperator {
+            node: Arc::clone(node),
+            wrapper,
+        });
+        let mut wrapped =
+            OperatorNode::new(operator, node.dependencies.clone()).with_period(node.period);
+        if let Some(condition) = &node.condition {
+            wrapped = wrapped.with_condition(Arc::clone(condition));
+        }
+        if keeps_memo {
+            wrapped = wrapped.memoized();
+        }
+        Arc::new(wrapped)
+    }
+
+    fn wrapped(
+        &self,
+        step: impl FnOnce() -> Result<(), OperatorError>,
+    ) -> Result<(), OperatorError> {
+        let operator = &*self.node.operator;
+        let state = self.wrapper.before_step(operator);
+        let result = step();
+        self.wrapper.after_step(operator, state, result)
+    }
+}
+
+impl<W> Debug for WrappedOperator<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple(&strip_module_paths(std::any::type_name::<W>()))
+            .field(&self.node.operator)
+            .finish()
+    }
+}
+
+impl<W: StepWrapper + 'static> Operator for WrappedOperator<W> {
+    fn step(&self) -> Result<(), OperatorError> {
+        self.wrapped(|| self.node.operator.step())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        self.node.operator.reads()
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        self.node.operator.writes()
+    }
+
+    fn requires_gil(&self) -> bool {
+        self.node.operator.requires_gil()
+    }
+
+    fn step_with_gil(&self, py: Python) -> Result<(), OperatorError> {
+        self.wrapped(|| self.node.operator.step_with_gil(py))
+    }
+
+    fn is_pure(&self) -> bool {
+        self.wrapper.is_pure() && self.node.operator.is_pure()
+    }
+
+    fn dt(&self) -> Option<f64> {
+        self.node.operator.dt()
+    }
+
+    fn increments(&self) -> bool {
+        self.node.operator.increments()
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        self.node.operator.check_shapes()
+    }
+
+    fn set_seed(&self, seed: u64) {
+        self.node.operator.set_seed(seed)
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.node.operator.memory_usage()
+    }
+
+    fn name(&self) -> String {
+        self.node.operator.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn py_args_cache_reuses_the_tuple_once_released() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let cache = PyArgsCache::default();
+
+        let first = cache.update(py, vec![1.to_object(py)]);
+        let first_ptr = first.as_ptr();
+        drop(first);
+        let second = cache.update(py, vec![2.to_object(py)]);
+
+        assert_eq!(second.as_ptr(), first_ptr);
+        assert_eq!(second.as_ref(py).get_item(0).extract::<i64>().unwrap(), 2);
+    }
+
+    #[test]
+    fn py_args_cache_does_not_change_tuples_still_referenced() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let cache = PyArgsCache::default();
+
+        let first = cache.update(py, vec![1.to_object(py)]);
+        let second = cache.update(py, vec![2.to_object(py)]);
+
+        assert_ne!(second.as_ptr(), first.as_ptr());
+        assert_eq!(first.as_ref(py).get_item(0).extract::<i64>().unwrap(), 1);
+        assert_eq!(second.as_ref(py).get_item(0).extract::<i64>().unwrap(), 2);
+    }
+
+    #[test]
+    fn strips_module_paths_from_type_names() {
+        assert_eq!(
+            strip_module_paths("nengo_rs::operator::CopyOp<ndarray::ArrayBase<f64>, u64>"),
+            "CopyOp<ArrayBase<f64>, u64>"
+        );
+    }
 }
@@ -0,0 +1,275 @@
+use crate::logging::log_warning;
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{ArraySignal, ShapeError, Signal, SignalAccess};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use std::collections::VecDeque;
+use std::io;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Longest stretch of audio in milliseconds buffered between a device and the
+/// simulation. It bounds the latency when one side runs ahead of the other.
+const MAX_LATENCY_MS: usize = 100;
+
+/// Interleaved samples passed between the callback of an audio device and an
+/// operator. Once full, the oldest samples are dropped.
+#[derive(Debug)]
+pub struct SampleBuffer {
+    samples: Mutex<VecDeque<f32>>,
+    capacity: usize,
+}
+
+impl SampleBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    fn push(&self, samples: impl Iterator<Item = f32>) {
+        let mut buffer = self.samples.lock().unwrap();
+        buffer.extend(samples);
+        let excess = buffer.len().saturating_sub(self.capacity);
+        buffer.drain(..excess);
+    }
+
+    /// Fills `samples` with the oldest buffered samples, and with silence once
+    /// the buffer runs empty.
+    fn pop(&self, samples: &mut [f32]) {
+        let mut buffer = self.samples.lock().unwrap();
+        for sample in samples.iter_mut() {
+            *sample = buffer.pop_front().unwrap_or(0.);
+        }
+    }
+}
+
+/// Stream of the default audio input or output device, running on its own
+/// thread as streams may not be sent between threads. The stream is stopped
+/// when dropped.
+#[derive(Debug)]
+pub struct AudioStream {
+    pub buffer: Arc<SampleBuffer>,
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+fn stream_config(sample_rate: u32, channels: u16) -> cpal::StreamConfig {
+    cpal::StreamConfig {
+        channels,
+        sample_rate: cpal::SampleRate(sample_rate),
+        buffer_size: cpal::BufferSize::Default,
+    }
+}
+
+fn buffer_capacity(sample_rate: u32, channels: u16) -> usize {
+    (sample_rate as usize * channels as usize * MAX_LATENCY_MS / 1000).max(1)
+}
+
+fn report_error(err: cpal::StreamError) {
+    log_warning(&format!("Audio stream failed: {}", err));
+}
+
+impl AudioStream {
+    /// Records interleaved samples of `channels` channels from the default
+    /// input device.
+    pub fn record(sample_rate: u32, channels: u16) -> io::Result<Self> {
+        let buffer = Arc::new(SampleBuffer::new(buffer_capacity(sample_rate, channels)));
+        let recorded = Arc::clone(&buffer);
+        Self::start(buffer, move || {
+            let device = cpal::default_host()
+                .default_input_device()
+                .ok_or_else(|| "No audio input device is available.".to_string())?;
+            device
+                .build_input_stream(
+                    &stream_config(sample_rate, channels),
+                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                        recorded.push(data.iter().copied())
+                    },
+                    report_error,
+                )
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    /// Plays interleaved samples of `channels` channels on the default output
+    /// device, and silence while none are buffered.
+    pub fn play(sample_rate: u32, channels: u16) -> io::Result<Self> {
+        let buffer = Arc::new(SampleBuffer::new(buffer_capacity(sample_rate, channels)));
+        let played = Arc::clone(&buffer);
+        Self::start(buffer, move || {
+            let device = cpal::default_host()
+                .default_output_device()
+                .ok_or_else(|| "No audio output device is available.".to_string())?;
+            device
+                .build_output_stream(
+                    &stream_config(sample_rate, channels),
+                    move |data: &mut [f32], _: &cpal::OutputCallbackInfo| played.pop(data),
+                    report_error,
+                )
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    fn start<F>(buffer: Arc<SampleBuffer>, build: F) -> io::Result<Self>
+    where
+        F: FnOnce() -> Result<cpal::Stream, String> + Send + 'static,
+    {
+        let (started, is_started) = mpsc::channel();
+        let (stop, is_stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || {
+            let stream = match build().and_then(|s| s.play().map(|_| s).map_err(|e| e.to_string()))
+            {
+                Ok(stream) => stream,
+                Err(err) => {
+                    let _ = started.send(Err(err));
+                    return;
+                }
+            };
+            let _ = started.send(Ok(()));
+            // Fails once the stop sender is dropped.
+            let _ = is_stopped.recv();
+            drop(stream);
+        });
+        match is_started.recv() {
+            Ok(Ok(())) => Ok(Self {
+                buffer,
+                stop: Some(stop),
+                thread: Some(thread),
+            }),
+            result => {
+                let _ = thread.join();
+                let message = match result {
+                    Ok(Err(message)) => message,
+                    _ => "Audio stream thread panicked.".to_string(),
+                };
+                Err(io::Error::other(message))
+            }
+        }
+    }
+}
+
+impl Drop for AudioStream {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn check_channels(signal: &ArraySignal<f64>, channels: u16) -> Result<(), ShapeError> {
+    let size: usize = signal.shape().iter().product();
+    if channels == 0 || !size.is_multiple_of(channels as usize) {
+        return Err(ShapeError {
+            message: format!(
+                "Signal `{}` of shape {:?} does not hold whole frames of {} audio channels.",
+                signal.name(),
+                signal.shape(),
+                channels
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Writes the samples recorded by an input device since the previous step to
+/// `output`, as interleaved frames of `channels` samples. The size of `output`
+/// sets the frames taken per step, which should match the sample rate times
+/// the time step. Missing samples are written as silence.
+#[derive(Debug)]
+pub struct RecordAudio {
+    pub output: Arc<ArraySignal<f64>>,
+    pub channels: u16,
+    pub stream: AudioStream,
+}
+
+impl Operator for RecordAudio {
+    fn step(&self) -> Result<(), OperatorError> {
+        let mut samples = vec![0.; self.output.shape().iter().product()];
+        self.stream.buffer.pop(&mut samples);
+        self.output.write().with_view_mut(|mut output| {
+            for (y, x) in output.iter_mut().zip(samples) {
+                *y = x as f64;
+            }
+        });
+        Ok(())
+    }
+
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.output]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        check_channels(&self.output, self.channels)
+    }
+}
+
+/// Queues `source`, as interleaved frames of `channels` samples, for playback
+/// on an output device each step.
+#[derive(Debug)]
+pub struct PlayAudio {
+    pub source: Arc<ArraySignal<f64>>,
+    pub channels: u16,
+    pub stream: AudioStream,
+}
+
+impl Operator for PlayAudio {
+    fn step(&self) -> Result<(), OperatorError> {
+        let source = self.source.read();
+        source.with_view(|source| self.stream.buffer.push(source.iter().map(|&x| x as f32)));
+        Ok(())
+    }
+
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.source]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        check_channels(&self.source, self.channels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_drops_the_oldest_samples_beyond_the_capacity() {
+        let buffer = SampleBuffer::new(3);
+        buffer.push([1., 2., 3., 4.].iter().copied());
+
+        let mut samples = [0.; 2];
+        buffer.pop(&mut samples);
+
+        assert_eq!(samples, [2., 3.]);
+    }
+
+    #[test]
+    fn it_pads_missing_samples_with_silence() {
+        let buffer = SampleBuffer::new(4);
+        buffer.push([1.].iter().copied());
+
+        let mut samples = [5.; 3];
+        buffer.pop(&mut samples);
+
+        assert_eq!(samples, [1., 0., 0.]);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::fft::Dft;
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{check_broadcastable, ArraySignal, ShapeError, Signal, SignalAccess};
+use ndarray::{ArrayViewD, Axis};
+use num_complex::Complex64;
+use std::sync::{Arc, OnceLock};
+
+/// Sets `target` to the circular convolution of `a` and `b` along the last
+/// axis, as used to bind semantic pointers. With `invert_a` or `invert_b`,
+/// the involution of that operand is used instead, which unbinds it.
+#[derive(Debug)]
+pub struct CircularConv {
+    pub target: Arc<ArraySignal<f64>>,
+    pub a: Arc<ArraySignal<f64>>,
+    pub b: Arc<ArraySignal<f64>>,
+    pub invert_a: bool,
+    pub invert_b: bool,
+    /// Created on the first step, as the dimensionality is fixed by the
+    /// signals.
+    pub plan: OnceLock<Dft<f64>>,
+}
+
+impl CircularConv {
+    /// Spectrum of each row of `x`.
+    fn spectra(plan: &Dft<f64>, x: &ArrayViewD<f64>, invert: bool) -> Vec<Vec<Complex64>> {
+        let axis = Axis(x.ndim().max(1) - 1);
+        x.lanes(axis)
+            .into_iter()
+            .map(|row| {
+                let mut spectrum: Vec<_> = row.iter().map(|&v| Complex64::new(v, 0.)).collect();
+                plan.forward(&mut spectrum);
+                if invert {
+                    spectrum.iter_mut().for_each(|v| *v = v.conj());
+                }
+                spectrum
+            })
+            .collect()
+    }
+}
+
+impl Operator for CircularConv {
+    fn step(&self) -> Result<(), OperatorError> {
+        let n = self.target.shape().last().copied().unwrap_or(1);
+        let plan = self.plan.get_or_init(|| Dft::new(n));
+        let a = self
+            .a
+            .read()
+            .with_view(|a| Self::spectra(plan, &a, self.invert_a));
+        let b = self
+            .b
+            .read()
+            .with_view(|b| Self::spectra(plan, &b, self.invert_b));
+        let mut target = self.target.write();
+        target.with_view_mut(|mut target| {
+            let axis = Axis(target.ndim().max(1) - 1);
+            for (i, mut row) in target.lanes_mut(axis).into_iter().enumerate() {
+                // One of the operands may be a single vector bound to each row.
+                let (a, b) = (&a[i % a.len()], &b[i % b.len()]);
+                let mut product: Vec<_> = a.iter().zip(b).map(|(a, b)| a * b).collect();
+                plan.inverse(&mut product);
+                for (t, p) in row.iter_mut().zip(&product) {
+                    *t = p.re;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.a, &*self.b]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        let target = self.target.shape();
+        for operand in [self.a.shape(), self.b.shape()].iter() {
+            if operand.last() != target.last() {
+                return Err(ShapeError {
+                    message: format!(
+                        "Operand of shape {:?} does not have the dimensionality of the \
+                         target of shape {:?}.",
+                        operand, target
+                    ),
+                });
+            }
+            check_broadcastable(operand, target)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    fn circular_conv(invert_b: bool) -> CircularConv {
+        CircularConv {
+            target: Arc::new(ArraySignal::from_array(
+                "target".to_string(),
+                Array::zeros(IxDyn(&[2, 3])),
+            )),
+            a: Arc::new(ArraySignal::from_array(
+                "a".to_string(),
+                array![[1., 2., 3.], [4., 5., 6.]].into_dyn(),
+            )),
+            b: Arc::new(ArraySignal::from_array(
+                "b".to_string(),
+                array![0., 1., 0.].into_dyn(),
+            )),
+            invert_a: false,
+            invert_b,
+            plan: OnceLock::new(),
+        }
+    }
+
+    fn assert_close(actual: ArrayD<f64>, expected: ArrayD<f64>) {
+        assert!(
+            actual
+                .iter()
+                .zip(expected.iter())
+                .all(|(a, e)| (a - e).abs() < 1e-12),
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn it_binds_each_row() {
+        let op = circular_conv(false);
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        assert_close(
+            op.target.read().clone_array(),
+            array![[3., 1., 2.], [6., 4., 5.]].into_dyn(),
+        );
+    }
+
+    #[test]
+    fn it_unbinds_with_the_involution() {
+        let op = circular_conv(true);
+
+        op.step().unwrap();
+
+        assert_close(
+            op.target.read().clone_array(),
+            array![[2., 3., 1.], [5., 6., 4.]].into_dyn(),
+        );
+    }
+}
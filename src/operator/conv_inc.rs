@@ -0,0 +1,678 @@
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{ArraySignal, ShapeError, Signal, SignalAccess};
+use num_complex::Complex;
+use num_traits::{Float, Zero};
+use numpy::Element;
+use rustfft::{FFTnum, FFTplanner, FFT};
+use std::fmt::{self, Debug};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// Layout of the flattened signals of a 2-D convolution. One-dimensional
+/// convolutions use a height of one.
+///
+/// With `groups`, the channels are split into groups that are convolved
+/// independently. Depthwise convolutions use as many groups as input channels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvGeometry {
+    pub input_size: (usize, usize),
+    pub output_size: (usize, usize),
+    pub kernel_size: (usize, usize),
+    pub strides: (usize, usize),
+    /// Implicit zero padding before the first row and column of the input.
+    pub padding: (usize, usize),
+    pub channels_in: usize,
+    pub channels_out: usize,
+    pub channels_last: bool,
+    pub groups: usize,
+    /// Computes the transpose of the convolution from the output to the input
+    /// size instead. `strides` and `padding` refer to that convolution.
+    pub transpose: bool,
+}
+
+impl ConvGeometry {
+    pub fn input_len(&self) -> usize {
+        self.input_size.0 * self.input_size.1 * self.channels_in
+    }
+
+    pub fn output_len(&self) -> usize {
+        self.output_size.0 * self.output_size.1 * self.channels_out
+    }
+
+    pub fn kernel_len(&self) -> usize {
+        self.kernel_size.0 * self.kernel_size.1 * self.channels_in * self.channels_out / self.groups
+    }
+
+    fn group_inputs(&self, co: usize) -> Range<usize> {
+        let (n_in, n_out) = (
+            self.channels_in / self.groups,
+            self.channels_out / self.groups,
+        );
+        let group = co / n_out;
+        group * n_in..(group + 1) * n_in
+    }
+
+    fn group_outputs(&self, ci: usize) -> Range<usize> {
+        let (n_in, n_out) = (
+            self.channels_in / self.groups,
+            self.channels_out / self.groups,
+        );
+        let group = ci / n_in;
+        group * n_out..(group + 1) * n_out
+    }
+
+    fn index(&self, size: (usize, usize), channels: usize, y: usize, x: usize, c: usize) -> usize {
+        if self.channels_last {
+            (y * size.1 + x) * channels + c
+        } else {
+            (c * size.0 + y) * size.1 + x
+        }
+    }
+
+    /// Index of the weight between input channel `ci` and output channel `co`
+    /// of the same group. Kernels are laid out as in nengo: rows, columns,
+    /// channels per group of the smaller side, and all channels of the larger
+    /// side (the output, unless transposed).
+    fn kernel_index(&self, ky: usize, kx: usize, ci: usize, co: usize) -> usize {
+        let position = ky * self.kernel_size.1 + kx;
+        if self.transpose {
+            let per_group = self.channels_out / self.groups;
+            (position * per_group + co % per_group) * self.channels_in + ci
+        } else {
+            let per_group = self.channels_in / self.groups;
+            (position * per_group + ci % per_group) * self.channels_out + co
+        }
+    }
+
+    fn fft_shape(&self) -> (usize, usize) {
+        (
+            (self.input_size.0 + self.kernel_size.0 - 1).next_power_of_two(),
+            (self.input_size.1 + self.kernel_size.1 - 1).next_power_of_two(),
+        )
+    }
+
+    /// Whether an FFT is estimated to take fewer operations than direct
+    /// convolution, which is the case for large kernels.
+    pub fn prefers_fft(&self) -> bool {
+        if self.transpose {
+            return false;
+        }
+        let direct = self.output_size.0 * self.output_size.1 * self.kernel_len();
+        let (n0, n1) = self.fft_shape();
+        let n = n0 * n1;
+        let transforms = n * n.trailing_zeros() as usize * (self.channels_in + self.channels_out);
+        transforms + n * self.channels_in * self.channels_out / self.groups < direct
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConvMethod {
+    /// Uses an FFT where `ConvGeometry::prefers_fft` estimates it to be faster.
+    Auto,
+    Direct,
+    /// Uses an FFT unless the convolution is transposed.
+    Fft,
+}
+
+/// Increments `target` by the convolution of `input` with `kernel`. Like in
+/// nengo, the kernel is not flipped. `input` and `target` may have a leading
+/// trial axis.
+#[derive(Debug)]
+pub struct ConvInc<T>
+where
+    T: Element,
+{
+    pub target: Arc<ArraySignal<T>>,
+    pub kernel: Arc<ArraySignal<T>>,
+    pub input: Arc<ArraySignal<T>>,
+    pub geometry: ConvGeometry,
+    pub method: ConvMethod,
+    pub plan: FftPlan<T>,
+}
+
+impl<T> ConvInc<T>
+where
+    T: Element,
+{
+    pub fn uses_fft(&self) -> bool {
+        match self.method {
+            ConvMethod::Auto => self.geometry.prefers_fft(),
+            ConvMethod::Direct => false,
+            ConvMethod::Fft => !self.geometry.transpose,
+        }
+    }
+}
+
+impl<T> Operator for ConvInc<T>
+where
+    T: Element + Float + FFTnum + Debug,
+{
+    fn step(&self) -> Result<(), OperatorError> {
+        let kernel = self.kernel.read();
+        let input = self.input.read();
+        let product = kernel.with_view(|kernel| {
+            input.with_view(|input| {
+                let kernel = kernel.as_standard_layout();
+                let input = input.as_standard_layout();
+                let (kernel, input) = (kernel.as_slice().unwrap(), input.as_slice().unwrap());
+                if self.uses_fft() {
+                    self.plan
+                        .convolve(&self.geometry, self.kernel.generation(), kernel, input)
+                } else {
+                    convolve_direct(&self.geometry, kernel, input)
+                }
+            })
+        });
+        let mut target = self.target.write();
+        target.with_view_mut(|mut target| {
+            for (t, p) in target.iter_mut().zip(product) {
+                *t = *t + p;
+            }
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.kernel, &*self.input]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn increments(&self) -> bool {
+        true
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        let geometry = &self.geometry;
+        let (input, target) = (self.input.shape(), self.target.shape());
+        let kernel_len: usize = self.kernel.shape().iter().product();
+        let splits = |channels: usize| channels.checked_rem(geometry.groups) == Some(0);
+        if !splits(geometry.channels_in) || !splits(geometry.channels_out) {
+            return Err(ShapeError {
+                message: format!(
+                    "Channels must split into {} groups in convolution {:?}.",
+                    geometry.groups, geometry
+                ),
+            });
+        }
+        if geometry.input_len() == 0 || geometry.output_len() == 0 || geometry.kernel_len() == 0 {
+            return Err(ShapeError {
+                message: format!("Convolution {:?} must not be empty.", geometry),
+            });
+        }
+        if kernel_len != geometry.kernel_len() {
+            return Err(ShapeError {
+                message: format!(
+                    "Kernel of shape {:?} does not have the {} weights of the convolution.",
+                    self.kernel.shape(),
+                    geometry.kernel_len()
+                ),
+            });
+        }
+        if input.len() > 2
+            || input.len() != target.len()
+            || input[..input.len() - 1] != target[..target.len() - 1]
+            || input.last() != Some(&geometry.input_len())
+            || target.last() != Some(&geometry.output_len())
+        {
+            return Err(ShapeError {
+                message: format!(
+                    "Input of shape {:?} and target of shape {:?} do not fit a convolution \
+                     of {} inputs to {} outputs.",
+                    input,
+                    target,
+                    geometry.input_len(),
+                    geometry.output_len()
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn convolve_direct<T: Float>(geometry: &ConvGeometry, kernel: &[T], input: &[T]) -> Vec<T> {
+    let g = geometry;
+    // A transposed convolution scatters each input position onto the output
+    // positions a forward convolution would gather it from.
+    let (outer, inner) = if g.transpose {
+        (g.input_size, g.output_size)
+    } else {
+        (g.output_size, g.input_size)
+    };
+    let mut output = vec![T::zero(); input.len() / g.input_len() * g.output_len()];
+    for (input, output) in input
+        .chunks(g.input_len())
+        .zip(output.chunks_mut(g.output_len()))
+    {
+        for py in 0..outer.0 {
+            for px in 0..outer.1 {
+                for ky in 0..g.kernel_size.0 {
+                    let qy = py * g.strides.0 + ky;
+                    if qy < g.padding.0 || qy - g.padding.0 >= inner.0 {
+                        continue;
+                    }
+                    for kx in 0..g.kernel_size.1 {
+                        let qx = px * g.strides.1 + kx;
+                        if qx < g.padding.1 || qx - g.padding.1 >= inner.1 {
+                            continue;
+                        }
+                        let (q, p) = ((qy - g.padding.0, qx - g.padding.1), (py, px));
+                        let ((iy, ix), (oy, ox)) = if g.transpose { (p, q) } else { (q, p) };
+                        for ci in 0..g.channels_in {
+                            let value = input[g.index(g.input_size, g.channels_in, iy, ix, ci)];
+                            for co in g.group_outputs(ci) {
+                                let i = g.index(g.output_size, g.channels_out, oy, ox, co);
+                                output[i] =
+                                    output[i] + value * kernel[g.kernel_index(ky, kx, ci, co)];
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    output
+}
+
+/// FFT plans and kernel spectra of a convolution, kept across steps. The
+/// kernel spectra are recomputed when the kernel signal was written.
+#[derive(Debug)]
+pub struct FftPlan<T> {
+    cache: Mutex<Option<PlanCache<T>>>,
+}
+
+impl<T> Default for FftPlan<T> {
+    fn default() -> Self {
+        FftPlan {
+            cache: Mutex::new(None),
+        }
+    }
+}
+
+struct PlanCache<T> {
+    shape: (usize, usize),
+    /// Transforms of the columns and rows, forward and inverse.
+    forward: (Arc<dyn FFT<T>>, Arc<dyn FFT<T>>),
+    inverse: (Arc<dyn FFT<T>>, Arc<dyn FFT<T>>),
+    kernel_generation: Option<u64>,
+    /// Spectrum of the flipped kernel for each pair of input and output channel
+    /// in the same group.
+    kernel_spectra: Vec<Vec<Complex<T>>>,
+}
+
+impl<T> Debug for PlanCache<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PlanCache")
+            .field("shape", &self.shape)
+            .field("kernel_generation", &self.kernel_generation)
+            .finish()
+    }
+}
+
+impl<T: Float + FFTnum> PlanCache<T> {
+    fn new(shape: (usize, usize)) -> Self {
+        let plan = |inverse| {
+            let mut planner = FFTplanner::new(inverse);
+            (planner.plan_fft(shape.0), planner.plan_fft(shape.1))
+        };
+        PlanCache {
+            shape,
+            forward: plan(false),
+            inverse: plan(true),
+            kernel_generation: None,
+            kernel_spectra: vec![],
+        }
+    }
+
+    fn update_kernel(&mut self, geometry: &ConvGeometry, generation: u64, kernel: &[T]) {
+        if self.kernel_generation == Some(generation) {
+            return;
+        }
+        let (kh, kw) = geometry.kernel_size;
+        let mut spectra = vec![vec![]; geometry.channels_in * geometry.channels_out];
+        for ci in 0..geometry.channels_in {
+            for co in geometry.group_outputs(ci) {
+                let mut plane = vec![Complex::zero(); self.shape.0 * self.shape.1];
+                for ky in 0..kh {
+                    for kx in 0..kw {
+                        plane[(kh - 1 - ky) * self.shape.1 + (kw - 1 - kx)] =
+                            Complex::new(kernel[geometry.kernel_index(ky, kx, ci, co)], T::zero());
+                    }
+                }
+                self.fft_2d(&mut plane);
+                spectra[ci * geometry.channels_out + co] = plane;
+            }
+        }
+        self.kernel_spectra = spectra;
+        self.kernel_generation = Some(generation);
+    }
+
+    fn transform_2d(data: &mut [Complex<T>], (columns, rows): &(Arc<dyn FFT<T>>, Arc<dyn FFT<T>>)) {
+        let (n0, n1) = (columns.len(), rows.len());
+        let mut scratch = data.to_vec();
+        rows.process_multi(&mut scratch, data);
+        let (mut column, mut spectrum) = (vec![Complex::zero(); n0], vec![Complex::zero(); n0]);
+        for x in 0..n1 {
+            for (y, c) in column.iter_mut().enumerate() {
+                *c = data[y * n1 + x];
+            }
+            columns.process(&mut column, &mut spectrum);
+            for (y, c) in spectrum.iter().enumerate() {
+                data[y * n1 + x] = *c;
+            }
+        }
+    }
+
+    fn fft_2d(&self, data: &mut [Complex<T>]) {
+        Self::transform_2d(data, &self.forward);
+    }
+
+    fn ifft_2d(&self, data: &mut [Complex<T>]) {
+        Self::transform_2d(data, &self.inverse);
+        let scale = T::from(data.len()).unwrap();
+        data.iter_mut().for_each(|v| *v = *v / scale);
+    }
+}
+
+impl<T: Float + FFTnum> FftPlan<T> {
+    fn convolve(
+        &self,
+        geometry: &ConvGeometry,
+        generation: u64,
+        kernel: &[T],
+        input: &[T],
+    ) -> Vec<T> {
+        let g = geometry;
+        let mut cache = self.cache.lock().unwrap();
+        let shape = g.fft_shape();
+        if cache.as_ref().map(|c| c.shape) != Some(shape) {
+            *cache = Some(PlanCache::new(shape));
+        }
+        let plan = cache.as_mut().unwrap();
+        plan.update_kernel(g, generation, kernel);
+
+        let (n0, n1) = shape;
+        let (kh, kw) = g.kernel_size;
+        let mut output = vec![T::zero(); input.len() / g.input_len() * g.output_len()];
+        for (input, output) in input
+            .chunks(g.input_len())
+            .zip(output.chunks_mut(g.output_len()))
+        {
+            let input_spectra: Vec<Vec<Complex<T>>> = (0..g.channels_in)
+                .map(|ci| {
+                    let mut plane = vec![Complex::zero(); n0 * n1];
+                    for y in 0..g.input_size.0 {
+                        for x in 0..g.input_size.1 {
+                            plane[y * n1 + x] = Complex::new(
+                                input[g.index(g.input_size, g.channels_in, y, x, ci)],
+                                T::zero(),
+                            );
+                        }
+                    }
+                    plan.fft_2d(&mut plane);
+                    plane
+                })
+                .collect();
+            for co in 0..g.channels_out {
+                let mut product = vec![Complex::zero(); n0 * n1];
+                for ci in g.group_inputs(co) {
+                    let spectrum = &input_spectra[ci];
+                    let kernel = &plan.kernel_spectra[ci * g.channels_out + co];
+                    for (p, (x, k)) in product.iter_mut().zip(spectrum.iter().zip(kernel)) {
+                        *p = *p + x * k;
+                    }
+                }
+                plan.ifft_2d(&mut product);
+                // The linear convolution with the flipped kernel is shifted by
+                // the kernel size relative to the correlation with the padded
+                // input.
+                for oy in 0..g.output_size.0 {
+                    let y = oy * g.strides.0 + kh - 1;
+                    if y < g.padding.0 || y - g.padding.0 >= g.input_size.0 + kh - 1 {
+                        continue;
+                    }
+                    for ox in 0..g.output_size.1 {
+                        let x = ox * g.strides.1 + kw - 1;
+                        if x < g.padding.1 || x - g.padding.1 >= g.input_size.1 + kw - 1 {
+                            continue;
+                        }
+                        output[g.index(g.output_size, g.channels_out, oy, ox, co)] =
+                            product[(y - g.padding.0) * n1 + x - g.padding.1].re;
+                    }
+                }
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    fn signal(name: &str, values: Vec<f64>) -> Arc<ArraySignal<f64>> {
+        Arc::new(ArraySignal::from_array(
+            name.to_string(),
+            Array::from(values).into_dyn(),
+        ))
+    }
+
+    fn geometry() -> ConvGeometry {
+        ConvGeometry {
+            input_size: (5, 4),
+            output_size: (3, 4),
+            kernel_size: (3, 3),
+            strides: (2, 1),
+            padding: (1, 1),
+            channels_in: 2,
+            channels_out: 3,
+            channels_last: false,
+            groups: 1,
+            transpose: false,
+        }
+    }
+
+    fn conv_inc(geometry: ConvGeometry, method: ConvMethod) -> ConvInc<f64> {
+        let input = (0..geometry.input_len())
+            .map(|i| ((i * 7) % 11) as f64 - 5.)
+            .collect();
+        let kernel = (0..geometry.kernel_len())
+            .map(|i| ((i * 5) % 13) as f64 / 13.)
+            .collect();
+        ConvInc {
+            target: signal("target", vec![0.; geometry.output_len()]),
+            kernel: signal("kernel", kernel),
+            input: signal("input", input),
+            geometry,
+            method,
+            plan: FftPlan::default(),
+        }
+    }
+
+    fn assert_all_close(actual: &ArrayD<f64>, expected: &ArrayD<f64>) {
+        assert_eq!(actual.shape(), expected.shape());
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-9, "{} != {}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn it_convolves_without_flipping_the_kernel() {
+        let op = ConvInc {
+            target: signal("target", vec![1.; 4]),
+            kernel: signal("kernel", vec![1., 0., 0., 2.]),
+            input: signal("input", (1..10).map(f64::from).collect()),
+            geometry: ConvGeometry {
+                input_size: (3, 3),
+                output_size: (2, 2),
+                kernel_size: (2, 2),
+                strides: (1, 1),
+                padding: (0, 0),
+                channels_in: 1,
+                channels_out: 1,
+                channels_last: true,
+                groups: 1,
+                transpose: false,
+            },
+            method: ConvMethod::Direct,
+            plan: FftPlan::default(),
+        };
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        assert_eq!(**op.target.read(), array![12., 15., 21., 24.].into_dyn());
+    }
+
+    #[test]
+    fn it_computes_the_same_convolution_with_an_fft() {
+        let direct = conv_inc(geometry(), ConvMethod::Direct);
+        let fft = conv_inc(geometry(), ConvMethod::Fft);
+        direct.check_shapes().unwrap();
+
+        direct.step().unwrap();
+        fft.step().unwrap();
+
+        assert_all_close(
+            &fft.target.read().clone_array(),
+            &direct.target.read().clone_array(),
+        );
+    }
+
+    #[test]
+    fn it_updates_the_cached_kernel_spectra_when_the_kernel_changes() {
+        let geometry = ConvGeometry {
+            channels_last: true,
+            ..geometry()
+        };
+        let direct = conv_inc(geometry.clone(), ConvMethod::Direct);
+        let fft = conv_inc(geometry, ConvMethod::Fft);
+        fft.step().unwrap();
+
+        for op in [&direct, &fft].iter() {
+            op.kernel.write().assign_array(&Array::from_elem(54, 0.5));
+            op.target.reset();
+            op.step().unwrap();
+        }
+
+        assert_all_close(
+            &fft.target.read().clone_array(),
+            &direct.target.read().clone_array(),
+        );
+    }
+
+    #[test]
+    fn it_prefers_an_fft_for_large_kernels() {
+        let small = ConvGeometry {
+            input_size: (32, 32),
+            output_size: (32, 32),
+            kernel_size: (3, 3),
+            strides: (1, 1),
+            padding: (1, 1),
+            channels_in: 1,
+            channels_out: 1,
+            channels_last: true,
+            groups: 1,
+            transpose: false,
+        };
+        let large = ConvGeometry {
+            kernel_size: (9, 9),
+            padding: (4, 4),
+            channels_in: 8,
+            channels_out: 8,
+            ..small.clone()
+        };
+
+        assert!(!small.prefers_fft());
+        assert!(large.prefers_fft());
+    }
+
+    #[test]
+    fn it_convolves_groups_independently() {
+        let grouped = conv_inc(
+            ConvGeometry {
+                channels_in: 4,
+                channels_out: 2,
+                groups: 2,
+                ..geometry()
+            },
+            ConvMethod::Direct,
+        );
+        let fft = conv_inc(grouped.geometry.clone(), ConvMethod::Fft);
+        grouped.check_shapes().unwrap();
+        grouped.step().unwrap();
+        fft.step().unwrap();
+
+        // Each output channel only sees the two input channels of its group.
+        let input_len = 20 * 2;
+        let input = grouped.input.read().clone_array();
+        let kernel = grouped.kernel.read().clone_array();
+        for group in 0..2 {
+            let single = conv_inc(
+                ConvGeometry {
+                    channels_in: 2,
+                    channels_out: 1,
+                    ..geometry()
+                },
+                ConvMethod::Direct,
+            );
+            single
+                .input
+                .write()
+                .assign_array(&input.slice(s![group * input_len..(group + 1) * input_len]));
+            let weights: Vec<f64> = (0..18).map(|i| kernel[[i * 2 + group]]).collect();
+            single.kernel.write().assign_array(&Array::from(weights));
+            single.step().unwrap();
+
+            assert_all_close(
+                &grouped
+                    .target
+                    .read()
+                    .clone_array()
+                    .slice(s![group * 12..(group + 1) * 12])
+                    .to_owned()
+                    .into_dyn(),
+                &single.target.read().clone_array(),
+            );
+        }
+        assert_all_close(
+            &fft.target.read().clone_array(),
+            &grouped.target.read().clone_array(),
+        );
+    }
+
+    #[test]
+    fn it_computes_the_transposed_convolution() {
+        let forward = conv_inc(geometry(), ConvMethod::Direct);
+        let transposed = ConvInc {
+            target: signal("target", vec![0.; 40]),
+            kernel: Arc::clone(&forward.kernel),
+            input: signal("input", vec![0.; 36]),
+            geometry: ConvGeometry {
+                input_size: (3, 4),
+                output_size: (5, 4),
+                channels_in: 3,
+                channels_out: 2,
+                transpose: true,
+                ..geometry()
+            },
+            method: ConvMethod::Auto,
+            plan: FftPlan::default(),
+        };
+        transposed.check_shapes().unwrap();
+
+        // <A x, y> = <x, A^T y> for the forward convolution A.
+        let x = forward.input.read().clone_array();
+        let y = Array::from((0..36).map(|i| (i % 5) as f64).collect::<Vec<_>>()).into_dyn();
+        transposed.input.write().assign_array(&y);
+        forward.step().unwrap();
+        transposed.step().unwrap();
+        let ax: f64 = (&forward.target.read().clone_array() * &y).sum();
+        let at_y: f64 = (&transposed.target.read().clone_array() * &x).sum();
+
+        assert!((ax - at_y).abs() < 1e-9, "{} != {}", ax, at_y);
+    }
+}
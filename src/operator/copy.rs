@@ -1,5 +1,7 @@
-use crate::operator::Operator;
-use crate::signal::{ArraySignal, ScalarSignal, SignalAccess};
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{
+    check_broadcastable, ArraySignal, ScalarSignal, ShapeError, Signal, SignalAccess,
+};
 use ndarray::ArrayD;
 use numpy::Element;
 use std::fmt::Debug;
@@ -15,30 +17,63 @@ pub struct CopyOp<T, S> {
     pub data_type: PhantomData<T>,
 }
 
-impl<T: Element + Debug + AddAssign<T>> Operator for CopyOp<ArrayD<T>, ArraySignal<T>> {
-    fn step(&self) {
+impl<T: Element + Debug + Send + Sync + AddAssign<T> + 'static> Operator
+    for CopyOp<ArrayD<T>, ArraySignal<T>>
+{
+    fn step(&self) -> Result<(), OperatorError> {
         if self.inc {
             **self.dst.write() += &**self.src.read();
         } else {
             self.dst.write().assign(&self.src.read());
         }
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.src]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.dst]
+    }
+
+    fn increments(&self) -> bool {
+        self.inc
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        check_broadcastable(self.src.shape(), self.dst.shape())
     }
 }
 
-impl<T: Copy + Debug + AddAssign<T>> Operator for CopyOp<T, ScalarSignal<T>> {
-    fn step(&self) {
+impl<T: Copy + Debug + Send + Sync + AddAssign<T> + 'static> Operator
+    for CopyOp<T, ScalarSignal<T>>
+{
+    fn step(&self) -> Result<(), OperatorError> {
         if self.inc {
             **self.dst.write() += **self.src.read();
         } else {
             **self.dst.write() = **self.src.read();
         }
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.src]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.dst]
+    }
+
+    fn increments(&self) -> bool {
+        self.inc
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::signal::Signal;
     use crate::venv::activate_venv;
     use ndarray::prelude::*;
     use numpy::IntoPyArray;
@@ -55,7 +90,7 @@ mod tests {
         op.src.reset();
         op.dst.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(**op.src.read(), **op.dst.read());
     }
@@ -80,7 +115,7 @@ mod tests {
         op.src.reset();
         op.dst.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(**op.src.read(), **op.dst.read());
     }
@@ -96,7 +131,7 @@ mod tests {
         op.src.reset();
         op.dst.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(**op.dst.read(), 43);
     }
@@ -121,7 +156,7 @@ mod tests {
         op.src.reset();
         op.dst.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(**op.dst.read(), array![43, 43].into_dyn());
     }
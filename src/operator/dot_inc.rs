@@ -1,7 +1,7 @@
-use crate::operator::Operator;
-use crate::signal::{ArraySignal, SignalAccess};
+use crate::operator::{ErrorCause, Operator, OperatorError};
+use crate::signal::{dot_shape, ArraySignal, ShapeError, Signal, SignalAccess};
 use core::ops::AddAssign;
-use ndarray::LinalgScalar;
+use ndarray::{arr1, ArrayD, ArrayViewD, Axis, Ix1, Ix2, LinalgScalar};
 use numpy::Element;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -14,24 +14,115 @@ where
     pub target: Arc<ArraySignal<T>>,
     pub left: Arc<ArraySignal<T>>,
     pub right: Arc<ArraySignal<T>>,
+    /// Multiplies with the transpose of `left` without copying it.
+    pub transpose_left: bool,
+}
+
+impl<T> DotInc<T>
+where
+    T: Element + Debug + Send + Sync + 'static,
+{
+    fn left_shape(&self) -> Vec<usize> {
+        let mut shape = self.left.shape().to_vec();
+        if self.transpose_left {
+            shape.reverse();
+        }
+        shape
+    }
+}
+
+/// Product of the transpose of `lhs` with `rhs`, where `rhs` may have a
+/// leading trial axis.
+fn dot_transposed<T: LinalgScalar>(lhs: ArrayViewD<T>, rhs: ArrayViewD<T>) -> ArrayD<T> {
+    if lhs.ndim() == 1 {
+        let lhs = lhs.into_dimensionality::<Ix1>().unwrap();
+        return match rhs.ndim() {
+            1 => arr1(&[lhs.dot(&rhs.into_dimensionality::<Ix1>().unwrap())]).into_dyn(),
+            _ => rhs
+                .into_dimensionality::<Ix2>()
+                .unwrap()
+                .dot(&lhs)
+                .insert_axis(Axis(1))
+                .into_dyn(),
+        };
+    }
+    let lhs = lhs.into_dimensionality::<Ix2>().unwrap();
+    match rhs.ndim() {
+        1 => lhs
+            .t()
+            .dot(&rhs.into_dimensionality::<Ix1>().unwrap())
+            .into_dyn(),
+        _ => rhs
+            .into_dimensionality::<Ix2>()
+            .unwrap()
+            .dot(&lhs)
+            .into_dyn(),
+    }
 }
 
 impl<T> Operator for DotInc<T>
 where
-    T: Element + AddAssign<T> + LinalgScalar + Debug,
+    T: Element + AddAssign<T> + LinalgScalar + Debug + Send + Sync,
 {
-    fn step(&self) {
+    fn step(&self) -> Result<(), OperatorError> {
         let left = self.left.read();
         let right = self.right.read();
+        let product = if self.transpose_left {
+            let right_shape = right.with_view(|right| right.shape().to_vec());
+            dot_shape(&self.left_shape(), &right_shape)
+                .map_err(|e| OperatorError::new(self, ErrorCause::Shape(e.message)))?;
+            left.with_view(|left| right.with_view(|right| dot_transposed(left, right)))
+        } else {
+            (**left)
+                .dot(&**right)
+                .map_err(|e| OperatorError::new(self, ErrorCause::Shape(e.message)))?
+        };
         let mut target = self.target.write();
-        **target += &(**left).dot(&**right);
+        let target_shape = target.with_view(|target| target.shape().to_vec());
+        if product.shape() != &target_shape[..] {
+            return Err(OperatorError::new(
+                self,
+                ErrorCause::Shape(format!(
+                    "Cannot increment target of shape {:?} by a product of shape {:?}.",
+                    target_shape,
+                    product.shape()
+                )),
+            ));
+        }
+        **target += &product;
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.left, &*self.right]
+    }
+
+    fn increments(&self) -> bool {
+        true
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        let product_shape = dot_shape(&self.left_shape(), self.right.shape())?;
+        if product_shape != self.target.shape() {
+            return Err(ShapeError {
+                message: format!(
+                    "Cannot increment target of shape {:?} by a product of shape {:?}.",
+                    self.target.shape(),
+                    product_shape
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::signal::Signal;
     use crate::venv::activate_venv;
     use ndarray::prelude::*;
     use numpy::IntoPyArray;
@@ -56,17 +147,49 @@ mod test {
                 "right".to_string(),
                 array![6, 7].into_dyn().into_pyarray(py),
             )),
+            transpose_left: false,
         };
         for signal in vec![&op.target, &op.left, &op.right].iter() {
             signal.reset();
         }
 
-        op.step();
+        op.step()?;
 
         assert_eq!(**op.target.read(), array![34].into_dyn());
         Ok(())
     }
 
+    #[test]
+    fn it_fails_for_mismatched_shapes() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let op = DotInc::<u64> {
+            target: Arc::new(ArraySignal::new(
+                "target".to_string(),
+                Array::ones(IxDyn(&[2])).into_pyarray(py),
+            )),
+            left: Arc::new(ArraySignal::new(
+                "left".to_string(),
+                array![[2, 3], [4, 5]].into_dyn().into_pyarray(py),
+            )),
+            right: Arc::new(ArraySignal::new(
+                "right".to_string(),
+                array![6, 7, 8].into_dyn().into_pyarray(py),
+            )),
+            transpose_left: false,
+        };
+        assert!(op.check_shapes().is_err());
+        for signal in [&op.target, &op.left, &op.right].iter() {
+            signal.reset();
+        }
+
+        let err = op.step().unwrap_err();
+
+        assert_eq!(err.operator, "DotInc<u64>");
+        assert_eq!(**op.target.read(), array![1, 1].into_dyn());
+    }
+
     #[test]
     fn it_performs_a_matrix_vector_product() -> Result<(), Box<dyn Error>> {
         let gil = Python::acquire_gil();
@@ -85,14 +208,120 @@ mod test {
                 "right".to_string(),
                 array![6, 7].into_dyn().into_pyarray(py),
             )),
+            transpose_left: false,
         };
         for signal in vec![&op.target, &op.left, &op.right].iter() {
             signal.reset();
         }
 
-        op.step();
+        op.step()?;
 
         assert_eq!(**op.target.read(), array![34, 60].into_dyn());
         Ok(())
     }
+
+    #[test]
+    fn it_performs_a_batched_matrix_vector_product() -> Result<(), Box<dyn Error>> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let op = DotInc::<u64> {
+            target: Arc::new(ArraySignal::new(
+                "target".to_string(),
+                Array::ones(IxDyn(&[3, 2])).into_pyarray(py),
+            )),
+            left: Arc::new(ArraySignal::new(
+                "left".to_string(),
+                array![[2, 3], [4, 5]].into_dyn().into_pyarray(py),
+            )),
+            right: Arc::new(ArraySignal::new(
+                "right".to_string(),
+                array![[6, 7], [1, 0], [0, 1]].into_dyn().into_pyarray(py),
+            )),
+            transpose_left: false,
+        };
+        for signal in [&op.target, &op.left, &op.right].iter() {
+            signal.reset();
+        }
+
+        op.step()?;
+
+        assert_eq!(
+            **op.target.read(),
+            array![[34, 60], [3, 5], [4, 6]].into_dyn()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_multiplies_with_the_transposed_left_operand() {
+        let op = DotInc::<u64> {
+            target: Arc::new(ArraySignal::from_array(
+                "target".to_string(),
+                Array::ones(IxDyn(&[3])),
+            )),
+            left: Arc::new(ArraySignal::from_array(
+                "left".to_string(),
+                array![[1, 2, 3], [4, 5, 6]].into_dyn(),
+            )),
+            right: Arc::new(ArraySignal::from_array(
+                "right".to_string(),
+                array![1, 2].into_dyn(),
+            )),
+            transpose_left: true,
+        };
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        assert_eq!(**op.target.read(), array![10, 13, 16].into_dyn());
+    }
+
+    #[test]
+    fn it_multiplies_batches_with_the_transposed_left_operand() {
+        let op = DotInc::<u64> {
+            target: Arc::new(ArraySignal::from_array(
+                "target".to_string(),
+                Array::zeros(IxDyn(&[2, 3])),
+            )),
+            left: Arc::new(ArraySignal::from_array(
+                "left".to_string(),
+                array![[1, 2, 3], [4, 5, 6]].into_dyn(),
+            )),
+            right: Arc::new(ArraySignal::from_array(
+                "right".to_string(),
+                array![[1, 0], [0, 1]].into_dyn(),
+            )),
+            transpose_left: true,
+        };
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        assert_eq!(**op.target.read(), array![[1, 2, 3], [4, 5, 6]].into_dyn());
+    }
+
+    #[test]
+    fn it_multiplies_single_precision_floats() {
+        let op = DotInc::<f32> {
+            target: Arc::new(ArraySignal::from_array(
+                "target".to_string(),
+                array![1f32, 1.].into_dyn(),
+            )),
+            left: Arc::new(ArraySignal::from_array(
+                "left".to_string(),
+                array![[1f32, 2.], [3., 4.]].into_dyn(),
+            )),
+            right: Arc::new(ArraySignal::from_array(
+                "right".to_string(),
+                array![0.5f32, 1.].into_dyn(),
+            )),
+            transpose_left: false,
+        };
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        assert_eq!(**op.target.read(), array![3.5f32, 6.5].into_dyn());
+    }
 }
@@ -1,11 +1,43 @@
-use crate::operator::Operator;
-use crate::signal::{ArraySignal, SignalAccess};
+use crate::operator::{ErrorCause, Operator, OperatorError};
+use crate::signal::{
+    check_broadcastable, mul_shape, ArraySignal, ShapeError, Signal, SignalAccess,
+};
 use core::ops::{AddAssign, Mul};
 use ndarray::ScalarOperand;
-use numpy::Element;
+use numpy::{c64, Element};
 use std::fmt::Debug;
 use std::sync::Arc;
 
+/// Arithmetic that fails instead of wrapping around on overflow. Floating
+/// point values never fail, as they overflow to infinity.
+pub trait CheckedArithmetic: Sized {
+    /// `self + a * b`, or `None` if it overflows.
+    fn checked_mul_add(self, a: Self, b: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_integer {
+    ($($t:ty),*) => {
+        $(impl CheckedArithmetic for $t {
+            fn checked_mul_add(self, a: Self, b: Self) -> Option<Self> {
+                a.checked_mul(b).and_then(|p| self.checked_add(p))
+            }
+        })*
+    };
+}
+
+macro_rules! impl_unchecked {
+    ($($t:ty),*) => {
+        $(impl CheckedArithmetic for $t {
+            fn checked_mul_add(self, a: Self, b: Self) -> Option<Self> {
+                Some(self + a * b)
+            }
+        })*
+    };
+}
+
+impl_checked_integer!(i8, i16, i32, i64, u8, u16, u32, u64);
+impl_unchecked!(f32, f64, c64);
+
 #[derive(Debug)]
 pub struct ElementwiseInc<T>
 where
@@ -14,27 +46,120 @@ where
     pub target: Arc<ArraySignal<T>>,
     pub left: Arc<ArraySignal<T>>,
     pub right: Arc<ArraySignal<T>>,
+    /// Fails with an overflow error instead of wrapping around, leaving the
+    /// target unchanged. Meant for integer signals such as counters.
+    pub checked: bool,
+}
+
+impl<T> ElementwiseInc<T>
+where
+    T: Element
+        + Copy
+        + Debug
+        + Send
+        + Sync
+        + Mul<T, Output = T>
+        + AddAssign<T>
+        + ScalarOperand
+        + CheckedArithmetic,
+{
+    fn step_checked(&self) -> Result<(), OperatorError> {
+        let left = self.left.read();
+        let right = self.right.read();
+        let mut target = self.target.write();
+        left.with_view(|left| {
+            right.with_view(|right| {
+                target.with_view_mut(|mut target| {
+                    let shape = target.shape().to_vec();
+                    let (left, right) =
+                        match (left.broadcast(&shape[..]), right.broadcast(&shape[..])) {
+                            (Some(left), Some(right)) => (left, right),
+                            _ => {
+                                return Err(OperatorError::new(
+                                    self,
+                                    ErrorCause::Shape(format!(
+                                        "Cannot increment target of shape {:?} by a product of \
+                                     shapes {:?} and {:?}.",
+                                        shape,
+                                        left.shape(),
+                                        right.shape()
+                                    )),
+                                ))
+                            }
+                        };
+                    let updated = target
+                        .iter()
+                        .zip(left.iter().zip(right.iter()))
+                        .map(|(&y, (&a, &b))| y.checked_mul_add(a, b))
+                        .collect::<Option<Vec<T>>>()
+                        .ok_or_else(|| {
+                            OperatorError::new(
+                                self,
+                                ErrorCause::Overflow(format!(
+                                    "Incrementing `{}` overflowed.",
+                                    self.target.name()
+                                )),
+                            )
+                        })?;
+                    for (y, value) in target.iter_mut().zip(updated) {
+                        *y = value;
+                    }
+                    Ok(())
+                })
+            })
+        })
+    }
 }
 
 impl<T> Operator for ElementwiseInc<T>
 where
-    T: Element + Copy + Debug + Mul<T, Output = T> + AddAssign<T> + ScalarOperand,
+    T: Element
+        + Copy
+        + Debug
+        + Send
+        + Sync
+        + Mul<T, Output = T>
+        + AddAssign<T>
+        + ScalarOperand
+        + CheckedArithmetic,
 {
-    fn step(&self) {
+    fn step(&self) -> Result<(), OperatorError> {
+        if self.checked {
+            return self.step_checked();
+        }
         let left = self.left.read();
         let right = self.right.read();
         let mut target = self.target.write();
         **target += &(&**left * &**right);
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.left, &*self.right]
+    }
+
+    fn increments(&self) -> bool {
+        true
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        check_broadcastable(
+            &mul_shape(self.left.shape(), self.right.shape())?,
+            self.target.shape(),
+        )
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::signal::Signal;
     use crate::venv::activate_venv;
     use ndarray::prelude::*;
-    use numpy::IntoPyArray;
+    use numpy::{c64, IntoPyArray};
     use pyo3::Python;
     use std::error::Error;
 
@@ -56,12 +181,13 @@ mod test {
                 "right".to_string(),
                 array![4, 5].into_dyn().into_pyarray(py),
             )),
+            checked: false,
         };
         for signal in vec![&op.target, &op.left, &op.right].iter() {
             signal.reset();
         }
 
-        op.step();
+        op.step()?;
 
         assert_eq!(
             **op.target.read(),
@@ -88,12 +214,13 @@ mod test {
                 "right".to_string(),
                 array![4, 5].into_dyn().into_pyarray(py),
             )),
+            checked: false,
         };
         for signal in vec![&op.target, &op.left, &op.right].iter() {
             signal.reset();
         }
 
-        op.step();
+        op.step()?;
 
         assert_eq!(
             **op.target.read(),
@@ -101,4 +228,87 @@ mod test {
         );
         Ok(())
     }
+
+    #[test]
+    fn it_broadcasts_over_the_batch_axis() -> Result<(), Box<dyn Error>> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let op = ElementwiseInc::<u64> {
+            target: Arc::new(ArraySignal::new(
+                "target".to_string(),
+                Array::ones(IxDyn(&[2, 2])).into_pyarray(py),
+            )),
+            left: Arc::new(ArraySignal::new(
+                "left".to_string(),
+                array![2, 3].into_dyn().into_pyarray(py),
+            )),
+            right: Arc::new(ArraySignal::new(
+                "right".to_string(),
+                array![[4, 5], [6, 7]].into_dyn().into_pyarray(py),
+            )),
+            checked: false,
+        };
+        for signal in [&op.target, &op.left, &op.right].iter() {
+            signal.reset();
+        }
+
+        op.step()?;
+
+        assert_eq!(
+            **op.target.read(),
+            array![[9, 16], [13, 22]].into_dimensionality::<IxDyn>()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_multiplies_complex_values() {
+        let op = ElementwiseInc::<c64> {
+            target: Arc::new(ArraySignal::from_array(
+                "target".to_string(),
+                array![c64::new(1., 0.)].into_dyn(),
+            )),
+            left: Arc::new(ArraySignal::from_array(
+                "left".to_string(),
+                array![c64::new(0., 1.)].into_dyn(),
+            )),
+            right: Arc::new(ArraySignal::from_array(
+                "right".to_string(),
+                array![c64::new(2., 3.)].into_dyn(),
+            )),
+            checked: false,
+        };
+
+        op.step().unwrap();
+
+        assert_eq!(**op.target.read(), array![c64::new(-2., 2.)].into_dyn());
+    }
+
+    #[test]
+    fn it_fails_on_overflow_if_checked() {
+        let op = ElementwiseInc::<u64> {
+            target: Arc::new(ArraySignal::from_array(
+                "counts".to_string(),
+                array![1, u64::MAX].into_dyn(),
+            )),
+            left: Arc::new(ArraySignal::from_array(
+                "left".to_string(),
+                array![1].into_dyn(),
+            )),
+            right: Arc::new(ArraySignal::from_array(
+                "right".to_string(),
+                array![1, 1].into_dyn(),
+            )),
+            checked: true,
+        };
+
+        assert!(op.step().is_err());
+        assert_eq!(**op.target.read(), array![1, u64::MAX].into_dyn());
+        op.target
+            .write()
+            .assign_array(&array![0, u64::MAX - 1].into_dyn());
+        op.step().unwrap();
+        assert_eq!(**op.target.read(), array![1, u64::MAX].into_dyn());
+    }
 }
@@ -0,0 +1,217 @@
+use crate::operator::{check_assignable, ErrorCause, Operator, OperatorError, PyArrayCache};
+use crate::signal::{ArraySignal, Signal, SignalAccess};
+use numpy::PyArrayDyn;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::{Arc, Mutex};
+
+/// Whether a `StepEnvironment` resets its environment on the next step, and
+/// with which seed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnvironmentState {
+    Reset(Option<u64>),
+    Running,
+}
+
+impl Default for EnvironmentState {
+    fn default() -> Self {
+        EnvironmentState::Reset(None)
+    }
+}
+
+/// Steps a Gymnasium environment with the value of `action` and writes the
+/// returned `observation`, `reward`, and whether the episode is `done`. With
+/// `discrete` actions, the index of the largest element of `action` is passed.
+///
+/// The first step after an engine reset resets the environment, seeded from
+/// the engine seed. Finished episodes are reset right away, so that `done` is
+/// set together with the first observation of the next episode. Use the
+/// operator period to step the environment every N simulation steps.
+#[derive(Debug)]
+pub struct StepEnvironment {
+    pub env: PyObject,
+    pub action: Arc<ArraySignal<f64>>,
+    pub observation: Arc<ArraySignal<f64>>,
+    pub reward: Option<Arc<ArraySignal<f64>>>,
+    pub done: Option<Arc<ArraySignal<f64>>>,
+    pub discrete: bool,
+    pub action_array: PyArrayCache<f64>,
+    pub state: Mutex<EnvironmentState>,
+}
+
+impl StepEnvironment {
+    fn action_value(&self, py: Python) -> PyResult<PyObject> {
+        if self.discrete {
+            let index = self.action.read().with_view(|action| {
+                action
+                    .iter()
+                    .enumerate()
+                    .fold((0, f64::NEG_INFINITY), |best, (i, &x)| {
+                        if x > best.1 {
+                            (i, x)
+                        } else {
+                            best
+                        }
+                    })
+                    .0
+            });
+            Ok(index.to_object(py))
+        } else {
+            self.action_array.update(py, &self.action)
+        }
+    }
+
+    fn reset_env<'p>(
+        &self,
+        py: Python<'p>,
+        env: &'p PyAny,
+        seed: Option<u64>,
+    ) -> PyResult<&'p PyAny> {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("seed", seed)?;
+        env.call_method("reset", (), Some(kwargs))?.get_item(0)
+    }
+
+    fn step_env<'p>(&'p self, py: Python<'p>) -> PyResult<(&'p PyAny, f64, bool)> {
+        let env = self.env.as_ref(py);
+        let mut state = self.state.lock().unwrap();
+        let result = match *state {
+            EnvironmentState::Reset(seed) => (self.reset_env(py, env, seed)?, 0., false),
+            EnvironmentState::Running => {
+                let result = env.call_method1("step", (self.action_value(py)?,))?;
+                let done = result.get_item(2)?.is_true()? || result.get_item(3)?.is_true()?;
+                let observation = if done {
+                    self.reset_env(py, env, None)?
+                } else {
+                    result.get_item(0)?
+                };
+                (observation, result.get_item(1)?.extract()?, done)
+            }
+        };
+        *state = EnvironmentState::Running;
+        Ok(result)
+    }
+}
+
+fn fill(signal: &Option<Arc<ArraySignal<f64>>>, value: f64) {
+    if let Some(signal) = signal {
+        signal.write().with_view_mut(|mut x| x.fill(value));
+    }
+}
+
+impl Operator for StepEnvironment {
+    fn step(&self) -> Result<(), OperatorError> {
+        let gil = Python::acquire_gil();
+        self.step_with_gil(gil.python())
+    }
+
+    fn requires_gil(&self) -> bool {
+        true
+    }
+
+    fn step_with_gil(&self, py: Python) -> Result<(), OperatorError> {
+        let (observation, reward, done) = self
+            .step_env(py)
+            .and_then(|(observation, reward, done)| {
+                let observation = PyModule::import(py, "numpy")?
+                    .call1("asarray", (observation, "float64"))?
+                    .extract::<&PyArrayDyn<f64>>()?;
+                Ok((observation, reward, done))
+            })
+            .map_err(|e| OperatorError::new(self, ErrorCause::python(py, e)))?;
+        let observation = observation.readonly();
+        check_assignable(self, &observation.as_array(), self.observation.shape())?;
+        self.observation
+            .write()
+            .assign_array(&observation.as_array());
+        fill(&self.reward, reward);
+        fill(&self.done, if done { 1. } else { 0. });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.action]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        let mut writes: Vec<&dyn Signal> = vec![&*self.observation];
+        if let Some(reward) = &self.reward {
+            writes.push(&**reward);
+        }
+        if let Some(done) = &self.done {
+            writes.push(&**done);
+        }
+        writes
+    }
+
+    fn set_seed(&self, seed: u64) {
+        *self.state.lock().unwrap() = EnvironmentState::Reset(Some(seed));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::venv::activate_venv;
+    use ndarray::prelude::*;
+
+    const COUNTER_ENV: &str = "
+class CounterEnv:
+    def reset(self, seed=None):
+        self.seeds.append(seed)
+        self.count = 0
+        return [self.count], {}
+
+    def step(self, action):
+        self.count += action
+        return [self.count], 0.5, self.count >= 3, False, {}
+
+env = CounterEnv()
+env.seeds = []
+";
+
+    fn signal(name: &str, value: ArrayD<f64>) -> Arc<ArraySignal<f64>> {
+        Arc::new(ArraySignal::from_array(name.to_string(), value))
+    }
+
+    #[test]
+    fn it_steps_and_resets_the_environment() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let locals = PyDict::new(py);
+        py.run(COUNTER_ENV, None, Some(locals)).unwrap();
+
+        let op = StepEnvironment {
+            env: locals.get_item("env").unwrap().into(),
+            action: signal("action", array![0.1, 0.9].into_dyn()),
+            observation: signal("observation", array![-1.].into_dyn()),
+            reward: Some(signal("reward", array![0.].into_dyn())),
+            done: Some(signal("done", array![0.].into_dyn())),
+            discrete: true,
+            action_array: Default::default(),
+            state: Default::default(),
+        };
+        op.set_seed(7);
+
+        let mut observations = vec![];
+        let mut done = vec![];
+        for _ in 0..4 {
+            op.step().unwrap();
+            observations.push(op.observation.read().with_view(|x| x[0]));
+            done.push(op.done.as_ref().unwrap().read().with_view(|x| x[0]));
+        }
+
+        assert_eq!(observations, vec![0., 1., 2., 0.]);
+        assert_eq!(done, vec![0., 0., 0., 1.]);
+        assert_eq!(**op.reward.as_ref().unwrap().read(), array![0.5].into_dyn());
+        let seeds: Vec<Option<u64>> = locals
+            .get_item("env")
+            .unwrap()
+            .getattr("seeds")
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(seeds, vec![Some(7), None]);
+    }
+}
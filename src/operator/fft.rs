@@ -0,0 +1,153 @@
+use crate::fft::Dft;
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{ArraySignal, ShapeError, Signal, SignalAccess};
+use ndarray::Axis;
+use num_complex::Complex64;
+use numpy::Element;
+use std::fmt::Debug;
+use std::sync::{Arc, OnceLock};
+
+/// Element types of the signals transformed by `Fft`. Complex values are
+/// stored as real ones by keeping their real part.
+pub trait FftValue: Element + Copy {
+    fn to_complex(self) -> Complex64;
+    fn from_complex(value: Complex64) -> Self;
+}
+
+impl FftValue for f64 {
+    fn to_complex(self) -> Complex64 {
+        Complex64::new(self, 0.)
+    }
+
+    fn from_complex(value: Complex64) -> Self {
+        value.re
+    }
+}
+
+impl FftValue for Complex64 {
+    fn to_complex(self) -> Complex64 {
+        self
+    }
+
+    fn from_complex(value: Complex64) -> Self {
+        value
+    }
+}
+
+/// Sets `output` to the discrete Fourier transform of `input`, or to the
+/// inverse transform if `inverse`, along the last axis.
+#[derive(Debug)]
+pub struct Fft<I, O>
+where
+    I: Element,
+    O: Element,
+{
+    pub input: Arc<ArraySignal<I>>,
+    pub output: Arc<ArraySignal<O>>,
+    pub inverse: bool,
+    /// Created on the first step, as the length is fixed by the signals.
+    pub plan: OnceLock<Dft<f64>>,
+}
+
+impl<I, O> Operator for Fft<I, O>
+where
+    I: FftValue + Debug + Send + Sync + 'static,
+    O: FftValue + Debug + Send + Sync + 'static,
+{
+    fn step(&self) -> Result<(), OperatorError> {
+        let n = self.input.shape().last().copied().unwrap_or(1);
+        let plan = self.plan.get_or_init(|| Dft::new(n));
+        let input = self.input.read();
+        let mut output = self.output.write();
+        let mut row = Vec::with_capacity(n);
+        input.with_view(|input| {
+            output.with_view_mut(|mut output| {
+                let axis = Axis(input.ndim().max(1) - 1);
+                for (x, mut y) in input.lanes(axis).into_iter().zip(output.lanes_mut(axis)) {
+                    row.clear();
+                    row.extend(x.iter().map(|v| v.to_complex()));
+                    if self.inverse {
+                        plan.inverse(&mut row);
+                    } else {
+                        plan.forward(&mut row);
+                    }
+                    for (y, v) in y.iter_mut().zip(&row) {
+                        *y = O::from_complex(*v);
+                    }
+                }
+            })
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.input]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.output]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        if self.input.shape() != self.output.shape() {
+            return Err(ShapeError {
+                message: format!(
+                    "Transform of shape {:?} does not fit output of shape {:?}.",
+                    self.input.shape(),
+                    self.output.shape()
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    #[test]
+    fn it_transforms_each_row_and_back() {
+        let input = Arc::new(ArraySignal::from_array(
+            "input".to_string(),
+            array![[1., 0., 0.], [1., 1., 1.]].into_dyn(),
+        ));
+        let spectrum = Arc::new(ArraySignal::from_array(
+            "spectrum".to_string(),
+            Array::zeros(IxDyn(&[2, 3])),
+        ));
+        let output = Arc::new(ArraySignal::from_array(
+            "output".to_string(),
+            Array::zeros(IxDyn(&[2, 3])),
+        ));
+        let fft = Fft::<f64, Complex64> {
+            input: Arc::clone(&input),
+            output: Arc::clone(&spectrum),
+            inverse: false,
+            plan: OnceLock::new(),
+        };
+        let ifft = Fft::<Complex64, f64> {
+            input: Arc::clone(&spectrum),
+            output: Arc::clone(&output),
+            inverse: true,
+            plan: OnceLock::new(),
+        };
+        fft.check_shapes().unwrap();
+
+        fft.step().unwrap();
+        ifft.step().unwrap();
+
+        let spectrum = spectrum.read().clone_array();
+        let expected = array![[1., 1., 1.], [3., 0., 0.]];
+        assert!(spectrum
+            .iter()
+            .zip(expected.iter())
+            .all(|(s, e)| (s - e).norm() < 1e-12));
+        let output = output.read().clone_array();
+        assert!(output
+            .iter()
+            .zip(input.read().clone_array().iter())
+            .all(|(o, i)| (o - i).abs() < 1e-12));
+    }
+}
@@ -0,0 +1,219 @@
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{ArraySignal, ShapeError, Signal, SignalAccess};
+use num_traits::Float;
+use numpy::Element;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Layout of the flattened signals of a 2-D pooling. One-dimensional pooling
+/// uses a height of one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolGeometry {
+    pub input_size: (usize, usize),
+    pub output_size: (usize, usize),
+    pub pool_size: (usize, usize),
+    pub strides: (usize, usize),
+    /// Implicit padding before the first row and column of the input, which
+    /// is ignored by the pooling.
+    pub padding: (usize, usize),
+    pub channels: usize,
+    pub channels_last: bool,
+}
+
+impl PoolGeometry {
+    pub fn input_len(&self) -> usize {
+        self.input_size.0 * self.input_size.1 * self.channels
+    }
+
+    pub fn output_len(&self) -> usize {
+        self.output_size.0 * self.output_size.1 * self.channels
+    }
+
+    fn index(&self, size: (usize, usize), y: usize, x: usize, c: usize) -> usize {
+        if self.channels_last {
+            (y * size.1 + x) * self.channels + c
+        } else {
+            (c * size.0 + y) * size.1 + x
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PoolKind {
+    Average,
+    Max,
+}
+
+/// Sets `target` to the average or maximum of each pooling window of `input`
+/// per channel. `input` and `target` may have a leading trial axis.
+#[derive(Debug)]
+pub struct Pool<T>
+where
+    T: Element,
+{
+    pub target: Arc<ArraySignal<T>>,
+    pub input: Arc<ArraySignal<T>>,
+    pub geometry: PoolGeometry,
+    pub kind: PoolKind,
+}
+
+impl<T> Pool<T>
+where
+    T: Element + Float,
+{
+    fn pool(&self, input: &[T]) -> Vec<T> {
+        let g = &self.geometry;
+        let mut output = Vec::with_capacity(input.len() / g.input_len() * g.output_len());
+        let mut window = Vec::with_capacity(g.pool_size.0 * g.pool_size.1);
+        for input in input.chunks(g.input_len()) {
+            let start = output.len();
+            output.resize(start + g.output_len(), T::zero());
+            for c in 0..g.channels {
+                for oy in 0..g.output_size.0 {
+                    for ox in 0..g.output_size.1 {
+                        window.clear();
+                        for py in 0..g.pool_size.0 {
+                            let y = oy * g.strides.0 + py;
+                            if y < g.padding.0 || y - g.padding.0 >= g.input_size.0 {
+                                continue;
+                            }
+                            for px in 0..g.pool_size.1 {
+                                let x = ox * g.strides.1 + px;
+                                if x < g.padding.1 || x - g.padding.1 >= g.input_size.1 {
+                                    continue;
+                                }
+                                window.push(
+                                    input[g.index(
+                                        g.input_size,
+                                        y - g.padding.0,
+                                        x - g.padding.1,
+                                        c,
+                                    )],
+                                );
+                            }
+                        }
+                        output[start + g.index(g.output_size, oy, ox, c)] = match self.kind {
+                            _ if window.is_empty() => T::zero(),
+                            PoolKind::Average => {
+                                window.iter().fold(T::zero(), |sum, &v| sum + v)
+                                    / T::from(window.len()).unwrap()
+                            }
+                            PoolKind::Max => {
+                                window.iter().fold(T::neg_infinity(), |m, &v| m.max(v))
+                            }
+                        };
+                    }
+                }
+            }
+        }
+        output
+    }
+}
+
+impl<T> Operator for Pool<T>
+where
+    T: Element + Float + Debug + Send + Sync + 'static,
+{
+    fn step(&self) -> Result<(), OperatorError> {
+        let input = self.input.read();
+        let pooled =
+            input.with_view(|input| self.pool(input.as_standard_layout().as_slice().unwrap()));
+        let mut target = self.target.write();
+        target.with_view_mut(|mut target| {
+            for (t, p) in target.iter_mut().zip(pooled) {
+                *t = p;
+            }
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.input]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        let geometry = &self.geometry;
+        let (input, target) = (self.input.shape(), self.target.shape());
+        if geometry.input_len() == 0 || geometry.output_len() == 0 {
+            return Err(ShapeError {
+                message: format!("Pooling {:?} must not be empty.", geometry),
+            });
+        }
+        if input.len() > 2
+            || input.len() != target.len()
+            || input[..input.len() - 1] != target[..target.len() - 1]
+            || input.last() != Some(&geometry.input_len())
+            || target.last() != Some(&geometry.output_len())
+        {
+            return Err(ShapeError {
+                message: format!(
+                    "Input of shape {:?} and target of shape {:?} do not fit a pooling \
+                     of {} inputs to {} outputs.",
+                    input,
+                    target,
+                    geometry.input_len(),
+                    geometry.output_len()
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    fn pool(kind: PoolKind, padding: (usize, usize)) -> Pool<f64> {
+        Pool {
+            target: Arc::new(ArraySignal::from_array(
+                "target".to_string(),
+                Array::zeros(IxDyn(&[8])),
+            )),
+            input: Arc::new(ArraySignal::from_array(
+                "input".to_string(),
+                Array::from((0..18).map(f64::from).collect::<Vec<_>>()).into_dyn(),
+            )),
+            geometry: PoolGeometry {
+                input_size: (3, 3),
+                output_size: (2, 2),
+                pool_size: (2, 2),
+                strides: (2, 2),
+                padding,
+                channels: 2,
+                channels_last: true,
+            },
+            kind,
+        }
+    }
+
+    #[test]
+    fn it_takes_the_maximum_of_each_window() {
+        let op = pool(PoolKind::Max, (0, 0));
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        assert_eq!(
+            **op.target.read(),
+            array![8., 9., 10., 11., 14., 15., 16., 17.].into_dyn()
+        );
+    }
+
+    #[test]
+    fn it_averages_the_window_without_padding() {
+        let op = pool(PoolKind::Average, (1, 1));
+
+        op.step().unwrap();
+
+        assert_eq!(
+            **op.target.read(),
+            array![0., 1., 3., 4., 9., 10., 12., 13.].into_dyn()
+        );
+    }
+}
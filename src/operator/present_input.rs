@@ -0,0 +1,126 @@
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{
+    check_broadcastable, ArraySignal, ScalarSignal, ShapeError, Signal, SignalAccess,
+};
+use ndarray::{ArrayD, Axis};
+use numpy::Element;
+use std::fmt::Debug;
+use std::mem;
+use std::ops::AddAssign;
+use std::sync::Arc;
+
+/// Native implementation of nengo's `PresentInput` process: outputs each of
+/// the `inputs` (indexed along the first axis) for `presentation_time` in
+/// turn, starting over after the last one.
+#[derive(Debug)]
+pub struct PresentInput<T>
+where
+    T: Element,
+{
+    pub mode_inc: bool,
+    pub inputs: ArrayD<T>,
+    pub presentation_time: f64,
+    pub dt: f64,
+    pub t: Arc<ScalarSignal<f64>>,
+    pub output: Arc<ArraySignal<T>>,
+}
+
+impl<T> PresentInput<T>
+where
+    T: Element,
+{
+    fn index(&self, t: f64) -> usize {
+        // Matches the rounding of nengo's implementation.
+        let i = ((t - self.dt) / self.presentation_time + 1e-7).floor() as i64;
+        i.rem_euclid(self.inputs.len_of(Axis(0)) as i64) as usize
+    }
+}
+
+impl<T> Operator for PresentInput<T>
+where
+    T: Element + AddAssign<T> + Debug + Send + Sync + 'static,
+{
+    fn step(&self) -> Result<(), OperatorError> {
+        let input = self.inputs.index_axis(Axis(0), self.index(**self.t.read()));
+        let mut output = self.output.write();
+        if self.mode_inc {
+            **output += &input;
+        } else {
+            output.assign_array(&input);
+        }
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.t]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.output]
+    }
+
+    fn increments(&self) -> bool {
+        self.mode_inc
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.inputs.len() * mem::size_of::<T>()
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        if self.inputs.ndim() == 0 || self.inputs.len_of(Axis(0)) == 0 {
+            return Err(ShapeError {
+                message: format!(
+                    "Inputs of shape {:?} must contain at least one presentation.",
+                    self.inputs.shape()
+                ),
+            });
+        }
+        check_broadcastable(&self.inputs.shape()[1..], self.output.shape())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    fn present_input(mode_inc: bool) -> PresentInput<f64> {
+        PresentInput {
+            mode_inc,
+            inputs: array![[1., 2.], [3., 4.], [5., 6.]].into_dyn(),
+            presentation_time: 0.002,
+            dt: 0.001,
+            t: Arc::new(ScalarSignal::new("t".to_string(), 0.)),
+            output: Arc::new(ArraySignal::from_array(
+                "output".to_string(),
+                Array::ones(IxDyn(&[2])),
+            )),
+        }
+    }
+
+    #[test]
+    fn it_presents_each_input_in_turn() {
+        let op = present_input(false);
+        op.check_shapes().unwrap();
+
+        let mut outputs = vec![];
+        for step in 1..=7 {
+            **op.t.write() = step as f64 * 0.001;
+            op.step().unwrap();
+            outputs.push(op.output.read().clone_array()[0]);
+        }
+
+        assert_eq!(outputs, vec![1., 1., 3., 3., 5., 5., 1.]);
+    }
+
+    #[test]
+    fn it_increments_the_output() {
+        let op = present_input(true);
+        **op.t.write() = 0.003;
+
+        op.step().unwrap();
+
+        assert_eq!(**op.output.read(), array![4., 5.].into_dyn());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::operator::{check_assignable, Operator, OperatorError};
+use crate::signal::{ArraySignal, Signal, SignalAccess};
+use ndarray::ArrayD;
+use numpy::Element;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// Arrays pushed from outside the engine, possibly from other threads and
+/// while the engine is running.
+#[derive(Debug)]
+pub struct InputQueue<T> {
+    items: Mutex<VecDeque<ArrayD<T>>>,
+}
+
+impl<T> Default for InputQueue<T> {
+    fn default() -> Self {
+        InputQueue {
+            items: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl<T> InputQueue<T> {
+    pub fn push(&self, item: ArrayD<T>) {
+        self.items.lock().unwrap().push_back(item);
+    }
+
+    /// Removes all queued items and returns the most recent one.
+    fn take_latest(&self) -> Option<ArrayD<T>> {
+        let mut items = self.items.lock().unwrap();
+        let latest = items.pop_back();
+        items.clear();
+        latest
+    }
+}
+
+/// Writes the most recent item pushed to `queue` to `output` each step. The
+/// item is written again until a newer one was pushed.
+#[derive(Debug)]
+pub struct QueueInput<T>
+where
+    T: Element,
+{
+    pub queue: Arc<InputQueue<T>>,
+    pub output: Arc<ArraySignal<T>>,
+    pub latest: Mutex<Option<ArrayD<T>>>,
+}
+
+impl<T> Operator for QueueInput<T>
+where
+    T: Element + Debug + Send + Sync + 'static,
+{
+    fn step(&self) -> Result<(), OperatorError> {
+        let mut latest = self.latest.lock().unwrap();
+        if let Some(item) = self.queue.take_latest() {
+            check_assignable(self, &item.view(), self.output.shape())?;
+            *latest = Some(item);
+        }
+        if let Some(item) = &*latest {
+            self.output.write().assign_array(item);
+        }
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.output]
+    }
+
+    fn is_pure(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    fn queue_input() -> QueueInput<f64> {
+        QueueInput {
+            queue: Arc::new(InputQueue::default()),
+            output: Arc::new(ArraySignal::from_array(
+                "output".to_string(),
+                Array::zeros(IxDyn(&[2])),
+            )),
+            latest: Mutex::new(None),
+        }
+    }
+
+    #[test]
+    fn it_writes_the_most_recent_item() {
+        let op = queue_input();
+        op.step().unwrap();
+        assert_eq!(**op.output.read(), array![0., 0.].into_dyn());
+
+        op.queue.push(array![1., 2.].into_dyn());
+        op.queue.push(array![3., 4.].into_dyn());
+        op.step().unwrap();
+        assert_eq!(**op.output.read(), array![3., 4.].into_dyn());
+
+        op.output.reset();
+        op.step().unwrap();
+        assert_eq!(**op.output.read(), array![3., 4.].into_dyn());
+    }
+
+    #[test]
+    fn it_fails_for_items_of_the_wrong_shape() {
+        let op = queue_input();
+        op.queue.push(array![1., 2., 3.].into_dyn());
+
+        assert!(op.step().is_err());
+    }
+}
@@ -1,5 +1,7 @@
-use crate::operator::Operator;
-use crate::signal::{ArraySignal, ScalarSignal, Signal, SignalAccess};
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{
+    check_broadcastable, ArraySignal, ScalarSignal, ShapeError, Signal, SignalAccess,
+};
 use ndarray::ArrayD;
 use numpy::Element;
 use std::fmt::Debug;
@@ -15,14 +17,36 @@ where
 }
 
 impl<T: Element + Debug + Send + Sync + 'static> Operator for Reset<ArrayD<T>, ArraySignal<T>> {
-    fn step(&self) {
+    fn step(&self) -> Result<(), OperatorError> {
         self.target.write().assign_array(&self.value);
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        check_broadcastable(self.value.shape(), self.target.shape())
     }
 }
 
 impl<T: Send + Sync + Copy + Debug + 'static> Operator for Reset<T, ScalarSignal<T>> {
-    fn step(&self) {
+    fn step(&self) -> Result<(), OperatorError> {
         **self.target.write() = self.value;
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
     }
 }
 
@@ -43,7 +67,7 @@ mod test {
         };
         op.target.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(**op.target.read(), 42);
     }
@@ -61,7 +85,7 @@ mod test {
         };
         op.target.reset();
 
-        op.step();
+        op.step()?;
 
         assert_eq!(
             **op.target.read(),
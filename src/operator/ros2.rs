@@ -0,0 +1,369 @@
+use crate::operator::{ErrorCause, Operator, OperatorError};
+use crate::signal::{ArraySignal, ShapeError, Signal, SignalAccess};
+use pyo3::exceptions as exc;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use pyo3::PyNativeType;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Upper bound on the callbacks processed by a subscription per step, so that
+/// a topic published faster than the simulation runs cannot stall a step.
+const MAX_SPINS: usize = 100;
+
+/// History depth of the quality of service of subscriptions and publishers.
+const QUEUE_DEPTH: usize = 10;
+
+static NODE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Field of a ROS 2 message mapped to consecutive elements of a signal. It is
+/// written as a dotted path, such as `linear.x`, and sequence fields give
+/// their length in brackets, such as `position[6]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageField {
+    pub path: Vec<String>,
+    pub len: Option<usize>,
+}
+
+impl MessageField {
+    /// Number of signal elements the field maps to.
+    pub fn size(&self) -> usize {
+        self.len.unwrap_or(1)
+    }
+
+    fn name(&self) -> String {
+        self.path.join(".")
+    }
+
+    fn get<'p>(&self, message: &'p PyAny) -> PyResult<&'p PyAny> {
+        self.path
+            .iter()
+            .try_fold(message, |value, attr| value.getattr(attr.as_str()))
+    }
+
+    fn read(&self, message: &PyAny, values: &mut Vec<f64>) -> PyResult<()> {
+        let value = self.get(message)?;
+        match self.len {
+            None => values.push(value.extract()?),
+            Some(len) => {
+                let start = values.len();
+                for item in value.iter()? {
+                    values.push(item?.extract()?);
+                }
+                if values.len() - start != len {
+                    return Err(PyErr::new::<exc::ValueError, _>(format!(
+                        "Field `{}` has {} values, but {} were expected.",
+                        self.name(),
+                        values.len() - start,
+                        len
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&self, py: Python, message: &PyAny, values: &[f64]) -> PyResult<()> {
+        let (attr, parents) = self.path.split_last().unwrap();
+        let parent = parents
+            .iter()
+            .try_fold(message, |value, attr| value.getattr(attr.as_str()))?;
+        match self.len {
+            None => parent.setattr(attr.as_str(), values[0]),
+            Some(_) => parent.setattr(attr.as_str(), PyList::new(py, values)),
+        }
+    }
+}
+
+impl FromStr for MessageField {
+    type Err = String;
+
+    fn from_str(field: &str) -> Result<Self, Self::Err> {
+        let (path, len) = match field.strip_suffix(']').and_then(|f| f.split_once('[')) {
+            Some((path, len)) => match len.parse() {
+                Ok(len) => (path, Some(len)),
+                Err(_) => return Err(format!("Invalid length in message field `{}`.", field)),
+            },
+            None => (field, None),
+        };
+        let path: Vec<String> = path.split('.').map(str::to_string).collect();
+        if path.iter().any(|attr| attr.is_empty()) {
+            return Err(format!("Invalid message field `{}`.", field));
+        }
+        Ok(Self { path, len })
+    }
+}
+
+fn check_fields(fields: &[MessageField], signal: &ArraySignal<f64>) -> Result<(), ShapeError> {
+    let size: usize = fields.iter().map(MessageField::size).sum();
+    let signal_size: usize = signal.shape().iter().product();
+    if size != signal_size {
+        return Err(ShapeError {
+            message: format!(
+                "Message fields of {} values do not fit signal `{}` of shape {:?}.",
+                size,
+                signal.name(),
+                signal.shape()
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// An `rclpy` node owned by a single operator, initializing `rclpy` if
+/// needed. The node is destroyed with the operator.
+#[derive(Debug)]
+pub struct Ros2Node {
+    node: PyObject,
+}
+
+impl Ros2Node {
+    pub fn new(py: Python) -> PyResult<Self> {
+        let rclpy = PyModule::import(py, "rclpy")?;
+        if !rclpy.call0("ok")?.is_true()? {
+            rclpy.call0("init")?;
+        }
+        let name = format!(
+            "nengo_rs_{}_{}",
+            std::process::id(),
+            NODE_COUNT.fetch_add(1, Ordering::SeqCst)
+        );
+        Ok(Self {
+            node: rclpy.call1("create_node", (name,))?.into(),
+        })
+    }
+
+    fn spin_once(&self, py: Python) -> PyResult<()> {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("timeout_sec", 0.)?;
+        PyModule::import(py, "rclpy")?.call("spin_once", (&self.node,), Some(kwargs))?;
+        Ok(())
+    }
+}
+
+impl Drop for Ros2Node {
+    fn drop(&mut self) {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        if let Err(err) = self.node.call_method0(py, "destroy_node") {
+            err.print(py);
+        }
+    }
+}
+
+/// Receiving end of a ROS 2 topic, collecting the messages delivered while
+/// spinning its node.
+#[derive(Debug)]
+pub struct Ros2Subscriber {
+    node: Ros2Node,
+    messages: Py<PyList>,
+    _subscription: PyObject,
+}
+
+impl Ros2Subscriber {
+    pub fn new(topic: &str, message_type: &PyAny) -> PyResult<Self> {
+        let py = message_type.py();
+        let node = Ros2Node::new(py)?;
+        let messages: &PyList = PyList::empty(py);
+        let subscription = node.node.call_method1(
+            py,
+            "create_subscription",
+            (
+                message_type,
+                topic,
+                messages.getattr("append")?,
+                QUEUE_DEPTH,
+            ),
+        )?;
+        Ok(Self {
+            node,
+            messages: messages.into(),
+            _subscription: subscription,
+        })
+    }
+
+    /// Processes pending callbacks and returns the most recent message
+    /// received since the last call.
+    fn take_latest(&self, py: Python) -> PyResult<Option<PyObject>> {
+        let messages = self.messages.as_ref(py);
+        for _ in 0..MAX_SPINS {
+            let received = messages.len();
+            self.node.spin_once(py)?;
+            if messages.len() == received {
+                break;
+            }
+        }
+        let latest = match messages.len() {
+            0 => None,
+            n => Some(messages.get_item(n as isize - 1).into()),
+        };
+        messages.call_method1("clear", ())?;
+        Ok(latest)
+    }
+}
+
+/// Sending end of a ROS 2 topic.
+#[derive(Debug)]
+pub struct Ros2Publisher {
+    _node: Ros2Node,
+    publisher: PyObject,
+    message_type: PyObject,
+}
+
+impl Ros2Publisher {
+    pub fn new(topic: &str, message_type: &PyAny) -> PyResult<Self> {
+        let py = message_type.py();
+        let node = Ros2Node::new(py)?;
+        let publisher =
+            node.node
+                .call_method1(py, "create_publisher", (message_type, topic, QUEUE_DEPTH))?;
+        Ok(Self {
+            _node: node,
+            publisher,
+            message_type: message_type.into(),
+        })
+    }
+}
+
+/// Writes the `fields` of the most recent message on a ROS 2 topic to
+/// `output` each step. The message is written again until a newer one
+/// arrives.
+#[derive(Debug)]
+pub struct SubscribeTopic {
+    pub output: Arc<ArraySignal<f64>>,
+    pub fields: Vec<MessageField>,
+    pub subscriber: Ros2Subscriber,
+    pub latest: Mutex<Option<Vec<f64>>>,
+}
+
+impl SubscribeTopic {
+    fn python_error(&self, py: Python, err: PyErr) -> OperatorError {
+        OperatorError::new(self, ErrorCause::python(py, err))
+    }
+}
+
+impl Operator for SubscribeTopic {
+    fn step(&self) -> Result<(), OperatorError> {
+        let gil = Python::acquire_gil();
+        self.step_with_gil(gil.python())
+    }
+
+    fn requires_gil(&self) -> bool {
+        true
+    }
+
+    fn step_with_gil(&self, py: Python) -> Result<(), OperatorError> {
+        let mut latest = self.latest.lock().unwrap();
+        if let Some(message) = self
+            .subscriber
+            .take_latest(py)
+            .map_err(|e| self.python_error(py, e))?
+        {
+            let mut values = Vec::with_capacity(self.output.shape().iter().product());
+            for field in self.fields.iter() {
+                field
+                    .read(message.as_ref(py), &mut values)
+                    .map_err(|e| self.python_error(py, e))?;
+            }
+            *latest = Some(values);
+        }
+        if let Some(values) = &*latest {
+            self.output.write().with_view_mut(|mut output| {
+                for (x, y) in output.iter_mut().zip(values) {
+                    *x = *y;
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.output]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        check_fields(&self.fields, &self.output)
+    }
+}
+
+/// Publishes a message with the `fields` set from `source` to a ROS 2 topic
+/// each step.
+#[derive(Debug)]
+pub struct PublishTopic {
+    pub source: Arc<ArraySignal<f64>>,
+    pub fields: Vec<MessageField>,
+    pub publisher: Ros2Publisher,
+}
+
+impl Operator for PublishTopic {
+    fn step(&self) -> Result<(), OperatorError> {
+        let gil = Python::acquire_gil();
+        self.step_with_gil(gil.python())
+    }
+
+    fn requires_gil(&self) -> bool {
+        true
+    }
+
+    fn step_with_gil(&self, py: Python) -> Result<(), OperatorError> {
+        let values: Vec<f64> = self
+            .source
+            .read()
+            .with_view(|source| source.iter().copied().collect());
+        let publish = || -> PyResult<()> {
+            let message = self.publisher.message_type.as_ref(py).call0()?;
+            let mut offset = 0;
+            for field in self.fields.iter() {
+                field.write(py, message, &values[offset..offset + field.size()])?;
+                offset += field.size();
+            }
+            self.publisher
+                .publisher
+                .call_method1(py, "publish", (message,))?;
+            Ok(())
+        };
+        publish().map_err(|e| OperatorError::new(self, ErrorCause::python(py, e)))
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.source]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        check_fields(&self.fields, &self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_message_fields() {
+        assert_eq!(
+            "linear.x".parse(),
+            Ok(MessageField {
+                path: vec!["linear".to_string(), "x".to_string()],
+                len: None
+            })
+        );
+        assert_eq!(
+            "position[6]".parse(),
+            Ok(MessageField {
+                path: vec!["position".to_string()],
+                len: Some(6)
+            })
+        );
+        assert!("linear..x".parse::<MessageField>().is_err());
+        assert!("position[n]".parse::<MessageField>().is_err());
+    }
+}
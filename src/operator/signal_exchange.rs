@@ -0,0 +1,249 @@
+use crate::operator::{ErrorCause, Operator, OperatorError};
+use crate::signal::{ArraySignal, Signal, SignalAccess};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug)]
+enum Endpoint {
+    Connect(String),
+    Listen {
+        listener: TcpListener,
+        peers: Vec<IpAddr>,
+    },
+}
+
+/// One direction of a TCP connection between two engines. The connection is
+/// established on first use, so that the engines on both ends can be created
+/// in any order.
+///
+/// Establishing the connection and each transfer fail after the timeout, so
+/// that a dead peer does not block the engine forever.
+#[derive(Debug)]
+pub struct TcpChannel {
+    endpoint: Endpoint,
+    stream: Mutex<Option<TcpStream>>,
+    timeout: Duration,
+}
+
+impl TcpChannel {
+    pub fn connect(address: &str) -> Self {
+        Self {
+            endpoint: Endpoint::Connect(address.to_string()),
+            stream: Mutex::new(None),
+            timeout: TIMEOUT,
+        }
+    }
+
+    /// Listens on `address` for a connection from the host `peer`.
+    /// Connections from any other host are closed.
+    pub fn listen<A: ToSocketAddrs>(address: A, peer: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            endpoint: Endpoint::Listen {
+                listener,
+                peers: (peer, 0).to_socket_addrs()?.map(|a| a.ip()).collect(),
+            },
+            stream: Mutex::new(None),
+            timeout: TIMEOUT,
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn open(&self) -> io::Result<TcpStream> {
+        let start = Instant::now();
+        let stream = match &self.endpoint {
+            Endpoint::Listen { listener, peers } => loop {
+                match listener.accept() {
+                    Ok((stream, address)) if peers.contains(&address.ip()) => break stream,
+                    Ok(_) => {}
+                    Err(err) if err.kind() != io::ErrorKind::WouldBlock => return Err(err),
+                    Err(_) if start.elapsed() >= self.timeout => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "no connection from the expected peer",
+                        ))
+                    }
+                    Err(_) => sleep(CONNECT_RETRY_INTERVAL),
+                }
+            },
+            Endpoint::Connect(address) => loop {
+                match TcpStream::connect(address) {
+                    Ok(stream) => break stream,
+                    Err(err) if start.elapsed() >= self.timeout => return Err(err),
+                    Err(_) => sleep(CONNECT_RETRY_INTERVAL),
+                }
+            },
+        };
+        stream.set_nonblocking(false)?;
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        Ok(stream)
+    }
+
+    fn with_stream<R, F: FnOnce(&mut TcpStream) -> io::Result<R>>(&self, f: F) -> io::Result<R> {
+        let mut stream = self.stream.lock().unwrap();
+        if stream.is_none() {
+            *stream = Some(self.open()?);
+        }
+        f(stream.as_mut().unwrap())
+    }
+
+    pub fn send(&self, data: &[f64]) -> io::Result<()> {
+        let bytes: Vec<u8> = data.iter().flat_map(|x| x.to_le_bytes()).collect();
+        self.with_stream(|stream| stream.write_all(&bytes))
+    }
+
+    pub fn receive(&self, data: &mut [f64]) -> io::Result<()> {
+        let mut bytes = vec![0; data.len() * 8];
+        self.with_stream(|stream| stream.read_exact(&mut bytes))?;
+        for (x, chunk) in data.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut buffer = [0; 8];
+            buffer.copy_from_slice(chunk);
+            *x = f64::from_le_bytes(buffer);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct SendSignal {
+    pub source: Arc<ArraySignal<f64>>,
+    pub channel: TcpChannel,
+}
+
+impl Operator for SendSignal {
+    fn step(&self) -> Result<(), OperatorError> {
+        let data: Vec<f64> = self
+            .source
+            .read()
+            .with_view(|source| source.iter().copied().collect());
+        self.channel
+            .send(&data)
+            .map_err(|e| OperatorError::new(self, ErrorCause::Io(e)))
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.source]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn is_pure(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug)]
+pub struct ReceiveSignal {
+    pub target: Arc<ArraySignal<f64>>,
+    pub channel: TcpChannel,
+}
+
+impl Operator for ReceiveSignal {
+    fn step(&self) -> Result<(), OperatorError> {
+        let mut data = vec![0.; self.target.shape().iter().product()];
+        self.channel
+            .receive(&mut data)
+            .map_err(|e| OperatorError::new(self, ErrorCause::Io(e)))?;
+        self.target.write().with_view_mut(|mut target| {
+            for (x, y) in target.iter_mut().zip(data) {
+                *x = y;
+            }
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn is_pure(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn local_address(channel: &TcpChannel) -> String {
+        match &channel.endpoint {
+            Endpoint::Listen { listener, .. } => listener.local_addr().unwrap().to_string(),
+            Endpoint::Connect(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn channel_transmits_data() {
+        let receiver = TcpChannel::listen("127.0.0.1:0", "127.0.0.1").unwrap();
+        let address = local_address(&receiver);
+
+        let sender = thread::spawn(move || {
+            let sender = TcpChannel::connect(&address);
+            sender.send(&[1., 2.5]).unwrap();
+            sender.send(&[-3., 4.]).unwrap();
+        });
+
+        let mut data = [0.; 2];
+        receiver.receive(&mut data).unwrap();
+        assert_eq!(data, [1., 2.5]);
+        receiver.receive(&mut data).unwrap();
+        assert_eq!(data, [-3., 4.]);
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn receive_times_out_without_peer() {
+        let receiver = TcpChannel::listen("127.0.0.1:0", "127.0.0.1")
+            .unwrap()
+            .with_timeout(Duration::from_millis(50));
+        let err = receiver.receive(&mut [0.; 2]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn receive_times_out_without_data() {
+        let receiver = TcpChannel::listen("127.0.0.1:0", "127.0.0.1")
+            .unwrap()
+            .with_timeout(Duration::from_millis(50));
+        let sender = TcpChannel::connect(&local_address(&receiver));
+        sender.send(&[]).unwrap();
+
+        let err = receiver.receive(&mut [0.; 2]).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        ));
+    }
+
+    #[test]
+    fn listen_rejects_unexpected_peer() {
+        let receiver = TcpChannel::listen("127.0.0.1:0", "127.0.0.2")
+            .unwrap()
+            .with_timeout(Duration::from_millis(50));
+        let sender = TcpChannel::connect(&local_address(&receiver));
+        sender.send(&[1., 2.]).unwrap();
+
+        let err = receiver.receive(&mut [0.; 2]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -1,5 +1,5 @@
-use crate::operator::Operator;
-use crate::signal::{ArraySignal, Signal, SignalAccess};
+use crate::operator::{ErrorCause, Operator, OperatorError};
+use crate::signal::{ArraySignal, ScalarSignal, ShapeError, Signal, SignalAccess};
 use numpy::Element;
 use numpy::PyArrayDyn;
 use pyo3::prelude::*;
@@ -19,33 +19,79 @@ where
     pub output: Arc<ArraySignal<T>>,
     pub state: Py<PyList>,
     pub step_fn: PyObject,
+    /// If given, the neurons are stepped with the current value of this
+    /// signal instead of the fixed `dt`.
+    pub dt_signal: Option<Arc<ScalarSignal<T>>>,
 }
 
 impl<T> Operator for SimNeurons<T>
 where
-    T: Element + Copy + Debug + Send + Sync + ToPyObject + 'static,
+    T: Element + Copy + Debug + Send + Sync + ToPyObject + Into<f64> + 'static,
 {
-    fn step(&self) {
+    fn step(&self) -> Result<(), OperatorError> {
         let gil = Python::acquire_gil();
-        let py = gil.python();
+        self.step_with_gil(gil.python())
+    }
+
+    fn requires_gil(&self) -> bool {
+        true
+    }
 
-        let dt = self.dt.to_object(py);
+    /// The neuron state lives in arrays of the Python state list.
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    fn step_with_gil(&self, py: Python) -> Result<(), OperatorError> {
+        let dt = match &self.dt_signal {
+            Some(dt) => **dt.read(),
+            None => self.dt,
+        }
+        .to_object(py);
         let input_current = self.input_current.read().to_py_array(py);
         let output = PyArrayDyn::new(py, self.output.shape(), false);
         let mut args = vec![dt, input_current.to_object(py), output.to_object(py)];
         args.extend_from_slice(&self.state.as_ref(py).extract::<Vec<PyObject>>().unwrap());
         let args = PyTuple::new(py, args);
 
-        &self
-            .step_fn
+        self.step_fn
             .as_ref(py)
             .call(args, None)
-            .unwrap_or_else(|e| {
-                e.print_and_set_sys_last_vars(py);
-                panic!("Call to neuron step function failed.");
-            });
+            .map_err(|e| OperatorError::new(self, ErrorCause::python(py, e)))?;
         let mut output_sig = self.output.write();
         output_sig.assign_array(&output.readonly().as_array());
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        match &self.dt_signal {
+            Some(dt) => vec![&*self.input_current, &**dt],
+            None => vec![&*self.input_current],
+        }
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        if self.input_current.shape() != self.output.shape() {
+            return Err(ShapeError {
+                message: format!(
+                    "Input current of shape {:?} does not match output of shape {:?}.",
+                    self.input_current.shape(),
+                    self.output.shape()
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.output]
+    }
+
+    fn dt(&self) -> Option<f64> {
+        match self.dt_signal {
+            Some(_) => None,
+            None => Some(self.dt.into()),
+        }
     }
 }
 
@@ -86,11 +132,12 @@ def step(dt, J, output):
             )),
             state: PyList::new(py, &[] as &[f64]).into(),
             step_fn: step_module.getattr("step").unwrap().into(),
+            dt_signal: None,
         };
         op.input_current.reset();
         op.output.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(
             **op.output.read(),
@@ -129,11 +176,12 @@ def step(dt, J, output, state_var):
             )),
             state: state.into(),
             step_fn: step_module.getattr("step").unwrap().into(),
+            dt_signal: None,
         };
         op.input_current.reset();
         op.output.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(
             **op.output.read(),
@@ -1,9 +1,11 @@
-use crate::operator::Operator;
-use crate::signal::{ArraySignal, ScalarSignal, SignalAccess};
+use crate::operator::{
+    check_assignable, ErrorCause, Operator, OperatorError, PyArgsCache, PyArrayCache,
+};
+use crate::signal::{ArraySignal, ScalarSignal, Signal, SignalAccess};
 use numpy::Element;
 use numpy::PyArrayDyn;
 use pyo3::prelude::*;
-use pyo3::types::{PyFloat, PyTuple};
+use pyo3::types::PyFloat;
 use std::fmt::Debug;
 use std::ops::AddAssign;
 use std::sync::Arc;
@@ -18,53 +20,74 @@ where
     pub input: Option<Arc<ArraySignal<T>>>,
     pub output: Arc<ArraySignal<T>>,
     pub step_fn: PyObject,
+    pub input_array: PyArrayCache<T>,
+    pub args: PyArgsCache,
 }
 
 impl<T> Operator for SimProcess<T>
 where
-    T: Element + AddAssign<T> + Debug,
+    T: Element + AddAssign<T> + Debug + Send + Sync + 'static,
 {
-    fn step(&self) {
+    fn step(&self) -> Result<(), OperatorError> {
         let gil = Python::acquire_gil();
-        let py = gil.python();
+        self.step_with_gil(gil.python())
+    }
+
+    fn requires_gil(&self) -> bool {
+        true
+    }
 
-        let t: &PyAny = PyFloat::new(py, **self.t.read());
-        let args = PyTuple::new(
-            py,
-            match &self.input {
-                Some(input) => vec![t, input.read().to_py_array(py)],
-                None => vec![t],
-            },
-        );
+    fn step_with_gil(&self, py: Python) -> Result<(), OperatorError> {
+        let mut args = vec![PyFloat::new(py, **self.t.read()).to_object(py)];
+        if let Some(input) = &self.input {
+            args.push(
+                self.input_array
+                    .update(py, input)
+                    .map_err(|e| OperatorError::new(self, ErrorCause::python(py, e)))?,
+            );
+        }
+        let args = self.args.update(py, args);
 
         let result = &self
             .step_fn
             .as_ref(py)
-            .call(args, None)
-            .unwrap_or_else(|e| {
-                e.print_and_set_sys_last_vars(py);
-                panic!("Call to process step function failed.");
-            })
-            .extract::<Option<&PyArrayDyn<T>>>()
-            .unwrap_or_else(|e| {
-                e.print_and_set_sys_last_vars(py);
-                panic!("Process step function did not return an array.");
-            });
+            .call(args.as_ref(py), None)
+            .and_then(|result| result.extract::<Option<&PyArrayDyn<T>>>())
+            .map_err(|e| OperatorError::new(self, ErrorCause::python(py, e)))?;
         if let Some(result) = result {
+            let result = result.readonly();
+            check_assignable(self, &result.as_array(), self.output.shape())?;
             let mut output = self.output.write();
             if self.mode_inc {
-                **output += &result.readonly().as_array();
+                **output += &result.as_array();
             } else {
-                output.assign_array(&result.readonly().as_array());
+                output.assign_array(&result.as_array());
             }
         }
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        let mut reads: Vec<&dyn Signal> = vec![&*self.t];
+        if let Some(input) = &self.input {
+            reads.push(&**input);
+        }
+        reads
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.output]
+    }
+
+    fn increments(&self) -> bool {
+        self.mode_inc
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::signal::{ArrayRef, Signal};
+    use crate::signal::ArrayRef;
     use crate::venv::activate_venv;
     use ndarray::prelude::*;
     use pyo3::types::IntoPyDict;
@@ -91,11 +114,13 @@ mod test {
                 .eval("lambda t: np.array([t])", Some(locals), None)
                 .unwrap()
                 .into(),
+            input_array: Default::default(),
+            args: Default::default(),
         };
         op.t.reset();
         op.output.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(**op.output.read(), ArrayRef::Owned(array![1.].into_dyn()));
     }
@@ -124,12 +149,14 @@ mod test {
                 .eval("lambda t, input: np.array(input)", Some(locals), None)
                 .unwrap()
                 .into(),
+            input_array: Default::default(),
+            args: Default::default(),
         };
         op.t.reset();
         op.input.as_ref().map(|input| input.reset());
         op.output.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(**op.output.read(), ArrayRef::Owned(array![2.].into_dyn()));
     }
@@ -155,11 +182,13 @@ mod test {
                 .eval("lambda t: np.array([t])", Some(locals), None)
                 .unwrap()
                 .into(),
+            input_array: Default::default(),
+            args: Default::default(),
         };
         op.t.reset();
         op.output.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(**op.output.read(), ArrayRef::Owned(array![2.].into_dyn()));
     }
@@ -1,9 +1,11 @@
-use crate::operator::Operator;
-use crate::signal::{ArraySignal, ScalarSignal, SignalAccess};
+use crate::operator::{
+    check_assignable, ErrorCause, Operator, OperatorError, PyArgsCache, PyArrayCache,
+};
+use crate::signal::{ArraySignal, ScalarSignal, Signal, SignalAccess};
 use numpy::Element;
 use numpy::PyArrayDyn;
 use pyo3::prelude::*;
-use pyo3::types::{PyFloat, PyTuple};
+use pyo3::types::PyFloat;
 use std::fmt::Debug;
 use std::sync::Arc;
 
@@ -16,60 +18,75 @@ where
     pub t: Option<Arc<ScalarSignal<f64>>>,
     pub output: Arc<ArraySignal<T>>,
     pub py_fn: PyObject,
+    pub x_array: PyArrayCache<T>,
+    pub args: PyArgsCache,
 }
 
 impl<T> Operator for SimPyFunc<T>
 where
-    T: Element + Debug,
+    T: Element + Debug + Send + Sync + 'static,
 {
-    fn step(&self) {
+    fn step(&self) -> Result<(), OperatorError> {
         let gil = Python::acquire_gil();
-        let py = gil.python();
+        self.step_with_gil(gil.python())
+    }
 
-        let args = PyTuple::new(
-            py,
-            match &self.t {
-                Some(t) => {
-                    let t: &PyAny = PyFloat::new(py, **t.read());
-                    match &self.x {
-                        Some(x) => vec![t, x.read().to_py_array(py)],
-                        None => vec![t],
-                    }
-                }
-                None => match &self.x {
-                    Some(x) => vec![x.read().to_py_array(py).into()],
-                    None => vec![],
-                },
-            },
-        );
+    fn requires_gil(&self) -> bool {
+        true
+    }
+
+    fn step_with_gil(&self, py: Python) -> Result<(), OperatorError> {
+        let mut args = vec![];
+        if let Some(t) = &self.t {
+            args.push(PyFloat::new(py, **t.read()).to_object(py));
+        }
+        if let Some(x) = &self.x {
+            args.push(
+                self.x_array
+                    .update(py, x)
+                    .map_err(|e| OperatorError::new(self, ErrorCause::python(py, e)))?,
+            );
+        }
+        let args = self.args.update(py, args);
 
         let result = &self
             .py_fn
             .as_ref(py)
-            .call(args, None)
-            .unwrap_or_else(|e| {
-                e.print_and_set_sys_last_vars(py);
-                panic!("Call to Python function failed");
-            })
-            .extract::<Option<&PyArrayDyn<T>>>()
-            .unwrap_or_else(|e| {
-                e.print_and_set_sys_last_vars(py);
-                panic!("Python function did not return an array.");
-            });
+            .call(args.as_ref(py), None)
+            .and_then(|result| result.extract::<Option<&PyArrayDyn<T>>>())
+            .map_err(|e| OperatorError::new(self, ErrorCause::python(py, e)))?;
         if let Some(result) = result {
+            let result = result.readonly();
+            check_assignable(self, &result.as_array(), self.output.shape())?;
             let mut output = self.output.write();
-            output.assign_array(&result.readonly().as_array());
+            output.assign_array(&result.as_array());
         }
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        let mut reads: Vec<&dyn Signal> = vec![];
+        if let Some(t) = &self.t {
+            reads.push(&**t);
+        }
+        if let Some(x) = &self.x {
+            reads.push(&**x);
+        }
+        reads
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.output]
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::signal::{ArrayRef, Signal};
+    use crate::signal::ArrayRef;
     use crate::venv::activate_venv;
     use ndarray::prelude::*;
-    use pyo3::types::IntoPyDict;
+    use pyo3::types::{IntoPyDict, PyDict};
     use pyo3::Python;
 
     #[test]
@@ -92,10 +109,12 @@ mod test {
                 .eval("lambda: np.array([42])", Some(locals), None)
                 .unwrap()
                 .into(),
+            x_array: Default::default(),
+            args: Default::default(),
         };
         op.output.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(
             **op.output.read(),
@@ -123,11 +142,13 @@ mod test {
                 .eval("lambda t: np.array([t])", Some(locals), None)
                 .unwrap()
                 .into(),
+            x_array: Default::default(),
+            args: Default::default(),
         };
         op.t.as_ref().map(|t| t.reset());
         op.output.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(
             **op.output.read(),
@@ -165,16 +186,97 @@ mod test {
                 )
                 .unwrap()
                 .into(),
+            x_array: Default::default(),
+            args: Default::default(),
         };
         op.x.as_ref().map(|x| x.reset());
         op.t.as_ref().map(|t| t.reset());
         op.output.reset();
 
-        op.step();
+        op.step().unwrap();
 
         assert_eq!(
             **op.output.read(),
             ArrayRef::Owned(array![1., 2., 3.].into_dimensionality::<IxDyn>().unwrap())
         );
     }
+
+    fn appending_op(py: Python, locals: &PyDict, reuse_arrays: bool) -> SimPyFunc<f64> {
+        SimPyFunc::<f64> {
+            x: Some(Arc::new(ArraySignal::new(
+                String::from("x"),
+                PyArrayDyn::from_array(py, &array![2.].into_dimensionality::<IxDyn>().unwrap()),
+            ))),
+            t: None,
+            output: Arc::new(ArraySignal::new(
+                String::from("output"),
+                PyArrayDyn::from_array(py, &array![0.].into_dimensionality::<IxDyn>().unwrap()),
+            )),
+            py_fn: py
+                .eval("lambda x: arrays.append(x) or x + 1", Some(locals), None)
+                .unwrap()
+                .into(),
+            x_array: PyArrayCache::new(reuse_arrays),
+            args: Default::default(),
+        }
+    }
+
+    fn step_twice(op: &SimPyFunc<f64>) {
+        let x = op.x.as_ref().unwrap();
+        x.reset();
+        op.output.reset();
+
+        op.step().unwrap();
+        x.write().assign(&op.output.read());
+        op.step().unwrap();
+
+        assert_eq!(
+            **op.output.read(),
+            ArrayRef::Owned(array![4.].into_dimensionality::<IxDyn>().unwrap())
+        );
+    }
+
+    #[test]
+    fn it_passes_a_new_x_array_each_step() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+
+        let locals = PyDict::new(py);
+        py.run("arrays = []", None, Some(locals)).unwrap();
+        step_twice(&appending_op(py, locals, false));
+
+        let kept_values: bool = py
+            .eval(
+                "arrays[0] is not arrays[1] and arrays[0][0] == 2. and arrays[1][0] == 3.",
+                None,
+                Some(locals),
+            )
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert!(kept_values);
+    }
+
+    #[test]
+    fn it_reuses_a_read_only_x_array_across_steps_if_enabled() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+
+        let locals = PyDict::new(py);
+        py.run("arrays = []", None, Some(locals)).unwrap();
+        step_twice(&appending_op(py, locals, true));
+
+        let is_same_read_only_array: bool = py
+            .eval(
+                "arrays[0] is arrays[1] and not arrays[0].flags.writeable",
+                None,
+                Some(locals),
+            )
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert!(is_same_read_only_array);
+    }
 }
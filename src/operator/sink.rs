@@ -0,0 +1,132 @@
+use crate::operator::{ErrorCause, Operator, OperatorError};
+use crate::signal::{ArraySignal, ScalarSignal, Signal, SignalAccess};
+use ndarray::ArrayViewD;
+use numpy::Element;
+use pyo3::prelude::*;
+use pyo3::types::{PyFloat, PyTuple};
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+
+pub type SinkFn<T> = Box<dyn Fn(Option<f64>, Option<ArrayViewD<T>>) + Send + Sync>;
+
+pub enum SinkCallback<T> {
+    /// Called with the time and a copy of the value, for those given.
+    Python(PyObject),
+    Rust(SinkFn<T>),
+}
+
+impl<T> Debug for SinkCallback<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SinkCallback::Python(callback) => f.debug_tuple("Python").field(callback).finish(),
+            SinkCallback::Rust(_) => f.write_str("Rust"),
+        }
+    }
+}
+
+/// Passes the value of `x` to a callback each time it is stepped, without
+/// writing any signal. Use the operator period to only call it every N steps.
+#[derive(Debug)]
+pub struct Sink<T>
+where
+    T: Element,
+{
+    pub t: Option<Arc<ScalarSignal<f64>>>,
+    pub x: Option<Arc<ArraySignal<T>>>,
+    pub callback: SinkCallback<T>,
+}
+
+impl<T> Operator for Sink<T>
+where
+    T: Element + Debug + Send + Sync + 'static,
+{
+    fn step(&self) -> Result<(), OperatorError> {
+        match &self.callback {
+            SinkCallback::Python(_) => {
+                let gil = Python::acquire_gil();
+                self.step_with_gil(gil.python())
+            }
+            SinkCallback::Rust(callback) => {
+                let t = self.t.as_ref().map(|t| **t.read());
+                match &self.x {
+                    Some(x) => x.read().with_view(|x| callback(t, Some(x))),
+                    None => callback(t, None),
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn requires_gil(&self) -> bool {
+        matches!(self.callback, SinkCallback::Python(_))
+    }
+
+    fn step_with_gil(&self, py: Python) -> Result<(), OperatorError> {
+        let callback = match &self.callback {
+            SinkCallback::Python(callback) => callback,
+            SinkCallback::Rust(_) => return self.step(),
+        };
+        let mut args = vec![];
+        if let Some(t) = &self.t {
+            args.push(PyFloat::new(py, **t.read()).to_object(py));
+        }
+        if let Some(x) = &self.x {
+            args.push(x.read().to_py_array(py).to_object(py));
+        }
+        callback
+            .as_ref(py)
+            .call(PyTuple::new(py, args), None)
+            .map_err(|e| OperatorError::new(self, ErrorCause::python(py, e)))?;
+        Ok(())
+    }
+
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        let mut reads: Vec<&dyn Signal> = vec![];
+        if let Some(t) = &self.t {
+            reads.push(&**t);
+        }
+        if let Some(x) = &self.x {
+            reads.push(&**x);
+        }
+        reads
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn it_passes_the_value_to_a_rust_closure() {
+        let received = Arc::new(Mutex::new(vec![]));
+        let op = Sink {
+            t: Some(Arc::new(ScalarSignal::new("t".to_string(), 0.5))),
+            x: Some(Arc::new(ArraySignal::from_array(
+                "x".to_string(),
+                array![1., 2.].into_dyn(),
+            ))),
+            callback: SinkCallback::Rust({
+                let received = Arc::clone(&received);
+                Box::new(move |t, x| received.lock().unwrap().push((t, x.map(|x| x.to_owned()))))
+            }),
+        };
+        assert!(!op.requires_gil());
+
+        op.step().unwrap();
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![(Some(0.5), Some(array![1., 2.].into_dyn()))]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{ArraySignal, ShapeError, Signal, SignalAccess, SparseSignal};
+use ndarray::Axis;
+use std::sync::Arc;
+
+/// Increments `target` by the product of the sparse `weights` with `input`
+/// along the last axis, so that `input` may have a leading trial axis. Only
+/// the stored weights are visited.
+#[derive(Debug)]
+pub struct SparseDotInc {
+    pub target: Arc<ArraySignal<f64>>,
+    pub weights: Arc<SparseSignal<f64>>,
+    pub input: Arc<ArraySignal<f64>>,
+}
+
+impl Operator for SparseDotInc {
+    fn step(&self) -> Result<(), OperatorError> {
+        let (indptr, indices) = (self.weights.indptr(), self.weights.indices());
+        let data = self.weights.read();
+        let input = self.input.read();
+        let mut target = self.target.write();
+        input.with_view(|input| {
+            target.with_view_mut(|mut target| {
+                let (in_axis, out_axis) = (Axis(input.ndim() - 1), Axis(target.ndim() - 1));
+                for (x, mut y) in input
+                    .lanes(in_axis)
+                    .into_iter()
+                    .zip(target.lanes_mut(out_axis))
+                {
+                    for (i, y) in y.iter_mut().enumerate() {
+                        let row = indptr[i]..indptr[i + 1];
+                        *y += indices[row.clone()]
+                            .iter()
+                            .zip(&data[row])
+                            .map(|(&j, &v)| v * x[j])
+                            .sum::<f64>();
+                    }
+                }
+            })
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.weights, &*self.input]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn increments(&self) -> bool {
+        true
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        let (input, target) = (self.input.shape(), self.target.shape());
+        let (rows, cols) = (self.weights.shape()[0], self.weights.shape()[1]);
+        if input.is_empty()
+            || input.len() != target.len()
+            || input[..input.len() - 1] != target[..target.len() - 1]
+            || input.last() != Some(&cols)
+            || target.last() != Some(&rows)
+        {
+            return Err(ShapeError {
+                message: format!(
+                    "Cannot increment target of shape {:?} by the product of weights of \
+                     shape {:?} with input of shape {:?}.",
+                    target,
+                    self.weights.shape(),
+                    input
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    #[test]
+    fn it_matches_the_dense_product() {
+        let dense = array![[0., 2., 0.], [0., 0., 0.], [1., 0., -3.]];
+        let op = SparseDotInc {
+            target: Arc::new(ArraySignal::from_array(
+                "target".to_string(),
+                Array::ones(IxDyn(&[2, 3])),
+            )),
+            weights: Arc::new(SparseSignal::from_dense(
+                "weights".to_string(),
+                dense.view(),
+            )),
+            input: Arc::new(ArraySignal::from_array(
+                "input".to_string(),
+                array![[1., 2., 3.], [4., 5., 6.]].into_dyn(),
+            )),
+        };
+        op.check_shapes().unwrap();
+        assert_eq!(op.weights.nnz(), 3);
+
+        op.step().unwrap();
+
+        assert_eq!(
+            **op.target.read(),
+            array![[5., 1., -7.], [11., 1., -13.]].into_dyn()
+        );
+    }
+}
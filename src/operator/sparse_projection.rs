@@ -0,0 +1,190 @@
+use crate::operator::{Operator, OperatorError};
+use crate::rng::{derive_seed, RngStream};
+use crate::signal::{ArraySignal, ShapeError, Signal, SignalAccess};
+use ndarray::Axis;
+use std::mem;
+use std::sync::{Arc, OnceLock};
+
+/// Nonzero entries of each row of a sparse sign matrix as the column index
+/// and whether the entry is negative.
+type SparseRows = Vec<Vec<(usize, bool)>>;
+
+/// Adds a sparse random projection of `input` to `target` along the last
+/// axis. Each entry of the projection matrix is nonzero with probability
+/// `density` and then `±1 / sqrt(density * d_out)` with equal probability,
+/// which preserves lengths in expectation. The matrix is determined by `seed`
+/// and only its nonzero entries are stored.
+#[derive(Debug)]
+pub struct SparseProjection {
+    pub target: Arc<ArraySignal<f64>>,
+    pub input: Arc<ArraySignal<f64>>,
+    pub density: f64,
+    pub seed: u64,
+    /// Drawn on the first step, as the size is fixed by the signals.
+    pub rows: OnceLock<SparseRows>,
+}
+
+impl SparseProjection {
+    /// Draws the gaps between nonzero entries, so that the time is
+    /// proportional to their number rather than to the size of the matrix.
+    fn draw_rows(&self, d_out: usize, d_in: usize) -> SparseRows {
+        (0..d_out)
+            .map(|i| {
+                let stream = RngStream::new(derive_seed(self.seed, i as u64));
+                let mut row = vec![];
+                let mut j = 0.;
+                loop {
+                    if self.density < 1. {
+                        let u = 1. - stream.next_f64();
+                        // `ln_1p` keeps tiny densities from rounding to a
+                        // zero denominator.
+                        j += (u.ln() / (-self.density).ln_1p()).floor();
+                    }
+                    if j >= d_in as f64 {
+                        break row;
+                    }
+                    row.push((j as usize, stream.next_u64() & 1 == 1));
+                    j += 1.;
+                }
+            })
+            .collect()
+    }
+}
+
+impl Operator for SparseProjection {
+    fn step(&self) -> Result<(), OperatorError> {
+        let d_out = self.target.shape().last().copied().unwrap_or(1);
+        let d_in = self.input.shape().last().copied().unwrap_or(1);
+        let rows = self.rows.get_or_init(|| self.draw_rows(d_out, d_in));
+        let scale = 1. / (self.density * d_out as f64).sqrt();
+        let input = self.input.read();
+        let mut target = self.target.write();
+        input.with_view(|input| {
+            target.with_view_mut(|mut target| {
+                let (in_axis, out_axis) = (Axis(input.ndim() - 1), Axis(target.ndim() - 1));
+                for (x, mut y) in input
+                    .lanes(in_axis)
+                    .into_iter()
+                    .zip(target.lanes_mut(out_axis))
+                {
+                    for (y, row) in y.iter_mut().zip(rows) {
+                        let sum: f64 = row
+                            .iter()
+                            .map(|&(j, negative)| if negative { -x[j] } else { x[j] })
+                            .sum();
+                        *y += scale * sum;
+                    }
+                }
+            })
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.input]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn increments(&self) -> bool {
+        true
+    }
+
+    /// The random matrix is drawn on the first step and kept afterwards.
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.rows.get().map_or(0, |rows| {
+            rows.iter().map(Vec::len).sum::<usize>() * mem::size_of::<(usize, bool)>()
+        })
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        let (input, target) = (self.input.shape(), self.target.shape());
+        if !(self.density > 0. && self.density <= 1.) {
+            return Err(ShapeError {
+                message: format!("Density {} must be in (0, 1].", self.density),
+            });
+        }
+        if input.is_empty()
+            || input.len() != target.len()
+            || input[..input.len() - 1] != target[..target.len() - 1]
+        {
+            return Err(ShapeError {
+                message: format!(
+                    "Input of shape {:?} cannot be projected to target of shape {:?}.",
+                    input, target
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    fn projection(density: f64, seed: u64) -> SparseProjection {
+        SparseProjection {
+            target: Arc::new(ArraySignal::from_array(
+                "target".to_string(),
+                Array::zeros(IxDyn(&[200])),
+            )),
+            input: Arc::new(ArraySignal::from_array(
+                "input".to_string(),
+                Array::ones(IxDyn(&[500])),
+            )),
+            density,
+            seed,
+            rows: OnceLock::new(),
+        }
+    }
+
+    #[test]
+    fn it_stores_only_the_nonzero_entries() {
+        let op = projection(0.1, 1);
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        let n_nonzero: usize = op.rows.get().unwrap().iter().map(Vec::len).sum();
+        assert!((9000..11000).contains(&n_nonzero), "{}", n_nonzero);
+        let length: f64 = op.target.read().clone_array().iter().map(|y| y * y).sum();
+        assert!((length / 500. - 1.).abs() < 0.2, "{}", length);
+    }
+
+    #[test]
+    fn it_draws_the_same_matrix_for_the_same_seed() {
+        let matrix = |seed| {
+            let op = projection(0.1, seed);
+            op.step().unwrap();
+            let target = op.target.read().clone_array();
+            target
+        };
+
+        assert_eq!(matrix(1), matrix(1));
+        assert_ne!(matrix(1), matrix(2));
+    }
+
+    #[test]
+    fn it_draws_tiny_densities() {
+        let op = projection(1e-17, 1);
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        assert!(op.rows.get().unwrap().iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn it_rejects_invalid_densities() {
+        assert!(projection(0., 1).check_shapes().is_err());
+        assert!(projection(1.5, 1).check_shapes().is_err());
+    }
+}
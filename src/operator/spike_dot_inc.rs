@@ -0,0 +1,155 @@
+use crate::operator::{ErrorCause, Operator, OperatorError};
+use crate::signal::{ArraySignal, ShapeError, Signal, SignalAccess};
+use ndarray::{Ix2, LinalgScalar};
+use numpy::Element;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct SpikeDotInc<T>
+where
+    T: Element,
+{
+    pub target: Arc<ArraySignal<T>>,
+    pub left: Arc<ArraySignal<T>>,
+    pub right: Arc<ArraySignal<T>>,
+}
+
+impl<T> Operator for SpikeDotInc<T>
+where
+    T: Element + LinalgScalar + Debug + Send + Sync,
+{
+    fn step(&self) -> Result<(), OperatorError> {
+        let spikes: Vec<(usize, T)> = self.right.read().with_view(|right| {
+            right
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| !x.is_zero())
+                .map(|(i, x)| (i, *x))
+                .collect()
+        });
+        if spikes.is_empty() {
+            return Ok(());
+        }
+
+        let left = self.left.read();
+        let mut target = self.target.write();
+        left.with_view(|left| {
+            let shape_error = |left: &[usize]| {
+                OperatorError::new(
+                    self,
+                    ErrorCause::Shape(format!(
+                        "Cannot multiply arrays of shape {:?} and {:?} into target of shape {:?}.",
+                        left,
+                        self.right.shape(),
+                        self.target.shape()
+                    )),
+                )
+            };
+            let left_shape = left.shape().to_vec();
+            let left = left
+                .into_dimensionality::<Ix2>()
+                .map_err(|_| shape_error(&left_shape))?;
+            if left.ncols() != self.right.shape().iter().product()
+                || self.target.shape() != [left.nrows()]
+            {
+                return Err(shape_error(&left_shape));
+            }
+            target.with_view_mut(|mut target| {
+                for (i, x) in spikes {
+                    target.scaled_add(x, &left.column(i));
+                }
+            });
+            Ok(())
+        })
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.left, &*self.right]
+    }
+
+    fn increments(&self) -> bool {
+        true
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        match (self.left.shape(), self.right.shape(), self.target.shape()) {
+            ([m, n], [k], [l]) if n == k && m == l => Ok(()),
+            (left, right, target) => Err(ShapeError {
+                message: format!(
+                    "Cannot multiply arrays of shape {:?} and {:?} into target of shape {:?}.",
+                    left, right, target
+                ),
+            }),
+        }
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::venv::activate_venv;
+    use ndarray::prelude::*;
+    use numpy::IntoPyArray;
+    use pyo3::Python;
+
+    #[test]
+    fn it_accumulates_the_columns_of_spiking_inputs() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let op = SpikeDotInc::<u64> {
+            target: Arc::new(ArraySignal::new(
+                "target".to_string(),
+                Array::ones(IxDyn(&[2])).into_pyarray(py),
+            )),
+            left: Arc::new(ArraySignal::new(
+                "left".to_string(),
+                array![[2, 3, 4], [5, 6, 7]].into_dyn().into_pyarray(py),
+            )),
+            right: Arc::new(ArraySignal::new(
+                "right".to_string(),
+                array![0, 10, 0].into_dyn().into_pyarray(py),
+            )),
+        };
+        for signal in [&op.target, &op.left, &op.right].iter() {
+            signal.reset();
+        }
+
+        op.step().unwrap();
+
+        assert_eq!(**op.target.read(), array![31, 61].into_dyn());
+    }
+
+    #[test]
+    fn it_leaves_the_target_unchanged_without_spikes() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        activate_venv(py);
+        let op = SpikeDotInc::<u64> {
+            target: Arc::new(ArraySignal::new(
+                "target".to_string(),
+                Array::ones(IxDyn(&[2])).into_pyarray(py),
+            )),
+            left: Arc::new(ArraySignal::new(
+                "left".to_string(),
+                array![[2, 3], [5, 6]].into_dyn().into_pyarray(py),
+            )),
+            right: Arc::new(ArraySignal::new(
+                "right".to_string(),
+                Array::zeros(IxDyn(&[2])).into_pyarray(py),
+            )),
+        };
+        for signal in [&op.target, &op.left, &op.right].iter() {
+            signal.reset();
+        }
+
+        op.step().unwrap();
+
+        assert_eq!(**op.target.read(), array![1, 1].into_dyn());
+    }
+}
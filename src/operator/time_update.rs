@@ -1,25 +1,93 @@
-use crate::operator::Operator;
-use crate::signal::{ScalarSignal, SignalAccess};
-use std::sync::Arc;
+use crate::operator::{ErrorCause, Operator, OperatorError};
+use crate::signal::{ScalarSignal, Signal, SignalAccess};
+use std::sync::{Arc, Mutex};
+
+/// State of the Kahan summation of variable timesteps.
+#[derive(Debug, Default)]
+pub struct TimeCompensation {
+    /// Time written last, to notice when the time signal was reset or set.
+    time: f64,
+    /// Low-order bits lost in the previous additions.
+    error: f64,
+}
 
 #[derive(Debug)]
 pub struct TimeUpdate<T, S> {
     pub dt: T,
     pub step_target: Arc<ScalarSignal<S>>,
     pub time_target: Arc<ScalarSignal<T>>,
+    /// If given, the time advances by the current value of this signal
+    /// instead of the fixed `dt`.
+    pub dt_signal: Option<Arc<ScalarSignal<T>>>,
+    /// Whether to use compensated summation of the `dt_signal` values, so
+    /// that the time stays accurate over many steps.
+    pub compensated: bool,
+    pub compensation: Mutex<TimeCompensation>,
+}
+
+impl TimeUpdate<f64, u64> {
+    fn advance_compensated(&self, time: &mut f64, dt: f64) {
+        let mut compensation = self.compensation.lock().unwrap();
+        if compensation.time != *time {
+            compensation.error = 0.;
+        }
+        let dt = dt - compensation.error;
+        let sum = *time + dt;
+        compensation.error = (sum - *time) - dt;
+        compensation.time = sum;
+        *time = sum;
+    }
 }
 
 impl Operator for TimeUpdate<f64, u64> {
-    fn step(&self) {
-        **self.step_target.write() += 1;
-        **self.time_target.write() = **self.step_target.read() as f64 * self.dt;
+    fn step(&self) -> Result<(), OperatorError> {
+        let step = {
+            let mut step = self.step_target.write();
+            **step = step.checked_add(1).ok_or_else(|| {
+                OperatorError::new(
+                    self,
+                    ErrorCause::Overflow(format!("Step counter overflowed after {}.", **step)),
+                )
+            })?;
+            **step
+        };
+        let mut time = self.time_target.write();
+        match &self.dt_signal {
+            Some(dt) if self.compensated => self.advance_compensated(&mut time, **dt.read()),
+            Some(dt) => **time += **dt.read(),
+            None => **time = step as f64 * self.dt,
+        }
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        match &self.dt_signal {
+            Some(dt) => vec![&**dt],
+            None => vec![],
+        }
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.step_target, &*self.time_target]
+    }
+
+    /// The step and time advance from their previous values, and the
+    /// compensation is kept across steps.
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    fn dt(&self) -> Option<f64> {
+        match self.dt_signal {
+            Some(_) => None,
+            None => Some(self.dt),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::signal::Signal;
 
     #[test]
     fn it_increments_the_steps() {
@@ -27,12 +95,15 @@ mod tests {
             dt: 0.001,
             step_target: Arc::new(ScalarSignal::new("step_target".to_string(), 0)),
             time_target: Arc::new(ScalarSignal::new("time_target".to_string(), 0.)),
+            dt_signal: None,
+            compensated: false,
+            compensation: Default::default(),
         };
         op.step_target.reset();
         op.time_target.reset();
 
         for _ in 0..3 {
-            op.step();
+            op.step().unwrap();
         }
 
         assert_eq!(**op.step_target.read(), 3);
@@ -44,14 +115,81 @@ mod tests {
             dt: 0.001,
             step_target: Arc::new(ScalarSignal::new("step_target".to_string(), 0)),
             time_target: Arc::new(ScalarSignal::new("time_target".to_string(), 0.)),
+            dt_signal: None,
+            compensated: false,
+            compensation: Default::default(),
         };
         op.step_target.reset();
         op.time_target.reset();
 
         for _ in 0..3 {
-            op.step();
+            op.step().unwrap();
         }
 
         assert_eq!(**op.time_target.read(), 3. * op.dt);
     }
+
+    #[test]
+    fn it_advances_the_time_by_the_dt_signal() {
+        let dt = Arc::new(ScalarSignal::new("dt".to_string(), 0.5));
+        let op = TimeUpdate::<f64, u64> {
+            dt: 0.001,
+            step_target: Arc::new(ScalarSignal::new("step_target".to_string(), 0)),
+            time_target: Arc::new(ScalarSignal::new("time_target".to_string(), 0.)),
+            dt_signal: Some(Arc::clone(&dt)),
+            compensated: false,
+            compensation: Default::default(),
+        };
+        op.step_target.reset();
+        op.time_target.reset();
+        dt.reset();
+
+        op.step().unwrap();
+        **dt.write() = 0.25;
+        op.step().unwrap();
+
+        assert_eq!(**op.step_target.read(), 2);
+        assert_eq!(**op.time_target.read(), 0.75);
+        assert_eq!(op.dt(), None);
+    }
+
+    #[test]
+    fn it_fails_when_the_step_counter_overflows() {
+        let op = TimeUpdate::<f64, u64> {
+            dt: 0.001,
+            step_target: Arc::new(ScalarSignal::new("step_target".to_string(), u64::MAX)),
+            time_target: Arc::new(ScalarSignal::new("time_target".to_string(), 0.)),
+            dt_signal: None,
+            compensated: false,
+            compensation: Default::default(),
+        };
+
+        assert!(matches!(
+            op.step().unwrap_err().cause,
+            ErrorCause::Overflow(_)
+        ));
+        assert_eq!(**op.step_target.read(), u64::MAX);
+    }
+
+    #[test]
+    fn it_compensates_the_rounding_of_variable_timesteps() {
+        let accumulate = |compensated| {
+            let op = TimeUpdate::<f64, u64> {
+                dt: 0.001,
+                step_target: Arc::new(ScalarSignal::new("step_target".to_string(), 0)),
+                time_target: Arc::new(ScalarSignal::new("time_target".to_string(), 1e6)),
+                dt_signal: Some(Arc::new(ScalarSignal::new("dt".to_string(), 0.001))),
+                compensated,
+                compensation: Default::default(),
+            };
+            for _ in 0..10000 {
+                op.step().unwrap();
+            }
+            let time = **op.time_target.read();
+            time
+        };
+
+        assert_eq!(accumulate(true), 1e6 + 10.);
+        assert!((accumulate(false) - (1e6 + 10.)).abs() > 1e-7);
+    }
 }
@@ -0,0 +1,291 @@
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{check_broadcastable, ArraySignal, ShapeError, Signal, SignalAccess};
+use ndarray::{Array2, ArrayD, Axis, Zip};
+use std::mem;
+use std::sync::Arc;
+
+/// Sets `target` to the sum of the `inputs`, scaled to unit length along the
+/// last axis if `normalize`. Sums of length zero are not scaled.
+#[derive(Debug)]
+pub struct Superposition {
+    pub target: Arc<ArraySignal<f64>>,
+    pub inputs: Vec<Arc<ArraySignal<f64>>>,
+    pub normalize: bool,
+}
+
+impl Operator for Superposition {
+    fn step(&self) -> Result<(), OperatorError> {
+        let mut sum = ArrayD::zeros(self.target.read().with_view(|t| t.raw_dim()));
+        for input in self.inputs.iter() {
+            input.read().with_view(|input| sum += &input);
+        }
+        if self.normalize {
+            let axis = Axis(sum.ndim().max(1) - 1);
+            for mut row in sum.lanes_mut(axis) {
+                let norm = row.dot(&row).sqrt();
+                if norm > 0. {
+                    row /= norm;
+                }
+            }
+        }
+        self.target.write().assign_array(&sum);
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        self.inputs.iter().map(|s| &**s as &dyn Signal).collect()
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        for input in self.inputs.iter() {
+            check_broadcastable(input.shape(), self.target.shape())?;
+        }
+        Ok(())
+    }
+}
+
+/// Sets `target` to the involution of `input` along the last axis, which
+/// keeps the first element and reverses the others. It is the approximate
+/// inverse of a semantic pointer under circular convolution.
+#[derive(Debug)]
+pub struct Involution {
+    pub target: Arc<ArraySignal<f64>>,
+    pub input: Arc<ArraySignal<f64>>,
+}
+
+impl Operator for Involution {
+    fn step(&self) -> Result<(), OperatorError> {
+        let mut involution = self.input.read().clone_array();
+        let axis = Axis(involution.ndim().max(1) - 1);
+        for mut row in involution.lanes_mut(axis) {
+            row.as_slice_mut().unwrap()[1..].reverse();
+        }
+        self.target.write().assign_array(&involution);
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.input]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        if self.input.shape() != self.target.shape() {
+            return Err(ShapeError {
+                message: format!(
+                    "Input of shape {:?} does not fit target of shape {:?}.",
+                    self.input.shape(),
+                    self.target.shape()
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Sets `target` to the dot product of `input` with each row of
+/// `vocabulary`, which holds one semantic pointer per row.
+#[derive(Debug)]
+pub struct Similarity {
+    pub target: Arc<ArraySignal<f64>>,
+    pub input: Arc<ArraySignal<f64>>,
+    pub vocabulary: Array2<f64>,
+}
+
+impl Operator for Similarity {
+    fn step(&self) -> Result<(), OperatorError> {
+        let d = self.vocabulary.ncols();
+        let input = self.input.read().clone_array();
+        let rows = input.len() / d.max(1);
+        let input = input.into_shape((rows, d)).unwrap();
+        let similarities = input.dot(&self.vocabulary.t());
+        let mut target = self.target.write();
+        target.with_view_mut(|target| {
+            let target = target.into_shape((rows, self.vocabulary.nrows())).unwrap();
+            Zip::from(target).and(&similarities).apply(|t, &s| *t = s);
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.input]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.vocabulary.len() * mem::size_of::<f64>()
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        let (input, target) = (self.input.shape(), self.target.shape());
+        let (n, d) = self.vocabulary.dim();
+        if input.last().copied().unwrap_or(1) != d
+            || target.last().copied().unwrap_or(1) != n
+            || input[..input.len().saturating_sub(1)] != target[..target.len().saturating_sub(1)]
+        {
+            return Err(ShapeError {
+                message: format!(
+                    "Input of shape {:?} and target of shape {:?} do not fit a vocabulary \
+                 of {} pointers with {} dimensions.",
+                    input, target, n, d
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Heteroassociative cleanup memory: sets `target` to the `values` recalled
+/// by `input`, weighting the value of each key in `keys` by its similarity
+/// to `input`, or by one if `binary`. Keys with a similarity of at most
+/// `threshold` are not recalled. Keys and values are stored one per row.
+#[derive(Debug)]
+pub struct AssociativeMemory {
+    pub target: Arc<ArraySignal<f64>>,
+    pub input: Arc<ArraySignal<f64>>,
+    pub keys: Array2<f64>,
+    pub values: Array2<f64>,
+    pub threshold: f64,
+    pub binary: bool,
+}
+
+impl Operator for AssociativeMemory {
+    fn step(&self) -> Result<(), OperatorError> {
+        let input = self.input.read().clone_array();
+        let rows = input.len() / self.keys.ncols().max(1);
+        let input = input.into_shape((rows, self.keys.ncols())).unwrap();
+        let mut weights = input.dot(&self.keys.t());
+        weights.mapv_inplace(|s| match s {
+            s if s <= self.threshold => 0.,
+            _ if self.binary => 1.,
+            s => s,
+        });
+        let recalled = weights.dot(&self.values);
+        let mut target = self.target.write();
+        target.with_view_mut(|target| {
+            let target = target.into_shape((rows, self.values.ncols())).unwrap();
+            Zip::from(target).and(&recalled).apply(|t, &r| *t = r);
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.input]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.target]
+    }
+
+    fn memory_usage(&self) -> usize {
+        (self.keys.len() + self.values.len()) * mem::size_of::<f64>()
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        let (input, target) = (self.input.shape(), self.target.shape());
+        if self.keys.nrows() != self.values.nrows()
+            || input.last().copied().unwrap_or(1) != self.keys.ncols()
+            || target.last().copied().unwrap_or(1) != self.values.ncols()
+            || input[..input.len().saturating_sub(1)] != target[..target.len().saturating_sub(1)]
+        {
+            return Err(ShapeError {
+                message: format!(
+                    "Input of shape {:?} and target of shape {:?} do not fit {:?} keys \
+                     and {:?} values.",
+                    input,
+                    target,
+                    self.keys.dim(),
+                    self.values.dim()
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    fn signal(name: &str, value: ArrayD<f64>) -> Arc<ArraySignal<f64>> {
+        Arc::new(ArraySignal::from_array(name.to_string(), value))
+    }
+
+    #[test]
+    fn superposition_normalizes_the_sum() {
+        let op = Superposition {
+            target: signal("target", Array::zeros(IxDyn(&[2]))),
+            inputs: vec![
+                signal("a", array![3., 0.].into_dyn()),
+                signal("b", array![0., 4.].into_dyn()),
+            ],
+            normalize: true,
+        };
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        assert_eq!(**op.target.read(), array![0.6, 0.8].into_dyn());
+    }
+
+    #[test]
+    fn involution_reverses_all_but_the_first_element() {
+        let op = Involution {
+            target: signal("target", Array::zeros(IxDyn(&[2, 3]))),
+            input: signal("input", array![[1., 2., 3.], [4., 5., 6.]].into_dyn()),
+        };
+
+        op.step().unwrap();
+
+        assert_eq!(
+            **op.target.read(),
+            array![[1., 3., 2.], [4., 6., 5.]].into_dyn()
+        );
+    }
+
+    #[test]
+    fn similarity_compares_against_each_pointer() {
+        let op = Similarity {
+            target: signal("target", Array::zeros(IxDyn(&[3]))),
+            input: signal("input", array![1., 2.].into_dyn()),
+            vocabulary: array![[1., 0.], [0., 1.], [1., 1.]],
+        };
+        op.check_shapes().unwrap();
+
+        op.step().unwrap();
+
+        assert_eq!(**op.target.read(), array![1., 2., 3.].into_dyn());
+    }
+
+    #[test]
+    fn associative_memory_recalls_the_values_of_similar_keys() {
+        let memory = |binary| AssociativeMemory {
+            target: signal("target", Array::zeros(IxDyn(&[3]))),
+            input: signal("input", array![0.8, 0.2].into_dyn()),
+            keys: array![[1., 0.], [0., 1.]],
+            values: array![[1., 0., 0.], [0., 0., 1.]],
+            threshold: 0.3,
+            binary,
+        };
+
+        let op = memory(false);
+        op.check_shapes().unwrap();
+        op.step().unwrap();
+        assert_eq!(**op.target.read(), array![0.8, 0., 0.].into_dyn());
+
+        let op = memory(true);
+        op.step().unwrap();
+        assert_eq!(**op.target.read(), array![1., 0., 0.].into_dyn());
+    }
+}
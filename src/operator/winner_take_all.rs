@@ -0,0 +1,140 @@
+use crate::operator::{Operator, OperatorError};
+use crate::signal::{ArraySignal, ShapeError, Signal, SignalAccess};
+use ndarray::{Axis, LinalgScalar};
+use numpy::Element;
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Lets the `k` largest entries along the last axis of the input win.
+///
+/// Winners are set to one with `one_hot`, or keep their input value
+/// otherwise. All other entries are inhibited to zero. Ties are resolved in
+/// favour of the lower index.
+#[derive(Debug)]
+pub struct WinnerTakeAll<T>
+where
+    T: Element,
+{
+    pub input: Arc<ArraySignal<T>>,
+    pub output: Arc<ArraySignal<T>>,
+    pub k: usize,
+    pub one_hot: bool,
+}
+
+/// Orders larger values first and values not comparable to themselves, such
+/// as NaN, last, so that the order stays total.
+fn rank<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    let is_nan = |v: &T| v.partial_cmp(v).is_none();
+    match (is_nan(a), is_nan(b)) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => b.partial_cmp(a).unwrap_or(Ordering::Equal),
+    }
+}
+
+impl<T> Operator for WinnerTakeAll<T>
+where
+    T: Element + LinalgScalar + PartialOrd + Debug + Send + Sync,
+{
+    fn step(&self) -> Result<(), OperatorError> {
+        let input = self.input.read();
+        let mut output = self.output.write();
+        input.with_view(|input| {
+            output.with_view_mut(|mut output| {
+                let axis = Axis(input.ndim() - 1);
+                for (x, mut y) in input.lanes(axis).into_iter().zip(output.lanes_mut(axis)) {
+                    let mut order: Vec<usize> = (0..x.len()).collect();
+                    order.sort_by(|&a, &b| rank(&x[a], &x[b]));
+                    y.fill(T::zero());
+                    for &i in order.iter().take(self.k) {
+                        y[i] = if self.one_hot { T::one() } else { x[i] };
+                    }
+                }
+            })
+        });
+        Ok(())
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.input]
+    }
+
+    fn writes(&self) -> Vec<&dyn Signal> {
+        vec![&*self.output]
+    }
+
+    fn check_shapes(&self) -> Result<(), ShapeError> {
+        if self.input.shape().is_empty() || self.input.shape() != self.output.shape() {
+            return Err(ShapeError {
+                message: format!(
+                    "Input of shape {:?} must have at least one dimension \
+                     and match output of shape {:?}.",
+                    self.input.shape(),
+                    self.output.shape()
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::prelude::*;
+
+    fn winner_take_all(
+        input: ArrayD<f64>,
+        k: usize,
+        one_hot: bool,
+    ) -> (WinnerTakeAll<f64>, Arc<ArraySignal<f64>>) {
+        let output = Arc::new(ArraySignal::from_array(
+            "output".to_string(),
+            ArrayD::zeros(input.shape()),
+        ));
+        let op = WinnerTakeAll {
+            input: Arc::new(ArraySignal::from_array("input".to_string(), input)),
+            output: Arc::clone(&output),
+            k,
+            one_hot,
+        };
+        (op, output)
+    }
+
+    #[test]
+    fn it_selects_the_top_k_entries() {
+        let (op, output) = winner_take_all(array![0.1, 0.7, 0.3, 0.5].into_dyn(), 2, false);
+        op.step().unwrap();
+        assert_eq!(
+            output.read().clone_array(),
+            array![0., 0.7, 0., 0.5].into_dyn()
+        );
+    }
+
+    #[test]
+    fn it_encodes_the_winners_one_hot_per_lane() {
+        let (op, output) =
+            winner_take_all(array![[0.2, 0.2, 0.1], [0.0, 0.3, 0.9]].into_dyn(), 1, true);
+        op.step().unwrap();
+        assert_eq!(
+            output.read().clone_array(),
+            array![[1., 0., 0.], [0., 0., 1.]].into_dyn()
+        );
+    }
+
+    #[test]
+    fn it_ranks_nan_inputs_last() {
+        let (op, output) = winner_take_all(
+            array![f64::NAN, 0.2, f64::NAN, 0.1, 0.4, f64::NAN].into_dyn(),
+            2,
+            true,
+        );
+        op.step().unwrap();
+        assert_eq!(
+            output.read().clone_array(),
+            array![0., 1., 0., 0., 1., 0.].into_dyn()
+        );
+    }
+}
@@ -1,46 +1,464 @@
+use crate::logging::log_warning;
 use crate::signal::{ArraySignal, ScalarSignal, Signal, SignalAccess};
-use ndarray::ArrayD;
+use ndarray::{Array2, ArrayD, Axis, IxDyn, LinalgScalar, Zip};
 use numpy::Element;
 use std::any::Any;
 use std::fmt::Debug;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::process;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 pub trait Probe {
     fn as_any(&self) -> &dyn Any;
     fn probe(&mut self);
+    fn reads(&self) -> Vec<&dyn Signal>;
+
+    /// Limits the memory used for samples, if the probe supports it. Samples
+    /// exceeding the budget are moved to a temporary file.
+    fn set_memory_budget(&mut self, _bytes: Option<usize>) {}
+
+    /// Bytes used by the samples held in memory.
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    /// Preallocates memory for the samples of another `n_steps` steps.
+    fn reserve(&mut self, _n_steps: usize) {}
+
+    /// Average growth of `memory_usage` per step, ignoring the memory budget.
+    fn bytes_per_step(&self) -> f64 {
+        0.
+    }
+
+    /// Number of samples taken, including those moved to a temporary file.
+    fn n_samples(&self) -> usize {
+        0
+    }
+}
+
+/// Reduces each window of `window` consecutive samples to a single sample.
+pub struct Decimation<T> {
+    window: usize,
+    combine: fn(&mut T, &T),
+    finish: fn(&mut T, usize),
+    pending: Option<(T, usize)>,
+}
+
+impl<T: Clone> Decimation<T> {
+    /// Keeps the last sample of each window.
+    pub fn sample(window: usize) -> Self {
+        Self::new(window, |acc, x| acc.clone_from(x), |_, _| ())
+    }
+}
+
+impl<T> Decimation<T> {
+    fn new(window: usize, combine: fn(&mut T, &T), finish: fn(&mut T, usize)) -> Self {
+        Decimation {
+            window,
+            combine,
+            finish,
+            pending: None,
+        }
+    }
+
+    /// Adds a sample and returns the aggregate once a window is complete.
+    fn push(&mut self, x: T) -> Option<T> {
+        let (acc, count) = match self.pending.take() {
+            Some((mut acc, count)) => {
+                (self.combine)(&mut acc, &x);
+                (acc, count + 1)
+            }
+            None => (x, 1),
+        };
+        if count < self.window {
+            self.pending = Some((acc, count));
+            None
+        } else {
+            let mut acc = acc;
+            (self.finish)(&mut acc, count);
+            Some(acc)
+        }
+    }
+}
+
+impl Decimation<ArrayD<f64>> {
+    pub fn mean(window: usize) -> Self {
+        Self::new(window, |acc, x| *acc += x, |acc, n| *acc /= n as f64)
+    }
+
+    pub fn max(window: usize) -> Self {
+        Self::new(
+            window,
+            |acc, x| acc.zip_mut_with(x, |a, &b| *a = a.max(b)),
+            |_, _| (),
+        )
+    }
+
+    pub fn min(window: usize) -> Self {
+        Self::new(
+            window,
+            |acc, x| acc.zip_mut_with(x, |a, &b| *a = a.min(b)),
+            |_, _| (),
+        )
+    }
+}
+
+/// Computes each sample from the probed signal instead of copying all of it.
+pub struct Projection<T, S> {
+    shape: Vec<usize>,
+    apply: Box<dyn Fn(&S) -> T + Send + Sync>,
+    /// Signals read in addition to the probed one.
+    reads: Vec<Arc<dyn Signal + Send + Sync>>,
+}
+
+impl<T: Element + Copy + Debug + Send + Sync + 'static> Projection<ArrayD<T>, ArraySignal<T>> {
+    /// Keeps the entries at `indices` along the last axis of `signal`, which
+    /// must be in bounds.
+    pub fn select(signal: &ArraySignal<T>, indices: Vec<usize>) -> Self {
+        let mut shape = signal.shape().to_vec();
+        if let Some(last) = shape.last_mut() {
+            *last = indices.len();
+        }
+        Projection {
+            shape,
+            apply: Box::new(move |signal: &ArraySignal<T>| {
+                signal
+                    .read()
+                    .with_view(|x| x.select(Axis(x.ndim() - 1), &indices))
+            }),
+            reads: vec![],
+        }
+    }
+
+    /// Concatenates `signals` along their last axis instead of recording the
+    /// probed signal. The other axes of all signals must match.
+    pub fn concatenate(signals: &[Arc<ArraySignal<T>>]) -> Self {
+        let mut shape = signals
+            .first()
+            .map(|s| s.shape().to_vec())
+            .unwrap_or_default();
+        if let Some(last) = shape.last_mut() {
+            *last = signals.iter().map(|s| s.shape().last().unwrap()).sum();
+        }
+        let concatenated = signals.to_vec();
+        Projection {
+            shape,
+            apply: Box::new(move |_: &ArraySignal<T>| {
+                let parts: Vec<ArrayD<T>> = concatenated
+                    .iter()
+                    .map(|s| s.read().clone_array())
+                    .collect();
+                let views: Vec<_> = parts.iter().map(|p| p.view()).collect();
+                ndarray::stack(Axis(views[0].ndim() - 1), &views).unwrap()
+            }),
+            reads: signals
+                .iter()
+                .map(|s| Arc::clone(s) as Arc<dyn Signal + Send + Sync>)
+                .collect(),
+        }
+    }
+}
+
+impl<T> Projection<ArrayD<T>, ArraySignal<T>>
+where
+    T: Element + LinalgScalar + Debug + Send + Sync + 'static,
+{
+    /// Multiplies the last axis of `signal` with `transform`, e.g. to record
+    /// the decoded value of neuron activities. The number of columns of
+    /// `transform` must match the length of the last axis.
+    pub fn transform(signal: &ArraySignal<T>, transform: Array2<T>) -> Self {
+        let mut shape = signal.shape().to_vec();
+        match shape.last_mut() {
+            Some(last) => *last = transform.nrows(),
+            None => shape.push(transform.nrows()),
+        }
+        let output_shape = shape.clone();
+        Projection {
+            shape,
+            apply: Box::new(move |signal: &ArraySignal<T>| {
+                signal.read().with_view(|x| {
+                    let x = x.as_standard_layout();
+                    let x = x
+                        .view()
+                        .into_shape((x.len() / transform.ncols(), transform.ncols()))
+                        .unwrap();
+                    x.dot(&transform.t())
+                        .into_shape(IxDyn(&output_shape))
+                        .unwrap()
+                })
+            }),
+            reads: vec![],
+        }
+    }
 }
 
 pub struct SignalProbe<T, S: Signal> {
     signal: Arc<S>,
+    projection: Option<Projection<T, S>>,
     data: Vec<T>,
+    time: Option<Arc<ScalarSignal<f64>>>,
+    times: Vec<f64>,
+    decimation: Option<Decimation<T>>,
+    memory_budget: Option<usize>,
+    spill: Option<SpillFile>,
+    /// Whether the last attempt to spill samples failed, so that the failure
+    /// is only logged once.
+    spill_failed: bool,
+}
+
+/// Temporary file holding samples spilled from memory, deleted on drop.
+struct SpillFile {
+    path: PathBuf,
+    file: File,
+    n_samples: usize,
+}
+
+impl SpillFile {
+    fn create() -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "nengo-rs-probe-{}-{}.bin",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(SpillFile {
+            path,
+            file,
+            n_samples: 0,
+        })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
 }
 
 impl<T, S: Signal> SignalProbe<T, S> {
     pub fn new(signal: &Arc<S>) -> Self {
         SignalProbe::<T, S> {
             signal: Arc::clone(signal),
+            projection: None,
             data: vec![],
+            time: None,
+            times: vec![],
+            decimation: None,
+            memory_budget: None,
+            spill: None,
+            spill_failed: false,
+        }
+    }
+
+    /// Only stores one aggregated sample per window of `decimation`. Like
+    /// nengo's `sample_every`, a trailing incomplete window is not stored.
+    pub fn decimated(self, decimation: Decimation<T>) -> Self {
+        SignalProbe::<T, S> {
+            decimation: Some(decimation),
+            ..self
+        }
+    }
+
+    /// Records the result of `projection` instead of the whole signal.
+    pub fn projected(self, projection: Projection<T, S>) -> Self {
+        SignalProbe::<T, S> {
+            projection: Some(projection),
+            ..self
+        }
+    }
+
+    /// Creates a probe that also records the value of `time` with each sample.
+    pub fn with_time(signal: &Arc<S>, time: &Arc<ScalarSignal<f64>>) -> Self {
+        SignalProbe::<T, S> {
+            time: Some(Arc::clone(time)),
+            ..Self::new(signal)
         }
     }
+
+    /// Simulation times of the samples, if the probe records them.
+    pub fn get_times(&self) -> Option<&Vec<f64>> {
+        self.time.as_ref().map(|_| &self.times)
+    }
+
+    fn record(&mut self, x: T) {
+        let x = match &mut self.decimation {
+            Some(decimation) => match decimation.push(x) {
+                Some(x) => x,
+                None => return,
+            },
+            None => x,
+        };
+        self.data.push(x);
+        if let Some(time) = &self.time {
+            self.times.push(**time.read());
+        }
+    }
+
+    fn reserve_samples(&mut self, n_steps: usize) {
+        let n_samples = match &self.decimation {
+            Some(decimation) => {
+                let pending = decimation.pending.as_ref().map_or(0, |(_, count)| *count);
+                (pending + n_steps) / decimation.window
+            }
+            None => n_steps,
+        };
+        self.data.reserve(n_samples);
+        if self.time.is_some() {
+            self.times.reserve(n_samples);
+        }
+    }
+
+    /// Bytes per step of samples of `sample_size` bytes and their times.
+    fn sample_bytes_per_step(&self, sample_size: usize) -> f64 {
+        let time_size = self.time.as_ref().map_or(0, |_| mem::size_of::<f64>());
+        let window = self.decimation.as_ref().map_or(1, |d| d.window);
+        (sample_size + time_size) as f64 / window as f64
+    }
+
+    fn probed_signals(&self) -> Vec<&dyn Signal> {
+        let mut signals: Vec<&dyn Signal> = vec![&*self.signal];
+        if let Some(time) = &self.time {
+            signals.push(&**time);
+        }
+        if let Some(projection) = &self.projection {
+            signals.extend(projection.reads.iter().map(|s| &**s as &dyn Signal));
+        }
+        signals
+    }
 }
 
-impl<T: Element + Debug + Send + Sync + 'static> Probe for SignalProbe<ArrayD<T>, ArraySignal<T>> {
+impl<T: Element + Copy + Debug + Send + Sync + 'static> Probe
+    for SignalProbe<ArrayD<T>, ArraySignal<T>>
+{
     fn as_any(&self) -> &dyn Any {
         self
     }
 
     fn probe(&mut self) {
-        self.data.push(self.signal.read().clone_array())
+        let x = match &self.projection {
+            Some(projection) => (projection.apply)(&self.signal),
+            None => self.signal.read().clone_array(),
+        };
+        self.record(x);
+        if let Some(budget) = self.memory_budget {
+            let sample_size = self.shape().iter().product::<usize>() * mem::size_of::<T>();
+            if self.data.len() * sample_size > budget {
+                // Keep the samples in memory if they cannot be spilled, and
+                // try again with the next sample.
+                match self.spill_data() {
+                    Ok(()) => self.spill_failed = false,
+                    Err(err) if !self.spill_failed => {
+                        self.spill_failed = true;
+                        log_warning(&format!(
+                            "Cannot move samples of probe of `{}` to disk, keeping them in \
+                             memory beyond the memory budget: {}",
+                            self.signal.name(),
+                            err
+                        ));
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        self.probed_signals()
+    }
+
+    fn set_memory_budget(&mut self, bytes: Option<usize>) {
+        self.memory_budget = bytes;
+    }
+
+    fn reserve(&mut self, n_steps: usize) {
+        // Samples beyond the memory budget are spilled anyway.
+        if self.memory_budget.is_none() {
+            self.reserve_samples(n_steps);
+        }
+    }
+
+    fn memory_usage(&self) -> usize {
+        let sample_size = self.shape().iter().product::<usize>() * mem::size_of::<T>();
+        self.data.len() * sample_size + self.times.len() * mem::size_of::<f64>()
+    }
+
+    fn bytes_per_step(&self) -> f64 {
+        let sample_size = self.shape().iter().product::<usize>() * mem::size_of::<T>();
+        self.sample_bytes_per_step(sample_size)
+    }
+
+    fn n_samples(&self) -> usize {
+        self.data.len() + self.spill.as_ref().map_or(0, |spill| spill.n_samples)
     }
 }
 
-impl<T: Element + Debug + Send + Sync + 'static> SignalProbe<ArrayD<T>, ArraySignal<T>> {
+impl<T: Element + Copy + Debug + Send + Sync + 'static> SignalProbe<ArrayD<T>, ArraySignal<T>> {
+    /// Samples held in memory, which excludes samples spilled to disk
+    /// because of the memory budget. Use `load_data` to get all samples.
     pub fn get_data(&self) -> &Vec<ArrayD<T>> {
         &self.data
     }
 
+    /// All samples, including those spilled to disk.
+    pub fn load_data(&self) -> io::Result<Vec<ArrayD<T>>> {
+        let mut data = Vec::with_capacity(self.data.len());
+        if let Some(spill) = &self.spill {
+            let mut bytes = vec![];
+            File::open(&spill.path)?.read_to_end(&mut bytes)?;
+            let values: Vec<T> = bytes
+                .chunks_exact(mem::size_of::<T>())
+                .map(|b| unsafe { ptr::read_unaligned(b.as_ptr() as *const T) })
+                .collect();
+            let sample_len: usize = self.shape().iter().product();
+            for i in 0..spill.n_samples {
+                let sample = values[i * sample_len..(i + 1) * sample_len].to_vec();
+                data.push(
+                    ArrayD::from_shape_vec(self.shape(), sample)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+                );
+            }
+        }
+        data.extend(self.get_data().iter().cloned());
+        Ok(data)
+    }
+
+    /// Shape of each sample.
     pub fn shape(&self) -> &[usize] {
-        self.signal.shape()
+        match &self.projection {
+            Some(projection) => &projection.shape,
+            None => self.signal.shape(),
+        }
+    }
+
+    /// Appends the samples in memory to the spill file and drops them.
+    fn spill_data(&mut self) -> io::Result<()> {
+        if self.spill.is_none() {
+            self.spill = Some(SpillFile::create()?);
+        }
+        let spill = self.spill.as_mut().unwrap();
+        let mut writer = BufWriter::new(&spill.file);
+        for x in self.data.iter() {
+            for value in x.iter() {
+                let bytes = unsafe {
+                    slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>())
+                };
+                writer.write_all(bytes)?;
+            }
+        }
+        writer.flush()?;
+        spill.n_samples += self.data.len();
+        self.data.clear();
+        Ok(())
     }
 }
 
@@ -50,7 +468,28 @@ impl<T: Element + Copy + Debug + Send + Sync + 'static> Probe for SignalProbe<T,
     }
 
     fn probe(&mut self) {
-        self.data.push(**self.signal.read());
+        let x = **self.signal.read();
+        self.record(x);
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        self.probed_signals()
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.data.len() * mem::size_of::<T>() + self.times.len() * mem::size_of::<f64>()
+    }
+
+    fn reserve(&mut self, n_steps: usize) {
+        self.reserve_samples(n_steps);
+    }
+
+    fn bytes_per_step(&self) -> f64 {
+        self.sample_bytes_per_step(mem::size_of::<T>())
+    }
+
+    fn n_samples(&self) -> usize {
+        self.data.len()
     }
 }
 
@@ -60,6 +499,94 @@ impl<T: Element + Copy + Debug + Send + Sync + 'static> SignalProbe<T, ScalarSig
     }
 }
 
+/// Keeps the running mean, variance, minimum, and maximum of each element of
+/// a signal instead of its samples, so that the memory use does not grow.
+pub struct StatisticsProbe {
+    signal: Arc<ArraySignal<f64>>,
+    count: u64,
+    mean: ArrayD<f64>,
+    m2: ArrayD<f64>,
+    min: ArrayD<f64>,
+    max: ArrayD<f64>,
+}
+
+impl StatisticsProbe {
+    pub fn new(signal: &Arc<ArraySignal<f64>>) -> Self {
+        let shape = IxDyn(signal.shape());
+        StatisticsProbe {
+            signal: Arc::clone(signal),
+            count: 0,
+            mean: ArrayD::zeros(shape.clone()),
+            m2: ArrayD::zeros(shape.clone()),
+            min: ArrayD::from_elem(shape.clone(), f64::INFINITY),
+            max: ArrayD::from_elem(shape, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> &ArrayD<f64> {
+        &self.mean
+    }
+
+    /// Population variance of the samples, NaN before the first one.
+    pub fn variance(&self) -> ArrayD<f64> {
+        &self.m2 / self.count as f64
+    }
+
+    pub fn min(&self) -> &ArrayD<f64> {
+        &self.min
+    }
+
+    pub fn max(&self) -> &ArrayD<f64> {
+        &self.max
+    }
+}
+
+impl Probe for StatisticsProbe {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn probe(&mut self) {
+        self.count += 1;
+        let n = self.count as f64;
+        let StatisticsProbe {
+            signal,
+            mean,
+            m2,
+            min,
+            max,
+            ..
+        } = self;
+        signal.read().with_view(|x| {
+            Zip::from(mean)
+                .and(m2)
+                .and(min)
+                .and(max)
+                .and(&x)
+                .apply(|mean, m2, min, max, &x| {
+                    // Welford's update, which avoids cancellation of large sums.
+                    let delta = x - *mean;
+                    *mean += delta / n;
+                    *m2 += delta * (x - *mean);
+                    *min = min.min(x);
+                    *max = max.max(x);
+                });
+        });
+    }
+
+    fn reads(&self) -> Vec<&dyn Signal> {
+        vec![&*self.signal]
+    }
+
+    fn memory_usage(&self) -> usize {
+        4 * self.mean.len() * mem::size_of::<f64>()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,6 +608,79 @@ mod tests {
         probe.probe();
 
         assert_eq!(probe.get_data(), &vec![0, 1, 42]);
+        assert_eq!(probe.get_times(), None);
+    }
+
+    #[test]
+    fn it_records_the_time_of_each_sample() {
+        let probed_signal = Arc::new(ScalarSignal::new("probed".to_string(), 0));
+        let time = Arc::new(ScalarSignal::new("time".to_string(), 0.));
+        let mut probe = SignalProbe::<u64, _>::with_time(&probed_signal, &time);
+
+        probe.probe();
+        **time.write() = 0.5;
+        **probed_signal.write() = 1;
+        probe.probe();
+
+        assert_eq!(probe.get_data(), &vec![0, 1]);
+        assert_eq!(probe.get_times(), Some(&vec![0., 0.5]));
+        assert_eq!(probe.reads().len(), 2);
+    }
+
+    #[test]
+    fn it_stores_one_sample_per_window_when_decimated() {
+        let probed_signal = Arc::new(ScalarSignal::new("probed".to_string(), 0));
+        let time = Arc::new(ScalarSignal::new("time".to_string(), 0.));
+        let mut probe = SignalProbe::<u64, _>::with_time(&probed_signal, &time)
+            .decimated(Decimation::sample(2));
+
+        for i in 1..=5 {
+            **probed_signal.write() = i;
+            **time.write() = i as f64;
+            probe.probe();
+        }
+
+        assert_eq!(probe.get_data(), &vec![2, 4]);
+        assert_eq!(probe.get_times(), Some(&vec![2., 4.]));
+    }
+
+    #[test]
+    fn it_reserves_room_for_the_expected_samples() {
+        let probed_signal = Arc::new(ScalarSignal::new("probed".to_string(), 0));
+        let time = Arc::new(ScalarSignal::new("time".to_string(), 0.));
+        let mut probe = SignalProbe::<u64, _>::with_time(&probed_signal, &time)
+            .decimated(Decimation::sample(2));
+        probe.probe();
+
+        probe.reserve(1000);
+
+        assert!(probe.data.capacity() >= 500);
+        assert!(probe.times.capacity() >= 500);
+        assert!(probe.data.capacity() < 1000);
+    }
+
+    #[test]
+    fn it_aggregates_decimation_windows() {
+        let aggregate = |decimation| {
+            let mut decimation: Decimation<ArrayD<f64>> = decimation;
+            vec![array![1., 4.], array![3., 2.]]
+                .into_iter()
+                .filter_map(|x| decimation.push(x.into_dyn()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            aggregate(Decimation::mean(2)),
+            vec![array![2., 3.].into_dyn()]
+        );
+        assert_eq!(
+            aggregate(Decimation::max(2)),
+            vec![array![3., 4.].into_dyn()]
+        );
+        assert_eq!(
+            aggregate(Decimation::min(2)),
+            vec![array![1., 2.].into_dyn()]
+        );
     }
 
     #[test]
@@ -113,4 +713,124 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn it_only_records_selected_dimensions() {
+        let probed_signal = Arc::new(ArraySignal::from_array(
+            "probed".to_string(),
+            array![[1., 2., 3.], [4., 5., 6.]].into_dyn(),
+        ));
+        let mut probe = SignalProbe::new(&probed_signal)
+            .projected(Projection::select(&probed_signal, vec![2, 0]));
+
+        probe.probe();
+
+        assert_eq!(probe.shape(), &[2, 2]);
+        assert_eq!(
+            probe.get_data(),
+            &vec![array![[3., 1.], [6., 4.]].into_dyn()]
+        );
+    }
+
+    #[test]
+    fn it_records_transformed_samples() {
+        let probed_signal = Arc::new(ArraySignal::from_array(
+            "probed".to_string(),
+            array![1., 2., 3.].into_dyn(),
+        ));
+        let mut probe = SignalProbe::new(&probed_signal).projected(Projection::transform(
+            &probed_signal,
+            array![[1., 0., 1.], [0., 2., 0.]],
+        ));
+
+        probe.probe();
+
+        assert_eq!(probe.shape(), &[2]);
+        assert_eq!(probe.get_data(), &vec![array![4., 4.].into_dyn()]);
+    }
+
+    #[test]
+    fn it_records_concatenated_signals() {
+        let signals = vec![
+            Arc::new(ArraySignal::from_array(
+                "a".to_string(),
+                array![1., 2.].into_dyn(),
+            )),
+            Arc::new(ArraySignal::from_array(
+                "b".to_string(),
+                array![3.].into_dyn(),
+            )),
+        ];
+        let mut probe = SignalProbe::new(&signals[0]).projected(Projection::concatenate(&signals));
+
+        probe.probe();
+        signals[1].write().assign_array(&array![4.]);
+        probe.probe();
+
+        assert_eq!(probe.shape(), &[3]);
+        assert_eq!(probe.reads().len(), 3);
+        assert_eq!(
+            probe.get_data(),
+            &vec![array![1., 2., 3.].into_dyn(), array![1., 2., 4.].into_dyn()]
+        );
+    }
+
+    #[test]
+    fn it_spills_samples_exceeding_the_memory_budget() -> Result<(), Box<dyn Error>> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let probed_signal = Arc::new(ArraySignal::new(
+            "probed".to_string(),
+            array![0, 0]
+                .into_dimensionality::<IxDyn>()?
+                .into_pyarray(py),
+        ));
+        probed_signal.reset();
+        let mut probe = SignalProbe::<ArrayD<u64>, _>::new(&Arc::clone(&probed_signal));
+        probe.set_memory_budget(Some(16));
+
+        for i in 0..3 {
+            probed_signal.write().assign_array(&array![i, i + 1]);
+            probe.probe();
+        }
+
+        assert_eq!(probe.get_data().len(), 1);
+        assert_eq!(
+            probe.load_data()?,
+            vec![
+                array![0, 1].into_dimensionality::<IxDyn>()?,
+                array![1, 2].into_dimensionality::<IxDyn>()?,
+                array![2, 3].into_dimensionality::<IxDyn>()?
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_running_statistics_of_each_element() {
+        let signal = Arc::new(ArraySignal::from_array(
+            "signal".to_string(),
+            array![0., 0.].into_dyn(),
+        ));
+        let mut probe = StatisticsProbe::new(&signal);
+
+        for x in [
+            array![1., 1e9 + 4.],
+            array![3., 1e9 + 7.],
+            array![2., 1e9 + 13.],
+        ]
+        .iter()
+        {
+            signal.write().assign_array(&x.clone().into_dyn());
+            probe.probe();
+        }
+
+        assert_eq!(probe.count(), 3);
+        assert_eq!(probe.mean(), &array![2., 1e9 + 8.].into_dyn());
+        assert_eq!(probe.variance(), array![2. / 3., 14.].into_dyn());
+        assert_eq!(probe.min(), &array![1., 1e9 + 4.].into_dyn());
+        assert_eq!(probe.max(), &array![3., 1e9 + 13.].into_dyn());
+        assert_eq!(probe.memory_usage(), 64);
+    }
 }
@@ -0,0 +1,411 @@
+use crate::operator::{
+    ErrorCause, Operator, OperatorError, OperatorNode, StepWrapper, WrappedOperator,
+};
+use crate::signal::{ArraySignal, ScalarSignal, Signal, SignalAccess};
+use ndarray::{ArrayD, Zip};
+use numpy::{c64, Element};
+use pyo3::Python;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Copy of the value of a signal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    ArrayF64(ArrayD<f64>),
+    ArrayF32(ArrayD<f32>),
+    ArrayI64(ArrayD<i64>),
+    ArrayC64(ArrayD<c64>),
+    ArrayU64(ArrayD<u64>),
+    F64(f64),
+    U64(u64),
+}
+
+impl Value {
+    /// Copies the value of `signal`, or returns `None` for signal types that
+    /// cannot be copied.
+    pub fn of(signal: &dyn Signal) -> Option<Self> {
+        let any = signal.as_any();
+        if let Some(signal) = any.downcast_ref::<ArraySignal<f64>>() {
+            Some(Value::ArrayF64(signal.read().clone_array()))
+        } else if let Some(signal) = any.downcast_ref::<ArraySignal<f32>>() {
+            Some(Value::ArrayF32(signal.read().clone_array()))
+        } else if let Some(signal) = any.downcast_ref::<ArraySignal<i64>>() {
+            Some(Value::ArrayI64(signal.read().clone_array()))
+        } else if let Some(signal) = any.downcast_ref::<ArraySignal<c64>>() {
+            Some(Value::ArrayC64(signal.read().clone_array()))
+        } else if let Some(signal) = any.downcast_ref::<ArraySignal<u64>>() {
+            Some(Value::ArrayU64(signal.read().clone_array()))
+        } else if let Some(signal) = any.downcast_ref::<ScalarSignal<f64>>() {
+            Some(Value::F64(**signal.read()))
+        } else {
+            any.downcast_ref::<ScalarSignal<u64>>()
+                .map(|signal| Value::U64(**signal.read()))
+        }
+    }
+
+    /// Whether the value can be written to `signal`.
+    pub fn fits(&self, signal: &dyn Signal) -> bool {
+        fn array<T: Element + 'static>(signal: &dyn Signal, value: &ArrayD<T>) -> bool {
+            signal.as_any().is::<ArraySignal<T>>() && signal.shape() == value.shape()
+        }
+
+        match self {
+            Value::ArrayF64(value) => array(signal, value),
+            Value::ArrayF32(value) => array(signal, value),
+            Value::ArrayI64(value) => array(signal, value),
+            Value::ArrayC64(value) => array(signal, value),
+            Value::ArrayU64(value) => array(signal, value),
+            Value::F64(_) => signal.as_any().is::<ScalarSignal<f64>>(),
+            Value::U64(_) => signal.as_any().is::<ScalarSignal<u64>>(),
+        }
+    }
+
+    /// Writes the value to `signal`, which it must fit.
+    pub fn assign_to(&self, signal: &dyn Signal) {
+        fn downcast<T: 'static>(signal: &dyn Signal) -> &T {
+            signal.as_any().downcast_ref::<T>().unwrap()
+        }
+
+        match self {
+            Value::ArrayF64(value) => downcast::<ArraySignal<f64>>(signal)
+                .write()
+                .assign_array(value),
+            Value::ArrayF32(value) => downcast::<ArraySignal<f32>>(signal)
+                .write()
+                .assign_array(value),
+            Value::ArrayI64(value) => downcast::<ArraySignal<i64>>(signal)
+                .write()
+                .assign_array(value),
+            Value::ArrayC64(value) => downcast::<ArraySignal<c64>>(signal)
+                .write()
+                .assign_array(value),
+            Value::ArrayU64(value) => downcast::<ArraySignal<u64>>(signal)
+                .write()
+                .assign_array(value),
+            Value::F64(value) => **downcast::<ScalarSignal<f64>>(signal).write() = *value,
+            Value::U64(value) => **downcast::<ScalarSignal<u64>>(signal).write() = *value,
+        }
+    }
+
+    /// Largest absolute difference between corresponding elements, which is
+    /// infinite if a single one is NaN or the values do not fit together.
+    pub fn max_difference(&self, other: &Value) -> f64 {
+        fn difference(a: f64, b: f64) -> f64 {
+            if a == b || (a.is_nan() && b.is_nan()) {
+                0.
+            } else {
+                let d = (a - b).abs();
+                if d.is_nan() {
+                    f64::INFINITY
+                } else {
+                    d
+                }
+            }
+        }
+
+        fn max<T: Copy>(a: &ArrayD<T>, b: &ArrayD<T>, d: impl Fn(T, T) -> f64) -> f64 {
+            if a.shape() != b.shape() {
+                return f64::INFINITY;
+            }
+            let mut max = 0f64;
+            Zip::from(a).and(b).apply(|&a, &b| max = max.max(d(a, b)));
+            max
+        }
+
+        match (self, other) {
+            (Value::ArrayF64(a), Value::ArrayF64(b)) => max(a, b, difference),
+            (Value::ArrayF32(a), Value::ArrayF32(b)) => {
+                max(a, b, |a, b| difference(a.into(), b.into()))
+            }
+            (Value::ArrayC64(a), Value::ArrayC64(b)) => max(a, b, |a, b| {
+                difference(a.re, b.re).max(difference(a.im, b.im))
+            }),
+            (Value::ArrayI64(a), Value::ArrayI64(b)) => {
+                max(a, b, |a, b| (a.max(b) as f64) - (a.min(b) as f64))
+            }
+            (Value::ArrayU64(a), Value::ArrayU64(b)) => {
+                max(a, b, |a, b| (a.max(b) - a.min(b)) as f64)
+            }
+            (Value::F64(a), Value::F64(b)) => difference(*a, *b),
+            (Value::U64(a), Value::U64(b)) => (a.max(b) - a.min(b)) as f64,
+            _ => f64::INFINITY,
+        }
+    }
+}
+
+/// Values of the signals an operator accessed when it was stepped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperatorRecord {
+    pub operator: String,
+    /// Names and values before the step of the signals read, followed by
+    /// those written, which incrementing operators also read.
+    pub inputs: Vec<(String, Option<Value>)>,
+    /// Names and values after the step of the signals written.
+    pub outputs: Vec<(String, Option<Value>)>,
+}
+
+/// Inputs and outputs of each operator on one step of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepRecord {
+    pub step: u64,
+    /// Indexed like the operators of the engine, with `None` for those that
+    /// were not active on the step.
+    pub operators: Vec<Option<OperatorRecord>>,
+}
+
+/// Output of a replayed operator differing from the expected one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Index of the operator in the engine.
+    pub index: usize,
+    pub operator: String,
+    pub signal: String,
+    pub max_difference: f64,
+}
+
+fn accessed_signals(operator: &dyn Operator) -> Vec<&dyn Signal> {
+    let mut signals = operator.reads();
+    signals.extend(operator.writes());
+    signals
+}
+
+fn copy_values(signals: Vec<&dyn Signal>) -> Vec<(String, Option<Value>)> {
+    signals
+        .into_iter()
+        .map(|signal| (signal.name().clone(), Value::of(signal)))
+        .collect()
+}
+
+/// Records the inputs and outputs of each operator on one step.
+pub(crate) struct StepRecorder {
+    step: u64,
+    operators: Vec<Arc<OperatorNode>>,
+    records: Vec<Arc<Mutex<Option<OperatorRecord>>>>,
+    is_done: AtomicBool,
+}
+
+impl StepRecorder {
+    pub(crate) fn new(step: u64, operators: &[Arc<OperatorNode>]) -> Self {
+        let records: Vec<_> = operators.iter().map(|_| Default::default()).collect();
+        let operators = operators
+            .iter()
+            .zip(records.iter())
+            .map(|(node, record)| OperatorRecorder::wrap(node, Arc::clone(record)))
+            .collect();
+        Self {
+            step,
+            operators,
+            records,
+            is_done: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the operators to run on `step`, which are replaced by
+    /// recording ones on the recorded step.
+    pub(crate) fn operators_for(
+        recorder: &Option<Arc<StepRecorder>>,
+        step: u64,
+        operators: &[Arc<OperatorNode>],
+    ) -> Vec<Arc<OperatorNode>> {
+        match recorder {
+            Some(recorder) if recorder.step == step => {
+                recorder.is_done.store(true, Ordering::SeqCst);
+                recorder.operators.clone()
+            }
+            _ => operators.to_vec(),
+        }
+    }
+
+    /// Returns the record once the step has run.
+    pub(crate) fn record(&self) -> Option<StepRecord> {
+        if !self.is_done.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(StepRecord {
+            step: self.step,
+            operators: self
+                .records
+                .iter()
+                .map(|record| record.lock().unwrap().clone())
+                .collect(),
+        })
+    }
+}
+
+/// Records the values of the signals the wrapped operator accesses before
+/// and after its step.
+struct OperatorRecorder {
+    record: Arc<Mutex<Option<OperatorRecord>>>,
+}
+
+impl OperatorRecorder {
+    fn wrap(
+        node: &Arc<OperatorNode>,
+        record: Arc<Mutex<Option<OperatorRecord>>>,
+    ) -> Arc<OperatorNode> {
+        WrappedOperator::wrap(node, OperatorRecorder { record })
+    }
+}
+
+impl StepWrapper for OperatorRecorder {
+    type State = Vec<(String, Option<Value>)>;
+
+    fn before_step(&self, operator: &dyn Operator) -> Self::State {
+        copy_values(accessed_signals(operator))
+    }
+
+    fn after_step(
+        &self,
+        operator: &dyn Operator,
+        inputs: Self::State,
+        result: Result<(), OperatorError>,
+    ) -> Result<(), OperatorError> {
+        *self.record.lock().unwrap() = Some(OperatorRecord {
+            operator: operator.name(),
+            inputs,
+            outputs: copy_values(operator.writes()),
+        });
+        result
+    }
+
+    /// Memoized operators are stepped anyway to record their inputs.
+    fn keeps_memo(&self) -> bool {
+        false
+    }
+}
+
+/// Steps each recorded operator once from its recorded inputs and compares
+/// its outputs to those in `reference`, or in `record` itself without one.
+/// Differences up to `tolerance` are accepted.
+pub(crate) fn replay(
+    operators: &[Arc<OperatorNode>],
+    record: &StepRecord,
+    reference: Option<&StepRecord>,
+    tolerance: f64,
+) -> Result<Vec<Divergence>, OperatorError> {
+    let mut divergences = vec![];
+    for (index, (node, recorded)) in operators.iter().zip(record.operators.iter()).enumerate() {
+        let recorded = match recorded {
+            Some(recorded) => recorded,
+            None => continue,
+        };
+        let expected = match reference {
+            Some(reference) => match reference.operators.get(index) {
+                Some(Some(expected)) => expected,
+                _ => continue,
+            },
+            None => recorded,
+        };
+        let operator = &*node.operator;
+        let signals = accessed_signals(operator);
+        let fits = recorded.operator == operator.name()
+            && signals.len() == recorded.inputs.len()
+            && signals
+                .iter()
+                .zip(recorded.inputs.iter())
+                .all(|(signal, (_, value))| match value {
+                    Some(value) => value.fits(*signal),
+                    None => true,
+                });
+        if !fits {
+            return Err(OperatorError::new(
+                operator,
+                ErrorCause::Shape(format!(
+                    "The record of {} at index {} does not fit.",
+                    recorded.operator, index
+                )),
+            ));
+        }
+        for (signal, (_, value)) in signals.into_iter().zip(recorded.inputs.iter()) {
+            match value {
+                // Read-only signals still hold the recorded value.
+                Some(value) if !signal.is_read_only() => value.assign_to(signal),
+                _ => {}
+            }
+        }
+        if operator.requires_gil() {
+            let gil = Python::acquire_gil();
+            operator.step_with_gil(gil.python())?;
+        } else {
+            operator.step()?;
+        }
+        for (signal, (_, expected)) in operator.writes().into_iter().zip(expected.outputs.iter()) {
+            if let (Some(actual), Some(expected)) = (Value::of(signal), expected) {
+                let max_difference = actual.max_difference(expected);
+                if max_difference > tolerance {
+                    divergences.push(Divergence {
+                        index,
+                        operator: operator.name(),
+                        signal: signal.name().clone(),
+                        max_difference,
+                    });
+                }
+            }
+        }
+    }
+    Ok(divergences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::ElementwiseInc;
+    use ndarray::prelude::*;
+
+    #[test]
+    fn it_replays_an_operator_from_its_recorded_inputs() {
+        let target = Arc::new(ArraySignal::from_array(
+            "target".to_string(),
+            array![1., 2.].into_dyn(),
+        ));
+        let node = Arc::new(OperatorNode::new(
+            Box::new(ElementwiseInc {
+                target: Arc::clone(&target),
+                left: Arc::new(ArraySignal::from_array(
+                    "left".to_string(),
+                    array![2., 3.].into_dyn(),
+                )),
+                right: Arc::new(ArraySignal::from_array(
+                    "right".to_string(),
+                    array![1., 1.].into_dyn(),
+                )),
+                checked: false,
+            }),
+            vec![],
+        ));
+        let recorder = Arc::new(StepRecorder::new(3, &[Arc::clone(&node)]));
+        assert!(recorder.record().is_none());
+        let recording = Some(Arc::clone(&recorder));
+        let operators = StepRecorder::operators_for(&recording, 3, &[Arc::clone(&node)]);
+        operators[0].operator.step().unwrap();
+        let record = recorder.record().unwrap();
+        assert_eq!(record.step, 3);
+        assert_eq!(
+            record.operators[0].as_ref().unwrap().outputs,
+            vec![(
+                "target".to_string(),
+                Some(Value::ArrayF64(array![3., 5.].into_dyn()))
+            )]
+        );
+
+        target.write().assign_array(&array![0., 0.]);
+        assert_eq!(
+            replay(&[Arc::clone(&node)], &record, None, 0.).unwrap(),
+            vec![]
+        );
+        assert_eq!(**target.read(), array![3., 5.].into_dyn());
+
+        let mut reference = record.clone();
+        reference.operators[0].as_mut().unwrap().outputs[0].1 =
+            Some(Value::ArrayF64(array![3., 4.5].into_dyn()));
+        assert_eq!(
+            replay(&[node], &record, Some(&reference), 0.1).unwrap(),
+            vec![Divergence {
+                index: 0,
+                operator: "ElementwiseInc<f64>".to_string(),
+                signal: "target".to_string(),
+                max_difference: 0.5,
+            }]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distribution {
+    Uniform { low: f64, high: f64 },
+    Gaussian { mean: f64, std: f64 },
+}
+
+impl Distribution {
+    /// Sample using the numbers at position `n` of `stream`.
+    pub fn sample_at(&self, stream: &RngStream, n: u64) -> f64 {
+        match *self {
+            Distribution::Uniform { low, high } => low + (high - low) * stream.f64_at(n),
+            Distribution::Gaussian { mean, std } => mean + std * stream.gaussian_at(n),
+        }
+    }
+}
+
+/// Derives the seed of the stream with the given `index` from a master
+/// `seed`. Seeds of different streams are unrelated, so adding a stream does
+/// not change the others.
+pub fn derive_seed(seed: u64, index: u64) -> u64 {
+    mix(seed ^ mix(index.wrapping_add(GOLDEN_GAMMA)))
+}
+
+/// Counter-based random numbers: the `n`-th number of a stream only depends
+/// on the seed and `n`, not on which numbers were drawn before or by whom.
+/// Each stochastic operator owns a stream seeded by the engine.
+#[derive(Debug, Default)]
+pub struct RngStream {
+    seed: AtomicU64,
+    counter: AtomicU64,
+}
+
+impl RngStream {
+    pub fn new(seed: u64) -> Self {
+        RngStream {
+            seed: AtomicU64::new(seed),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Restarts the stream at the first number for `seed`.
+    pub fn reseed(&self, seed: u64) {
+        self.seed.store(seed, Ordering::SeqCst);
+        self.counter.store(0, Ordering::SeqCst);
+    }
+
+    pub fn u64_at(&self, n: u64) -> u64 {
+        mix(self
+            .seed
+            .load(Ordering::SeqCst)
+            .wrapping_add(n.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA)))
+    }
+
+    /// Uniformly distributed in [0, 1).
+    pub fn f64_at(&self, n: u64) -> f64 {
+        (self.u64_at(n) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normally distributed, using the uniform numbers at `2 n` and
+    /// `2 n + 1`.
+    pub fn gaussian_at(&self, n: u64) -> f64 {
+        let u = 1. - self.f64_at(2 * n);
+        let v = self.f64_at(2 * n + 1);
+        (-2. * u.ln()).sqrt() * (2. * std::f64::consts::PI * v).cos()
+    }
+
+    /// Reserves the next `n` positions of the stream and returns the first.
+    pub fn advance(&self, n: u64) -> u64 {
+        self.counter.fetch_add(n, Ordering::SeqCst)
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        self.u64_at(self.advance(1))
+    }
+
+    pub fn next_f64(&self) -> f64 {
+        self.f64_at(self.advance(1))
+    }
+
+    pub fn next_gaussian(&self) -> f64 {
+        self.gaussian_at(self.advance(1))
+    }
+}
+
+/// Finalizer of SplitMix64.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_derives_distinct_deterministic_seeds() {
+        let seeds: Vec<u64> = (0..3).map(|i| derive_seed(42, i)).collect();
+        assert_eq!(
+            seeds,
+            (0..3).map(|i| derive_seed(42, i)).collect::<Vec<_>>()
+        );
+        assert_ne!(seeds[0], seeds[1]);
+        assert_ne!(seeds[1], seeds[2]);
+        assert_ne!(derive_seed(42, 0), derive_seed(43, 0));
+    }
+
+    #[test]
+    fn stream_numbers_only_depend_on_seed_and_position() {
+        let stream = RngStream::new(3);
+        let drawn: Vec<u64> = (0..4).map(|_| stream.next_u64()).collect();
+        assert_eq!(drawn, (0..4).map(|n| stream.u64_at(n)).collect::<Vec<_>>());
+
+        stream.reseed(3);
+        assert_eq!(stream.next_u64(), drawn[0]);
+        assert_ne!(RngStream::new(4).u64_at(0), drawn[0]);
+    }
+
+    #[test]
+    fn stream_samples_standard_normal_numbers() {
+        let stream = RngStream::new(0);
+        let samples: Vec<f64> = (0..10000).map(|_| stream.next_gaussian()).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let var = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.05);
+        assert!((var - 1.).abs() < 0.05);
+    }
+}
@@ -1,16 +1,19 @@
+use crate::rng::{Distribution, RngStream};
 use ndarray::prelude::*;
 use ndarray::LinalgScalar;
 use ndarray::ScalarOperand;
-use ndarray::{
-    Array, ArrayBase, ArrayD, Data, Dimension, Ix, IxDyn, RawData, SliceInfo, SliceOrIndex,
-};
+use ndarray::{ArrayBase, ArrayD, Data, Dimension, Ix, IxDyn, RawData, SliceInfo, SliceOrIndex};
+use num_traits::{NumCast, Zero};
 use numpy::{Element, PyArrayDyn};
 use pyo3::prelude::*;
 use std::any::Any;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
+use std::mem;
 use std::ops::{AddAssign, Deref, DerefMut, Mul};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{Mutex, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+use std::time::{Duration, Instant};
 
 pub type AnySignal = dyn Any + Send + Sync;
 
@@ -19,7 +22,174 @@ pub trait Signal: Debug {
     fn as_any_arc(self: Arc<Self>) -> Arc<AnySignal>;
     fn name(&self) -> &String;
     fn shape(&self) -> &[Ix];
+    fn dtype(&self) -> &'static str;
     fn reset(&self);
+    /// Counter incremented whenever the signal (or a view sharing its base)
+    /// is acquired for writing.
+    fn generation(&self) -> u64;
+    /// Part of the underlying memory the signal refers to.
+    fn region(&self) -> Region;
+    /// Bytes of memory owned by the signal, including a stored initial
+    /// value. Views own no memory.
+    fn nbytes(&self) -> usize;
+    /// Total time spent waiting to acquire the signal while lock wait
+    /// tracking is enabled. Waits on views count for their base.
+    fn lock_wait(&self) -> Duration {
+        Duration::default()
+    }
+    /// Whether operators must not write the signal, which the engine checks
+    /// when it is built.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+}
+
+static TRACK_LOCK_WAITS: AtomicBool = AtomicBool::new(false);
+
+static READ_ONLY_SIGNALS: AtomicU64 = AtomicU64::new(0);
+
+/// Number of signals made read-only in the process so far, which tells
+/// engines whether signals may have become read-only since they were built.
+pub fn read_only_signal_count() -> u64 {
+    READ_ONLY_SIGNALS.load(Ordering::SeqCst)
+}
+
+/// Enables measuring how long signal accesses block on other accesses, for all
+/// signals in the process. This adds some overhead to every access.
+pub fn track_lock_waits(enabled: bool) {
+    TRACK_LOCK_WAITS.store(enabled, Ordering::SeqCst);
+}
+
+/// Time spent blocked on the lock of a signal.
+#[derive(Debug, Default)]
+struct LockWaits {
+    nanos: AtomicU64,
+}
+
+impl LockWaits {
+    fn total(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+
+    fn read<'a, T>(&self, lock: &'a RwLock<T>) -> RwLockReadGuard<'a, T> {
+        if !TRACK_LOCK_WAITS.load(Ordering::Relaxed) {
+            return lock.read().unwrap();
+        }
+        match lock.try_read() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => {
+                let start = Instant::now();
+                let guard = lock.read().unwrap();
+                self.add(start.elapsed());
+                guard
+            }
+            Err(TryLockError::Poisoned(err)) => panic!("{}", err),
+        }
+    }
+
+    fn write<'a, T>(&self, lock: &'a RwLock<T>) -> RwLockWriteGuard<'a, T> {
+        if !TRACK_LOCK_WAITS.load(Ordering::Relaxed) {
+            return lock.write().unwrap();
+        }
+        match lock.try_write() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => {
+                let start = Instant::now();
+                let guard = lock.write().unwrap();
+                self.add(start.elapsed());
+                guard
+            }
+            Err(TryLockError::Poisoned(err)) => panic!("{}", err),
+        }
+    }
+
+    fn add(&self, duration: Duration) {
+        self.nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Part of a buffer referred to by a signal, used to detect signals (or views)
+/// that share memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    buffer: usize,
+    /// Sorted indices covered along each axis; `None` covers the whole buffer.
+    indices: Option<Vec<Vec<Ix>>>,
+}
+
+impl Region {
+    pub fn whole<T>(buffer: &T) -> Self {
+        Self {
+            buffer: buffer as *const T as usize,
+            indices: None,
+        }
+    }
+
+    fn slice<T>(buffer: &T, shape: &[Ix], slice: &[SliceOrIndex]) -> Self {
+        Self {
+            buffer: buffer as *const T as usize,
+            indices: Some(
+                slice
+                    .iter()
+                    .zip(shape.iter())
+                    .map(|(elem, len)| axis_indices(elem, *len))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn buffer(&self) -> usize {
+        self.buffer
+    }
+
+    pub fn overlaps(&self, other: &Region) -> bool {
+        self.buffer == other.buffer
+            && match (&self.indices, &other.indices) {
+                (Some(lhs), Some(rhs)) => lhs
+                    .iter()
+                    .zip(rhs.iter())
+                    .all(|(lhs, rhs)| intersect(lhs, rhs)),
+                _ => true,
+            }
+    }
+}
+
+fn axis_indices(elem: &SliceOrIndex, len: Ix) -> Vec<Ix> {
+    let resolve = |i: isize| {
+        if i < 0 {
+            (i + len as isize).max(0) as Ix
+        } else {
+            (i as Ix).min(len)
+        }
+    };
+    match *elem {
+        SliceOrIndex::Index(i) => vec![resolve(i)],
+        SliceOrIndex::Slice { start, end, step } => {
+            let start = resolve(start);
+            let end = end.map_or(len, resolve).max(start);
+            let by = step.unsigned_abs().max(1);
+            let mut indices: Vec<Ix> = if step < 0 {
+                (start..end).rev().step_by(by).collect()
+            } else {
+                (start..end).step_by(by).collect()
+            };
+            indices.sort_unstable();
+            indices
+        }
+    }
+}
+
+fn intersect(lhs: &[Ix], rhs: &[Ix]) -> bool {
+    let (mut i, mut j) = (0, 0);
+    while i < lhs.len() && j < rhs.len() {
+        match lhs[i].cmp(&rhs[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => return true,
+        }
+    }
+    false
 }
 
 pub trait SignalAccess<T> {
@@ -32,6 +202,8 @@ pub struct ScalarSignal<T> {
     name: String,
     value: RwLock<T>,
     initial_value: T,
+    generation: AtomicU64,
+    lock_waits: LockWaits,
 }
 
 impl<T: Copy> ScalarSignal<T> {
@@ -40,6 +212,8 @@ impl<T: Copy> ScalarSignal<T> {
             name,
             value: RwLock::new(initial_value),
             initial_value,
+            generation: AtomicU64::new(0),
+            lock_waits: LockWaits::default(),
         }
     }
 }
@@ -61,18 +235,40 @@ impl<T: Copy + Send + Sync + Debug + 'static> Signal for ScalarSignal<T> {
         &[]
     }
 
+    fn dtype(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
     fn reset(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
         *self.value.write().unwrap() = self.initial_value;
     }
+
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    fn region(&self) -> Region {
+        Region::whole(self)
+    }
+
+    fn nbytes(&self) -> usize {
+        2 * mem::size_of::<T>()
+    }
+
+    fn lock_wait(&self) -> Duration {
+        self.lock_waits.total()
+    }
 }
 
 impl<T> SignalAccess<T> for ScalarSignal<T> {
     fn read<'a>(&'a self) -> Box<dyn Deref<Target = T> + 'a> {
-        Box::new(self.value.read().unwrap())
+        Box::new(self.lock_waits.read(&self.value))
     }
 
     fn write<'a>(&'a self) -> Box<dyn DerefMut<Target = T> + 'a> {
-        Box::new(self.value.write().unwrap())
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        Box::new(self.lock_waits.write(&self.value))
     }
 }
 
@@ -91,7 +287,7 @@ impl<T: Element + Debug> Debug for ArrayRef<T> {
     ) -> std::result::Result<(), std::fmt::Error> {
         match self {
             ArrayRef::Owned(array) => array.fmt(formatter),
-            ArrayRef::View(base, slice) => match &*base.buffer.read().unwrap() {
+            ArrayRef::View(base, slice) => match &**base.read() {
                 ArrayRef::Owned(base) => base.slice(slice.as_ref().as_ref()).fmt(formatter),
                 ArrayRef::View(_, _) => formatter.write_str("transitive ArrayRef::View"),
             },
@@ -103,7 +299,7 @@ impl<T: Element> ArrayRef<T> {
     pub fn assign(&mut self, src: &ArrayRef<T>) {
         match src {
             ArrayRef::Owned(src) => self.assign_array(&src),
-            ArrayRef::View(src, slice) => match &*src.buffer.read().unwrap() {
+            ArrayRef::View(src, slice) => match &**src.read() {
                 ArrayRef::Owned(base) => self.assign_array(&base.slice(slice.as_ref().as_ref())),
                 ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
             },
@@ -128,17 +324,41 @@ impl<T: Element> ArrayRef<T> {
     pub fn clone_array(&self) -> ArrayD<T> {
         match self {
             ArrayRef::Owned(src) => src.clone(),
-            ArrayRef::View(src, slice) => match &*src.buffer.read().unwrap() {
+            ArrayRef::View(src, slice) => match &**src.read() {
                 ArrayRef::Owned(base) => base.slice(slice.as_ref().as_ref()).to_owned(),
                 ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
             },
         }
     }
 
+    pub fn with_view<R, F: FnOnce(ArrayViewD<T>) -> R>(&self, f: F) -> R {
+        match self {
+            ArrayRef::Owned(array) => f(array.view()),
+            ArrayRef::View(base, slice) => match &**base.read() {
+                ArrayRef::Owned(base) => f(base.slice(slice.as_ref().as_ref())),
+                ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
+            },
+        }
+    }
+
+    pub fn with_view_mut<R, F: FnOnce(ArrayViewMutD<T>) -> R>(&mut self, f: F) -> R {
+        match self {
+            ArrayRef::Owned(array) => f(array.view_mut()),
+            ArrayRef::View(base, slice) => match &mut *base.lock_waits.write(&base.buffer) {
+                ArrayRef::Owned(base) => f(base.slice_mut(slice.as_ref().as_ref())),
+                ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
+            },
+        }
+    }
+
+    pub fn any<F: Fn(&T) -> bool>(&self, predicate: F) -> bool {
+        self.with_view(|view| view.iter().any(predicate))
+    }
+
     pub fn to_py_array<'py>(&self, py: Python<'py>) -> &'py PyArrayDyn<T> {
         match self {
             ArrayRef::Owned(array) => PyArrayDyn::from_array(py, array),
-            ArrayRef::View(base, slice) => match &*base.buffer.read().unwrap() {
+            ArrayRef::View(base, slice) => match &**base.read() {
                 ArrayRef::Owned(base) => {
                     PyArrayDyn::from_array(py, &base.slice(slice.as_ref().as_ref()))
                 }
@@ -164,7 +384,7 @@ impl<T: Element + LinalgScalar> ArrayRef<T> {
                     .into_dyn(),
                 _ => panic!("Invalid array dimensionality."),
             },
-            ArrayRef::View(lhs, slice) => match &*lhs.buffer.read().unwrap() {
+            ArrayRef::View(lhs, slice) => match &**lhs.read() {
                 ArrayRef::Owned(base) => {
                     let view = base.slice(slice.as_ref().as_ref());
                     match view.ndim() {
@@ -182,23 +402,118 @@ impl<T: Element + LinalgScalar> ArrayRef<T> {
         }
     }
 
-    pub fn dot(&self, rhs: &ArrayRef<T>) -> ArrayD<T> {
-        match rhs {
+    /// Multiplies each row of `rhs` (one trial of a batch) with this array.
+    pub fn dot_array_2d<S: RawData<Elem = T> + Data>(&self, rhs: &ArrayBase<S, Ix2>) -> ArrayD<T> {
+        self.with_view(|lhs| match lhs.ndim() {
+            1 => rhs
+                .dot(&lhs.into_dimensionality::<Ix1>().unwrap())
+                .insert_axis(Axis(1))
+                .into_dyn(),
+            2 => rhs
+                .dot(&lhs.into_dimensionality::<Ix2>().unwrap().t())
+                .into_dyn(),
+            _ => panic!("Invalid array dimensionality."),
+        })
+    }
+
+    /// Matrix-vector product supporting a leading trial axis on `rhs`. Fails
+    /// for operands of more than two dimensions or mismatched inner lengths.
+    pub fn dot(&self, rhs: &ArrayRef<T>) -> Result<ArrayD<T>, ShapeError> {
+        dot_shape(
+            &self.with_view(|lhs| lhs.shape().to_vec()),
+            &rhs.with_view(|rhs| rhs.shape().to_vec()),
+        )?;
+        Ok(match rhs {
             ArrayRef::Owned(rhs) => match rhs.ndim() {
                 1 => self.dot_array_1d(&rhs.view().into_dimensionality::<Ix1>().unwrap()),
+                2 => self.dot_array_2d(&rhs.view().into_dimensionality::<Ix2>().unwrap()),
                 _ => panic!("Only matrix-vector multiplies supported."),
             },
-            ArrayRef::View(rhs, slice) => match &*rhs.buffer.read().unwrap() {
+            ArrayRef::View(rhs, slice) => match &**rhs.read() {
                 ArrayRef::Owned(base) => {
                     let view = base.slice(slice.as_ref().as_ref());
                     match view.ndim() {
                         1 => self.dot_array_1d(&view.into_dimensionality::<Ix1>().unwrap()),
+                        2 => self.dot_array_2d(&view.into_dimensionality::<Ix2>().unwrap()),
                         _ => panic!("Only matrix-vector multiplies supported."),
                     }
                 }
                 ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
             },
-        }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeError {
+    pub message: String,
+}
+
+impl Display for ShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+impl ShapeError {
+    fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+/// Whether an array of shape `from` can be broadcast to shape `to`.
+pub fn is_broadcastable(from: &[Ix], to: &[Ix]) -> bool {
+    from.len() <= to.len()
+        && from
+            .iter()
+            .rev()
+            .zip(to.iter().rev())
+            .all(|(a, b)| a == b || *a == 1)
+}
+
+/// Shape of `ArrayRef::dot` for operands of the given shapes.
+pub fn dot_shape(lhs: &[Ix], rhs: &[Ix]) -> Result<Vec<Ix>, ShapeError> {
+    match (lhs, rhs) {
+        ([n], [k]) if n == k => Ok(vec![1]),
+        ([m, n], [k]) if n == k => Ok(vec![*m]),
+        ([n], [trials, k]) if n == k => Ok(vec![*trials, 1]),
+        ([m, n], [trials, k]) if n == k => Ok(vec![*trials, *m]),
+        _ => Err(ShapeError::new(format!(
+            "Cannot multiply arrays of shape {:?} and {:?}.",
+            lhs, rhs
+        ))),
+    }
+}
+
+/// Shape of the elementwise product of arrays of the given shapes.
+pub fn mul_shape(lhs: &[Ix], rhs: &[Ix]) -> Result<Vec<Ix>, ShapeError> {
+    let (larger, smaller) = match (lhs, rhs) {
+        ([1], _) => return Ok(rhs.to_vec()),
+        (_, [1]) => return Ok(lhs.to_vec()),
+        _ if lhs.len() < rhs.len() => (rhs, lhs),
+        _ => (lhs, rhs),
+    };
+    if is_broadcastable(smaller, larger) {
+        Ok(larger.to_vec())
+    } else {
+        Err(ShapeError::new(format!(
+            "Cannot multiply arrays of shape {:?} and {:?} elementwise.",
+            lhs, rhs
+        )))
+    }
+}
+
+/// Fails if a value of shape `from` cannot be assigned (or added) to `to`.
+pub fn check_broadcastable(from: &[Ix], to: &[Ix]) -> Result<(), ShapeError> {
+    if is_broadcastable(from, to) {
+        Ok(())
+    } else {
+        Err(ShapeError::new(format!(
+            "Cannot assign array of shape {:?} to signal of shape {:?}.",
+            from, to
+        )))
     }
 }
 
@@ -228,7 +543,7 @@ where
     fn add_assign(&mut self, rhs: &ArrayRef<T>) {
         match rhs {
             ArrayRef::Owned(rhs) => *self += rhs,
-            ArrayRef::View(rhs, slice) => match &*rhs.buffer.read().unwrap() {
+            ArrayRef::View(rhs, slice) => match &**rhs.read() {
                 ArrayRef::Owned(base) => *self += &base.slice(slice.as_ref().as_ref()),
                 ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
             },
@@ -245,7 +560,7 @@ where
     fn mul(self, rhs: &ArrayRef<T>) -> Self::Output {
         match rhs {
             ArrayRef::Owned(rhs) => self * rhs,
-            ArrayRef::View(rhs, slice) => match &*rhs.buffer.read().unwrap() {
+            ArrayRef::View(rhs, slice) => match &**rhs.read() {
                 ArrayRef::Owned(base) => self * &base.slice(slice.as_ref().as_ref()),
                 ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
             },
@@ -263,7 +578,7 @@ where
     fn mul(self, rhs: &ArrayBase<S, IxDyn>) -> Self::Output {
         match self {
             ArrayRef::Owned(lhs) => mul_view(&lhs, rhs),
-            ArrayRef::View(lhs, slice) => match &*lhs.buffer.read().unwrap() {
+            ArrayRef::View(lhs, slice) => match &**lhs.read() {
                 ArrayRef::Owned(base) => mul_view(&base.slice(slice.as_ref().as_ref()), rhs),
                 ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
             },
@@ -281,6 +596,7 @@ where
         ([1], [1]) => lhs * rhs,
         ([1], _) => rhs * *lhs.first().unwrap(),
         (_, [1]) => lhs * *rhs.first().unwrap(),
+        _ if lhs.ndim() < rhs.ndim() => rhs * lhs,
         _ => lhs * rhs,
     }
 }
@@ -289,7 +605,7 @@ impl<T: Element + PartialEq> PartialEq for ArrayRef<T> {
     fn eq(&self, rhs: &ArrayRef<T>) -> bool {
         match rhs {
             ArrayRef::Owned(rhs) => self == rhs,
-            ArrayRef::View(rhs, slice) => match &*rhs.buffer.read().unwrap() {
+            ArrayRef::View(rhs, slice) => match &**rhs.read() {
                 ArrayRef::Owned(base) => *self == base.slice(slice.as_ref().as_ref()),
                 ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
             },
@@ -303,7 +619,7 @@ impl<T: Element + PartialEq, S: RawData<Elem = T> + Data> PartialEq<ArrayBase<S,
     fn eq(&self, rhs: &ArrayBase<S, IxDyn>) -> bool {
         match self {
             ArrayRef::Owned(lhs) => *lhs == *rhs,
-            ArrayRef::View(lhs, slice) => match &*lhs.buffer.read().unwrap() {
+            ArrayRef::View(lhs, slice) => match &**lhs.read() {
                 ArrayRef::Owned(base) => base.slice(slice.as_ref().as_ref()) == *rhs,
                 ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
             },
@@ -311,43 +627,143 @@ impl<T: Element + PartialEq, S: RawData<Elem = T> + Data> PartialEq<ArrayBase<S,
     }
 }
 
+#[derive(Debug)]
+enum InitialValue<T> {
+    Array(ArrayD<T>),
+    /// Drawn again on every reset instead of being stored.
+    Sampled {
+        distribution: Distribution,
+        seed: u64,
+        cast: fn(f64) -> T,
+    },
+}
+
+impl<T: Clone> InitialValue<T> {
+    fn to_array(&self, shape: &[Ix]) -> ArrayD<T> {
+        match self {
+            InitialValue::Array(array) => array.clone(),
+            InitialValue::Sampled {
+                distribution,
+                seed,
+                cast,
+            } => {
+                let stream = RngStream::new(*seed);
+                let len = shape.iter().product::<usize>();
+                let samples = (0..len as u64).map(|n| cast(distribution.sample_at(&stream, n)));
+                Array::from(samples.collect::<Vec<_>>())
+                    .into_shape(IxDyn(if shape.is_empty() { &[1] } else { shape }))
+                    .unwrap()
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ArraySignal<T: Element> {
     name: String,
     buffer: RwLock<ArrayRef<T>>,
-    initial_value: Option<Py<PyArrayDyn<T>>>,
+    /// Taken when the signal is made read-only, as it is not reset anymore.
+    initial_value: Mutex<Option<InitialValue<T>>>,
     shape: Vec<Ix>,
+    generation: Arc<AtomicU64>,
+    lock_waits: LockWaits,
+    /// Value moved out of `buffer` once the signal is read-only, read
+    /// without locking.
+    frozen: OnceLock<ArrayRef<T>>,
 }
 
 impl<T: Element + Copy> ArraySignal<T> {
+    /// Copies the initial value so that resetting the signal does not need
+    /// the GIL.
     pub fn new(name: String, initial_value: &PyArrayDyn<T>) -> Self {
+        Self::from_array(name, initial_value.readonly().as_array().to_owned())
+    }
+
+    /// Creates a signal without going through a NumPy array.
+    pub fn from_array(name: String, initial_value: ArrayD<T>) -> Self {
+        let shape = initial_value.shape().to_vec();
+        let initial_value = match shape.as_slice() {
+            [] => initial_value.into_shape(IxDyn(&[1])).unwrap(),
+            _ => initial_value,
+        };
+        ArraySignal {
+            name,
+            buffer: RwLock::new(ArrayRef::Owned(initial_value.clone())),
+            initial_value: Mutex::new(Some(InitialValue::Array(initial_value))),
+            shape,
+            generation: Arc::new(AtomicU64::new(0)),
+            lock_waits: LockWaits::default(),
+            frozen: OnceLock::new(),
+        }
+    }
+
+    /// Creates a signal whose initial value is drawn from `distribution` in
+    /// Rust, so that large random matrices need not be copied from Python.
+    pub fn sampled(name: String, shape: Vec<Ix>, distribution: Distribution, seed: u64) -> Self
+    where
+        T: NumCast,
+    {
+        let initial_value = InitialValue::Sampled {
+            distribution,
+            seed,
+            cast: |x| T::from(x).unwrap(),
+        };
         ArraySignal {
             name,
-            buffer: RwLock::new(ArrayRef::Owned(unsafe {
-                Array::uninitialized(match initial_value.shape() {
-                    [] => &[1],
-                    x => x,
-                })
-            })),
-            initial_value: Some(Py::from(initial_value)),
-            shape: initial_value.shape().to_vec(),
+            buffer: RwLock::new(ArrayRef::Owned(initial_value.to_array(&shape))),
+            initial_value: Mutex::new(Some(initial_value)),
+            shape,
+            generation: Arc::new(AtomicU64::new(0)),
+            lock_waits: LockWaits::default(),
+            frozen: OnceLock::new(),
         }
     }
 
+    /// Whether the signal is a view of another signal's buffer.
+    pub fn is_view(&self) -> bool {
+        matches!(&*self.buffer.read().unwrap(), ArrayRef::View(_, _))
+    }
+
     pub fn new_view(
         name: String,
         base: Arc<Self>,
         slice: Box<SliceInfo<Vec<SliceOrIndex>, IxDyn>>,
     ) -> Self {
-        let shape = match &*base.buffer.read().unwrap() {
+        let shape = match &**base.read() {
             ArrayRef::Owned(base) => base.slice(slice.as_ref().as_ref()).shape().to_vec(),
             ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
         };
+        let generation = Arc::clone(&base.generation);
         ArraySignal {
             name,
             buffer: RwLock::new(ArrayRef::View(base, slice)),
-            initial_value: None,
+            initial_value: Mutex::new(None),
             shape,
+            generation,
+            lock_waits: LockWaits::default(),
+            frozen: OnceLock::new(),
+        }
+    }
+
+    /// Keeps the current value for good, such as for weights of a model
+    /// without learning. Afterwards, the signal is read without locking,
+    /// resets leave it unchanged, and writing it panics. Engines that were
+    /// built before and write the signal fail at the start of their next
+    /// run instead. Views are read-only exactly when their base is. The
+    /// value moves out of the locked buffer instead of being copied, so
+    /// views read it through `read` as well, and the initial value is
+    /// dropped.
+    pub fn make_read_only(&self) {
+        // Locked in the same order as in `reset`.
+        let mut initial_value = self.initial_value.lock().unwrap();
+        let mut buffer = self.buffer.write().unwrap();
+        if let (ArrayRef::Owned(_), None) = (&*buffer, self.frozen.get()) {
+            let empty = ArrayD::from_shape_vec(IxDyn(&[0]), vec![]).unwrap();
+            let _ = self
+                .frozen
+                .set(mem::replace(&mut *buffer, ArrayRef::Owned(empty)));
+            initial_value.take();
+            READ_ONLY_SIGNALS.fetch_add(1, Ordering::SeqCst);
         }
     }
 }
@@ -369,24 +785,375 @@ impl<T: Element + Debug + Send + Sync + 'static> Signal for ArraySignal<T> {
         &self.shape
     }
 
+    fn dtype(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
     fn reset(&self) {
-        if let Some(initial_value) = &self.initial_value {
-            let gil = Python::acquire_gil();
-            let py = gil.python();
-            self.buffer
-                .write()
-                .unwrap()
-                .assign_array(&initial_value.as_ref(py).readonly().as_array());
+        if self.frozen.get().is_some() {
+            return;
+        }
+        match &*self.initial_value.lock().unwrap() {
+            Some(InitialValue::Array(initial_value)) => {
+                self.generation.fetch_add(1, Ordering::SeqCst);
+                self.buffer.write().unwrap().assign_array(initial_value);
+            }
+            Some(initial_value) => {
+                self.generation.fetch_add(1, Ordering::SeqCst);
+                let initial_value = initial_value.to_array(&self.shape);
+                self.buffer.write().unwrap().assign_array(&initial_value);
+            }
+            None => {}
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    fn region(&self) -> Region {
+        match &*self.buffer.read().unwrap() {
+            ArrayRef::Owned(_) => Region::whole(self),
+            ArrayRef::View(base, slice) => match &**base.read() {
+                ArrayRef::Owned(array) => {
+                    Region::slice(&**base, array.shape(), slice.as_ref().as_ref())
+                }
+                ArrayRef::View(_, _) => panic!("Transitive array views are not supported."),
+            },
+        }
+    }
+
+    fn nbytes(&self) -> usize {
+        if let ArrayRef::View(_, _) = &*self.buffer.read().unwrap() {
+            return 0;
+        }
+        let initial_len = match &*self.initial_value.lock().unwrap() {
+            Some(InitialValue::Array(initial_value)) => initial_value.len(),
+            _ => 0,
+        };
+        (self.shape.iter().product::<usize>() + initial_len) * mem::size_of::<T>()
+    }
+
+    fn lock_wait(&self) -> Duration {
+        self.lock_waits.total()
+    }
+
+    fn is_read_only(&self) -> bool {
+        match &*self.buffer.read().unwrap() {
+            ArrayRef::Owned(_) => self.frozen.get().is_some(),
+            ArrayRef::View(base, _) => base.is_read_only(),
         }
     }
 }
 
 impl<T: Element> SignalAccess<ArrayRef<T>> for ArraySignal<T> {
     fn read<'a>(&'a self) -> Box<dyn Deref<Target = ArrayRef<T>> + 'a> {
-        Box::new(self.buffer.read().unwrap())
+        match self.frozen.get() {
+            Some(frozen) => Box::new(frozen),
+            None => Box::new(self.lock_waits.read(&self.buffer)),
+        }
     }
 
     fn write<'a>(&'a self) -> Box<dyn DerefMut<Target = ArrayRef<T>> + 'a> {
-        Box::new(self.buffer.write().unwrap())
+        assert!(
+            self.frozen.get().is_none(),
+            "Signal `{}` is read-only.",
+            self.name
+        );
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        Box::new(self.lock_waits.write(&self.buffer))
+    }
+}
+
+/// Matrix signal in compressed sparse row format, e.g. for sparse connection
+/// weights. The sparsity structure is fixed, only the stored values change.
+#[derive(Debug)]
+pub struct SparseSignal<T> {
+    name: String,
+    shape: Vec<Ix>,
+    /// Row `i` has its entries at `indptr[i]..indptr[i + 1]`.
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    data: RwLock<Vec<T>>,
+    initial_data: Vec<T>,
+    generation: AtomicU64,
+    lock_waits: LockWaits,
+}
+
+impl<T: Clone> SparseSignal<T> {
+    /// Fails unless `indptr`, `indices`, and `data` describe a matrix of
+    /// `shape`.
+    pub fn new(
+        name: String,
+        shape: (usize, usize),
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        data: Vec<T>,
+    ) -> Result<Self, ShapeError> {
+        let (rows, cols) = shape;
+        if indptr.len() != rows + 1
+            || indptr.first() != Some(&0)
+            || indptr.windows(2).any(|w| w[0] > w[1])
+            || indptr.last() != Some(&indices.len())
+            || indices.len() != data.len()
+            || indices.iter().any(|&j| j >= cols)
+        {
+            return Err(ShapeError::new(format!(
+                "Sparse signal `{}` has no valid CSR structure for shape {:?}.",
+                name, shape
+            )));
+        }
+        Ok(SparseSignal {
+            name,
+            shape: vec![rows, cols],
+            indptr,
+            indices,
+            data: RwLock::new(data.clone()),
+            initial_data: data,
+            generation: AtomicU64::new(0),
+            lock_waits: LockWaits::default(),
+        })
+    }
+
+    /// Keeps the nonzero entries of `dense`.
+    pub fn from_dense(name: String, dense: ArrayView2<T>) -> Self
+    where
+        T: Zero,
+    {
+        let mut indptr = vec![0];
+        let (mut indices, mut data) = (vec![], vec![]);
+        for row in dense.outer_iter() {
+            for (j, v) in row.iter().enumerate() {
+                if !v.is_zero() {
+                    indices.push(j);
+                    data.push(v.clone());
+                }
+            }
+            indptr.push(data.len());
+        }
+        Self::new(name, dense.dim(), indptr, indices, data).unwrap()
+    }
+
+    pub fn indptr(&self) -> &[usize] {
+        &self.indptr
+    }
+
+    /// Column of each stored value.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.indices.len()
+    }
+}
+
+impl<T: Clone + Send + Sync + Debug + 'static> Signal for SparseSignal<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<AnySignal> {
+        self
+    }
+
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn shape(&self) -> &[Ix] {
+        &self.shape
+    }
+
+    fn dtype(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn reset(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.data.write().unwrap().clone_from(&self.initial_data);
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    fn region(&self) -> Region {
+        Region::whole(self)
+    }
+
+    fn nbytes(&self) -> usize {
+        (self.indptr.len() + self.indices.len()) * mem::size_of::<usize>()
+            + 2 * self.initial_data.len() * mem::size_of::<T>()
+    }
+
+    fn lock_wait(&self) -> Duration {
+        self.lock_waits.total()
+    }
+}
+
+/// Accesses the stored values, in the order of `indices`.
+impl<T> SignalAccess<Vec<T>> for SparseSignal<T> {
+    fn read<'a>(&'a self) -> Box<dyn Deref<Target = Vec<T>> + 'a> {
+        Box::new(self.lock_waits.read(&self.data))
+    }
+
+    fn write<'a>(&'a self) -> Box<dyn DerefMut<Target = Vec<T>> + 'a> {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        Box::new(self.lock_waits.write(&self.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_measures_waits_for_a_signal_lock() {
+        track_lock_waits(true);
+        let signal = Arc::new(ScalarSignal::new("contended".to_string(), 0));
+        let guard = signal.write();
+        let reader = {
+            let signal = Arc::clone(&signal);
+            std::thread::spawn(move || **signal.read())
+        };
+        std::thread::sleep(Duration::from_millis(20));
+        drop(guard);
+        reader.join().unwrap();
+        track_lock_waits(false);
+
+        assert!(signal.lock_wait() >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn sampled_signals_redraw_the_same_values_on_reset() {
+        let signal = ArraySignal::<f64>::sampled(
+            "weights".to_string(),
+            vec![3, 4],
+            Distribution::Uniform { low: -1., high: 1. },
+            5,
+        );
+        let initial = signal.read().clone_array();
+        assert_eq!(initial.shape(), &[3, 4]);
+        assert!(initial.iter().all(|x| (-1. ..1.).contains(x)));
+
+        signal.write().assign_array(&Array::zeros(IxDyn(&[3, 4])));
+        signal.reset();
+
+        assert_eq!(signal.read().clone_array(), initial);
+    }
+
+    #[test]
+    fn broadcastability_follows_ndarray() {
+        assert!(is_broadcastable(&[3], &[2, 3]));
+        assert!(is_broadcastable(&[1, 3], &[2, 3]));
+        assert!(is_broadcastable(&[], &[2]));
+        assert!(!is_broadcastable(&[2], &[2, 3]));
+        assert!(!is_broadcastable(&[2, 3], &[3]));
+    }
+
+    #[test]
+    fn dot_shape_supports_batches() {
+        assert_eq!(dot_shape(&[2], &[2]), Ok(vec![1]));
+        assert_eq!(dot_shape(&[3, 2], &[2]), Ok(vec![3]));
+        assert_eq!(dot_shape(&[2], &[4, 2]), Ok(vec![4, 1]));
+        assert_eq!(dot_shape(&[3, 2], &[4, 2]), Ok(vec![4, 3]));
+        assert!(dot_shape(&[3, 2], &[3]).is_err());
+    }
+
+    #[test]
+    fn regions_overlap_if_all_axes_intersect() {
+        let buffer = 0;
+        let full = |end| SliceOrIndex::Slice {
+            start: 0,
+            end,
+            step: 1,
+        };
+        let whole = Region::whole(&buffer);
+        let rows = Region::slice(&buffer, &[4, 3], &[full(Some(2)), full(None)]);
+        let last_row = Region::slice(&buffer, &[4, 3], &[SliceOrIndex::Index(-1), full(None)]);
+        let odd_rows = Region::slice(
+            &buffer,
+            &[4, 3],
+            &[
+                SliceOrIndex::Slice {
+                    start: 1,
+                    end: None,
+                    step: 2,
+                },
+                full(None),
+            ],
+        );
+
+        assert!(whole.overlaps(&rows));
+        assert!(!rows.overlaps(&last_row));
+        assert!(rows.overlaps(&odd_rows));
+        assert!(last_row.overlaps(&odd_rows));
+        assert!(!rows.overlaps(&Region::whole(&1)));
+    }
+
+    #[test]
+    fn mul_shape_broadcasts_the_smaller_operand() {
+        assert_eq!(mul_shape(&[1], &[3]), Ok(vec![3]));
+        assert_eq!(mul_shape(&[3], &[4, 3]), Ok(vec![4, 3]));
+        assert_eq!(mul_shape(&[4, 3], &[3]), Ok(vec![4, 3]));
+        assert!(mul_shape(&[2], &[3]).is_err());
+    }
+
+    #[test]
+    fn read_only_signals_keep_their_value() {
+        let base = Arc::new(ArraySignal::from_array(
+            "base".to_string(),
+            array![1., 2.].into_dyn(),
+        ));
+        let slice = SliceInfo::new(vec![SliceOrIndex::Slice {
+            start: 0,
+            end: Some(1),
+            step: 1,
+        }])
+        .unwrap();
+        let view = ArraySignal::new_view("view".to_string(), Arc::clone(&base), Box::new(slice));
+        base.write().assign_array(&array![3., 4.]);
+
+        base.make_read_only();
+        base.reset();
+
+        assert!(base.is_read_only());
+        assert!(view.is_read_only());
+        assert_eq!(**base.read(), array![3., 4.].into_dyn());
+        assert_eq!(**view.read(), array![3.].into_dyn());
+        // The initial value is dropped.
+        assert_eq!(base.nbytes(), 2 * mem::size_of::<f64>());
+    }
+
+    #[test]
+    fn sparse_signals_reject_invalid_structures() {
+        let signal = |indptr, indices| {
+            SparseSignal::new("w".to_string(), (2, 3), indptr, indices, vec![1., 2.])
+        };
+
+        assert!(signal(vec![0, 1, 2], vec![2, 0]).is_ok());
+        assert!(signal(vec![0, 2], vec![2, 0]).is_err());
+        assert!(signal(vec![0, 2, 1], vec![2, 0]).is_err());
+        assert!(signal(vec![0, 1, 2], vec![3, 0]).is_err());
+    }
+
+    #[test]
+    fn views_own_no_memory() {
+        let base = Arc::new(ArraySignal::from_array(
+            "base".to_string(),
+            Array::<f64, _>::zeros(IxDyn(&[4])),
+        ));
+        let slice = SliceInfo::new(vec![SliceOrIndex::Slice {
+            start: 0,
+            end: Some(2),
+            step: 1,
+        }])
+        .unwrap();
+        let view = ArraySignal::new_view("view".to_string(), Arc::clone(&base), Box::new(slice));
+
+        assert_eq!(base.nbytes(), 64);
+        assert_eq!(view.nbytes(), 0);
+        assert_eq!(ScalarSignal::new("scalar".to_string(), 0u64).nbytes(), 16);
     }
 }
@@ -0,0 +1,141 @@
+use crate::operator::{Operator, OperatorError, OperatorNode, StepWrapper, WrappedOperator};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Number of consecutive steps over which an operator is judged.
+const WINDOW: u32 = 50;
+
+/// Share of the steps in a window that must be too slow for a warning.
+const SLOW_SHARE: f64 = 0.8;
+
+pub(crate) type Report = Arc<dyn Fn(String) + Send + Sync>;
+
+#[derive(Default)]
+struct Window {
+    steps: u32,
+    slow_steps: u32,
+    total: Duration,
+}
+
+/// Times the steps of the wrapped operator and reports once when most steps
+/// in a window take longer than `fraction` of its timestep. The fraction is
+/// shared by all monitors of an engine, as `f64` bits.
+pub(crate) struct SlowCallbackMonitor {
+    dt: f64,
+    fraction: Arc<AtomicU64>,
+    report: Report,
+    window: Mutex<Window>,
+    reported: AtomicBool,
+}
+
+impl SlowCallbackMonitor {
+    pub(crate) fn wrap(
+        node: &Arc<OperatorNode>,
+        dt: f64,
+        fraction: Arc<AtomicU64>,
+        report: Report,
+    ) -> Arc<OperatorNode> {
+        WrappedOperator::wrap(
+            node,
+            SlowCallbackMonitor {
+                dt,
+                fraction,
+                report,
+                window: Default::default(),
+                reported: AtomicBool::new(false),
+            },
+        )
+    }
+
+    /// Names the operator with the first signal it writes or reads, which
+    /// identifies the node calling Python.
+    fn describe(operator: &dyn Operator) -> String {
+        match (operator.writes().first(), operator.reads().last()) {
+            (Some(signal), _) => format!("{} writing `{}`", operator.name(), signal.name()),
+            (None, Some(signal)) => format!("{} reading `{}`", operator.name(), signal.name()),
+            (None, None) => operator.name(),
+        }
+    }
+}
+
+impl StepWrapper for SlowCallbackMonitor {
+    type State = Instant;
+
+    fn before_step(&self, _operator: &dyn Operator) -> Instant {
+        Instant::now()
+    }
+
+    fn after_step(
+        &self,
+        operator: &dyn Operator,
+        start: Instant,
+        result: Result<(), OperatorError>,
+    ) -> Result<(), OperatorError> {
+        let elapsed = start.elapsed();
+        if self.reported.load(Ordering::SeqCst) {
+            return result;
+        }
+        let fraction = f64::from_bits(self.fraction.load(Ordering::SeqCst));
+        let budget = fraction * self.dt;
+        let mut window = self.window.lock().unwrap();
+        window.steps += 1;
+        window.total += elapsed;
+        if elapsed.as_secs_f64() > budget {
+            window.slow_steps += 1;
+        }
+        if window.steps >= WINDOW {
+            if window.slow_steps as f64 >= SLOW_SHARE * window.steps as f64
+                && !self.reported.swap(true, Ordering::SeqCst)
+            {
+                (self.report)(format!(
+                    "{} took longer than {:.0}% of its timestep of {} s in {} of {} steps \
+                     ({:.3} s per step on average).",
+                    Self::describe(operator),
+                    100. * fraction,
+                    self.dt,
+                    window.slow_steps,
+                    window.steps,
+                    window.total.as_secs_f64() / window.steps as f64
+                ));
+            }
+            *window = Window::default();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::{Sink, SinkCallback};
+    use crate::signal::ScalarSignal;
+
+    #[test]
+    fn it_reports_operators_slower_than_the_fraction_of_dt_once() {
+        let node = Arc::new(OperatorNode::new(
+            Box::new(Sink::<f64> {
+                t: Some(Arc::new(ScalarSignal::new("t".to_string(), 0.))),
+                x: None,
+                callback: SinkCallback::Rust(Box::new(|_, _| {
+                    std::thread::sleep(Duration::from_micros(200))
+                })),
+            }),
+            vec![],
+        ));
+        let reports = Arc::new(Mutex::new(vec![]));
+        let fraction = Arc::new(AtomicU64::new(0.5f64.to_bits()));
+        let monitor = SlowCallbackMonitor::wrap(&node, 1e-4, Arc::clone(&fraction), {
+            let reports = Arc::clone(&reports);
+            Arc::new(move |message| reports.lock().unwrap().push(message))
+        });
+
+        for _ in 0..2 * WINDOW {
+            monitor.operator.step().unwrap();
+        }
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].starts_with("Sink<f64> reading `t` took longer than 50%"));
+    }
+}
@@ -1,4 +1,8 @@
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::{Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 
 pub struct Event(Mutex<bool>, Condvar);
 
@@ -24,12 +28,89 @@ impl Event {
         }
     }
 
-    pub fn wait(&self) {
+    /// Waits until the event is set or `timeout` elapsed and returns whether
+    /// the event is set.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
         let Event(lock, cvar) = self;
-        let mut finished = lock.lock().unwrap();
+        let finished = lock.lock().unwrap();
+        let (finished, _) = cvar
+            .wait_timeout_while(finished, timeout, |finished| !*finished)
+            .unwrap();
+        *finished
+    }
+}
+
+/// Lets the step loops of several tasks wait for each other after each step
+/// without blocking threads of the pool. The last task to arrive calls the
+/// action before all of them continue. Once a task leaves, e.g. because one
+/// of its steps failed, the waiting tasks are told to stop as well.
+pub struct StepBarrier {
+    n_tasks: usize,
+    action: Box<dyn Fn() + Send + Sync>,
+    state: Mutex<BarrierState>,
+}
+
+#[derive(Default)]
+struct BarrierState {
+    arrived: usize,
+    generation: u64,
+    stopped: bool,
+    wakers: Vec<Waker>,
+}
+
+impl StepBarrier {
+    pub fn new(n_tasks: usize, action: Box<dyn Fn() + Send + Sync>) -> Self {
+        Self {
+            n_tasks,
+            action,
+            state: Mutex::new(BarrierState::default()),
+        }
+    }
+
+    /// Resolves to whether the task continues with its next step.
+    pub fn wait(&self) -> BarrierWait<'_> {
+        BarrierWait {
+            barrier: self,
+            generation: None,
+        }
+    }
+
+    pub fn leave(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.stopped = true;
+        state.wakers.drain(..).for_each(Waker::wake);
+    }
+}
+
+pub struct BarrierWait<'a> {
+    barrier: &'a StepBarrier,
+    /// Generation of the barrier when the task arrived.
+    generation: Option<u64>,
+}
+
+impl Future for BarrierWait<'_> {
+    type Output = bool;
 
-        while !*finished {
-            finished = cvar.wait(finished).unwrap();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<bool> {
+        let barrier = self.barrier;
+        let mut state = barrier.state.lock().unwrap();
+        match self.generation {
+            Some(generation) if generation != state.generation => return Poll::Ready(true),
+            _ if state.stopped => return Poll::Ready(false),
+            Some(_) => {}
+            None => {
+                state.arrived += 1;
+                if state.arrived == barrier.n_tasks {
+                    (barrier.action)();
+                    state.arrived = 0;
+                    state.generation += 1;
+                    state.wakers.drain(..).for_each(Waker::wake);
+                    return Poll::Ready(true);
+                }
+                self.generation = Some(state.generation);
+            }
         }
+        state.wakers.push(cx.waker().clone());
+        Poll::Pending
     }
 }
@@ -0,0 +1,241 @@
+use crate::operator::{Operator, OperatorError, OperatorNode, StepWrapper, WrappedOperator};
+use crate::replay::Value;
+use crate::signal::{ArraySignal, ScalarSignal, ShapeError, Signal, SignalAccess};
+use numpy::c64;
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Problem found by `Engine::verify`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The signal shapes of an operator do not fit together.
+    Shape { operator: String, message: String },
+    /// An operator writes memory overlapping another signal that it reads.
+    Aliasing {
+        operator: String,
+        read: String,
+        written: String,
+    },
+    /// An operator left NaN or infinite values in a signal it writes.
+    NonFinite { operator: String, signal: String },
+    /// Stepping the operators failed.
+    Failed(String),
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Shape { operator, message } => write!(f, "{}: {}", operator, message),
+            Problem::Aliasing {
+                operator,
+                read,
+                written,
+            } => write!(
+                f,
+                "{} reads `{}`, which overlaps `{}` that it writes.",
+                operator, read, written
+            ),
+            Problem::NonFinite { operator, signal } => {
+                write!(f, "{} wrote non-finite values to `{}`.", operator, signal)
+            }
+            Problem::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+fn same_signal(a: &dyn Signal, b: &dyn Signal) -> bool {
+    std::ptr::eq(
+        a as *const dyn Signal as *const (),
+        b as *const dyn Signal as *const (),
+    )
+}
+
+/// Checks the signal shapes of each operator and whether it reads memory
+/// that it writes through a different signal.
+pub(crate) fn check_operators(operators: &[Arc<OperatorNode>]) -> Vec<Problem> {
+    let mut problems = vec![];
+    for node in operators.iter() {
+        let operator = &node.operator;
+        if let Err(ShapeError { message }) = operator.check_shapes() {
+            problems.push(Problem::Shape {
+                operator: operator.name(),
+                message,
+            });
+        }
+        for written in operator.writes() {
+            for read in operator.reads() {
+                if !same_signal(read, written) && read.region().overlaps(&written.region()) {
+                    problems.push(Problem::Aliasing {
+                        operator: operator.name(),
+                        read: read.name().clone(),
+                        written: written.name().clone(),
+                    });
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn has_non_finite_values(signal: &dyn Signal) -> bool {
+    let any = signal.as_any();
+    if let Some(signal) = any.downcast_ref::<ArraySignal<f64>>() {
+        signal
+            .read()
+            .with_view(|x| x.iter().any(|v| !v.is_finite()))
+    } else if let Some(signal) = any.downcast_ref::<ArraySignal<c64>>() {
+        signal
+            .read()
+            .with_view(|x| x.iter().any(|v| !v.re.is_finite() || !v.im.is_finite()))
+    } else if let Some(signal) = any.downcast_ref::<ScalarSignal<f64>>() {
+        !signal.read().is_finite()
+    } else {
+        false
+    }
+}
+
+/// Reports the first step of the wrapped operator leaving non-finite values
+/// in the signals it writes.
+pub(crate) struct NonFiniteGuard {
+    problems: Arc<Mutex<Vec<Problem>>>,
+    reported: AtomicBool,
+}
+
+impl NonFiniteGuard {
+    pub(crate) fn wrap(
+        node: &Arc<OperatorNode>,
+        problems: Arc<Mutex<Vec<Problem>>>,
+    ) -> Arc<OperatorNode> {
+        WrappedOperator::wrap(
+            node,
+            NonFiniteGuard {
+                problems,
+                reported: AtomicBool::new(false),
+            },
+        )
+    }
+}
+
+impl StepWrapper for NonFiniteGuard {
+    type State = ();
+
+    fn before_step(&self, _operator: &dyn Operator) {}
+
+    fn after_step(
+        &self,
+        operator: &dyn Operator,
+        _state: (),
+        result: Result<(), OperatorError>,
+    ) -> Result<(), OperatorError> {
+        if result.is_ok() && !self.reported.load(Ordering::SeqCst) {
+            for signal in operator.writes() {
+                if has_non_finite_values(signal) && !self.reported.swap(true, Ordering::SeqCst) {
+                    self.problems.lock().unwrap().push(Problem::NonFinite {
+                        operator: operator.name(),
+                        signal: signal.name().clone(),
+                    });
+                }
+            }
+        }
+        result
+    }
+}
+
+pub(crate) fn is_view(signal: &dyn Signal) -> bool {
+    let any = signal.as_any();
+    any.downcast_ref::<ArraySignal<f64>>()
+        .map(ArraySignal::is_view)
+        .or_else(|| {
+            any.downcast_ref::<ArraySignal<c64>>()
+                .map(ArraySignal::is_view)
+        })
+        .or_else(|| {
+            any.downcast_ref::<ArraySignal<u64>>()
+                .map(ArraySignal::is_view)
+        })
+        .unwrap_or(false)
+}
+
+/// Values of signals to be restored after a trial run. Views are restored
+/// with their base signals, read-only signals keep their value, and signals
+/// that cannot be copied are reset.
+pub(crate) struct Snapshot(Vec<Option<Value>>);
+
+impl Snapshot {
+    pub(crate) fn save(signals: &[Arc<dyn Signal + Send + Sync>]) -> Self {
+        Snapshot(
+            signals
+                .iter()
+                .map(|signal| {
+                    if is_view(&**signal) || signal.is_read_only() {
+                        None
+                    } else {
+                        Value::of(&**signal)
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    pub(crate) fn restore(self, signals: &[Arc<dyn Signal + Send + Sync>]) {
+        for (signal, value) in signals.iter().zip(self.0) {
+            match value {
+                Some(value) => value.assign_to(&**signal),
+                None if !is_view(&**signal) => signal.reset(),
+                None => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::ElementwiseInc;
+    use ndarray::prelude::*;
+    use ndarray::{SliceInfo, SliceOrIndex};
+
+    fn view(base: &Arc<ArraySignal<f64>>, name: &str, start: isize) -> Arc<ArraySignal<f64>> {
+        let slice = SliceInfo::new(vec![SliceOrIndex::Slice {
+            start,
+            end: Some(start + 2),
+            step: 1,
+        }])
+        .unwrap();
+        Arc::new(ArraySignal::new_view(
+            name.to_string(),
+            Arc::clone(base),
+            Box::new(slice),
+        ))
+    }
+
+    #[test]
+    fn it_reports_operators_reading_memory_they_write() {
+        let base = Arc::new(ArraySignal::from_array(
+            "base".to_string(),
+            Array::zeros(IxDyn(&[4])),
+        ));
+        let node = Arc::new(OperatorNode::new(
+            Box::new(ElementwiseInc {
+                target: view(&base, "target", 0),
+                left: view(&base, "left", 1),
+                right: Arc::new(ArraySignal::from_array(
+                    "right".to_string(),
+                    Array::ones(IxDyn(&[2])),
+                )),
+                checked: false,
+            }),
+            vec![],
+        ));
+
+        assert_eq!(
+            check_operators(&[node]),
+            vec![Problem::Aliasing {
+                operator: "ElementwiseInc<f64>".to_string(),
+                read: "left".to_string(),
+                written: "target".to_string(),
+            }]
+        );
+    }
+}